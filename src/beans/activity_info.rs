@@ -0,0 +1,135 @@
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// 前台 Activity，`activity` 总是完整类名。
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ActivityInfo {
+    pub package: String,
+    pub activity: String,
+}
+
+impl ActivityInfo {
+    /// 由 `com.pkg/.MainActivity` 或 `com.pkg/com.pkg.MainActivity` 形式的组件名构造。
+    pub fn from_component(component: &str) -> Option<ActivityInfo> {
+        let (package, activity) = component.split_once('/')?;
+        if package.is_empty() || activity.is_empty() {
+            return None;
+        }
+        let activity = match activity.strip_prefix('.') {
+            Some(short) => format!("{}.{}", package, short),
+            None => activity.to_string(),
+        };
+        Some(ActivityInfo {
+            package: package.to_string(),
+            activity,
+        })
+    }
+
+    /// `am start -n` 可用的组件名。
+    pub fn component(&self) -> String {
+        format!("{}/{}", self.package, self.activity)
+    }
+}
+
+/// 从 `dumpsys activity activities` 中解析处于 resumed 状态的 Activity。
+///
+/// Android 9 及以前为 `mResumedActivity: ActivityRecord{...}`，
+/// 之后的版本为 `ResumedActivity: ActivityRecord{...}` 或 `topResumedActivity=ActivityRecord{...}`。
+pub fn parse_resumed_activity(output: &str) -> Option<ActivityInfo> {
+    let re = regex::Regex::new(
+        r"(?:mResumedActivity|ResumedActivity|topResumedActivity)[:=]\s*ActivityRecord\{\S+ u\d+ ([^\s}]+)",
+    )
+    .unwrap();
+    let info = re
+        .captures_iter(output)
+        .find_map(|cap| ActivityInfo::from_component(&cap[1]));
+    info
+}
+
+/// 从 `dumpsys window` 的 `mCurrentFocus=Window{...}` 中解析获得焦点的 Activity。
+///
+/// 锁屏或通知栏获得焦点时窗口名不是组件名，返回 `None`。
+pub fn parse_current_focus(output: &str) -> Option<ActivityInfo> {
+    let re = regex::Regex::new(r"mCurrentFocus=Window\{\S+ u\d+ ([^\s}]+)\}").unwrap();
+    let info = re
+        .captures_iter(output)
+        .find_map(|cap| ActivityInfo::from_component(&cap[1]));
+    info
+}
+
+/// 解析 `cmd package resolve-activity --brief` 的输出，最后一行为解析出的组件名。
+///
+/// 没有可启动的 Activity 时输出 `No activity found`，返回 `None`。
+pub fn parse_resolve_activity(output: &str) -> Option<ActivityInfo> {
+    let component = output.lines().map(str::trim).rfind(|l| !l.is_empty())?;
+    if component.contains(char::is_whitespace) {
+        return None;
+    }
+    ActivityInfo::from_component(component)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ACTIVITIES_ANDROID_9: &str = "ACTIVITY MANAGER ACTIVITIES (dumpsys activity activities)
+Display #0 (activities from top to bottom):
+  Stack #1: type=standard mode=fullscreen
+    mResumedActivity: ActivityRecord{8a1f2c0 u0 com.android.settings/.Settings t12}
+    mLastPausedActivity: ActivityRecord{3c4d5e6 u0 com.android.launcher3/.Launcher t1}
+";
+
+    const ACTIVITIES_ANDROID_13: &str = "ACTIVITY MANAGER ACTIVITIES (dumpsys activity activities)
+Display #0 (activities from top to bottom):
+  * Task{5b1c2d3 #24 type=standard A=10123:com.example.app U=0 visible=true mode=fullscreen}
+    topResumedActivity=ActivityRecord{1f2e3d4 u0 com.example.app/com.example.app.ui.MainActivity t24}
+  ResumedActivity: ActivityRecord{1f2e3d4 u0 com.example.app/com.example.app.ui.MainActivity t24}
+";
+
+    #[test]
+    fn test_parse_resumed_activity() {
+        assert_eq!(
+            parse_resumed_activity(ACTIVITIES_ANDROID_9),
+            Some(ActivityInfo {
+                package: "com.android.settings".to_string(),
+                activity: "com.android.settings.Settings".to_string(),
+            })
+        );
+        let info = parse_resumed_activity(ACTIVITIES_ANDROID_13).unwrap();
+        assert_eq!(info.package, "com.example.app");
+        assert_eq!(info.activity, "com.example.app.ui.MainActivity");
+        assert_eq!(
+            info.component(),
+            "com.example.app/com.example.app.ui.MainActivity"
+        );
+    }
+
+    #[test]
+    fn test_parse_current_focus() {
+        let window = "WINDOW MANAGER WINDOWS (dumpsys window windows)
+  mCurrentFocus=Window{c0ffee1 u0 com.android.chrome/org.chromium.chrome.browser.ChromeTabbedActivity}
+  mFocusedApp=ActivityRecord{abc1234 u0 com.android.chrome/org.chromium.chrome.browser.ChromeTabbedActivity t7}
+";
+        assert_eq!(
+            parse_current_focus(window).unwrap().activity,
+            "org.chromium.chrome.browser.ChromeTabbedActivity"
+        );
+        let locked = "  mCurrentFocus=Window{7d8e9f0 u0 NotificationShade}\n";
+        assert_eq!(parse_current_focus(locked), None);
+        assert_eq!(parse_current_focus("  mCurrentFocus=null\n"), None);
+        assert_eq!(parse_resumed_activity("  mResumedActivity: null\n"), None);
+    }
+
+    #[test]
+    fn test_parse_resolve_activity() {
+        let output = "priority=0 preferredOrder=0 match=0x108000 specificIndex=-1 isDefault=true
+com.android.settings/.Settings
+";
+        assert_eq!(
+            parse_resolve_activity(output).unwrap().component(),
+            "com.android.settings/com.android.settings.Settings"
+        );
+        assert_eq!(parse_resolve_activity("No activity found\n"), None);
+    }
+}
@@ -1,6 +1,9 @@
 use chrono::{DateTime, Utc};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
 
 #[derive(Debug, PartialEq, Ord, PartialOrd, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct AppInfo {
     pub package_name: String,
     pub version_name: Option<String>,
@@ -27,4 +30,52 @@ impl AppInfo {
             sub_apk_paths: vec![],
         }
     }
+
+    /// 根据 `pm path` 的结果设置 `path` 与 `sub_apk_paths`：`base.apk` 作为主路径，
+    /// 其余 `split_config.*` 等作为子 APK，没有 `base.apk` 时取第一个路径。
+    pub fn set_apk_paths(&mut self, mut paths: Vec<String>) {
+        if paths.is_empty() {
+            return;
+        }
+        let base = paths
+            .iter()
+            .position(|p| p.ends_with("/base.apk"))
+            .unwrap_or(0);
+        self.path = paths.remove(base);
+        self.sub_apk_paths = paths;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_set_apk_paths() {
+        let mut info = AppInfo::new("com.example.app");
+        info.set_apk_paths(vec![
+            "/data/app/com.example.app/split_config.en.apk".to_string(),
+            "/data/app/com.example.app/base.apk".to_string(),
+        ]);
+        assert_eq!(info.path, "/data/app/com.example.app/base.apk");
+        assert_eq!(
+            info.sub_apk_paths,
+            vec!["/data/app/com.example.app/split_config.en.apk"]
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_app_info_serde_round_trip() {
+        let mut info = AppInfo::new("com.example.app");
+        info.version_name = Some("1.2.3".into());
+        info.version_code = Some(123);
+        info.first_install_time = DateTime::from_timestamp(1_700_000_000, 0);
+        info.set_apk_paths(vec![
+            "/data/app/com.example.app/base.apk".into(),
+            "/data/app/com.example.app/split_config.en.apk".into(),
+        ]);
+        let json = serde_json::to_string(&info).unwrap();
+        assert_eq!(serde_json::from_str::<AppInfo>(&json).unwrap(), info);
+    }
 }
@@ -0,0 +1,205 @@
+use std::collections::HashMap;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::errors::{AdbError, AdbResult};
+
+/// 充电状态，对应 `BatteryManager.BATTERY_STATUS_*`。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum BatteryStatus {
+    Unknown,
+    Charging,
+    Discharging,
+    NotCharging,
+    Full,
+}
+
+impl BatteryStatus {
+    pub fn from_code(code: u32) -> BatteryStatus {
+        match code {
+            2 => BatteryStatus::Charging,
+            3 => BatteryStatus::Discharging,
+            4 => BatteryStatus::NotCharging,
+            5 => BatteryStatus::Full,
+            _ => BatteryStatus::Unknown,
+        }
+    }
+}
+
+/// 电池健康状态，对应 `BatteryManager.BATTERY_HEALTH_*`。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum BatteryHealth {
+    Unknown,
+    Good,
+    Overheat,
+    Dead,
+    OverVoltage,
+    UnspecifiedFailure,
+    Cold,
+}
+
+impl BatteryHealth {
+    pub fn from_code(code: u32) -> BatteryHealth {
+        match code {
+            2 => BatteryHealth::Good,
+            3 => BatteryHealth::Overheat,
+            4 => BatteryHealth::Dead,
+            5 => BatteryHealth::OverVoltage,
+            6 => BatteryHealth::UnspecifiedFailure,
+            7 => BatteryHealth::Cold,
+            _ => BatteryHealth::Unknown,
+        }
+    }
+}
+
+/// 充电来源，对应 `BatteryManager.BATTERY_PLUGGED_*`。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum PluggedState {
+    Unplugged,
+    Ac,
+    Usb,
+    Wireless,
+    Dock,
+}
+
+impl PluggedState {
+    pub fn from_code(code: u32) -> PluggedState {
+        match code {
+            1 => PluggedState::Ac,
+            2 => PluggedState::Usb,
+            4 => PluggedState::Wireless,
+            8 => PluggedState::Dock,
+            _ => PluggedState::Unplugged,
+        }
+    }
+}
+
+/// `dumpsys battery` 的解析结果，温度单位为摄氏度，电压单位为毫伏。
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct BatteryInfo {
+    pub level: u8,
+    pub scale: u8,
+    pub status: BatteryStatus,
+    pub health: BatteryHealth,
+    pub plugged: PluggedState,
+    pub temperature: Option<f32>,
+    pub voltage: Option<u32>,
+    pub technology: Option<String>,
+}
+
+impl BatteryInfo {
+    /// 解析 `dumpsys battery` 的输出。
+    ///
+    /// 只有 `level` 与 `scale` 是必需的；厂商额外添加的行会被忽略，同名字段以第一次出现为准。
+    /// 没有 `plugged` 字段的旧版本根据 `AC powered` 等行推断充电来源。
+    pub fn parse(output: &str) -> AdbResult<BatteryInfo> {
+        let mut fields = HashMap::new();
+        for line in output.lines() {
+            if let Some((key, value)) = line.split_once(':') {
+                fields
+                    .entry(key.trim().to_string())
+                    .or_insert_with(|| value.trim().to_string());
+            }
+        }
+        let number = |key: &str| fields.get(key).and_then(|v| v.parse::<u32>().ok());
+        let required = |key: &str| {
+            number(key)
+                .and_then(|v| u8::try_from(v).ok())
+                .ok_or_else(|| AdbError::unknown(format!("battery {} not found in dumpsys", key)))
+        };
+        let plugged = match number("plugged") {
+            Some(code) => PluggedState::from_code(code),
+            None => [
+                ("AC powered", PluggedState::Ac),
+                ("USB powered", PluggedState::Usb),
+                ("Wireless powered", PluggedState::Wireless),
+                ("Dock powered", PluggedState::Dock),
+            ]
+            .into_iter()
+            .find(|(key, _)| fields.get(*key).map(String::as_str) == Some("true"))
+            .map_or(PluggedState::Unplugged, |(_, state)| state),
+        };
+        Ok(BatteryInfo {
+            level: required("level")?,
+            scale: required("scale")?,
+            status: BatteryStatus::from_code(number("status").unwrap_or(1)),
+            health: BatteryHealth::from_code(number("health").unwrap_or(1)),
+            plugged,
+            temperature: fields
+                .get("temperature")
+                .and_then(|v| v.parse::<i32>().ok())
+                .map(|v| v as f32 / 10.0),
+            voltage: number("voltage"),
+            technology: fields.get("technology").filter(|v| !v.is_empty()).cloned(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_emulator_battery() {
+        let output = "Current Battery Service state:
+  AC powered: true
+  USB powered: false
+  Wireless powered: false
+  Max charging current: 0
+  status: 2
+  health: 2
+  present: true
+  level: 100
+  scale: 100
+  voltage: 5000
+  temperature: 250
+  technology: Li-ion
+";
+        let battery = BatteryInfo::parse(output).unwrap();
+        assert_eq!(
+            battery,
+            BatteryInfo {
+                level: 100,
+                scale: 100,
+                status: BatteryStatus::Charging,
+                health: BatteryHealth::Good,
+                plugged: PluggedState::Ac,
+                temperature: Some(25.0),
+                voltage: Some(5000),
+                technology: Some("Li-ion".to_string()),
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_vendor_battery() {
+        let output = "Current Battery Service state:
+  (UPDATES STOPPED -- use 'reset' to restart)
+  plugged: 2
+  status: 5
+  health: 7
+  level: 87
+  scale: 100
+  temperature: -35
+  mSecPlugTypeSummary: 0
+  level: 12
+";
+        let battery = BatteryInfo::parse(output).unwrap();
+        assert_eq!(battery.level, 87);
+        assert_eq!(battery.status, BatteryStatus::Full);
+        assert_eq!(battery.health, BatteryHealth::Cold);
+        assert_eq!(battery.plugged, PluggedState::Usb);
+        assert_eq!(battery.temperature, Some(-3.5));
+        assert_eq!(battery.voltage, None);
+        assert_eq!(battery.technology, None);
+        assert!(BatteryInfo::parse("Current Battery Service state:\n  scale: 100\n").is_err());
+    }
+}
@@ -0,0 +1,41 @@
+/// 设备端校验命令，依次尝试，前一个不存在时换下一个。
+///
+/// Android 6.0 起由 toybox 提供 `sha256sum`，部分设备的 PATH 中没有该链接，需要通过 `toybox` 调用；
+/// 更早的 toolbox 只有 `md5`。
+pub(crate) const SHA256_COMMANDS: [&[&str]; 2] = [&["sha256sum"], &["toybox", "sha256sum"]];
+pub(crate) const MD5_COMMANDS: [&[&str]; 2] = [&["md5sum"], &["md5"]];
+
+/// 解析 `sha256sum`、`md5sum` 等命令 `<摘要>  <路径>` 格式的输出，返回小写的十六进制摘要。
+///
+/// `hex_len` 为摘要的十六进制长度，SHA-256 为 64，MD5 为 32。
+pub fn parse_checksum(output: &str, hex_len: usize) -> Option<String> {
+    let digest = output.split_whitespace().next()?;
+    (digest.len() == hex_len && digest.chars().all(|c| c.is_ascii_hexdigit()))
+        .then(|| digest.to_ascii_lowercase())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_checksum() {
+        assert_eq!(
+            parse_checksum(
+                "E3B0C44298FC1C149AFBF4C8996FB92427AE41E4649B934CA495991B7852B855  /data/local/tmp/empty\n",
+                64
+            )
+            .as_deref(),
+            Some("e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855")
+        );
+        assert_eq!(
+            parse_checksum("d41d8cd98f00b204e9800998ecf8427e  /sdcard/empty\n", 32).as_deref(),
+            Some("d41d8cd98f00b204e9800998ecf8427e")
+        );
+        assert_eq!(
+            parse_checksum("sha256sum: /nope: No such file or directory\n", 64),
+            None
+        );
+        assert_eq!(parse_checksum("", 32), None);
+    }
+}
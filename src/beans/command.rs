@@ -1,50 +1,402 @@
-#[derive(Ord, PartialOrd, Eq, PartialEq, Debug)]
-pub enum AdbCommand<'a> {
-    Slice(&'a [&'a str]),
-    String(&'a str),
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// 一条 adb 命令：整条命令行字符串，或按参数拆分的列表。
+#[derive(Ord, PartialOrd, Eq, PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum AdbCommand {
+    Single(String),
+    Multiple(Vec<String>),
 }
 
-impl<'b, 'a> Into<AdbCommand<'a>> for &'b [&str]
-where
-    'b: 'a,
-{
-    fn into(self) -> AdbCommand<'a> {
-        AdbCommand::Slice(self.as_ref())
+impl AdbCommand {
+    /// 不含任何参数的命令，之后可以通过 `push`/`extend` 追加参数。
+    pub fn empty() -> Self {
+        AdbCommand::Multiple(vec![])
     }
-}
-impl<'a> AdbCommand<'a> {
+
+    /// 返回一个 [`AdbCommandBuilder`]，用于按条件逐个追加参数。
+    pub fn builder() -> AdbCommandBuilder {
+        AdbCommandBuilder::default()
+    }
+
+    /// 追加一个参数。整条命令行（`Single`）会先经 [`parse`](AdbCommand::parse) 按 shell 规则拆分为参数列表。
+    pub fn push<S: ToString>(&mut self, arg: S) {
+        self.as_args_mut().push(arg.to_string());
+    }
+
+    /// 追加多个参数，规则同 `push`。
+    pub fn extend<I>(&mut self, args: I)
+    where
+        I: IntoIterator,
+        I::Item: ToString,
+    {
+        self.as_args_mut()
+            .extend(args.into_iter().map(|arg| arg.to_string()));
+    }
+
+    fn as_args_mut(&mut self) -> &mut Vec<String> {
+        if let AdbCommand::Single(s) = self {
+            *self = AdbCommand::parse(s);
+        }
+        match self {
+            AdbCommand::Multiple(args) => args,
+            AdbCommand::Single(_) => unreachable!(),
+        }
+    }
+
+    /// 按 shell 规则把整条命令行拆分为参数列表（[`AdbCommand::Multiple`]）。
+    ///
+    /// 支持单引号（内部按字面处理）、双引号（内部 `\` 可转义 `"`、`\`、`$`、`` ` ``）
+    /// 以及引号外的反斜杠转义；未闭合的引号延续到行尾。
+    ///
+    /// ```
+    /// use radb::beans::AdbCommand;
+    ///
+    /// let cmd = AdbCommand::parse(r#"am start -n "com.x/.Y" --es msg 'hello world'"#);
+    /// assert_eq!(
+    ///     cmd,
+    ///     AdbCommand::from(["am", "start", "-n", "com.x/.Y", "--es", "msg", "hello world"])
+    /// );
+    /// ```
+    pub fn parse(line: &str) -> Self {
+        let mut args = vec![];
+        let mut current: Option<String> = None;
+        let mut chars = line.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                c if c.is_whitespace() => {
+                    if let Some(arg) = current.take() {
+                        args.push(arg);
+                    }
+                }
+                '\'' => {
+                    let arg = current.get_or_insert_with(String::new);
+                    for c in chars.by_ref() {
+                        if c == '\'' {
+                            break;
+                        }
+                        arg.push(c);
+                    }
+                }
+                '"' => {
+                    let arg = current.get_or_insert_with(String::new);
+                    while let Some(c) = chars.next() {
+                        match c {
+                            '"' => break,
+                            '\\' => match chars.next() {
+                                Some(next @ ('"' | '\\' | '$' | '`')) => arg.push(next),
+                                Some(next) => {
+                                    arg.push('\\');
+                                    arg.push(next);
+                                }
+                                None => arg.push('\\'),
+                            },
+                            c => arg.push(c),
+                        }
+                    }
+                }
+                '\\' => {
+                    let arg = current.get_or_insert_with(String::new);
+                    if let Some(next) = chars.next() {
+                        arg.push(next);
+                    }
+                }
+                c => current.get_or_insert_with(String::new).push(c),
+            }
+        }
+        args.extend(current);
+        AdbCommand::Multiple(args)
+    }
+
     pub fn get_command(&self) -> String {
         match self {
-            AdbCommand::Slice(s) => s.join(" "),
-            AdbCommand::String(s) => s.to_string(),
+            AdbCommand::Multiple(s) => s.join(" "),
+            AdbCommand::Single(s) => s.to_string(),
+        }
+    }
+
+    /// 渲染为用于日志的命令行：整条命令原样使用，参数列表逐个转义，与实际发送的命令一致。
+    pub fn display(&self) -> String {
+        self.display_redacted(&[])
+    }
+
+    /// 同 [`display`](AdbCommand::display)，但 `redact` 中下标对应的参数替换为 `***`，
+    /// 用于记录含密码等敏感参数的命令。整条命令行（`Single`）会先经 [`parse`](AdbCommand::parse)
+    /// 按 shell 规则拆分为参数，再与参数列表一样逐个转义，引号内含空白的参数也会被整体隐去。
+    pub fn display_redacted(&self, redact: &[usize]) -> String {
+        match self {
+            AdbCommand::Single(s) if redact.is_empty() => s.clone(),
+            AdbCommand::Single(s) => AdbCommand::parse(s).display_redacted(redact),
+            AdbCommand::Multiple(args) => {
+                redact_args(args.iter().map(|s| s.as_str()), redact, shell_escape_arg)
+            }
+        }
+    }
+}
+
+/// 按下标把 `redact` 中的参数替换为 `***`，其余参数经 `render` 渲染后以空格连接。
+fn redact_args<'a, I, F>(args: I, redact: &[usize], render: F) -> String
+where
+    I: Iterator<Item = &'a str>,
+    F: Fn(&str) -> String,
+{
+    args.enumerate()
+        .map(|(i, arg)| {
+            if redact.contains(&i) {
+                "***".to_string()
+            } else {
+                render(arg)
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// 把单个参数转义为设备 shell 上的双引号字符串。
+///
+/// 双引号内 `"`、`\`、`$` 与 `` ` `` 仍有特殊含义，均加反斜杠转义，
+/// 保证参数按字面传给远端命令而不会被展开。
+///
+/// ```
+/// use radb::beans::shell_escape_arg;
+///
+/// assert_eq!(shell_escape_arg("$HOME"), r#""\$HOME""#);
+/// ```
+pub fn shell_escape_arg(arg: &str) -> String {
+    let mut quoted = String::with_capacity(arg.len() + 2);
+    quoted.push('"');
+    for c in arg.chars() {
+        if matches!(c, '"' | '\\' | '$' | '`') {
+            quoted.push('\\');
         }
+        quoted.push(c);
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// 逐个追加参数构造 [`AdbCommand::Multiple`]。
+///
+/// ```
+/// use radb::beans::AdbCommand;
+///
+/// let keep_data = true;
+/// let mut builder = AdbCommand::builder().args(["pm", "uninstall"]);
+/// if keep_data {
+///     builder = builder.arg("-k");
+/// }
+/// let cmd = builder.args(["--user", "0"]).arg("com.example").build();
+/// assert_eq!(cmd.get_command(), "pm uninstall -k --user 0 com.example");
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct AdbCommandBuilder {
+    args: Vec<String>,
+}
+
+impl AdbCommandBuilder {
+    pub fn arg<S: ToString>(mut self, arg: S) -> Self {
+        self.args.push(arg.to_string());
+        self
+    }
+
+    pub fn args<I>(mut self, args: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: ToString,
+    {
+        self.args
+            .extend(args.into_iter().map(|arg| arg.to_string()));
+        self
+    }
+
+    pub fn build(self) -> AdbCommand {
+        AdbCommand::Multiple(self.args)
+    }
+}
+
+impl FromIterator<String> for AdbCommand {
+    fn from_iter<I: IntoIterator<Item = String>>(iter: I) -> Self {
+        AdbCommand::Multiple(iter.into_iter().collect())
+    }
+}
+
+impl From<AdbCommandBuilder> for AdbCommand {
+    fn from(value: AdbCommandBuilder) -> Self {
+        value.build()
+    }
+}
+
+impl From<&[&str]> for AdbCommand {
+    fn from(value: &[&str]) -> Self {
+        AdbCommand::Multiple(value.iter().map(|s| s.to_string()).collect())
     }
 }
 
-impl<'a> Into<AdbCommand<'a>> for &'a str {
-    fn into(self) -> AdbCommand<'a> {
-        AdbCommand::String(self)
+impl From<&str> for AdbCommand {
+    fn from(value: &str) -> Self {
+        AdbCommand::Single(value.to_string())
     }
 }
 
-impl<'a, const N: usize> Into<AdbCommand<'a>> for &'a [&'a str; N] {
-    fn into(self) -> AdbCommand<'a> {
-        AdbCommand::Slice(self)
+impl<const N: usize> From<&[&str; N]> for AdbCommand {
+    fn from(value: &[&str; N]) -> Self {
+        value.as_slice().into()
     }
 }
-impl<'a> Into<AdbCommand<'a>> for &'a Vec<&'a str> {
-    fn into(self) -> AdbCommand<'a> {
-        AdbCommand::Slice(self)
+
+impl<const N: usize> From<[&str; N]> for AdbCommand {
+    fn from(value: [&str; N]) -> Self {
+        value.as_slice().into()
+    }
+}
+
+impl From<&Vec<&str>> for AdbCommand {
+    fn from(value: &Vec<&str>) -> Self {
+        value.as_slice().into()
     }
 }
 
+/// 命令的输出与退出码表明设备上没有该命令（shell 返回 127，或 toybox 不认识该子命令）。
+///
+/// 只匹配 shell 的 `<tool>: not found`（mksh 为 `<tool>: inaccessible or not found`）整行结尾，
+/// 以免参数中的路径含有 `not found` 时被误判。
+pub(crate) fn is_command_missing(output: &str, code: i32) -> bool {
+    code == 127
+        || output.lines().any(|line| {
+            let line = line.trim_end();
+            line.ends_with(": not found")
+                || line.ends_with(": inaccessible or not found")
+                || line.contains("Unknown command")
+        })
+}
+
 #[test]
 fn test_into() {
     let a = "a";
     let b = ["a", "b", "c"];
     let c = vec![a, "b", "c"];
-    let d = vec!["a".to_string()];
-    assert_eq!(AdbCommand::String(a), a.into());
-    assert_eq!(AdbCommand::Slice(&b), (&b).into());
-    assert_eq!(AdbCommand::Slice(&c), (&c).into());
+    let multiple = AdbCommand::Multiple(vec!["a".into(), "b".into(), "c".into()]);
+    assert_eq!(AdbCommand::Single(a.to_string()), a.into());
+    assert_eq!(multiple, (&b).into());
+    assert_eq!(multiple, (&c).into());
+    assert_eq!(multiple.get_command(), "a b c");
+}
+
+#[test]
+fn test_builder() {
+    use crate::client::AdbDevice;
+
+    let cmd = AdbCommand::builder()
+        .args(["am", "start"])
+        .arg("-n")
+        .arg("com.example/.Main")
+        .args(["--es", "msg"])
+        .arg(r#"say "hi""#)
+        .build();
+    assert_eq!(
+        AdbDevice::<&str>::command_line(&cmd),
+        r#""am" "start" "-n" "com.example/.Main" "--es" "msg" "say \"hi\"""#
+    );
+
+    let mut cmd = AdbCommand::empty();
+    cmd.push("pm");
+    cmd.extend(["install", "-r"]);
+    assert_eq!(cmd, AdbCommand::from(["pm", "install", "-r"]));
+
+    let mut single = AdbCommand::from("pm list packages");
+    single.push("-3");
+    assert_eq!(single, AdbCommand::from(["pm", "list", "packages", "-3"]));
+
+    let mut quoted = AdbCommand::from(r#"am broadcast --es msg "hello world""#);
+    quoted.push("-p");
+    assert_eq!(
+        quoted,
+        AdbCommand::from(["am", "broadcast", "--es", "msg", "hello world", "-p"])
+    );
+
+    let collected: AdbCommand = ["ls", "-l"].iter().map(|s| s.to_string()).collect();
+    assert_eq!(collected, AdbCommand::from(["ls", "-l"]));
+}
+
+#[test]
+fn test_shell_escape_arg() {
+    assert_eq!(shell_escape_arg("plain"), r#""plain""#);
+    assert_eq!(shell_escape_arg("$HOME"), r#""\$HOME""#);
+    assert_eq!(shell_escape_arg("`id`"), r#""\`id\`""#);
+    assert_eq!(shell_escape_arg("a;b"), r#""a;b""#);
+    assert_eq!(shell_escape_arg(r#"say "hi" \n"#), r#""say \"hi\" \\n""#);
+    assert_eq!(shell_escape_arg(""), r#""""#);
+}
+
+#[test]
+fn test_display_redacted() {
+    let cmd = AdbCommand::from(&vec!["login", "--user", "alice", "--password", "s3cr$t"]);
+    assert_eq!(
+        cmd.display(),
+        r#""login" "--user" "alice" "--password" "s3cr\$t""#
+    );
+    let masked = cmd.display_redacted(&[4]);
+    assert_eq!(masked, r#""login" "--user" "alice" "--password" ***"#);
+    assert!(!masked.contains("s3cr"));
+
+    let single = AdbCommand::Single("login alice hunter2".to_string());
+    assert_eq!(single.display(), "login alice hunter2");
+    assert_eq!(single.display_redacted(&[2]), r#""login" "alice" ***"#);
+
+    let quoted = AdbCommand::Single("login 'my secret'".to_string());
+    let masked = quoted.display_redacted(&[1]);
+    assert_eq!(masked, r#""login" ***"#);
+    assert!(!masked.contains("secret"));
+}
+
+#[test]
+fn test_parse() {
+    assert_eq!(
+        AdbCommand::parse("echo 'hello world'"),
+        AdbCommand::from(["echo", "hello world"])
+    );
+    assert_eq!(
+        AdbCommand::parse(r#"  echo  "a \"b\" \$HOME"   c\ d  "#),
+        AdbCommand::from(["echo", r#"a "b" $HOME"#, "c d"])
+    );
+    assert_eq!(
+        AdbCommand::parse(r#"echo '' "" x'y'"z""#),
+        AdbCommand::from(["echo", "", "", "xyz"])
+    );
+    assert_eq!(
+        AdbCommand::parse(r#"echo "a\nb" 'c\d'"#),
+        AdbCommand::from(["echo", r"a\nb", r"c\d"])
+    );
+    assert_eq!(
+        AdbCommand::parse("echo 'open"),
+        AdbCommand::from(["echo", "open"])
+    );
+    assert_eq!(AdbCommand::parse("   "), AdbCommand::empty());
+
+    let args = ["echo", "$HOME", "`id`", "a;b", r#"say "hi""#, "it's"];
+    let line = AdbCommand::from(args).display();
+    assert_eq!(AdbCommand::parse(&line), AdbCommand::from(args));
+}
+
+#[test]
+fn test_is_command_missing() {
+    assert!(is_command_missing(
+        "/system/bin/sh: sha256sum: not found\n",
+        127
+    ));
+    assert!(is_command_missing("toybox: Unknown command sha256sum\n", 1));
+    assert!(!is_command_missing(
+        "sha256sum: /nope: No such file or directory\n",
+        1
+    ));
+    assert!(is_command_missing(
+        "/system/bin/sh: sha256sum: inaccessible or not found\n",
+        1
+    ));
+    assert!(!is_command_missing(
+        "sha256sum: /sdcard/not found.bin: Permission denied\n",
+        1
+    ));
 }
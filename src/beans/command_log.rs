@@ -0,0 +1,114 @@
+use chrono::{DateTime, Utc};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::beans::command::AdbCommand;
+
+/// 一条已执行命令的记录。
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct CommandRecord {
+    pub command: AdbCommand,
+    pub timestamp: DateTime<Utc>,
+    pub result: Result<String, String>, // 成功时为命令输出，失败时为错误信息
+}
+
+/// 按执行顺序记录的命令日志，可导出为 JSON 用于回放。
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct CommandLog {
+    pub records: Vec<CommandRecord>,
+}
+
+impl CommandLog {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 记录一条命令及其执行结果，时间戳取当前时间。
+    pub fn record<C, E>(&mut self, command: C, result: &Result<String, E>)
+    where
+        C: Into<AdbCommand>,
+        E: std::fmt::Display,
+    {
+        self.records.push(CommandRecord {
+            command: command.into(),
+            timestamp: Utc::now(),
+            result: match result {
+                Ok(output) => Ok(output.clone()),
+                Err(e) => Err(e.to_string()),
+            },
+        });
+    }
+
+    #[cfg(feature = "serde")]
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string_pretty(self)
+    }
+
+    #[cfg(feature = "serde")]
+    pub fn from_json(json: &str) -> serde_json::Result<Self> {
+        serde_json::from_str(json)
+    }
+}
+
+/// 回放时命令执行失败的处理方式。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ReplayErrorPolicy {
+    #[default]
+    Abort, // 遇到失败立即停止并返回错误
+    Continue, // 记录失败后继续执行后续命令
+}
+
+/// `AdbDevice::replay` 的选项。
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ReplayOptions {
+    pub keep_timing: bool, // 是否按录制时的时间戳间隔在命令之间等待
+    pub on_error: ReplayErrorPolicy,
+}
+
+impl CommandLog {
+    /// 返回第 `index` 条记录之前应等待的时间，即与上一条记录的时间戳之差。
+    pub(crate) fn delay_before(&self, index: usize) -> std::time::Duration {
+        if index == 0 {
+            return std::time::Duration::ZERO;
+        }
+        let previous = self.records[index - 1].timestamp;
+        (self.records[index].timestamp - previous)
+            .to_std()
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(all(test, feature = "serde"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_command_serde_round_trip() {
+        let single = AdbCommand::from("input tap 10 20");
+        let multiple = AdbCommand::from(["ls", "-l", "/sdcard"]);
+        let single_json = serde_json::to_string(&single).unwrap();
+        let multiple_json = serde_json::to_string(&multiple).unwrap();
+        assert_eq!(single_json, r#"{"single":"input tap 10 20"}"#);
+        assert_eq!(multiple_json, r#"{"multiple":["ls","-l","/sdcard"]}"#);
+        assert_eq!(
+            serde_json::from_str::<AdbCommand>(&single_json).unwrap(),
+            single
+        );
+        assert_eq!(
+            serde_json::from_str::<AdbCommand>(&multiple_json).unwrap(),
+            multiple
+        );
+    }
+
+    #[test]
+    fn test_command_log_round_trip() {
+        let mut log = CommandLog::new();
+        log.record("getprop ro.product.model", &Ok::<_, String>("Pixel".into()));
+        log.record(["input", "keyevent", "3"], &Err("device offline"));
+        let restored = CommandLog::from_json(&log.to_json().unwrap()).unwrap();
+        assert_eq!(restored, log);
+        assert_eq!(restored.records[1].result, Err("device offline".into()));
+    }
+}
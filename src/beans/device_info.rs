@@ -1,6 +1,9 @@
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct AdbDeviceInfo {
     pub serial: String,
     pub state: String,
@@ -16,3 +19,17 @@ impl AdbDeviceInfo {
         }
     }
 }
+
+#[cfg(all(test, feature = "serde"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_device_info_serde_round_trip() {
+        let mut info = AdbDeviceInfo::new("emulator-5554".into(), "device".into());
+        info.properties
+            .insert("model".into(), "sdk_gphone64_x86_64".into());
+        let json = serde_json::to_string(&info).unwrap();
+        assert_eq!(serde_json::from_str::<AdbDeviceInfo>(&json).unwrap(), info);
+    }
+}
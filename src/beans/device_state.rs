@@ -0,0 +1,149 @@
+use std::fmt::Display;
+use std::str::FromStr;
+
+use crate::errors::AdbError;
+
+/// 设备所处的状态，对应 `adb get-state` 的输出和 `adb wait-for-<transport>-<state>` 中的 `state`。
+///
+/// `wait-for` 只支持 `Device`、`Recovery`、`Rescue`、`Sideload`、`Bootloader`、`Disconnect`，
+/// 其余状态只会出现在 `get-state` 的结果中。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeviceState {
+    Device,
+    Recovery,
+    Rescue,
+    Sideload,
+    Bootloader,
+    Disconnect,
+    Offline,
+    Unauthorized,
+    Authorizing,
+    Connecting,
+    NoPermissions,
+    Host,
+}
+
+impl DeviceState {
+    /// 是否可以作为 `adb wait-for-<transport>-<state>` 的目标状态。
+    pub fn is_waitable(&self) -> bool {
+        matches!(
+            self,
+            DeviceState::Device
+                | DeviceState::Recovery
+                | DeviceState::Rescue
+                | DeviceState::Sideload
+                | DeviceState::Bootloader
+                | DeviceState::Disconnect
+        )
+    }
+}
+
+impl Display for DeviceState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let str = match self {
+            DeviceState::Device => "device",
+            DeviceState::Recovery => "recovery",
+            DeviceState::Rescue => "rescue",
+            DeviceState::Sideload => "sideload",
+            DeviceState::Bootloader => "bootloader",
+            DeviceState::Disconnect => "disconnect",
+            DeviceState::Offline => "offline",
+            DeviceState::Unauthorized => "unauthorized",
+            DeviceState::Authorizing => "authorizing",
+            DeviceState::Connecting => "connecting",
+            DeviceState::NoPermissions => "no permissions",
+            DeviceState::Host => "host",
+        };
+        write!(f, "{}", str)
+    }
+}
+
+impl FromStr for DeviceState {
+    type Err = AdbError;
+
+    /// 解析 `get-state` 的输出，`no permissions` 之后附带的说明会被忽略。
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let state = match s.trim() {
+            "device" => DeviceState::Device,
+            "recovery" => DeviceState::Recovery,
+            "rescue" => DeviceState::Rescue,
+            "sideload" => DeviceState::Sideload,
+            "bootloader" => DeviceState::Bootloader,
+            "disconnect" => DeviceState::Disconnect,
+            "offline" => DeviceState::Offline,
+            "unauthorized" => DeviceState::Unauthorized,
+            "authorizing" => DeviceState::Authorizing,
+            "connecting" => DeviceState::Connecting,
+            "host" => DeviceState::Host,
+            s if s.starts_with("no permissions") => DeviceState::NoPermissions,
+            s => return Err(AdbError::unknown(format!("unknown device state {:#?}", s))),
+        };
+        Ok(state)
+    }
+}
+
+/// 设备的连接方式，对应 `adb wait-for-<transport>-<state>` 中的 `transport`。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TransportKind {
+    Usb,
+    Local,
+    #[default]
+    Any,
+}
+
+impl Display for TransportKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let str = match self {
+            TransportKind::Usb => "usb",
+            TransportKind::Local => "local",
+            TransportKind::Any => "any",
+        };
+        write!(f, "{}", str)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_device_state() {
+        let states = [
+            DeviceState::Device,
+            DeviceState::Recovery,
+            DeviceState::Rescue,
+            DeviceState::Sideload,
+            DeviceState::Bootloader,
+            DeviceState::Disconnect,
+            DeviceState::Offline,
+            DeviceState::Unauthorized,
+            DeviceState::Authorizing,
+            DeviceState::Connecting,
+            DeviceState::NoPermissions,
+            DeviceState::Host,
+        ];
+        for state in states {
+            assert_eq!(state.to_string().parse::<DeviceState>().unwrap(), state);
+        }
+        assert_eq!(
+            "device\n".parse::<DeviceState>().unwrap(),
+            DeviceState::Device
+        );
+        assert_eq!(
+            "no permissions (missing udev rules? user is in the plugdev group); see [http://developer.android.com/tools/device.html]"
+                .parse::<DeviceState>()
+                .unwrap(),
+            DeviceState::NoPermissions
+        );
+        assert!("unknown".parse::<DeviceState>().is_err());
+    }
+
+    #[test]
+    fn test_is_waitable() {
+        assert!(DeviceState::Device.is_waitable());
+        assert!(DeviceState::Disconnect.is_waitable());
+        assert!(!DeviceState::Offline.is_waitable());
+        assert!(!DeviceState::NoPermissions.is_waitable());
+        assert!(!DeviceState::Host.is_waitable());
+    }
+}
@@ -0,0 +1,139 @@
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// 设备清单中的一项，由 `host:devices-l` 与设备属性组成。
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct DeviceSummary {
+    pub serial: String,
+    pub state: String,
+    pub transport_id: Option<u64>,
+    pub model: Option<String>,           // ro.product.model
+    pub brand: Option<String>,           // ro.product.brand
+    pub android_version: Option<String>, // ro.build.version.release
+}
+
+impl DeviceSummary {
+    /// 需要从设备读取的属性，依次对应 `model`、`brand`、`android_version`。
+    pub const PROPS: [&'static str; 3] = [
+        "ro.product.model",
+        "ro.product.brand",
+        "ro.build.version.release",
+    ];
+
+    /// 按 `PROPS` 的顺序填充属性，读取失败的属性保持为 `None`。
+    pub fn set_props(&mut self, values: [Option<String>; 3]) {
+        let [model, brand, android_version] = values;
+        self.model = model;
+        self.brand = brand;
+        self.android_version = android_version;
+    }
+}
+
+/// 解析 `host:devices-l` 的输出，例如
+/// `emulator-5554  device product:sdk_gphone64_x86_64 model:sdk_gphone64_x86_64 transport_id:1`。
+pub fn parse_device_list_long(output: &str) -> Vec<DeviceSummary> {
+    output
+        .lines()
+        .filter_map(parse_device_line)
+        .map(|line| DeviceSummary {
+            serial: line.serial.to_string(),
+            state: line.state,
+            transport_id: line
+                .qualifiers
+                .iter()
+                .find(|(key, _)| *key == "transport_id")
+                .and_then(|(_, id)| id.parse().ok()),
+            model: None,
+            brand: None,
+            android_version: None,
+        })
+        .collect()
+}
+
+/// `host:devices-l` 中的一行：序列号、状态与之后的 `key:value` 限定信息。
+pub(crate) struct DeviceLine<'a> {
+    pub serial: &'a str,
+    pub state: String,
+    pub qualifiers: Vec<(&'a str, &'a str)>,
+}
+
+/// 解析 `host:devices-l` 中的一行。
+///
+/// 状态可能由多个单词组成，例如
+/// `no permissions (missing udev rules? user is in the plugdev group); see [http://developer.android.com/tools/device.html]`，
+/// 因此状态取序列号之后、第一个 `key:value` 限定信息之前的全部内容。
+pub(crate) fn parse_device_line(line: &str) -> Option<DeviceLine<'_>> {
+    let mut parts = line.split_whitespace();
+    let serial = parts.next()?;
+    let mut state = vec![];
+    let mut qualifiers = vec![];
+    for part in parts {
+        match part.split_once(':') {
+            Some((key, value))
+                if !key.is_empty()
+                    && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') =>
+            {
+                qualifiers.push((key, value))
+            }
+            _ if qualifiers.is_empty() => state.push(part),
+            _ => {}
+        }
+    }
+    if state.is_empty() {
+        return None;
+    }
+    Some(DeviceLine {
+        serial,
+        state: state.join(" "),
+        qualifiers,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_device_list_long() {
+        let output = "emulator-5554          device product:sdk_gphone64_x86_64 model:sdk_gphone64_x86_64 device:emu64xa transport_id:1\n\
+                      0123456789ABCDEF       unauthorized usb:1-1 transport_id:3\n\
+                      192.168.1.20:5555      offline\n";
+        let devices = parse_device_list_long(output);
+        assert_eq!(devices.len(), 3);
+        assert_eq!(
+            (devices[0].serial.as_str(), devices[0].state.as_str()),
+            ("emulator-5554", "device")
+        );
+        assert_eq!(devices[0].transport_id, Some(1));
+        assert_eq!(devices[1].state, "unauthorized");
+        assert_eq!(devices[1].transport_id, Some(3));
+        assert_eq!(devices[2].transport_id, None);
+    }
+
+    #[test]
+    fn test_parse_device_list_long_no_permissions() {
+        let output = "0123456789ABCDEF       no permissions (missing udev rules? user is in the plugdev group); \
+                      see [http://developer.android.com/tools/device.html] usb:1-1 transport_id:2\n";
+        let devices = parse_device_list_long(output);
+        assert_eq!(devices.len(), 1);
+        assert_eq!(devices[0].serial, "0123456789ABCDEF");
+        assert!(devices[0]
+            .state
+            .starts_with("no permissions (missing udev rules?"));
+        assert!(devices[0].state.ends_with("device.html]"));
+        assert_eq!(devices[0].transport_id, Some(2));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_device_summary_serde_round_trip() {
+        let mut summary = parse_device_list_long("emulator-5554 device transport_id:1").remove(0);
+        summary.set_props([Some("Pixel 7".into()), Some("google".into()), None]);
+        let json = serde_json::to_string(&summary).unwrap();
+        assert_eq!(
+            serde_json::from_str::<DeviceSummary>(&json).unwrap(),
+            summary
+        );
+    }
+}
@@ -0,0 +1,48 @@
+use chrono::{DateTime, Utc};
+
+/// 生成设置设备时间（UTC）的 `date` 命令参数。
+///
+/// Android 6.0（API 23）起 `date` 由 toybox 提供，格式为 `MMDDhhmmCCYY.ss`；
+/// 更早的版本使用 toolbox，格式为 `-s YYYYMMDD.hhmmss`。
+pub(crate) fn date_set_args(sdk: u32, dt: &DateTime<Utc>) -> Vec<String> {
+    let time = if sdk >= 23 {
+        dt.format("%m%d%H%M%Y.%S").to_string()
+    } else {
+        format!("-s {}", dt.format("%Y%m%d.%H%M%S"))
+    };
+    let mut args = vec!["date".to_string(), "-u".to_string()];
+    args.extend(time.split(' ').map(str::to_string));
+    args
+}
+
+/// 设备端命令的输出表明缺少权限（例如非 root 时修改系统时间）。
+pub(crate) fn is_permission_error(output: &str) -> bool {
+    output.contains("Operation not permitted") || output.contains("Permission denied")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_date_set_args() {
+        let dt = Utc.with_ymd_and_hms(2024, 6, 15, 8, 5, 9).unwrap();
+        assert_eq!(date_set_args(34, &dt), ["date", "-u", "061508052024.09"]);
+        assert_eq!(
+            date_set_args(22, &dt),
+            ["date", "-u", "-s", "20240615.080509"]
+        );
+    }
+
+    #[test]
+    fn test_is_permission_error() {
+        assert!(is_permission_error(
+            "date: cannot set date: Operation not permitted\n"
+        ));
+        assert!(is_permission_error(
+            "/system/bin/sh: date: Permission denied"
+        ));
+        assert!(!is_permission_error("Sat Jun 15 08:05:09 UTC 2024\n"));
+    }
+}
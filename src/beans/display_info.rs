@@ -0,0 +1,198 @@
+/// 取出 `wm size` / `wm density` 输出中主屏的行。
+///
+/// 多屏设备会输出 `Display 0: Physical size: ...` 形式的行，这里只保留 0 号屏（没有时取第一个出现的屏），
+/// 并去掉 `Display N:` 前缀；单屏格式原样返回。
+fn primary_display_lines(output: &str) -> Vec<&str> {
+    let re = regex::Regex::new(r"^\s*Display\s+(\d+):\s*(.*)$").unwrap();
+    let mut tagged = vec![];
+    let mut plain = vec![];
+    for line in output.lines() {
+        match re.captures(line) {
+            Some(cap) => {
+                let id: u32 = cap[1].parse().unwrap_or(u32::MAX);
+                tagged.push((id, cap.get(2).unwrap().as_str()));
+            }
+            None => plain.push(line.trim()),
+        }
+    }
+    if tagged.is_empty() {
+        return plain;
+    }
+    let primary = if tagged.iter().any(|(id, _)| *id == 0) {
+        0
+    } else {
+        tagged[0].0
+    };
+    tagged
+        .into_iter()
+        .filter(|(id, _)| *id == primary)
+        .map(|(_, line)| line.trim())
+        .collect()
+}
+
+/// 取 `Override <key>` 的值，不存在时取 `Physical <key>` 的值。
+fn wm_value<'a>(lines: &[&'a str], key: &str) -> Option<&'a str> {
+    ["Override", "Physical"].iter().find_map(|kind| {
+        let prefix = format!("{} {}:", kind, key);
+        lines
+            .iter()
+            .find_map(|line| line.strip_prefix(prefix.as_str()))
+            .map(str::trim)
+    })
+}
+
+/// 解析 `wm size` 的输出，返回主屏的 `(宽, 高)`，存在 `Override size` 时优先使用。
+pub fn parse_wm_size(output: &str) -> Option<(u32, u32)> {
+    let lines = primary_display_lines(output);
+    let (width, height) = wm_value(&lines, "size")?.split_once('x')?;
+    Some((width.trim().parse().ok()?, height.trim().parse().ok()?))
+}
+
+/// 解析 `wm density` 的输出，返回主屏的 dpi，存在 `Override density` 时优先使用。
+pub fn parse_wm_density(output: &str) -> Option<u32> {
+    let lines = primary_display_lines(output);
+    wm_value(&lines, "density")?.parse().ok()
+}
+
+/// 解析 `dumpsys power` 的输出，判断屏幕是否点亮。
+///
+/// 不同版本的系统字段不同，按以下顺序取第一个出现的信号：
+/// `Display Power: state=ON`、`mWakefulness=Awake`、旧版本的 `mHoldingDisplaySuspendBlocker=true`。
+/// 都不存在时返回 `None`。
+pub fn parse_screen_on(output: &str) -> Option<bool> {
+    // (字段前缀, 表示点亮的值)
+    let signals = [
+        ("Display Power: state=", "ON"),
+        ("mWakefulness=", "Awake"),
+        ("mHoldingDisplaySuspendBlocker=", "true"),
+    ];
+    signals.iter().find_map(|(prefix, on)| {
+        output
+            .lines()
+            .find_map(|line| line.trim().strip_prefix(prefix))
+            .map(|value| value.split_whitespace().next() == Some(on))
+    })
+}
+
+/// 解析 `dumpsys input` 的输出，返回主屏当前的旋转角度（0、90、180、270）。
+///
+/// Android 12 及以前的版本输出 `SurfaceOrientation: N`，之后的版本在 `Viewport INTERNAL` 行中输出
+/// `orientation=N`，`N` 为 `Surface.ROTATION_*`（0-3）。
+pub fn parse_display_rotation(output: &str) -> Option<u32> {
+    let rotation = output
+        .lines()
+        .find_map(|line| line.trim().strip_prefix("SurfaceOrientation:"))
+        .or_else(|| {
+            output
+                .lines()
+                .filter(|line| line.trim_start().starts_with("Viewport INTERNAL"))
+                .find_map(|line| {
+                    line.split([',', ' '])
+                        .find_map(|field| field.strip_prefix("orientation="))
+                })
+        })?;
+    let rotation: u32 = rotation.trim().parse().ok()?;
+    (rotation < 4).then_some(rotation * 90)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_wm_size() {
+        assert_eq!(
+            parse_wm_size("Physical size: 1080x2340\n"),
+            Some((1080, 2340))
+        );
+        assert_eq!(
+            parse_wm_size("Physical size: 1080x2340\nOverride size: 720x1560\n"),
+            Some((720, 1560))
+        );
+        let multi = "Display 0: Physical size: 1080x2400
+Display 0: Override size: 1080x2000
+Display 2: Physical size: 1920x1080
+";
+        assert_eq!(parse_wm_size(multi), Some((1080, 2000)));
+        assert_eq!(
+            parse_wm_size("Display 3: Physical size: 800x600\n"),
+            Some((800, 600))
+        );
+        assert_eq!(parse_wm_size("Error: no such display\n"), None);
+    }
+
+    #[test]
+    fn test_parse_wm_density() {
+        assert_eq!(parse_wm_density("Physical density: 440\n"), Some(440));
+        assert_eq!(
+            parse_wm_density("Physical density: 440\r\nOverride density: 380\r\n"),
+            Some(380)
+        );
+        let multi = "Display 1: Physical density: 160\nDisplay 0: Physical density: 420\n";
+        assert_eq!(parse_wm_density(multi), Some(420));
+    }
+
+    #[test]
+    fn test_parse_screen_on() {
+        // Android 9，屏幕关闭
+        let android_9 = "POWER MANAGER (dumpsys power)
+
+Power Manager State:
+  mDirty=0x0
+  mWakefulness=Asleep
+  mWakefulnessChanging=false
+  mHoldingWakeLockSuspendBlocker=false
+  mHoldingDisplaySuspendBlocker=false
+
+Display Power: state=OFF
+";
+        assert_eq!(parse_screen_on(android_9), Some(false));
+
+        // Android 11 部分厂商 ROM 没有 Display Power 行
+        let android_11 = "Power Manager State:
+  mWakefulness=Awake
+  mWakefulnessChanging=false
+  mIsPowered=true
+";
+        assert_eq!(parse_screen_on(android_11), Some(true));
+
+        // Android 14：mWakefulnessRaw 不应被当作 mWakefulness
+        let android_14 = "Power Manager State:
+  Settings power_manager_constants:
+  mWakefulnessRaw=Dozing
+  mWakefulness=Dozing
+  mHoldingDisplaySuspendBlocker=true
+
+Display Power: state=ON
+";
+        assert_eq!(parse_screen_on(android_14), Some(true));
+
+        let legacy = "  mHoldingDisplaySuspendBlocker=true\n";
+        assert_eq!(parse_screen_on(legacy), Some(true));
+        assert_eq!(parse_screen_on("Can't find service: power\n"), None);
+    }
+
+    #[test]
+    fn test_parse_display_rotation() {
+        let android_11 = "  Input Device 3: virtio_input_multi_touch_1
+    Touch Input Mapper (mode - DIRECT):
+      SurfaceWidth: 2400px
+      SurfaceHeight: 1080px
+      SurfaceOrientation: 1
+";
+        assert_eq!(parse_display_rotation(android_11), Some(90));
+        let android_13 = "  Viewports:
+    Viewport INTERNAL: displayId=0, uniqueId=local:4619827259835644672, port=0, orientation=3, logicalFrame=[0, 0, 2400, 1080], physicalFrame=[0, 0, 2400, 1080], deviceSize=[1080, 2400], isActive=[1]
+    Viewport VIRTUAL: displayId=2, orientation=0
+";
+        assert_eq!(parse_display_rotation(android_13), Some(270));
+        assert_eq!(
+            parse_display_rotation("      SurfaceOrientation: 0\n"),
+            Some(0)
+        );
+        assert_eq!(
+            parse_display_rotation("INPUT MANAGER (dumpsys input)\n"),
+            None
+        );
+    }
+}
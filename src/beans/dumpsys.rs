@@ -0,0 +1,442 @@
+//! `dumpsys` 各服务输出的解析。
+//!
+//! 不同系统版本、厂商的输出格式差异较大，解析都按行查找字段名，
+//! 只把识别不了关键字段的情况当作错误，其他缺失的字段返回 `None`。
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::beans::battery_info::BatteryInfo;
+use crate::beans::display_info::parse_screen_on;
+use crate::errors::{AdbError, AdbResult};
+
+/// 在 `line` 中查找 `key=value` 形式的字段，返回 value（到下一个空白为止）。
+fn field<'a>(line: &'a str, key: &str) -> Option<&'a str> {
+    line.split_whitespace()
+        .find_map(|token| token.strip_prefix(key)?.strip_prefix('='))
+}
+
+/// 在整段输出中查找第一个 `key=value` 字段。
+fn find_field<'a>(output: &'a str, key: &str) -> Option<&'a str> {
+    output.lines().find_map(|line| field(line, key))
+}
+
+/// 在整段输出中查找第一个 `key: value` 行，返回去掉首尾空白的 value。
+fn find_colon_value<'a>(output: &'a str, key: &str) -> Option<&'a str> {
+    output.lines().find_map(|line| {
+        let (k, v) = line.split_once(':')?;
+        (k.trim() == key).then(|| v.trim())
+    })
+}
+
+/// 取字符串开头的数字部分并解析，例如 `"56 (4.54%)"`、`"8ms"`。
+fn leading_number<N: std::str::FromStr>(value: &str) -> Option<N> {
+    let end = value
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(value.len());
+    value[..end].parse().ok()
+}
+
+/// `dumpsys power` 的解析结果。
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct PowerInfo {
+    pub screen_on: Option<bool>,
+    /// `mWakefulness`，例如 `Awake`、`Asleep`、`Dozing`。
+    pub wakefulness: Option<String>,
+    /// `mIsPowered`，是否连接了电源。
+    pub is_powered: Option<bool>,
+    pub battery_level: Option<u8>,
+    /// `mStayOn`，开发者选项中的“不锁定屏幕”是否生效。
+    pub stay_on: Option<bool>,
+}
+
+impl PowerInfo {
+    /// 解析 `dumpsys power` 的输出，屏幕状态的判断规则同 [`parse_screen_on`]。
+    ///
+    /// 输出中没有任何可识别的字段时返回错误。
+    pub fn parse(output: &str) -> AdbResult<PowerInfo> {
+        let boolean = |key: &str| find_field(output, key).and_then(|v| v.parse().ok());
+        let info = PowerInfo {
+            screen_on: parse_screen_on(output),
+            wakefulness: find_field(output, "mWakefulness").map(str::to_string),
+            is_powered: boolean("mIsPowered"),
+            battery_level: find_field(output, "mBatteryLevel").and_then(|v| v.parse().ok()),
+            stay_on: boolean("mStayOn"),
+        };
+        if info == PowerInfo::default() {
+            return Err(AdbError::unknown("no known field found in dumpsys power"));
+        }
+        Ok(info)
+    }
+}
+
+/// 解析 `dumpsys battery` 的输出，同 [`BatteryInfo::parse`]。
+pub fn parse_battery(output: &str) -> AdbResult<BatteryInfo> {
+    BatteryInfo::parse(output)
+}
+
+/// `dumpsys meminfo <package>` 的解析结果，单位均为 KB。
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct MemInfo {
+    pub pss: u64,
+    pub private_dirty: u64,
+    pub private_clean: u64,
+    /// `TOTAL RSS`，Android 10 之前的版本没有。
+    pub rss: Option<u64>,
+    // 以下为 App Summary 中的 Pss，Android 6 之前的版本没有
+    pub java_heap: Option<u64>,
+    pub native_heap: Option<u64>,
+    pub code: Option<u64>,
+    pub stack: Option<u64>,
+    pub graphics: Option<u64>,
+}
+
+impl MemInfo {
+    /// 解析 `dumpsys meminfo <package>` 的输出。
+    ///
+    /// `pss`、`private_dirty`、`private_clean` 取自明细表的 `TOTAL` 行（各版本前三列相同），
+    /// 进程不存在（`No process found for: ...`）或没有 `TOTAL` 行时返回错误。
+    pub fn parse(output: &str) -> AdbResult<MemInfo> {
+        let total = output
+            .lines()
+            .find_map(|line| {
+                let mut tokens = line.split_whitespace();
+                if tokens.next() != Some("TOTAL") {
+                    return None;
+                }
+                let values: Vec<u64> = tokens.map_while(|t| t.parse().ok()).collect();
+                (values.len() >= 3).then_some(values)
+            })
+            .ok_or_else(|| {
+                AdbError::unknown(format!(
+                    "no TOTAL row found in dumpsys meminfo: {}",
+                    output.lines().find(|l| !l.trim().is_empty()).unwrap_or("")
+                ))
+            })?;
+        let summary = |key: &str| find_colon_value(output, key).and_then(leading_number);
+        Ok(MemInfo {
+            pss: total[0],
+            private_dirty: total[1],
+            private_clean: total[2],
+            rss: output.lines().find_map(|line| {
+                let (_, rest) = line.split_once("TOTAL RSS:")?;
+                leading_number(rest.trim_start())
+            }),
+            java_heap: summary("Java Heap"),
+            native_heap: summary("Native Heap"),
+            code: summary("Code"),
+            stack: summary("Stack"),
+            graphics: summary("Graphics"),
+        })
+    }
+}
+
+/// `dumpsys gfxinfo <package>` 中的帧耗时统计，百分位耗时单位为毫秒。
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct GfxInfo {
+    pub total_frames: u64,
+    pub janky_frames: u64,
+    /// 卡顿帧占比（百分比），没有渲染任何帧时为 0。
+    pub janky_percent: f64,
+    pub percentile_50: Option<u32>,
+    pub percentile_90: Option<u32>,
+    pub percentile_95: Option<u32>,
+    pub percentile_99: Option<u32>,
+    pub missed_vsync: Option<u64>,
+    pub high_input_latency: Option<u64>,
+    pub slow_ui_thread: Option<u64>,
+    pub slow_bitmap_uploads: Option<u64>,
+    pub slow_draw_commands: Option<u64>,
+    /// `Number Frame deadline missed`，Android 12 起才有。
+    pub frame_deadline_missed: Option<u64>,
+}
+
+impl GfxInfo {
+    /// 解析 `dumpsys gfxinfo <package>` 的输出。
+    ///
+    /// `Total frames rendered` 与 `Janky frames` 是必需的，应用未运行时输出中没有这两项，返回错误。
+    pub fn parse(output: &str) -> AdbResult<GfxInfo> {
+        let value = |key: &str| find_colon_value(output, key);
+        let number = |key: &str| value(key).and_then(leading_number::<u64>);
+        let percentile = |key: &str| value(key).and_then(leading_number::<u32>);
+        let required = |key: &str| {
+            number(key)
+                .ok_or_else(|| AdbError::unknown(format!("{} not found in dumpsys gfxinfo", key)))
+        };
+        let total_frames = required("Total frames rendered")?;
+        let janky_frames = required("Janky frames")?;
+        let janky_percent = if total_frames == 0 {
+            0.0
+        } else {
+            janky_frames as f64 * 100.0 / total_frames as f64
+        };
+        Ok(GfxInfo {
+            total_frames,
+            janky_frames,
+            janky_percent,
+            percentile_50: percentile("50th percentile"),
+            percentile_90: percentile("90th percentile"),
+            percentile_95: percentile("95th percentile"),
+            percentile_99: percentile("99th percentile"),
+            missed_vsync: number("Number Missed Vsync"),
+            high_input_latency: number("Number High input latency"),
+            slow_ui_thread: number("Number Slow UI thread"),
+            slow_bitmap_uploads: number("Number Slow bitmap uploads"),
+            slow_draw_commands: number("Number Slow issue draw commands"),
+            frame_deadline_missed: number("Number Frame deadline missed"),
+        })
+    }
+}
+
+/// `dumpsys SurfaceFlinger` 中 `GLES:` 行描述的 GPU 信息。
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct GpuInfo {
+    pub vendor: String,
+    pub renderer: String,
+    /// 驱动版本，例如 `OpenGL ES 3.2 V@415.0 (GIT@663be55, ...)`，本身可能包含逗号。
+    pub version: String,
+    /// `GLES:` 下一行列出的 OpenGL ES 扩展。
+    pub extensions: Vec<String>,
+}
+
+impl GpuInfo {
+    /// 解析 `dumpsys SurfaceFlinger` 的输出：`GLES:` 行依次为逗号分隔的厂商、渲染器和版本，
+    /// 扩展列表在下一行，以空格分隔。
+    ///
+    /// 输出中没有 `GLES:` 行时返回错误。
+    pub fn parse(output: &str) -> AdbResult<GpuInfo> {
+        let mut lines = output.lines();
+        let gles = lines
+            .by_ref()
+            .find_map(|line| line.trim().strip_prefix("GLES:"))
+            .ok_or_else(|| AdbError::unknown("GLES not found in dumpsys SurfaceFlinger"))?;
+        let mut fields = gles.splitn(3, ',').map(|field| field.trim().to_string());
+        let mut next = || fields.next().unwrap_or_default();
+        let (vendor, renderer, version) = (next(), next(), next());
+        let extensions = lines
+            .next()
+            .map(str::trim)
+            .filter(|line| line.starts_with("GL_"))
+            .map(|line| line.split_whitespace().map(String::from).collect())
+            .unwrap_or_default();
+        Ok(GpuInfo {
+            vendor,
+            renderer,
+            version,
+            extensions,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const POWER_ANDROID_13: &str = "POWER MANAGER (dumpsys power)
+
+Power Manager State:
+  Settings power_manager_constants:
+    no_cached_wake_locks=true
+  mDirty=0x0
+  mWakefulness=Awake
+  mWakefulnessChanging=false
+  mIsPowered=true
+  mPlugType=2
+  mBatteryLevel=87
+  mStayOn=false
+  mHoldingDisplaySuspendBlocker=true
+
+Display Power: state=ON
+";
+
+    #[test]
+    fn test_parse_power() {
+        let power = PowerInfo::parse(POWER_ANDROID_13).unwrap();
+        assert_eq!(
+            power,
+            PowerInfo {
+                screen_on: Some(true),
+                wakefulness: Some("Awake".to_string()),
+                is_powered: Some(true),
+                battery_level: Some(87),
+                stay_on: Some(false),
+            }
+        );
+        let asleep = PowerInfo::parse("  mWakefulness=Asleep\n").unwrap();
+        assert_eq!(asleep.screen_on, Some(false));
+        assert_eq!(asleep.battery_level, None);
+        assert!(PowerInfo::parse("Can't find service: power\n").is_err());
+    }
+
+    #[test]
+    fn test_parse_battery() {
+        let battery = parse_battery("Current Battery Service state:\n  level: 42\n  scale: 100\n");
+        assert_eq!(battery.unwrap().level, 42);
+    }
+
+    const MEMINFO_ANDROID_13: &str = "Applications Memory Usage (in Kilobytes):
+Uptime: 1862474 Realtime: 1862474
+
+** MEMINFO in pid 4321 [com.example.app] **
+                   Pss  Private  Private  SwapPss      Rss     Heap     Heap     Heap
+                 Total    Dirty    Clean    Dirty    Total     Size    Alloc     Free
+                ------   ------   ------   ------   ------   ------   ------   ------
+  Native Heap    10468    10408        0        0    12228    21504    13620     7883
+  Dalvik Heap     2172     2068        0        0     6456     3619     1810     1809
+        TOTAL    45670    38102     4256        0    84216    25123    15430     9692
+
+ App Summary
+                       Pss(KB)                        Rss(KB)
+                        ------                         ------
+           Java Heap:     4104                          13240
+         Native Heap:    10408                          12228
+                Code:     5652                          38232
+               Stack:      480                            488
+            Graphics:    18832                          18832
+       Private Other:     1872
+              System:     4322
+             Unknown:                                    1196
+
+           TOTAL PSS:    45670            TOTAL RSS:    84216       TOTAL SWAP PSS:        0
+";
+
+    #[test]
+    fn test_parse_meminfo() {
+        let mem = MemInfo::parse(MEMINFO_ANDROID_13).unwrap();
+        assert_eq!(
+            mem,
+            MemInfo {
+                pss: 45670,
+                private_dirty: 38102,
+                private_clean: 4256,
+                rss: Some(84216),
+                java_heap: Some(4104),
+                native_heap: Some(10408),
+                code: Some(5652),
+                stack: Some(480),
+                graphics: Some(18832),
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_meminfo_android_5() {
+        let output = "** MEMINFO in pid 1234 [com.example.app] **
+                   Pss  Private  Private  Swapped     Heap     Heap     Heap
+                 Total    Dirty    Clean    Dirty     Size    Alloc     Free
+                ------   ------   ------   ------   ------   ------   ------
+  Native Heap     3044     2996        0        0     8192     4830     3361
+        TOTAL    20101    14196     2412        0    23232    16482     6749
+";
+        let mem = MemInfo::parse(output).unwrap();
+        assert_eq!(
+            (mem.pss, mem.private_dirty, mem.private_clean),
+            (20101, 14196, 2412)
+        );
+        assert_eq!(mem.rss, None);
+        assert_eq!(mem.java_heap, None);
+        let missing = MemInfo::parse("No process found for: com.missing\n").unwrap_err();
+        assert!(missing.to_string().contains("No process found"));
+    }
+
+    const GFXINFO_ANDROID_13: &str = "Applications Graphics Acceleration Info:
+Uptime: 1862474 Realtime: 1862474
+
+** Graphics info for pid 4321 [com.example.app] **
+
+Stats since: 1835236029155ns
+Total frames rendered: 1234
+Janky frames: 56 (4.54%)
+Janky frames (legacy): 50 (4.05%)
+50th percentile: 8ms
+90th percentile: 14ms
+95th percentile: 18ms
+99th percentile: 32ms
+GPU 50th percentile: 3ms
+GPU 90th percentile: 6ms
+Number Missed Vsync: 10
+Number High input latency: 5
+Number Slow UI thread: 20
+Number Slow bitmap uploads: 1
+Number Slow issue draw commands: 3
+Number Frame deadline missed: 7
+HISTOGRAM: 5ms=100 6ms=200
+";
+
+    #[test]
+    fn test_parse_gfxinfo() {
+        let gfx = GfxInfo::parse(GFXINFO_ANDROID_13).unwrap();
+        assert_eq!(gfx.total_frames, 1234);
+        assert_eq!(gfx.janky_frames, 56);
+        assert!((gfx.janky_percent - 4.538).abs() < 0.01);
+        assert_eq!(
+            (
+                gfx.percentile_50,
+                gfx.percentile_90,
+                gfx.percentile_95,
+                gfx.percentile_99
+            ),
+            (Some(8), Some(14), Some(18), Some(32))
+        );
+        assert_eq!(gfx.missed_vsync, Some(10));
+        assert_eq!(gfx.high_input_latency, Some(5));
+        assert_eq!(gfx.slow_ui_thread, Some(20));
+        assert_eq!(gfx.slow_bitmap_uploads, Some(1));
+        assert_eq!(gfx.slow_draw_commands, Some(3));
+        assert_eq!(gfx.frame_deadline_missed, Some(7));
+    }
+
+    #[test]
+    fn test_parse_gfxinfo_without_frames() {
+        let gfx = GfxInfo::parse("Total frames rendered: 0\nJanky frames: 0 (0.00%)\n").unwrap();
+        assert_eq!(gfx.janky_percent, 0.0);
+        assert_eq!(gfx.percentile_90, None);
+        assert_eq!(gfx.frame_deadline_missed, None);
+        assert!(GfxInfo::parse("No process found for: com.missing\n").is_err());
+    }
+
+    const SURFACE_FLINGER_ADRENO: &str = "Build configuration: [sf PRESENT_TIME_OFFSET=0 FORCE_HWC_FOR_RBG_TO_YUV=1 MAX_VIRT_DISPLAY_DIM=4096 RUNNING_WITHOUT_SYNC_FRAMEWORK=0 NUM_FRAMEBUFFER_SURFACE_BUFFERS=3]
+Sync configuration: [using: EGL_ANDROID_native_fence_sync EGL_KHR_wait_sync]
+
+DisplayManager State:
+------------RE GLES------------
+EGL implementation : 1.5
+EGL_ANDROID_front_buffer_auto_refresh EGL_ANDROID_get_native_client_buffer EGL_KHR_fence_sync
+GLES: Qualcomm, Adreno (TM) 640, OpenGL ES 3.2 V@415.0 (GIT@663be55, I724753c5e3, 1573037262) (Date:11/06/19)
+GL_OES_EGL_image GL_OES_EGL_image_external GL_OES_EGL_sync GL_OES_vertex_half_float
+RenderEngine supports protected context: 1
+";
+
+    #[test]
+    fn test_parse_gpu_info() {
+        let gpu = GpuInfo::parse(SURFACE_FLINGER_ADRENO).unwrap();
+        assert_eq!(gpu.vendor, "Qualcomm");
+        assert_eq!(gpu.renderer, "Adreno (TM) 640");
+        assert_eq!(
+            gpu.version,
+            "OpenGL ES 3.2 V@415.0 (GIT@663be55, I724753c5e3, 1573037262) (Date:11/06/19)"
+        );
+        assert_eq!(
+            gpu.extensions,
+            [
+                "GL_OES_EGL_image",
+                "GL_OES_EGL_image_external",
+                "GL_OES_EGL_sync",
+                "GL_OES_vertex_half_float"
+            ]
+        );
+
+        let emulator = GpuInfo::parse("GLES: Google (Google Inc.), Android Emulator OpenGL ES Translator (Google SwiftShader), OpenGL ES 3.0 (OpenGL ES 3.0 SwiftShader 4.0.0.1)\n").unwrap();
+        assert_eq!(emulator.vendor, "Google (Google Inc.)");
+        assert_eq!(
+            emulator.version,
+            "OpenGL ES 3.0 (OpenGL ES 3.0 SwiftShader 4.0.0.1)"
+        );
+        assert!(emulator.extensions.is_empty());
+        assert!(GpuInfo::parse("SurfaceFlinger is not running\n").is_err());
+    }
+}
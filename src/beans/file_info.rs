@@ -1,14 +1,44 @@
 use anyhow::{anyhow, Result};
 use chrono::Utc;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
 use std::convert::TryInto;
 
+/// 文件类型位掩码。
+pub const S_IFMT: u32 = 0o170000;
+/// 普通文件类型位。
+pub const S_IFREG: u32 = 0o100000;
+/// 目录类型位。
+pub const S_IFDIR: u32 = 0o040000;
+/// 符号链接类型位。
+pub const S_IFLNK: u32 = 0o120000;
+/// 块设备类型位。
+pub const S_IFBLK: u32 = 0o060000;
+/// 字符设备类型位。
+pub const S_IFCHR: u32 = 0o020000;
+/// 命名管道类型位。
+pub const S_IFIFO: u32 = 0o010000;
+/// 套接字类型位。
+pub const S_IFSOCK: u32 = 0o140000;
+
+/// v2 `STA2` 响应与 `DNT2` 目录项中文件信息部分的长度（不含 4 字节命令和文件名长度）。
+pub const STAT_V2_LEN: usize = 68;
+
 #[derive(Debug, PartialEq, PartialOrd, Ord, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct FileInfo {
     pub mode: u32,
     pub size: u32,
     pub mtime: u32,
     pub mdtime: Option<chrono::DateTime<Utc>>,
     pub path: String,
+    // 以下字段只在设备支持 sync v2（stat_v2 / ls_v2）时填充
+    pub size64: Option<u64>, // 完整的 64 位文件大小，`size` 超过 u32 时会被截断
+    pub uid: Option<u32>,
+    pub gid: Option<u32>,
+    pub atime: Option<i64>,
+    pub ctime: Option<i64>,
+    pub error: Option<u32>, // 设备端 stat 失败时的 errno，成功为 0
 }
 
 pub fn parse_file_info<T: ToString>(data: Vec<u8>, path: T) -> Result<FileInfo> {
@@ -27,6 +57,44 @@ pub fn parse_file_info<T: ToString>(data: Vec<u8>, path: T) -> Result<FileInfo>
     Ok(FileInfo::new(mode, size, mtime, mdtime, path.to_string()))
 }
 
+/// 解析 v2 sync 协议的文件信息：error、dev、ino、mode、nlink、uid、gid、size、atime、mtime、ctime。
+pub fn parse_file_info_v2<T: ToString>(data: &[u8], path: T) -> Result<FileInfo> {
+    if data.len() < STAT_V2_LEN {
+        return Err(anyhow!(
+            "truncated v2 stat: expected {} bytes, got {}",
+            STAT_V2_LEN,
+            data.len()
+        ));
+    }
+    let u32_at = |offset: usize| u32::from_le_bytes(data[offset..offset + 4].try_into().unwrap());
+    let i64_at = |offset: usize| i64::from_le_bytes(data[offset..offset + 8].try_into().unwrap());
+    let size = u64::from_le_bytes(data[36..44].try_into()?);
+    let mtime = i64_at(52);
+    let mut file_info = FileInfo::new(
+        u32_at(20),
+        size.min(u32::MAX as u64) as u32,
+        mtime.clamp(0, u32::MAX as i64) as u32,
+        chrono::DateTime::<Utc>::from_timestamp(mtime, 0),
+        path.to_string(),
+    );
+    file_info.size64 = Some(size);
+    file_info.uid = Some(u32_at(28));
+    file_info.gid = Some(u32_at(32));
+    file_info.atime = Some(i64_at(44));
+    file_info.ctime = Some(i64_at(60));
+    file_info.error = Some(u32_at(0));
+    Ok(file_info)
+}
+
+/// 解析目录项的原始数据，按长度区分 v1（16 字节）与 v2（72 字节）格式。
+pub fn parse_dent<T: ToString>(data: Vec<u8>, path: T) -> Result<FileInfo> {
+    if data.len() > STAT_V2_LEN {
+        parse_file_info_v2(&data, path)
+    } else {
+        parse_file_info(data, path)
+    }
+}
+
 impl FileInfo {
     fn new(
         mode: u32,
@@ -41,6 +109,178 @@ impl FileInfo {
             mtime,
             mdtime,
             path,
+            size64: None,
+            uid: None,
+            gid: None,
+            atime: None,
+            ctime: None,
+            error: None,
+        }
+    }
+
+    fn file_type(&self) -> u32 {
+        self.mode & S_IFMT
+    }
+
+    pub fn is_dir(&self) -> bool {
+        self.file_type() == S_IFDIR
+    }
+
+    pub fn is_file(&self) -> bool {
+        self.file_type() == S_IFREG
+    }
+
+    pub fn is_symlink(&self) -> bool {
+        self.file_type() == S_IFLNK
+    }
+
+    pub fn is_block_device(&self) -> bool {
+        self.file_type() == S_IFBLK
+    }
+
+    pub fn is_char_device(&self) -> bool {
+        self.file_type() == S_IFCHR
+    }
+
+    pub fn is_fifo(&self) -> bool {
+        self.file_type() == S_IFIFO
+    }
+
+    pub fn is_socket(&self) -> bool {
+        self.file_type() == S_IFSOCK
+    }
+
+    /// 权限位（低 12 位，含 setuid/setgid/sticky）。
+    pub fn permissions(&self) -> u16 {
+        (self.mode & 0o7777) as u16
+    }
+
+    /// 与 `ls -l` 相同的权限字符串，例如 `drwxr-xr-x`、`-rw-r--r--`、`lrwxrwxrwx`。
+    pub fn permissions_string(&self) -> String {
+        let type_char = match self.file_type() {
+            S_IFDIR => 'd',
+            S_IFLNK => 'l',
+            S_IFBLK => 'b',
+            S_IFCHR => 'c',
+            S_IFIFO => 'p',
+            S_IFSOCK => 's',
+            _ => '-',
+        };
+        let mode = self.mode;
+        // (读, 写, 执行位, 特殊位, 特殊位+执行, 特殊位无执行)
+        let groups = [
+            (0o400, 0o200, 0o100, 0o4000, 's', 'S'),
+            (0o040, 0o020, 0o010, 0o2000, 's', 'S'),
+            (0o004, 0o002, 0o001, 0o1000, 't', 'T'),
+        ];
+        let mut result = String::with_capacity(10);
+        result.push(type_char);
+        for (read, write, exec, special, set_exec, set_no_exec) in groups {
+            result.push(if mode & read != 0 { 'r' } else { '-' });
+            result.push(if mode & write != 0 { 'w' } else { '-' });
+            result.push(match (mode & exec != 0, mode & special != 0) {
+                (true, true) => set_exec,
+                (false, true) => set_no_exec,
+                (true, false) => 'x',
+                (false, false) => '-',
+            });
         }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 设备对 `/sdcard/big.bin`（5 GiB，uid 1000）返回的 `STA2` 数据。
+    fn stat_v2_frame() -> Vec<u8> {
+        let mut data = vec![];
+        data.extend_from_slice(&0u32.to_le_bytes()); // error
+        data.extend_from_slice(&0xfd00u64.to_le_bytes()); // dev
+        data.extend_from_slice(&1234u64.to_le_bytes()); // ino
+        data.extend_from_slice(&0o100644u32.to_le_bytes()); // mode
+        data.extend_from_slice(&1u32.to_le_bytes()); // nlink
+        data.extend_from_slice(&1000u32.to_le_bytes()); // uid
+        data.extend_from_slice(&1015u32.to_le_bytes()); // gid
+        data.extend_from_slice(&(5u64 << 30).to_le_bytes()); // size
+        data.extend_from_slice(&1_700_000_001i64.to_le_bytes()); // atime
+        data.extend_from_slice(&1_700_000_002i64.to_le_bytes()); // mtime
+        data.extend_from_slice(&1_700_000_003i64.to_le_bytes()); // ctime
+        data
+    }
+
+    #[test]
+    fn test_parse_file_info_v2() {
+        let info = parse_file_info_v2(&stat_v2_frame(), "/sdcard/big.bin").unwrap();
+        assert_eq!(info.mode, 0o100644);
+        assert_eq!(info.size, u32::MAX);
+        assert_eq!(info.size64, Some(5 << 30));
+        assert_eq!(info.mtime, 1_700_000_002);
+        assert_eq!((info.uid, info.gid), (Some(1000), Some(1015)));
+        assert_eq!(
+            (info.atime, info.ctime),
+            (Some(1_700_000_001), Some(1_700_000_003))
+        );
+        assert_eq!(info.error, Some(0));
+        assert!(parse_file_info_v2(&stat_v2_frame()[..40], "/sdcard/big.bin").is_err());
+    }
+
+    #[test]
+    fn test_parse_dent_by_length() {
+        let mut dent_v2 = stat_v2_frame();
+        dent_v2.extend_from_slice(&7u32.to_le_bytes());
+        assert_eq!(
+            parse_dent(dent_v2, "big.bin").unwrap().size64,
+            Some(5 << 30)
+        );
+
+        let mut dent_v1 = vec![];
+        for value in [0o100644u32, 3, 1_700_000_000, 5] {
+            dent_v1.extend_from_slice(&value.to_le_bytes());
+        }
+        let info = parse_dent(dent_v1, "a.txt").unwrap();
+        assert_eq!((info.size, info.size64), (3, None));
+    }
+
+    fn with_mode(mode: u32) -> FileInfo {
+        FileInfo::new(mode, 0, 0, None, "x".to_string())
+    }
+
+    #[test]
+    fn test_mode_helpers() {
+        let dir = with_mode(0o040755);
+        assert!(dir.is_dir() && !dir.is_file() && !dir.is_symlink());
+        assert_eq!(dir.permissions(), 0o755);
+        assert_eq!(dir.permissions_string(), "drwxr-xr-x");
+
+        let file = with_mode(0o100644);
+        assert!(file.is_file() && !file.is_dir());
+        assert_eq!(file.permissions(), 0o644);
+        assert_eq!(file.permissions_string(), "-rw-r--r--");
+
+        let link = with_mode(0o120777);
+        assert!(link.is_symlink() && !link.is_file());
+        assert_eq!(link.permissions_string(), "lrwxrwxrwx");
+
+        assert!(with_mode(0o060660).is_block_device());
+        assert!(with_mode(0o020666).is_char_device());
+        assert!(with_mode(0o010600).is_fifo());
+        assert!(with_mode(0o140777).is_socket());
+        assert_eq!(with_mode(0o041777).permissions_string(), "drwxrwxrwt");
+        assert_eq!(with_mode(0o104754).permissions_string(), "-rwsr-xr--");
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_file_info_serde_round_trip() {
+        let info = parse_file_info_v2(&stat_v2_frame(), "/sdcard/big.bin").unwrap();
+        let json = serde_json::to_string(&info).unwrap();
+        assert_eq!(serde_json::from_str::<FileInfo>(&json).unwrap(), info);
+
+        // v1 结果中缺失的 v2 字段反序列化为 None
+        let v1 = r#"{"mode":33188,"size":3,"mtime":0,"mdtime":null,"path":"a.txt"}"#;
+        let info = serde_json::from_str::<FileInfo>(v1).unwrap();
+        assert_eq!((info.size, info.uid), (3, None));
     }
 }
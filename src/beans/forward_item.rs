@@ -1,8 +1,12 @@
-#[derive(Debug)]
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct ForwardItem {
-    pub(crate) serial: String,
-    pub(crate) local: String,
-    pub(crate) remote: String,
+    pub serial: String,
+    pub local: String,
+    pub remote: String,
 }
 
 impl ForwardItem {
@@ -16,4 +20,70 @@ impl ForwardItem {
             remote: remote.to_string(),
         }
     }
+
+    /// `local` 为 `tcp:<port>` 时返回端口号。
+    pub fn local_port(&self) -> Option<u16> {
+        tcp_port(&self.local)
+    }
+
+    /// `remote` 为 `tcp:<port>` 时返回端口号。
+    pub fn remote_port(&self) -> Option<u16> {
+        tcp_port(&self.remote)
+    }
+}
+
+/// 解析 `tcp:<port>` 形式的转发地址，其他类型（`localabstract:` 等）返回 `None`。
+pub fn tcp_port(spec: &str) -> Option<u16> {
+    spec.strip_prefix("tcp:")?.trim().parse().ok()
+}
+
+/// 解析 `host-serial:<serial>:list-forward` 的输出，每行为 `<serial> <local> <remote>`。
+///
+/// 列之间可以是空格或制表符；不是三列或者不像转发地址的行（例如表头）会被忽略。
+pub fn parse_forward_list(output: &str) -> Vec<ForwardItem> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let parts: Vec<&str> = line.split_whitespace().collect();
+            match parts.as_slice() {
+                [serial, local, remote] if local.contains(':') && remote.contains(':') => {
+                    Some(ForwardItem::new(*serial, *local, *remote))
+                }
+                _ => None,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_forward_list() {
+        let output = "List of forwards:\n\
+                      emulator-5554 tcp:8000 tcp:9000\n\
+                      emulator-5554\ttcp:27183\tlocalabstract:scrcpy\n\
+                      192.168.1.20:5555  tcp:6100  tcp:6100\n\
+                      \n";
+        let items = parse_forward_list(output);
+        assert_eq!(items.len(), 3);
+        assert_eq!(
+            items[1],
+            ForwardItem::new("emulator-5554", "tcp:27183", "localabstract:scrcpy")
+        );
+        assert_eq!(items[1].local_port(), Some(27183));
+        assert_eq!(items[1].remote_port(), None);
+        assert_eq!(items[2].serial, "192.168.1.20:5555");
+        assert_eq!(items[2].remote_port(), Some(6100));
+        assert_eq!(tcp_port("tcp:70000"), None);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_forward_item_serde_round_trip() {
+        let item = ForwardItem::new("emulator-5554", "tcp:8000", "tcp:9000");
+        let json = serde_json::to_string(&item).unwrap();
+        assert_eq!(serde_json::from_str::<ForwardItem>(&json).unwrap(), item);
+    }
 }
@@ -0,0 +1,104 @@
+/// `pm install` 的安装选项，默认与 `adb install -r -t` 相同。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InstallOptions {
+    pub replace: bool,           // 覆盖安装已存在的应用（-r）
+    pub allow_test: bool,        // 允许安装 testOnly 的应用（-t）
+    pub grant_permissions: bool, // 授予所有运行时权限（-g）
+    pub allow_downgrade: bool,   // 允许降级安装（-d）
+    pub sdcard: bool,            // 安装到 SD 卡（-s）
+    pub user: Option<String>,    // 安装到指定用户（--user）
+    pub stream_download: bool,   // 从 URL 安装时把下载内容直接推送到设备，不落本地临时文件
+}
+
+impl Default for InstallOptions {
+    fn default() -> Self {
+        InstallOptions {
+            replace: true,
+            allow_test: true,
+            grant_permissions: false,
+            allow_downgrade: false,
+            sdcard: false,
+            user: None,
+            stream_download: false,
+        }
+    }
+}
+
+impl InstallOptions {
+    /// 转换为 `pm install` / `pm install-create` 的选项参数，`stream_download` 不影响参数。
+    pub fn to_args(&self) -> Vec<String> {
+        let mut args = vec![];
+        for (enabled, flag) in [
+            (self.replace, "-r"),
+            (self.allow_test, "-t"),
+            (self.grant_permissions, "-g"),
+            (self.allow_downgrade, "-d"),
+            (self.sdcard, "-s"),
+        ] {
+            if enabled {
+                args.push(flag.to_string());
+            }
+        }
+        if let Some(user) = &self.user {
+            args.push("--user".to_string());
+            args.push(user.clone());
+        }
+        args
+    }
+}
+
+/// 从 `pm` 的输出中取出失败原因，例如 `Failure [INSTALL_FAILED_VERSION_DOWNGRADE]` 中括号内的部分。
+///
+/// 输出中没有 `Failure [...]` 时取第一行 `Error: ...` 的内容。
+pub fn parse_pm_failure(output: &str) -> Option<String> {
+    let re = regex::Regex::new(r"Failure \[(.+?)\]").unwrap();
+    if let Some(cap) = re.captures(output) {
+        return Some(cap[1].to_string());
+    }
+    output
+        .lines()
+        .find_map(|line| line.trim().strip_prefix("Error:"))
+        .map(|reason| reason.trim().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_args() {
+        assert_eq!(InstallOptions::default().to_args(), vec!["-r", "-t"]);
+        let options = InstallOptions {
+            replace: false,
+            grant_permissions: true,
+            allow_downgrade: true,
+            user: Some("10".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(options.to_args().join(" "), "-t -g -d --user 10");
+    }
+
+    #[test]
+    fn test_parse_pm_failure() {
+        assert_eq!(
+            parse_pm_failure(
+                "Performing Streamed Install\nFailure [INSTALL_FAILED_VERSION_DOWNGRADE]\n"
+            ),
+            Some("INSTALL_FAILED_VERSION_DOWNGRADE".to_string())
+        );
+        assert_eq!(
+            parse_pm_failure(
+                "Failure [INSTALL_FAILED_ALREADY_EXISTS: Attempt to re-install com.example.app without first uninstalling.]"
+            ),
+            Some(
+                "INSTALL_FAILED_ALREADY_EXISTS: Attempt to re-install com.example.app without first uninstalling."
+                    .to_string()
+            )
+        );
+        assert_eq!(
+            parse_pm_failure("Error: Unknown option: -x\n"),
+            Some("Unknown option: -x".to_string())
+        );
+        assert_eq!(parse_pm_failure("Success\n"), None);
+    }
+}
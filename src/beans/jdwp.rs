@@ -0,0 +1,33 @@
+/// 解析 `jdwp`/`track-jdwp` 服务返回的可调试进程列表，每行一个 PID。
+///
+/// `jdwp` 服务直接发送列表，`track-jdwp` 的每次更新带有 4 位十六进制的长度前缀；
+/// 前缀与后续内容的长度一致时会被去掉，因此两种格式都可以直接传入。
+pub fn parse_jdwp_pids(data: &str) -> Vec<u32> {
+    let body = match (data.get(..4), data.get(4..)) {
+        (Some(prefix), Some(rest))
+            if prefix.bytes().all(|b| b.is_ascii_hexdigit())
+                && usize::from_str_radix(prefix, 16).ok() == Some(rest.len()) =>
+        {
+            rest
+        }
+        _ => data,
+    };
+    body.split_whitespace()
+        .filter_map(|pid| pid.parse().ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_jdwp_pids() {
+        assert_eq!(parse_jdwp_pids("1234\n5678\n"), [1234, 5678]);
+        assert_eq!(parse_jdwp_pids("000a1234\n5678\n"), [1234, 5678]);
+        // 长度不符时 4 位数字按普通 PID 处理
+        assert_eq!(parse_jdwp_pids("1234\n"), [1234]);
+        assert!(parse_jdwp_pids("").is_empty());
+        assert!(parse_jdwp_pids("0000").is_empty());
+    }
+}
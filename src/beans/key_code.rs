@@ -0,0 +1,115 @@
+use std::fmt::Display;
+
+/// 常用的 Android 按键码，`Display` 输出 `input keyevent` 可以接受的数字形式。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyCode {
+    Home,
+    Back,
+    Call,
+    EndCall,
+    DpadUp,
+    DpadDown,
+    DpadLeft,
+    DpadRight,
+    DpadCenter,
+    VolumeUp,
+    VolumeDown,
+    VolumeMute,
+    Power,
+    Camera,
+    Tab,
+    Space,
+    Enter,
+    Del,
+    ForwardDel,
+    Escape,
+    Menu,
+    Search,
+    MediaPlayPause,
+    MediaNext,
+    MediaPrevious,
+    PageUp,
+    PageDown,
+    MoveHome,
+    MoveEnd,
+    AppSwitch,
+    Sleep,
+    Wakeup,
+    Cut,
+    Copy,
+    Paste,
+}
+
+impl KeyCode {
+    /// 对应 `android.view.KeyEvent` 中 `KEYCODE_*` 的数值。
+    pub fn code(&self) -> u32 {
+        match self {
+            KeyCode::Home => 3,
+            KeyCode::Back => 4,
+            KeyCode::Call => 5,
+            KeyCode::EndCall => 6,
+            KeyCode::DpadUp => 19,
+            KeyCode::DpadDown => 20,
+            KeyCode::DpadLeft => 21,
+            KeyCode::DpadRight => 22,
+            KeyCode::DpadCenter => 23,
+            KeyCode::VolumeUp => 24,
+            KeyCode::VolumeDown => 25,
+            KeyCode::VolumeMute => 164,
+            KeyCode::Power => 26,
+            KeyCode::Camera => 27,
+            KeyCode::Tab => 61,
+            KeyCode::Space => 62,
+            KeyCode::Enter => 66,
+            KeyCode::Del => 67,
+            KeyCode::ForwardDel => 112,
+            KeyCode::Escape => 111,
+            KeyCode::Menu => 82,
+            KeyCode::Search => 84,
+            KeyCode::MediaPlayPause => 85,
+            KeyCode::MediaNext => 87,
+            KeyCode::MediaPrevious => 88,
+            KeyCode::PageUp => 92,
+            KeyCode::PageDown => 93,
+            KeyCode::MoveHome => 122,
+            KeyCode::MoveEnd => 123,
+            KeyCode::AppSwitch => 187,
+            KeyCode::Sleep => 223,
+            KeyCode::Wakeup => 224,
+            KeyCode::Cut => 277,
+            KeyCode::Copy => 278,
+            KeyCode::Paste => 279,
+        }
+    }
+}
+
+impl Display for KeyCode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.code())
+    }
+}
+
+/// 依次发送多个按键的 `input keyevent` 参数。
+pub fn keyevent_args<T: ToString>(codes: &[T]) -> Vec<String> {
+    let mut args = vec!["input".to_string(), "keyevent".to_string()];
+    args.extend(codes.iter().map(|code| code.to_string()));
+    args
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keyevent_args() {
+        assert_eq!(
+            keyevent_args(&[KeyCode::Back, KeyCode::Home]),
+            vec!["input", "keyevent", "4", "3"]
+        );
+        assert_eq!(
+            keyevent_args(&["KEYCODE_MENU", "224"]),
+            vec!["input", "keyevent", "KEYCODE_MENU", "224"]
+        );
+        assert_eq!(KeyCode::Wakeup.to_string(), "224");
+    }
+}
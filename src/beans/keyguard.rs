@@ -0,0 +1,113 @@
+/// 解析 `dumpsys window` 的输出，判断锁屏界面（keyguard）是否显示。
+///
+/// 不同版本的系统字段不同：`mDreamingLockscreen`、`mShowingLockscreen`（Android 9 及以前）、
+/// `isStatusBarKeyguard`、`mKeyguardShowing`，任一字段为 `true` 即视为锁定。
+/// 都不存在时返回 `None`。
+pub fn parse_keyguard_locked(output: &str) -> Option<bool> {
+    const FIELDS: [&str; 4] = [
+        "mDreamingLockscreen=",
+        "mShowingLockscreen=",
+        "isStatusBarKeyguard=",
+        "mKeyguardShowing=",
+    ];
+    let values: Vec<bool> = output
+        .split_whitespace()
+        .filter_map(|token| {
+            FIELDS
+                .iter()
+                .find_map(|field| token.strip_prefix(field))
+                .map(|value| value == "true")
+        })
+        .collect();
+    (!values.is_empty()).then(|| values.contains(&true))
+}
+
+/// `unlock` 的一个步骤。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum UnlockStep {
+    /// 按 `KEYCODE_WAKEUP` 点亮屏幕。
+    Wake,
+    /// 上滑关闭锁屏界面。
+    Swipe,
+    /// 输入 PIN 并按回车。
+    EnterPin,
+    Done,
+}
+
+/// 根据上一步和当前的屏幕、锁屏状态决定 `unlock` 的下一步。
+///
+/// 每个步骤最多执行一次：上滑后仍锁定且提供了 PIN 时输入 PIN，否则结束，
+/// 因此不会因为不支持的锁屏方式（图案、生物识别）反复重试。
+pub(crate) fn next_unlock_step(
+    prev: Option<UnlockStep>,
+    screen_on: bool,
+    locked: bool,
+    has_pin: bool,
+) -> UnlockStep {
+    match prev {
+        None if !screen_on => UnlockStep::Wake,
+        None | Some(UnlockStep::Wake) if !locked => UnlockStep::Done,
+        None | Some(UnlockStep::Wake) => UnlockStep::Swipe,
+        Some(UnlockStep::Swipe) if locked && has_pin => UnlockStep::EnterPin,
+        _ => UnlockStep::Done,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_keyguard_locked() {
+        let android_9 = "  mShowingLockscreen=true mShowingDream=false mDreamingLockscreen=true mDreamingSleepToken=null\n";
+        assert_eq!(parse_keyguard_locked(android_9), Some(true));
+        let android_9_unlocked =
+            "  mShowingLockscreen=false mShowingDream=false mDreamingLockscreen=false\n";
+        assert_eq!(parse_keyguard_locked(android_9_unlocked), Some(false));
+        let android_13 = "  mTopFullscreenOpaqueWindowState=null\n    isStatusBarKeyguard=true\n";
+        assert_eq!(parse_keyguard_locked(android_13), Some(true));
+        assert_eq!(parse_keyguard_locked("WINDOW MANAGER WINDOWS\n"), None);
+    }
+
+    #[test]
+    fn test_unlock_without_pin() {
+        // 熄屏且锁定：点亮 -> 上滑 -> 结束
+        let step = next_unlock_step(None, false, true, false);
+        assert_eq!(step, UnlockStep::Wake);
+        let step = next_unlock_step(Some(step), true, true, false);
+        assert_eq!(step, UnlockStep::Swipe);
+        // 上滑后即使仍显示锁屏（需要图案等）也不再重试
+        assert_eq!(
+            next_unlock_step(Some(step), true, true, false),
+            UnlockStep::Done
+        );
+        assert_eq!(
+            next_unlock_step(Some(step), true, false, false),
+            UnlockStep::Done
+        );
+
+        // 已解锁时不执行任何操作
+        assert_eq!(next_unlock_step(None, true, false, false), UnlockStep::Done);
+        // 熄屏但未锁定：只点亮屏幕
+        assert_eq!(
+            next_unlock_step(Some(UnlockStep::Wake), true, false, false),
+            UnlockStep::Done
+        );
+    }
+
+    #[test]
+    fn test_unlock_with_pin() {
+        assert_eq!(
+            next_unlock_step(Some(UnlockStep::Swipe), true, true, true),
+            UnlockStep::EnterPin
+        );
+        assert_eq!(
+            next_unlock_step(Some(UnlockStep::Swipe), true, false, true),
+            UnlockStep::Done
+        );
+        assert_eq!(
+            next_unlock_step(Some(UnlockStep::EnterPin), true, true, true),
+            UnlockStep::Done
+        );
+    }
+}
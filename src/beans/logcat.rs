@@ -0,0 +1,247 @@
+use std::fmt::{Display, Formatter};
+use std::sync::OnceLock;
+
+use chrono::{DateTime, Datelike, Local, NaiveDate, NaiveDateTime, Utc};
+
+/// 日志优先级，对应 logcat 过滤规则中的 `V/D/I/W/E/F/S`。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LogPriority {
+    Verbose,
+    Debug,
+    Info,
+    Warn,
+    Error,
+    Fatal,
+    Silent,
+}
+
+impl LogPriority {
+    pub fn as_char(&self) -> char {
+        match self {
+            LogPriority::Verbose => 'V',
+            LogPriority::Debug => 'D',
+            LogPriority::Info => 'I',
+            LogPriority::Warn => 'W',
+            LogPriority::Error => 'E',
+            LogPriority::Fatal => 'F',
+            LogPriority::Silent => 'S',
+        }
+    }
+
+    pub fn from_char(c: char) -> Option<LogPriority> {
+        match c {
+            'V' => Some(LogPriority::Verbose),
+            'D' => Some(LogPriority::Debug),
+            'I' => Some(LogPriority::Info),
+            'W' => Some(LogPriority::Warn),
+            'E' => Some(LogPriority::Error),
+            'F' | 'A' => Some(LogPriority::Fatal),
+            'S' => Some(LogPriority::Silent),
+            _ => None,
+        }
+    }
+}
+
+/// logcat 的日志缓冲区，对应 `-b` 参数。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogBuffer {
+    Main,
+    System,
+    Crash,
+    Radio,
+    Events,
+    All,
+}
+
+impl Display for LogBuffer {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            LogBuffer::Main => "main",
+            LogBuffer::System => "system",
+            LogBuffer::Crash => "crash",
+            LogBuffer::Radio => "radio",
+            LogBuffer::Events => "events",
+            LogBuffer::All => "all",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// logcat 的过滤条件。
+///
+/// 设置了 `specs` 时只输出这些 tag，其余 tag 通过末尾的 `*:S` 屏蔽。
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LogcatFilter {
+    /// 读取的缓冲区（-b），为空时使用设备默认缓冲区
+    pub buffers: Vec<LogBuffer>,
+    /// `TAG:LEVEL` 过滤规则
+    pub specs: Vec<(String, LogPriority)>,
+    /// 只输出该时间之后的日志（-T）
+    pub since: Option<DateTime<Utc>>,
+    /// 输出格式（-v），默认为 `time`
+    pub format: Option<String>,
+}
+
+impl LogcatFilter {
+    /// 转换为完整的 `logcat` 命令行参数。
+    pub fn to_args(&self) -> Vec<String> {
+        let mut args = vec!["logcat".to_string()];
+        for buffer in &self.buffers {
+            args.push("-b".to_string());
+            args.push(buffer.to_string());
+        }
+        args.push("-v".to_string());
+        args.push(self.format.clone().unwrap_or_else(|| "time".to_string()));
+        if let Some(since) = self.since {
+            // 使用 `秒.毫秒` 格式，避免设备时区与本地不同
+            args.push("-T".to_string());
+            args.push(format!(
+                "{}.{:03}",
+                since.timestamp(),
+                since.timestamp_subsec_millis()
+            ));
+        }
+        if !self.specs.is_empty() {
+            for (tag, priority) in &self.specs {
+                args.push(format!("{}:{}", tag, priority.as_char()));
+            }
+            args.push("*:S".to_string());
+        }
+        args
+    }
+}
+
+/// `-v threadtime` 格式的一行日志。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LogEntry {
+    Message {
+        /// 设备本地时间；输出中没有年份时按本机当前年份补全
+        timestamp: NaiveDateTime,
+        pid: u32,
+        tid: u32,
+        priority: LogPriority,
+        tag: String,
+        message: String,
+    },
+    /// 不符合 threadtime 格式的行，例如 `--------- beginning of main`
+    Marker(String),
+}
+
+impl LogEntry {
+    /// 解析一行 `-v threadtime` 输出，兼容附加了 `-v year` 的格式。
+    pub fn parse(line: &str) -> LogEntry {
+        static THREADTIME: OnceLock<regex::Regex> = OnceLock::new();
+        let re = THREADTIME.get_or_init(|| {
+            regex::Regex::new(
+                r"^(?:(\d{4})-)?(\d{2})-(\d{2})\s+(\d{2}:\d{2}:\d{2}\.\d{3})\s+(\d+)\s+(\d+)\s+([VDIWEFAS])\s+(.*?)\s*: (.*)$",
+            )
+            .unwrap()
+        });
+        let line = line.trim_end_matches(['\r', '\n']);
+        let Some(cap) = re.captures(line) else {
+            return LogEntry::Marker(line.to_string());
+        };
+        let year = cap
+            .get(1)
+            .and_then(|y| y.as_str().parse().ok())
+            .unwrap_or_else(|| Local::now().year());
+        let timestamp = cap[2]
+            .parse()
+            .ok()
+            .zip(cap[3].parse().ok())
+            .and_then(|(month, day)| NaiveDate::from_ymd_opt(year, month, day))
+            .and_then(|date| {
+                chrono::NaiveTime::parse_from_str(&cap[4], "%H:%M:%S%.3f")
+                    .ok()
+                    .map(|time| date.and_time(time))
+            });
+        let (Some(timestamp), Ok(pid), Ok(tid), Some(priority)) = (
+            timestamp,
+            cap[5].parse(),
+            cap[6].parse(),
+            cap[7].chars().next().and_then(LogPriority::from_char),
+        ) else {
+            return LogEntry::Marker(line.to_string());
+        };
+        LogEntry::Message {
+            timestamp,
+            pid,
+            tid,
+            priority,
+            tag: cap[8].to_string(),
+            message: cap[9].to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_parse_threadtime() {
+        let entry = LogEntry::parse(
+            "2024-10-15 12:34:56.789  1234  5678 I ActivityManager: Start proc 4321:com.example.app/u0a123\n",
+        );
+        assert_eq!(
+            entry,
+            LogEntry::Message {
+                timestamp: NaiveDate::from_ymd_opt(2024, 10, 15)
+                    .unwrap()
+                    .and_hms_milli_opt(12, 34, 56, 789)
+                    .unwrap(),
+                pid: 1234,
+                tid: 5678,
+                priority: LogPriority::Info,
+                tag: "ActivityManager".to_string(),
+                message: "Start proc 4321:com.example.app/u0a123".to_string(),
+            }
+        );
+        match LogEntry::parse("10-15 01:02:03.004   99   100 E chromium: [ERROR:a.cc(1)] x: y\r\n")
+        {
+            LogEntry::Message {
+                timestamp,
+                priority,
+                tag,
+                message,
+                ..
+            } => {
+                assert_eq!(timestamp.year(), Local::now().year());
+                assert_eq!(priority, LogPriority::Error);
+                assert_eq!(tag, "chromium");
+                assert_eq!(message, "[ERROR:a.cc(1)] x: y");
+            }
+            other => panic!("unexpected entry {:?}", other),
+        }
+        assert_eq!(
+            LogEntry::parse("--------- beginning of main\n"),
+            LogEntry::Marker("--------- beginning of main".to_string())
+        );
+    }
+
+    #[test]
+    fn test_default_args() {
+        assert_eq!(
+            LogcatFilter::default().to_args(),
+            vec!["logcat", "-v", "time"]
+        );
+    }
+
+    #[test]
+    fn test_filter_args() {
+        let filter = LogcatFilter {
+            buffers: vec![LogBuffer::Main, LogBuffer::Crash],
+            specs: vec![
+                ("ActivityManager".to_string(), LogPriority::Info),
+                ("MyApp".to_string(), LogPriority::Verbose),
+            ],
+            since: Some(Utc.timestamp_millis_opt(1_700_000_000_123).unwrap()),
+            format: Some("threadtime".to_string()),
+        };
+        assert_eq!(
+            filter.to_args().join(" "),
+            "logcat -b main -b crash -v threadtime -T 1700000000.123 ActivityManager:I MyApp:V *:S"
+        );
+    }
+}
@@ -1,12 +1,64 @@
+pub(crate) mod activity_info;
 pub(crate) mod app_info;
+pub(crate) mod battery_info;
+pub(crate) mod checksum;
 pub(crate) mod command;
+pub(crate) mod command_log;
 pub(crate) mod device_info;
+pub(crate) mod device_state;
+pub(crate) mod device_summary;
+pub(crate) mod device_time;
+pub(crate) mod display_info;
+pub(crate) mod dumpsys;
 pub(crate) mod file_info;
 pub(crate) mod forward_item;
+pub(crate) mod install_options;
+pub(crate) mod jdwp;
+pub(crate) mod key_code;
+pub(crate) mod keyguard;
+pub(crate) mod logcat;
+pub(crate) mod mount_info;
 pub(crate) mod net_info;
+pub(crate) mod package_filter;
+pub(crate) mod proc_meminfo;
+pub(crate) mod process_info;
+pub(crate) mod reboot_mode;
+pub(crate) mod screen_capture;
+pub(crate) mod screen_record;
+pub(crate) mod settings;
+pub(crate) mod shell_result;
+pub(crate) mod ui_element;
+pub(crate) mod volume;
 
+pub use activity_info::{
+    parse_current_focus, parse_resolve_activity, parse_resumed_activity, ActivityInfo,
+};
 pub use app_info::AppInfo;
+pub use battery_info::{BatteryHealth, BatteryInfo, BatteryStatus, PluggedState};
+pub use checksum::parse_checksum;
+pub use command::{shell_escape_arg, AdbCommand, AdbCommandBuilder};
+pub use command_log::{CommandLog, CommandRecord, ReplayErrorPolicy, ReplayOptions};
 pub use device_info::AdbDeviceInfo;
-pub use file_info::{parse_file_info, FileInfo};
-pub use forward_item::ForwardItem;
-pub use net_info::NetworkType;
+pub use device_state::{DeviceState, TransportKind};
+pub use device_summary::{parse_device_list_long, DeviceSummary};
+pub use display_info::{parse_display_rotation, parse_screen_on, parse_wm_density, parse_wm_size};
+pub use dumpsys::{parse_battery, GfxInfo, GpuInfo, MemInfo, PowerInfo};
+pub use file_info::{parse_dent, parse_file_info, parse_file_info_v2, FileInfo};
+pub use forward_item::{parse_forward_list, tcp_port, ForwardItem};
+pub use install_options::{parse_pm_failure, InstallOptions};
+pub use jdwp::parse_jdwp_pids;
+pub use key_code::{keyevent_args, KeyCode};
+pub use keyguard::parse_keyguard_locked;
+pub use logcat::{LogBuffer, LogEntry, LogPriority, LogcatFilter};
+pub use mount_info::{parse_df_output, MountInfo, StorageInfo};
+pub use net_info::{parse_ipv4, parse_ipv6_addrs, NetworkType};
+pub use package_filter::{parse_package_list, PackageFilter};
+pub use proc_meminfo::{available_memory, parse_proc_meminfo};
+pub use process_info::{parse_pidof, parse_ps_output, ProcessInfo};
+pub use reboot_mode::RebootMode;
+pub use screen_capture::{rotate_upright, PixelFormat, ScreenCapture};
+pub use screen_record::ScreenRecordOptions;
+pub use settings::SettingsNamespace;
+pub use shell_result::ShellResult;
+pub use ui_element::{parse_ui_elements, Bounds, By, UiElement};
+pub use volume::VolumeStream;
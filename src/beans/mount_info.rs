@@ -0,0 +1,146 @@
+/// `df` 输出中的一个挂载点，容量单位为字节。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MountInfo {
+    pub filesystem: String,
+    pub mount_point: String,
+    pub total: u64,
+    pub used: u64,
+    pub available: u64,
+    pub use_percent: u8,
+}
+
+/// 某个路径所在文件系统的容量，单位为字节，由 `AdbDevice::storage` 返回。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StorageInfo {
+    pub total: u64,
+    pub used: u64,
+    pub available: u64,
+}
+
+impl From<&MountInfo> for StorageInfo {
+    fn from(mount: &MountInfo) -> Self {
+        StorageInfo {
+            total: mount.total,
+            used: mount.used,
+            available: mount.available,
+        }
+    }
+}
+
+/// 解析 `df -k`（或 `df -h`）的完整输出。
+///
+/// toybox 的 `df` 在设备名过长时会把数值列折到下一行，这里会把两行合并后再解析。
+pub fn parse_df_output(output: &str) -> Vec<MountInfo> {
+    let mut mounts = vec![];
+    let mut pending: Option<String> = None;
+    for line in output
+        .lines()
+        .skip_while(|l| !l.starts_with("Filesystem"))
+        .skip(1)
+    {
+        let mut parts: Vec<&str> = line.split_whitespace().collect();
+        if parts.is_empty() {
+            continue;
+        }
+        let previous = pending.take();
+        if let Some(filesystem) = previous.as_deref() {
+            parts.insert(0, filesystem);
+        }
+        if parts.len() == 1 {
+            pending = Some(parts[0].to_string());
+            continue;
+        }
+        if parts.len() < 6 {
+            continue;
+        }
+        let (Some(total), Some(used), Some(available)) = (
+            parse_df_size(parts[1]),
+            parse_df_size(parts[2]),
+            parse_df_size(parts[3]),
+        ) else {
+            continue;
+        };
+        mounts.push(MountInfo {
+            filesystem: parts[0].to_string(),
+            mount_point: parts[5..].join(" "),
+            total,
+            used,
+            available,
+            use_percent: parts[4].trim_end_matches('%').parse().unwrap_or(0),
+        });
+    }
+    mounts
+}
+
+/// 解析 `df` 的容量列：纯数字按 1K 块计算，带 `K/M/G/T` 后缀的按对应单位换算为字节。
+pub fn parse_df_size(value: &str) -> Option<u64> {
+    let unit = match value.chars().last()? {
+        'K' | 'k' => 1u64 << 10,
+        'M' | 'm' => 1 << 20,
+        'G' | 'g' => 1 << 30,
+        'T' | 't' => 1 << 40,
+        c if c.is_ascii_digit() => return value.parse::<u64>().ok().map(|v| v * 1024),
+        _ => return None,
+    };
+    let number: f64 = value[..value.len() - 1].parse().ok()?;
+    Some((number * unit as f64) as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DF_OUTPUT: &str = "Filesystem            1K-blocks    Used Available Use% Mounted on
+/dev/root               2031440 1612344    402712  81% /
+tmpfs                   1509564     996   1508568   1% /dev
+/dev/block/vold/public:179,1
+                       30528000    1234  30526766   1% /mnt/media_rw/1A2B-3C4D
+/data/media             5923216 3026688   2896528  52% /storage/emulated
+";
+
+    #[test]
+    fn test_parse_df_output() {
+        let mounts = parse_df_output(DF_OUTPUT);
+        assert_eq!(mounts.len(), 4);
+        assert_eq!(
+            mounts[0],
+            MountInfo {
+                filesystem: "/dev/root".to_string(),
+                mount_point: "/".to_string(),
+                total: 2031440 * 1024,
+                used: 1612344 * 1024,
+                available: 402712 * 1024,
+                use_percent: 81,
+            }
+        );
+        assert_eq!(mounts[2].filesystem, "/dev/block/vold/public:179,1");
+        assert_eq!(mounts[2].mount_point, "/mnt/media_rw/1A2B-3C4D");
+        assert_eq!(mounts[2].total, 30528000 * 1024);
+        assert_eq!(mounts[3].use_percent, 52);
+    }
+
+    #[test]
+    fn test_parse_df_size() {
+        assert_eq!(parse_df_size("4"), Some(4096));
+        assert_eq!(parse_df_size("1.5G"), Some(3 << 29));
+        assert_eq!(parse_df_size("84K"), Some(84 * 1024));
+        assert_eq!(parse_df_size("-"), None);
+    }
+
+    #[test]
+    fn test_storage_info_from_df_data() {
+        let output = "Filesystem      1K-blocks    Used Available Use% Mounted on
+/dev/block/dm-5  57591052 9143524  48316456  16% /data
+";
+        let mounts = parse_df_output(output);
+        assert_eq!(mounts.len(), 1);
+        assert_eq!(
+            StorageInfo::from(&mounts[0]),
+            StorageInfo {
+                total: 57591052 * 1024,
+                used: 9143524 * 1024,
+                available: 48316456 * 1024,
+            }
+        );
+    }
+}
@@ -1,5 +1,50 @@
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
 use std::fmt::Display;
+use std::net::{Ipv4Addr, Ipv6Addr};
 
+/// `wlan_ip` 依次尝试的查询方式：所需工具、命令以及提取 IPv4 地址的正则。
+///
+/// busybox 的 `ifconfig` 输出 `inet addr:192.168.1.5  Bcast:...`，toybox 的 `ip addr` 输出
+/// `inet 192.168.1.5/24 ...`，`ip route get` 输出默认路由使用的源地址 `src 192.168.1.5`。
+pub(crate) const WLAN_IP_QUERIES: [(&str, &[&str], &str); 4] = [
+    (
+        "ifconfig",
+        &["ifconfig", "wlan0"],
+        r"inet\s*addr:\s*([\d.]+)",
+    ),
+    (
+        "ip",
+        &["ip", "addr", "show", "dev", "wlan0"],
+        r"inet\s+([\d.]+)/\d+",
+    ),
+    (
+        "ip",
+        &["ip", "route", "get", "1.1.1.1"],
+        r"\bsrc\s+([\d.]+)",
+    ),
+    (
+        "ifconfig",
+        &["ifconfig", "eth0"],
+        r"inet\s*addr:\s*([\d.]+)",
+    ),
+];
+
+/// 按 `pattern` 提取输出中的地址，返回第一个合法且不是回环地址的 IPv4 地址。
+///
+/// `pattern` 的第一个捕获组为地址。
+pub fn parse_ipv4(output: &str, pattern: &str) -> Option<Ipv4Addr> {
+    let re = regex::Regex::new(pattern).ok()?;
+    let ip = re
+        .captures_iter(output)
+        .filter_map(|cap| cap.get(1)?.as_str().parse::<Ipv4Addr>().ok())
+        .find(|ip| !ip.is_loopback() && !ip.is_unspecified());
+    ip
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
 pub enum NetworkType {
     Tcp,
     Unix,
@@ -23,3 +68,102 @@ impl Display for NetworkType {
         write!(f, "{}", str)
     }
 }
+
+/// 解析 `ip -6 addr show` 的输出，返回其中的 IPv6 地址，忽略回环地址。
+///
+/// # 参数
+/// - `include_link_local`: 是否包含 `fe80::/10` 链路本地地址
+pub fn parse_ipv6_addrs(output: &str, include_link_local: bool) -> Vec<Ipv6Addr> {
+    let re = regex::Regex::new(r"inet6\s+([0-9a-fA-F:]+)/\d+").unwrap();
+    let addrs = re
+        .captures_iter(output)
+        .filter_map(|cap| cap[1].parse::<Ipv6Addr>().ok())
+        .filter(|ip| !ip.is_loopback())
+        .filter(|ip| include_link_local || ip.segments()[0] & 0xffc0 != 0xfe80)
+        .collect();
+    addrs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn query_ip(index: usize, output: &str) -> Option<Ipv4Addr> {
+        parse_ipv4(output, WLAN_IP_QUERIES[index].2)
+    }
+
+    #[test]
+    fn test_parse_busybox_ifconfig() {
+        let output = "wlan0     Link encap:Ethernet  HWaddr 02:00:00:44:55:66
+          inet addr:192.168.1.5  Bcast:192.168.1.255  Mask:255.255.255.0
+          UP BROADCAST RUNNING MULTICAST  MTU:1500  Metric:1
+";
+        assert_eq!(query_ip(0, output), Some(Ipv4Addr::new(192, 168, 1, 5)));
+        // 部分实现在 addr: 后带空格
+        assert_eq!(
+            query_ip(0, "inet addr: 10.0.2.15  Mask:255.255.255.0"),
+            Some(Ipv4Addr::new(10, 0, 2, 15))
+        );
+    }
+
+    #[test]
+    fn test_parse_toybox_ip_addr() {
+        let output = "1: lo: <LOOPBACK,UP,LOWER_UP> mtu 65536 qdisc noqueue state UNKNOWN group default qlen 1000
+    inet 127.0.0.1/8 scope host lo
+18: wlan0: <BROADCAST,MULTICAST,UP,LOWER_UP> mtu 1500 qdisc mq state UP group default qlen 3000
+    link/ether 02:15:b2:00:00:00 brd ff:ff:ff:ff:ff:ff
+    inet 10.0.2.16/24 brd 10.0.2.255 scope global wlan0
+       valid_lft forever preferred_lft forever
+    inet6 fe80::15:b2ff:fe00:0/64 scope link
+";
+        assert_eq!(query_ip(1, output), Some(Ipv4Addr::new(10, 0, 2, 16)));
+        assert_eq!(query_ip(1, "    inet 127.0.0.1/8 scope host lo\n"), None);
+    }
+
+    #[test]
+    fn test_parse_ip_route_get() {
+        let output =
+            "1.1.1.1 via 10.0.2.2 dev wlan0 table 1021 src 10.0.2.16 uid 2000 \n    cache \n";
+        assert_eq!(query_ip(2, output), Some(Ipv4Addr::new(10, 0, 2, 16)));
+        assert_eq!(
+            query_ip(2, "RTNETLINK answers: Network is unreachable\n"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_parse_ipv6_addrs() {
+        let output = "18: wlan0: <BROADCAST,MULTICAST,UP,LOWER_UP> mtu 1500 state UP qlen 3000
+    inet6 2001:db8:1234:5678:15:b2ff:fe00:0/64 scope global dynamic noprefixroute
+       valid_lft 86380sec preferred_lft 14380sec
+    inet6 fdc4:7a3e:1::15/64 scope global
+       valid_lft forever preferred_lft forever
+    inet6 fe80::15:b2ff:fe00:0/64 scope link
+       valid_lft forever preferred_lft forever
+";
+        assert_eq!(
+            parse_ipv6_addrs(output, false),
+            vec![
+                "2001:db8:1234:5678:15:b2ff:fe00:0"
+                    .parse::<Ipv6Addr>()
+                    .unwrap(),
+                "fdc4:7a3e:1::15".parse().unwrap(),
+            ]
+        );
+        let all = parse_ipv6_addrs(output, true);
+        assert_eq!(all.len(), 3);
+        assert_eq!(all[2], "fe80::15:b2ff:fe00:0".parse::<Ipv6Addr>().unwrap());
+        assert!(parse_ipv6_addrs("    inet6 ::1/128 scope host\n", true).is_empty());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_network_type_serde_round_trip() {
+        let json = serde_json::to_string(&NetworkType::LocalAbstrcat).unwrap();
+        assert_eq!(json, r#""local_abstrcat""#);
+        assert_eq!(
+            serde_json::from_str::<NetworkType>(&json).unwrap(),
+            NetworkType::LocalAbstrcat
+        );
+    }
+}
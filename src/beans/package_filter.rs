@@ -0,0 +1,78 @@
+/// `pm list packages` 的过滤条件，对应 `-3`、`-s`、`-d`、`-e` 以及包名子串过滤。
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PackageFilter {
+    pub third_party: bool,    // 只列出第三方应用（-3）
+    pub system: bool,         // 只列出系统应用（-s）
+    pub disabled: bool,       // 只列出已停用的应用（-d）
+    pub enabled: bool,        // 只列出已启用的应用（-e）
+    pub name: Option<String>, // 包名需包含的子串
+}
+
+impl PackageFilter {
+    /// 转换为 `pm list packages` 的命令行参数。
+    pub fn to_args(&self) -> Vec<String> {
+        let mut args = vec!["pm".to_string(), "list".to_string(), "packages".to_string()];
+        for (enabled, flag) in [
+            (self.third_party, "-3"),
+            (self.system, "-s"),
+            (self.disabled, "-d"),
+            (self.enabled, "-e"),
+        ] {
+            if enabled {
+                args.push(flag.to_string());
+            }
+        }
+        if let Some(name) = &self.name {
+            args.push(name.clone());
+        }
+        args
+    }
+}
+
+/// 解析 `pm list packages` 输出中的 `package:<name>` 行，返回包名列表。
+pub fn parse_package_list(output: &str) -> Vec<String> {
+    output
+        .lines()
+        .filter_map(|line| line.trim().strip_prefix("package:"))
+        .filter(|name| !name.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_args() {
+        let filter = PackageFilter {
+            third_party: true,
+            enabled: true,
+            name: Some("google".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(filter.to_args().join(" "), "pm list packages -3 -e google");
+        assert_eq!(
+            PackageFilter::default().to_args().join(" "),
+            "pm list packages"
+        );
+    }
+
+    #[test]
+    fn test_parse_package_list() {
+        let output = "package:com.android.settings\r
+package:com.google.android.gms\r
+package:com.example.app\r
+\r
+";
+        assert_eq!(
+            parse_package_list(output),
+            vec![
+                "com.android.settings",
+                "com.google.android.gms",
+                "com.example.app"
+            ]
+        );
+        assert!(parse_package_list("").is_empty());
+    }
+}
@@ -0,0 +1,67 @@
+//! `/proc/meminfo` 的解析。
+
+use std::collections::HashMap;
+
+/// 解析 `/proc/meminfo`，返回字段名到数值的映射。
+///
+/// 带 `kB` 单位的字段换算为字节，没有单位的计数（例如 `HugePages_Total`）原样保留。
+pub fn parse_proc_meminfo(output: &str) -> HashMap<String, u64> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let (key, value) = line.split_once(':')?;
+            let mut parts = value.split_whitespace();
+            let number: u64 = parts.next()?.parse().ok()?;
+            let number = match parts.next() {
+                Some(unit) if unit.eq_ignore_ascii_case("kb") => number * 1024,
+                _ => number,
+            };
+            Some((key.trim().to_string(), number))
+        })
+        .collect()
+}
+
+/// 可用内存（字节）：优先取 `MemAvailable`（Linux 3.14 起提供），
+/// 旧内核按 `MemFree + Buffers + Cached` 估算。
+pub fn available_memory(meminfo: &HashMap<String, u64>) -> Option<u64> {
+    meminfo.get("MemAvailable").copied().or_else(|| {
+        let free = meminfo.get("MemFree")?;
+        Some(free + meminfo.get("Buffers").unwrap_or(&0) + meminfo.get("Cached").unwrap_or(&0))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PROC_MEMINFO: &str = "MemTotal:        3969016 kB
+MemFree:          127516 kB
+MemAvailable:    1623064 kB
+Buffers:            5336 kB
+Cached:          1548212 kB
+SwapCached:        21448 kB
+HugePages_Total:       0
+Hugepagesize:       2048 kB
+";
+
+    #[test]
+    fn test_parse_proc_meminfo() {
+        let meminfo = parse_proc_meminfo(PROC_MEMINFO);
+        assert_eq!(meminfo["MemTotal"], 3969016 * 1024);
+        assert_eq!(meminfo["HugePages_Total"], 0);
+        assert_eq!(available_memory(&meminfo), Some(1623064 * 1024));
+    }
+
+    #[test]
+    fn test_available_memory_without_mem_available() {
+        // Linux 3.10 内核没有 MemAvailable
+        let meminfo = parse_proc_meminfo(
+            "MemTotal:        1893416 kB\nMemFree:          102400 kB\nBuffers:           10240 kB\nCached:           512000 kB\n",
+        );
+        assert_eq!(
+            available_memory(&meminfo),
+            Some((102400 + 10240 + 512000) * 1024)
+        );
+        assert_eq!(available_memory(&HashMap::new()), None);
+    }
+}
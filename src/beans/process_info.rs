@@ -0,0 +1,107 @@
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// `ps` 输出中的一个进程。
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ProcessInfo {
+    pub pid: u32,
+    pub ppid: u32,
+    pub user: String,
+    /// 进程名，应用进程为包名（或 `包名:子进程名`）。
+    pub name: String,
+}
+
+/// 解析 `ps` 的输出，根据表头确定 `PID`、`PPID`、`USER` 所在的列，进程名取每行的最后一列。
+///
+/// 同时支持 toybox（Android 8.0 起，`ps -A -o PID,PPID,USER,NAME`）与 toolbox（`ps`，
+/// 列为 `USER PID PPID VSIZE RSS WCHAN PC NAME`，且 `NAME` 前还有一个没有表头的状态列）。
+/// 缺少必要列的表头或无法解析的行被忽略。
+pub fn parse_ps_output(output: &str) -> Vec<ProcessInfo> {
+    let mut lines = output.lines();
+    let Some(header) = lines.next() else {
+        return vec![];
+    };
+    let columns: Vec<&str> = header.split_whitespace().collect();
+    let column = |name: &str| columns.iter().position(|c| *c == name);
+    let (Some(pid), Some(ppid), Some(user)) = (column("PID"), column("PPID"), column("USER"))
+    else {
+        return vec![];
+    };
+    lines
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            if fields.len() < columns.len() {
+                return None;
+            }
+            Some(ProcessInfo {
+                pid: fields[pid].parse().ok()?,
+                ppid: fields[ppid].parse().ok()?,
+                user: fields[user].to_string(),
+                name: fields.last()?.to_string(),
+            })
+        })
+        .collect()
+}
+
+/// 解析 `pidof` 的输出：空格分隔的 PID。
+pub fn parse_pidof(output: &str) -> Vec<u32> {
+    output
+        .split_whitespace()
+        .filter_map(|pid| pid.parse().ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_toybox_ps() {
+        let output = "  PID  PPID USER           NAME
+    1     0 root           init
+  573     1 system         system_server
+ 2048   573 u0_a123        com.example.app:remote
+";
+        let processes = parse_ps_output(output);
+        assert_eq!(processes.len(), 3);
+        assert_eq!(
+            processes[2],
+            ProcessInfo {
+                pid: 2048,
+                ppid: 573,
+                user: "u0_a123".to_string(),
+                name: "com.example.app:remote".to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_toolbox_ps() {
+        let output = "USER      PID   PPID  VSIZE  RSS   WCHAN              PC  NAME
+root      1     0     8896   764   SyS_epoll_ 00000000 S /init
+system    573   245   1697880 112640 SyS_epoll_ 00000000 S system_server
+u0_a54    1893  245   1541284 49052 SyS_epoll_ 00000000 S com.android.launcher3
+";
+        let processes = parse_ps_output(output);
+        assert_eq!(processes.len(), 3);
+        assert_eq!(processes[0].name, "/init");
+        assert_eq!(
+            processes[2],
+            ProcessInfo {
+                pid: 1893,
+                ppid: 245,
+                user: "u0_a54".to_string(),
+                name: "com.android.launcher3".to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_ps_unexpected_output() {
+        assert!(parse_ps_output("").is_empty());
+        assert!(parse_ps_output("bad pid '-A'\n").is_empty());
+        assert_eq!(parse_pidof("573 1893\n"), [573, 1893]);
+        assert!(parse_pidof("").is_empty());
+    }
+}
@@ -0,0 +1,27 @@
+use std::fmt::Display;
+
+/// 重启的目标模式，对应 `reboot:<mode>` 服务中的 `mode`。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RebootMode {
+    #[default]
+    System,
+    Bootloader,
+    Recovery,
+    Sideload,
+    SideloadAutoReboot,
+    Fastboot,
+}
+
+impl Display for RebootMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let str = match self {
+            RebootMode::System => "",
+            RebootMode::Bootloader => "bootloader",
+            RebootMode::Recovery => "recovery",
+            RebootMode::Sideload => "sideload",
+            RebootMode::SideloadAutoReboot => "sideload-auto-reboot",
+            RebootMode::Fastboot => "fastboot",
+        };
+        write!(f, "{}", str)
+    }
+}
@@ -0,0 +1,242 @@
+use std::path::Path;
+
+use image::{ImageFormat, RgbImage};
+
+use crate::errors::{AdbError, AdbResult};
+
+/// `screencap` 原始输出的像素格式，取值与 Android `PixelFormat` 一致。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PixelFormat {
+    Rgba8888,
+    Rgbx8888,
+    Rgb888,
+    Rgb565,
+    Bgra8888,
+    Unknown(u32),
+}
+
+impl PixelFormat {
+    pub fn from_raw(value: u32) -> PixelFormat {
+        match value {
+            1 => PixelFormat::Rgba8888,
+            2 => PixelFormat::Rgbx8888,
+            3 => PixelFormat::Rgb888,
+            4 => PixelFormat::Rgb565,
+            5 => PixelFormat::Bgra8888,
+            other => PixelFormat::Unknown(other),
+        }
+    }
+
+    /// 每个像素占用的字节数，未知格式返回 `None`。
+    pub fn bytes_per_pixel(&self) -> Option<usize> {
+        match self {
+            PixelFormat::Rgba8888 | PixelFormat::Rgbx8888 | PixelFormat::Bgra8888 => Some(4),
+            PixelFormat::Rgb888 => Some(3),
+            PixelFormat::Rgb565 => Some(2),
+            PixelFormat::Unknown(_) => None,
+        }
+    }
+}
+
+/// 不带 `-p` 的 `screencap` 输出的原始帧。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScreenCapture {
+    pub width: u32,
+    pub height: u32,
+    pub format: PixelFormat,
+    pub pixels: Vec<u8>,
+}
+
+impl ScreenCapture {
+    /// 解析 `screencap` 的二进制输出。
+    ///
+    /// 头部为 width、height、format 三个小端 u32，Android 9 起还会多一个 colorspace，
+    /// 这里根据像素数据长度判断头部是 12 还是 16 字节。
+    pub fn parse(data: &[u8]) -> AdbResult<ScreenCapture> {
+        let header = |index: usize| -> AdbResult<u32> {
+            data.get(index * 4..index * 4 + 4)
+                .map(|b| u32::from_le_bytes(b.try_into().unwrap()))
+                .ok_or_else(|| AdbError::unknown("screencap output too short"))
+        };
+        let (width, height) = (header(0)?, header(1)?);
+        let format = PixelFormat::from_raw(header(2)?);
+        let bpp = format
+            .bytes_per_pixel()
+            .ok_or_else(|| AdbError::unknown(format!("unsupported pixel format {:?}", format)))?;
+        let size = width as usize * height as usize * bpp;
+        let header_size = [16, 12]
+            .into_iter()
+            .find(|header_size| data.len() >= header_size + size)
+            .ok_or_else(|| {
+                AdbError::unknown(format!(
+                    "screencap output has {} bytes, expected at least {}",
+                    data.len(),
+                    size + 12
+                ))
+            })?;
+        Ok(ScreenCapture {
+            width,
+            height,
+            format,
+            pixels: data[header_size..header_size + size].to_vec(),
+        })
+    }
+
+    /// 转换为 `RgbImage`，alpha 通道被丢弃。
+    ///
+    /// 像素格式未知或像素数据少于 `width * height` 个像素时返回错误。
+    pub fn to_rgb_image(&self) -> AdbResult<RgbImage> {
+        let bpp = self.format.bytes_per_pixel().ok_or_else(|| {
+            AdbError::unknown(format!("unsupported pixel format {:?}", self.format))
+        })?;
+        let rgb: Vec<u8> = self
+            .pixels
+            .chunks_exact(bpp)
+            .flat_map(|p| match self.format {
+                PixelFormat::Rgb565 => {
+                    let v = u16::from_le_bytes([p[0], p[1]]);
+                    let (r, g, b) = ((v >> 11) & 0x1f, (v >> 5) & 0x3f, v & 0x1f);
+                    [
+                        ((r << 3) | (r >> 2)) as u8,
+                        ((g << 2) | (g >> 4)) as u8,
+                        ((b << 3) | (b >> 2)) as u8,
+                    ]
+                }
+                PixelFormat::Bgra8888 => [p[2], p[1], p[0]],
+                _ => [p[0], p[1], p[2]],
+            })
+            .collect();
+        RgbImage::from_raw(self.width, self.height, rgb).ok_or_else(|| {
+            AdbError::unknown(format!(
+                "screencap pixels have {} bytes, expected {} for {}x{}",
+                self.pixels.len(),
+                self.width as usize * self.height as usize * bpp,
+                self.width,
+                self.height
+            ))
+        })
+    }
+}
+
+/// PNG 文件头，用于判断 `screencap -p` 的输出是否完整。
+pub(crate) const PNG_SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+
+/// 根据扩展名确定截图保存格式，只支持 `.png` 和 `.jpg`/`.jpeg`。
+///
+/// 未启用 `image` 的 `webp-encoder` 特性，无法编码 WebP，`.webp` 在截图之前就返回错误。
+pub(crate) fn screenshot_format(path: &Path) -> AdbResult<ImageFormat> {
+    match ImageFormat::from_path(path) {
+        Ok(format @ (ImageFormat::Png | ImageFormat::Jpeg)) => Ok(format),
+        _ => Err(AdbError::unknown(format!(
+            "unsupported screenshot format: {}, expected .png or .jpg",
+            path.display()
+        ))),
+    }
+}
+
+/// 按屏幕旋转角度（0、90、180、270，对应 `Surface.ROTATION_*`）把自然方向的截图转为用户看到的方向。
+///
+/// 屏幕逆时针旋转 90 度时画面需要顺时针旋转 270 度才是正向，其余同理；其他角度原样返回。
+pub fn rotate_upright(image: RgbImage, rotation: u32) -> RgbImage {
+    match rotation {
+        90 => image::imageops::rotate270(&image),
+        180 => image::imageops::rotate180(&image),
+        270 => image::imageops::rotate90(&image),
+        _ => image,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn header(width: u32, height: u32, format: u32) -> Vec<u8> {
+        [width, height, format]
+            .iter()
+            .flat_map(|v| v.to_le_bytes())
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_rgba_with_colorspace() {
+        let mut data = header(2, 1, 1);
+        data.extend_from_slice(&1u32.to_le_bytes());
+        data.extend_from_slice(&[255, 0, 0, 255, 0, 0, 255, 255]);
+        let capture = ScreenCapture::parse(&data).unwrap();
+        assert_eq!((capture.width, capture.height), (2, 1));
+        assert_eq!(capture.format, PixelFormat::Rgba8888);
+        let image = capture.to_rgb_image().unwrap();
+        assert_eq!(image.get_pixel(0, 0).0, [255, 0, 0]);
+        assert_eq!(image.get_pixel(1, 0).0, [0, 0, 255]);
+    }
+
+    #[test]
+    fn test_parse_rgb565_legacy_header() {
+        let mut data = header(1, 1, 4);
+        data.extend_from_slice(&0x07e0u16.to_le_bytes());
+        let capture = ScreenCapture::parse(&data).unwrap();
+        assert_eq!(capture.format, PixelFormat::Rgb565);
+        assert_eq!(
+            capture.to_rgb_image().unwrap().get_pixel(0, 0).0,
+            [0, 255, 0]
+        );
+    }
+
+    #[test]
+    fn test_parse_truncated() {
+        let mut data = header(10, 10, 1);
+        data.extend_from_slice(&[0; 16]);
+        assert!(ScreenCapture::parse(&data).is_err());
+
+        let capture = ScreenCapture {
+            width: 2,
+            height: 2,
+            format: PixelFormat::Rgba8888,
+            pixels: vec![0; 8],
+        };
+        assert!(capture.to_rgb_image().is_err());
+    }
+
+    #[test]
+    fn test_rotate_upright() {
+        // 2x3 的竖屏画面，左上角为红色
+        let mut image = RgbImage::new(2, 3);
+        image.put_pixel(0, 0, image::Rgb([255, 0, 0]));
+        for rotation in [90, 270] {
+            let rotated = rotate_upright(image.clone(), rotation);
+            assert_eq!(rotated.dimensions(), (3, 2));
+        }
+        // 逆时针转 90 度后，原左上角到了左下角
+        assert_eq!(
+            rotate_upright(image.clone(), 90).get_pixel(0, 1).0,
+            [255, 0, 0]
+        );
+        assert_eq!(
+            rotate_upright(image.clone(), 270).get_pixel(2, 0).0,
+            [255, 0, 0]
+        );
+        let flipped = rotate_upright(image.clone(), 180);
+        assert_eq!(flipped.dimensions(), (2, 3));
+        assert_eq!(flipped.get_pixel(1, 2).0, [255, 0, 0]);
+        assert_eq!(rotate_upright(image.clone(), 0), image);
+    }
+
+    #[test]
+    fn test_screenshot_format() {
+        assert_eq!(
+            screenshot_format(Path::new("shot.png")).unwrap(),
+            ImageFormat::Png
+        );
+        assert_eq!(
+            screenshot_format(Path::new("/tmp/shot.JPG")).unwrap(),
+            ImageFormat::Jpeg
+        );
+        assert_eq!(
+            screenshot_format(Path::new("shot.jpeg")).unwrap(),
+            ImageFormat::Jpeg
+        );
+        assert!(screenshot_format(Path::new("shot.webp")).is_err());
+        assert!(screenshot_format(Path::new("shot.bmp")).is_err());
+        assert!(screenshot_format(Path::new("shot")).is_err());
+    }
+}
@@ -0,0 +1,74 @@
+/// `screenrecord` 的录制参数。
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ScreenRecordOptions {
+    pub time_limit: Option<u32>,  // 单段录制时长（秒），设备端上限为 180
+    pub bit_rate: Option<u32>,    // 码率（bit/s）
+    pub size: Option<(u32, u32)>, // 录制分辨率（宽, 高）
+    pub rotate: bool,             // 是否旋转 90 度
+    pub auto_restart: bool,       // 单段结束后是否自动开始下一段，用于突破 180 秒限制
+}
+
+impl ScreenRecordOptions {
+    /// 转换为 `screenrecord` 的命令行参数，不含输出路径。
+    pub fn to_args(&self) -> Vec<String> {
+        let mut args = vec!["screenrecord".to_string()];
+        if let Some(time_limit) = self.time_limit {
+            args.push("--time-limit".to_string());
+            args.push(time_limit.to_string());
+        }
+        if let Some(bit_rate) = self.bit_rate {
+            args.push("--bit-rate".to_string());
+            args.push(bit_rate.to_string());
+        }
+        if let Some((width, height)) = self.size {
+            args.push("--size".to_string());
+            args.push(format!("{}x{}", width, height));
+        }
+        if self.rotate {
+            args.push("--rotate".to_string());
+        }
+        args
+    }
+}
+
+/// 第 `index` 段录制在设备上的路径：第一段使用原路径，之后在扩展名前加上序号。
+pub fn segment_path(remote: &str, index: usize) -> String {
+    if index == 0 {
+        return remote.to_string();
+    }
+    match remote.rsplit_once('.') {
+        Some((stem, ext)) if !ext.contains('/') => format!("{}_{}.{}", stem, index, ext),
+        _ => format!("{}_{}", remote, index),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_args() {
+        let options = ScreenRecordOptions {
+            time_limit: Some(30),
+            bit_rate: Some(4_000_000),
+            size: Some((720, 1280)),
+            rotate: true,
+            auto_restart: false,
+        };
+        assert_eq!(
+            options.to_args().join(" "),
+            "screenrecord --time-limit 30 --bit-rate 4000000 --size 720x1280 --rotate"
+        );
+        assert_eq!(
+            ScreenRecordOptions::default().to_args(),
+            vec!["screenrecord"]
+        );
+    }
+
+    #[test]
+    fn test_segment_path() {
+        assert_eq!(segment_path("/sdcard/rec.mp4", 0), "/sdcard/rec.mp4");
+        assert_eq!(segment_path("/sdcard/rec.mp4", 2), "/sdcard/rec_2.mp4");
+        assert_eq!(segment_path("/sdcard/v1.0/rec", 1), "/sdcard/v1.0/rec_1");
+    }
+}
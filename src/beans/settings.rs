@@ -0,0 +1,65 @@
+use std::fmt::Display;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// `settings` 命令的命名空间。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum SettingsNamespace {
+    System,
+    Secure,
+    Global,
+}
+
+impl Display for SettingsNamespace {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let str = match self {
+            SettingsNamespace::System => "system",
+            SettingsNamespace::Secure => "secure",
+            SettingsNamespace::Global => "global",
+        };
+        write!(f, "{}", str)
+    }
+}
+
+/// 解析 `settings get` 的输出，未设置的键输出 `null`，返回 `None`。
+pub(crate) fn parse_settings_value(output: &str) -> Option<String> {
+    let value = output.trim_end_matches(['\r', '\n']);
+    (value != "null").then(|| value.to_string())
+}
+
+/// 开关类设置项是否为开启状态。
+///
+/// `wifi_on`、`bluetooth_on` 为 2 时表示在飞行模式下保持开启，同样视为开启；
+/// `wifi_on` 为 3 表示仅保留扫描，视为关闭；未设置视为关闭。
+pub(crate) fn setting_enabled(value: Option<&str>) -> bool {
+    matches!(value, Some("1" | "2"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_settings_value() {
+        assert_eq!(parse_settings_value("1\n"), Some("1".to_string()));
+        assert_eq!(parse_settings_value("null\n"), None);
+        assert_eq!(parse_settings_value("\n"), Some(String::new()));
+        assert_eq!(
+            parse_settings_value("com.a/.Svc:com.b/.Svc\r\n"),
+            Some("com.a/.Svc:com.b/.Svc".to_string())
+        );
+        assert_eq!(SettingsNamespace::Global.to_string(), "global");
+    }
+
+    #[test]
+    fn test_setting_enabled() {
+        assert!(setting_enabled(Some("1")));
+        assert!(setting_enabled(Some("2")));
+        assert!(!setting_enabled(Some("0")));
+        assert!(!setting_enabled(Some("3")));
+        assert!(!setting_enabled(None));
+    }
+}
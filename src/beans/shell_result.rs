@@ -0,0 +1,98 @@
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::errors::{AdbError, AdbResult};
+
+/// shell v2 协议中的数据包类型。
+const ID_STDOUT: u8 = 1;
+const ID_STDERR: u8 = 2;
+const ID_EXIT: u8 = 3;
+
+/// `shell_v2` 的执行结果。
+///
+/// 设备支持 shell v2 协议时 stdout 与 stderr 分开，并带有退出码；
+/// 不支持时退回普通 `shell:`，两者合并在 `stdout` 中，`exit_code` 为 `None`。
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ShellResult {
+    pub stdout: Vec<u8>,
+    pub stderr: Vec<u8>,
+    pub exit_code: Option<i32>,
+}
+
+impl ShellResult {
+    /// 解析 shell v2 协议的输出：每个数据包为 1 字节类型 + 小端 u32 长度 + 数据。
+    ///
+    /// STDOUT、STDERR 的数据分别追加到 `stdout`、`stderr`，EXIT 数据包的 1 字节数据为退出码，
+    /// 其余类型的数据包被忽略。
+    pub fn parse_v2(data: &[u8]) -> AdbResult<ShellResult> {
+        let mut result = ShellResult::default();
+        let mut rest = data;
+        while !rest.is_empty() {
+            if rest.len() < 5 {
+                return Err(AdbError::unknown("truncated shell v2 packet header"));
+            }
+            let length = u32::from_le_bytes(rest[1..5].try_into().unwrap()) as usize;
+            let payload = rest
+                .get(5..5 + length)
+                .ok_or_else(|| AdbError::unknown("truncated shell v2 packet"))?;
+            match rest[0] {
+                ID_STDOUT => result.stdout.extend_from_slice(payload),
+                ID_STDERR => result.stderr.extend_from_slice(payload),
+                ID_EXIT => result.exit_code = payload.first().map(|&code| code as i32),
+                _ => {}
+            }
+            rest = &rest[5 + length..];
+        }
+        Ok(result)
+    }
+
+    /// 以字符串形式返回 stdout，非法的 UTF-8 字节会被替换。
+    pub fn stdout_str(&self) -> String {
+        String::from_utf8_lossy(&self.stdout).to_string()
+    }
+
+    /// 以字符串形式返回 stderr，非法的 UTF-8 字节会被替换。
+    pub fn stderr_str(&self) -> String {
+        String::from_utf8_lossy(&self.stderr).to_string()
+    }
+
+    /// 退出码为 0 时返回 `true`；不知道退出码（旧设备）时返回 `None`。
+    pub fn success(&self) -> Option<bool> {
+        self.exit_code.map(|code| code == 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn packet(id: u8, payload: &[u8]) -> Vec<u8> {
+        let mut data = vec![id];
+        data.extend_from_slice(&(payload.len() as u32).to_le_bytes());
+        data.extend_from_slice(payload);
+        data
+    }
+
+    #[test]
+    fn test_parse_v2_separates_streams() {
+        let mut data = packet(ID_STDOUT, b"out 1\n");
+        data.extend(packet(ID_STDERR, b"err\n"));
+        data.extend(packet(ID_STDOUT, b"out 2\n"));
+        data.extend(packet(ID_EXIT, &[2]));
+        let result = ShellResult::parse_v2(&data).unwrap();
+        assert_eq!(result.stdout_str(), "out 1\nout 2\n");
+        assert_eq!(result.stderr_str(), "err\n");
+        assert_eq!(result.exit_code, Some(2));
+        assert_eq!(result.success(), Some(false));
+    }
+
+    #[test]
+    fn test_parse_v2_truncated() {
+        let mut data = packet(ID_STDOUT, b"hello");
+        data.truncate(7);
+        assert!(ShellResult::parse_v2(&data).is_err());
+        assert!(ShellResult::parse_v2(&[ID_EXIT, 1, 0]).is_err());
+        assert_eq!(ShellResult::parse_v2(&[]).unwrap(), ShellResult::default());
+    }
+}
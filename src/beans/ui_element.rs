@@ -0,0 +1,169 @@
+use regex::Regex;
+use std::fmt::{Display, Formatter};
+
+/// 控件在屏幕上的矩形区域，对应 `bounds="[left,top][right,bottom]"`。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Bounds {
+    pub left: i32,
+    pub top: i32,
+    pub right: i32,
+    pub bottom: i32,
+}
+
+impl Bounds {
+    pub fn parse(bounds: &str) -> Option<Bounds> {
+        let re = Regex::new(r"\[(-?\d+),(-?\d+)\]\[(-?\d+),(-?\d+)\]").unwrap();
+        let cap = re.captures(bounds)?;
+        Some(Bounds {
+            left: cap[1].parse().ok()?,
+            top: cap[2].parse().ok()?,
+            right: cap[3].parse().ok()?,
+            bottom: cap[4].parse().ok()?,
+        })
+    }
+
+    /// 返回矩形区域的中心点坐标。
+    pub fn center(&self) -> (i32, i32) {
+        ((self.left + self.right) / 2, (self.top + self.bottom) / 2)
+    }
+}
+
+/// `uiautomator dump` 输出中的一个控件节点。
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct UiElement {
+    pub class: String,
+    pub text: String,
+    pub resource_id: String,
+    pub content_desc: String,
+    pub bounds: Bounds,
+}
+
+impl UiElement {
+    /// 控件中心点坐标，可直接用于 `click`。
+    pub fn center(&self) -> (i32, i32) {
+        self.bounds.center()
+    }
+}
+
+/// 控件的查找条件。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum By {
+    Text(String),
+    ResourceId(String),
+    ContentDesc(String),
+    Class(String),
+}
+
+impl By {
+    pub fn matches(&self, element: &UiElement) -> bool {
+        match self {
+            By::Text(text) => element.text.eq(text),
+            By::ResourceId(id) => element.resource_id.eq(id),
+            By::ContentDesc(desc) => element.content_desc.eq(desc),
+            By::Class(class) => element.class.eq(class),
+        }
+    }
+}
+
+impl Display for By {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            By::Text(text) => write!(f, "text={:?}", text),
+            By::ResourceId(id) => write!(f, "resource-id={:?}", id),
+            By::ContentDesc(desc) => write!(f, "content-desc={:?}", desc),
+            By::Class(class) => write!(f, "class={:?}", class),
+        }
+    }
+}
+
+/// 解析 `uiautomator dump` 生成的 XML，按文档顺序返回所有 `node` 节点。
+pub fn parse_ui_elements(xml: &str) -> Vec<UiElement> {
+    let node_regex = Regex::new(r"<node\s([^>]*?)/?>").unwrap();
+    let attr_regex = Regex::new(r#"([\w-]+)="([^"]*)""#).unwrap();
+    node_regex
+        .captures_iter(xml)
+        .map(|node| {
+            let mut element = UiElement::default();
+            for (_, [key, value]) in attr_regex.captures_iter(&node[1]).map(|c| c.extract()) {
+                let value = unescape_xml(value);
+                match key {
+                    "class" => element.class = value,
+                    "text" => element.text = value,
+                    "resource-id" => element.resource_id = value,
+                    "content-desc" => element.content_desc = value,
+                    "bounds" => element.bounds = Bounds::parse(&value).unwrap_or_default(),
+                    _ => {}
+                }
+            }
+            element
+        })
+        .collect()
+}
+
+fn unescape_xml(value: &str) -> String {
+    value
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&#10;", "\n")
+        .replace("&amp;", "&")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE_DUMP: &str = r#"<?xml version='1.0' encoding='UTF-8' standalone='yes' ?><hierarchy rotation="0"><node index="0" text="" resource-id="" class="android.widget.FrameLayout" package="com.android.settings" content-desc="" checkable="false" checked="false" clickable="false" enabled="true" focusable="false" focused="false" scrollable="false" long-clickable="false" password="false" selected="false" bounds="[0,0][1080,1920]"><node index="0" text="Network &amp; internet" resource-id="android:id/title" class="android.widget.TextView" package="com.android.settings" content-desc="" checkable="false" checked="false" clickable="false" enabled="true" focusable="false" focused="false" scrollable="false" long-clickable="false" password="false" selected="false" bounds="[189,400][608,458]" /></node></hierarchy>"#;
+
+    #[test]
+    fn test_parse_ui_elements() {
+        let elements = parse_ui_elements(SAMPLE_DUMP);
+        assert_eq!(elements.len(), 2);
+        assert_eq!(elements[0].class, "android.widget.FrameLayout");
+        assert_eq!(
+            elements[0].bounds,
+            Bounds {
+                left: 0,
+                top: 0,
+                right: 1080,
+                bottom: 1920
+            }
+        );
+        assert_eq!(elements[1].text, "Network & internet");
+        assert_eq!(elements[1].resource_id, "android:id/title");
+        assert_eq!(
+            elements[1].bounds,
+            Bounds::parse("[189,400][608,458]").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_by_matches() {
+        let elements = parse_ui_elements(SAMPLE_DUMP);
+        let by_text = By::Text("Network & internet".to_string());
+        let by_id = By::ResourceId("android:id/title".to_string());
+        let by_class = By::Class("android.widget.FrameLayout".to_string());
+        let by_desc = By::ContentDesc("Navigate up".to_string());
+        assert!(!by_text.matches(&elements[0]));
+        assert!(by_text.matches(&elements[1]));
+        assert!(by_id.matches(&elements[1]));
+        assert!(by_class.matches(&elements[0]));
+        assert!(!elements.iter().any(|e| by_desc.matches(e)));
+    }
+
+    #[test]
+    fn test_bounds_center() {
+        let bounds = Bounds::parse("[189,400][608,458]").unwrap();
+        assert_eq!(bounds.center(), (398, 429));
+        assert_eq!(
+            Bounds::parse("[0,0][1080,1920]").unwrap().center(),
+            (540, 960)
+        );
+    }
+
+    #[test]
+    fn test_parse_empty_hierarchy() {
+        assert!(parse_ui_elements("<hierarchy rotation=\"0\"></hierarchy>").is_empty());
+    }
+}
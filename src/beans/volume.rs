@@ -0,0 +1,82 @@
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// 音量类型，对应 `AudioManager.STREAM_*`。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum VolumeStream {
+    VoiceCall,
+    System,
+    Ring,
+    Music,
+    Alarm,
+    Notification,
+    Accessibility,
+}
+
+impl VolumeStream {
+    /// `media volume --stream` 使用的编号。
+    pub fn code(&self) -> u8 {
+        match self {
+            VolumeStream::VoiceCall => 0,
+            VolumeStream::System => 1,
+            VolumeStream::Ring => 2,
+            VolumeStream::Music => 3,
+            VolumeStream::Alarm => 4,
+            VolumeStream::Notification => 5,
+            VolumeStream::Accessibility => 10,
+        }
+    }
+}
+
+/// 生成 `media volume` 命令：`level` 为 `None` 时查询当前音量，否则设置音量。
+pub(crate) fn media_volume_args(stream: VolumeStream, level: Option<u8>) -> Vec<String> {
+    let mut args = vec![
+        "media".to_string(),
+        "volume".to_string(),
+        "--stream".to_string(),
+        stream.code().to_string(),
+    ];
+    match level {
+        Some(level) => args.extend(["--set".to_string(), level.to_string()]),
+        None => args.push("--get".to_string()),
+    }
+    args
+}
+
+/// 解析 `media volume --get` 输出中的 `volume is 5 in range [0..15]`，返回音量的取值范围。
+pub(crate) fn parse_volume_range(output: &str) -> Option<(u8, u8)> {
+    let range = output.split("in range [").nth(1)?;
+    let (min, rest) = range.split_once("..")?;
+    let max = rest.split(']').next()?;
+    Some((min.trim().parse().ok()?, max.trim().parse().ok()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_media_volume_args() {
+        assert_eq!(
+            media_volume_args(VolumeStream::Music, Some(7)),
+            ["media", "volume", "--stream", "3", "--set", "7"]
+        );
+        assert_eq!(
+            media_volume_args(VolumeStream::Accessibility, None),
+            ["media", "volume", "--stream", "10", "--get"]
+        );
+    }
+
+    #[test]
+    fn test_parse_volume_range() {
+        let output = "[v] will get volume\n[v] stream=3\n[v] volume is 5 in range [0..15]\n";
+        assert_eq!(parse_volume_range(output), Some((0, 15)));
+        assert_eq!(
+            parse_volume_range("[v] volume is 2 in range [1..7]"),
+            Some((1, 7))
+        );
+        assert_eq!(parse_volume_range("Error: unknown stream\n"), None);
+    }
+}
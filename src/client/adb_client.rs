@@ -1,24 +1,196 @@
-use crate::client::adb_device::AdbDevice;
+use crate::beans::device_summary::parse_device_line;
+#[cfg(feature = "serde")]
+use crate::beans::device_summary::{parse_device_list_long, DeviceSummary};
+use crate::client::adb_device::{parse_features, AdbDevice};
+use crate::errors::{AdbError, AdbResult};
 use std::fmt::Debug;
+use std::net::SocketAddr;
+use std::time::Duration;
 
-use anyhow::{anyhow, Context, Result};
+use anyhow::{anyhow, Result};
 
+#[cfg(feature = "tokio_async")]
+use async_stream::stream;
 #[cfg(feature = "tokio_async")]
 use futures_core::Stream;
 #[cfg(feature = "tokio_async")]
 use futures_util::stream;
 #[cfg(feature = "tokio_async")]
+use tokio::io::AsyncWriteExt;
+#[cfg(feature = "tokio_async")]
 use tokio::net::{TcpStream, ToSocketAddrs};
 
 use crate::protocols::AdbProtocol;
+use crate::utils::try_start_adb_server;
 #[cfg(feature = "blocking")]
 use std::net::{TcpStream, ToSocketAddrs};
 
 pub struct AdbClient {
     stream: TcpStream,
+    config: ClientConfig,
+}
+
+/// `track_devices` 推送的一份设备列表快照。
+#[derive(Debug)]
+pub struct DeviceSnapshot<T>
+where
+    T: ToSocketAddrs + Clone + Debug,
+{
+    pub devices: Vec<AdbDevice<T>>,
+    /// 是否为断线重连后重新订阅得到的第一份快照，期间的设备变化可能已经丢失。
+    pub resynced: bool,
+}
+
+/// `track_devices` 在 adb server 断开后的重连策略，重试间隔按指数退避增长。
+#[derive(Debug, Clone)]
+pub struct TrackReconnect {
+    pub max_attempts: u32,         // 连续重连失败的最大次数，超过后结束跟踪
+    pub initial_backoff: Duration, // 第一次重连前的等待时间
+    pub max_backoff: Duration,     // 重连等待时间的上限
+}
+
+impl Default for TrackReconnect {
+    fn default() -> Self {
+        TrackReconnect {
+            max_attempts: 10,
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_secs(5),
+        }
+    }
+}
+
+/// 连接 adb server 所需的配置：地址、连接超时与读写超时。
+///
+/// 读写超时只对同步版本生效，tokio 的 `TcpStream` 不支持读写超时，异步版本只使用连接超时。
+///
+/// `AdbClient` 会保存自己的配置，通过 [`AdbClient::reconnect`] 可以用同一份配置打开新的连接，
+/// 也可以把配置交给多个线程或任务，各自通过 [`AdbClient::with_config`] 建立连接。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClientConfig {
+    pub addr: String,
+    pub connect_timeout: Option<Duration>,
+    pub read_timeout: Option<Duration>,
+    pub write_timeout: Option<Duration>,
+}
+
+impl Default for ClientConfig {
+    fn default() -> Self {
+        ClientConfig {
+            addr: DEFAULT_ADB_ADDR.to_string(),
+            connect_timeout: None,
+            read_timeout: None,
+            write_timeout: None,
+        }
+    }
+}
+
+/// `AdbClient` 的构建器，可以指定 adb server 地址、连接超时与读写超时。
+///
+/// ```no_run
+/// use radb::client::AdbClientBuilder;
+/// use std::time::Duration;
+///
+/// let mut adb = AdbClientBuilder::new()
+///     .addr("127.0.0.1:5037")
+///     .timeout(Duration::from_secs(5))
+///     .build()
+///     .unwrap();
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct AdbClientBuilder {
+    config: ClientConfig,
+}
+
+impl AdbClientBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// adb server 的地址，默认为 `127.0.0.1:5037`。
+    pub fn addr<T: ToString>(mut self, addr: T) -> Self {
+        self.config.addr = addr.to_string();
+        self
+    }
+
+    /// 同时设置连接超时、读超时与写超时。
+    pub fn timeout(self, timeout: Duration) -> Self {
+        self.connect_timeout(timeout)
+            .read_timeout(timeout)
+            .write_timeout(timeout)
+    }
+
+    /// 建立连接的超时，超时返回 `AdbError::Timeout`。
+    pub fn connect_timeout(mut self, timeout: Duration) -> Self {
+        self.config.connect_timeout = Some(timeout);
+        self
+    }
+
+    /// 读超时，只对同步版本生效。
+    pub fn read_timeout(mut self, timeout: Duration) -> Self {
+        self.config.read_timeout = Some(timeout);
+        self
+    }
+
+    /// 写超时，只对同步版本生效。
+    pub fn write_timeout(mut self, timeout: Duration) -> Self {
+        self.config.write_timeout = Some(timeout);
+        self
+    }
+
+    /// 返回构建出的配置，不建立连接。
+    pub fn config(self) -> ClientConfig {
+        self.config
+    }
+
+    /// 连接 adb server 并将读写超时应用到连接上，连接失败时返回 `AdbError::ConnectionFailed`。
+    #[cfg(feature = "blocking")]
+    pub fn build(self) -> AdbResult<AdbClient> {
+        AdbClient::with_config(self.config)
+    }
+
+    /// 异步版本的 `build`，只应用连接超时，读写超时被忽略。
+    #[cfg(feature = "tokio_async")]
+    pub async fn build_async(self) -> AdbResult<AdbClient> {
+        AdbClient::with_config(self.config).await
+    }
+}
+
+/// 本机 adb server 的默认地址。
+const DEFAULT_ADB_ADDR: &str = "127.0.0.1:5037";
+
+fn connection_failed(addr: impl Debug, err: std::io::Error) -> AdbError {
+    AdbError::ConnectionFailed(format!("{:?}: {}", addr, err))
+}
+
+/// 依次尝试 `addr` 解析出的每个地址，每个地址最多等待 `timeout`，返回最后一个错误。
+#[cfg(feature = "blocking")]
+fn connect_timeout(addr: &str, timeout: Duration) -> std::io::Result<TcpStream> {
+    let mut last_err = None;
+    for addr in addr.to_socket_addrs()? {
+        match TcpStream::connect_timeout(&addr, timeout) {
+            Ok(stream) => return Ok(stream),
+            Err(err) => last_err = Some(err),
+        }
+    }
+    Err(last_err.unwrap_or_else(|| {
+        std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            "could not resolve to any address",
+        )
+    }))
 }
 
 impl AdbClient {
+    /// 返回一个 [`AdbClientBuilder`]。
+    pub fn builder() -> AdbClientBuilder {
+        AdbClientBuilder::new()
+    }
+
+    /// 建立当前连接时使用的配置。
+    pub fn config(&self) -> &ClientConfig {
+        &self.config
+    }
+
     pub fn parse_device_list_lines<T>(
         lines: &str,
         addr: T,
@@ -28,7 +200,7 @@ impl AdbClient {
     {
         let mut devices = vec![];
         if !lines.is_empty() {
-            lines.lines().into_iter().for_each(|line| {
+            lines.lines().for_each(|line| {
                 let parts: Vec<&str> = line.split("\t").collect();
                 if !parts.is_empty() {
                     let device = AdbDevice::new(parts[0], addr.clone());
@@ -38,16 +210,106 @@ impl AdbClient {
         };
         Ok(devices)
     }
+
+    /// 解析 `host:devices-l` 的输出，例如
+    /// `emulator-5554 device product:sdk_gphone64_x86_64 model:sdk_gphone64_x86_64 transport_id:1`。
+    ///
+    /// `transport_id` 与状态分别填入设备的 `transport_id`、`state`，其余 `key:value` 限定信息放入 `extra`。
+    /// 无法识别的状态记为 `None`。
+    pub fn parse_device_list_long_lines<T>(lines: &str, addr: T) -> Vec<AdbDevice<T>>
+    where
+        T: ToSocketAddrs + Clone + Debug,
+    {
+        lines
+            .lines()
+            .filter_map(parse_device_line)
+            .map(|line| {
+                let mut device = AdbDevice::new(line.serial, addr.clone());
+                device.state = line.state.parse().ok();
+                for (key, value) in line.qualifiers {
+                    if key == "transport_id" {
+                        device.transport_id = value.parse().ok();
+                    } else {
+                        device.extra.insert(key.to_string(), value.to_string());
+                    }
+                }
+                device
+            })
+            .collect()
+    }
 }
 
 #[cfg(feature = "tokio_async")]
 impl AdbClient {
+    /// 连接 `addr` 上的 adb server。
+    ///
+    /// # Panics
+    /// 连接失败时 panic，推荐使用 [`try_new`](Self::try_new)。
     pub async fn new<T>(addr: T) -> Self
     where
         T: ToSocketAddrs,
     {
-        let stream = TcpStream::connect(addr).await.unwrap();
-        Self { stream }
+        Self::try_new(addr)
+            .await
+            .expect("failed to connect to adb server")
+    }
+
+    /// 连接 `addr` 上的 adb server，连接失败时返回 `AdbError::ConnectionFailed`。
+    pub async fn try_new<T>(addr: T) -> AdbResult<Self>
+    where
+        T: ToSocketAddrs,
+    {
+        let addrs: Vec<SocketAddr> = tokio::net::lookup_host(addr)
+            .await
+            .map_err(|e| connection_failed("adb server", e))?
+            .collect();
+        let stream = TcpStream::connect(addrs.as_slice())
+            .await
+            .map_err(|e| connection_failed(&addrs, e))?;
+        let config = ClientConfig {
+            addr: stream.peer_addr()?.to_string(),
+            ..Default::default()
+        };
+        Ok(Self { stream, config })
+    }
+
+    /// 按 `config` 连接 adb server，连接超时返回 `AdbError::Timeout`。
+    ///
+    /// tokio 的 `TcpStream` 不支持读写超时，`read_timeout` 与 `write_timeout` 被忽略。
+    pub async fn with_config(config: ClientConfig) -> AdbResult<Self> {
+        let connect = TcpStream::connect(config.addr.as_str());
+        let stream = match config.connect_timeout {
+            Some(timeout) => tokio::time::timeout(timeout, connect)
+                .await
+                .map_err(|_| AdbError::timeout(format!("connect to {}", config.addr)))?,
+            None => connect.await,
+        }
+        .map_err(|e| connection_failed(&config.addr, e))?;
+        Ok(AdbClient { stream, config })
+    }
+
+    /// 使用相同的配置打开一条新连接，返回新的客户端，原客户端不受影响。
+    pub async fn reconnect(&self) -> AdbResult<Self> {
+        Self::with_config(self.config.clone()).await
+    }
+
+    /// 同 [`reconnect`](Self::reconnect)。
+    pub async fn try_clone(&self) -> AdbResult<Self> {
+        self.reconnect().await
+    }
+
+    /// 连接本机默认的 adb server（`127.0.0.1:5037`），连接失败时执行 `adb start-server` 后重试一次。
+    pub async fn try_default() -> AdbResult<Self> {
+        match Self::try_new(DEFAULT_ADB_ADDR).await {
+            Ok(client) => Ok(client),
+            Err(err) => {
+                let started = tokio::task::spawn_blocking(try_start_adb_server).await;
+                if !matches!(started, Ok(Ok(()))) {
+                    return Err(err);
+                }
+                Self::try_new(DEFAULT_ADB_ADDR).await
+            }
+        }
     }
 
     /// 以迭代器的形式列出所有连接的 ADB 设备。
@@ -56,7 +318,7 @@ impl AdbClient {
     /// 返回一个设备迭代器，如果获取设备列表失败，则返回错误。
     pub async fn iter_devices(
         &mut self,
-    ) -> impl Stream<Item = AdbDevice<impl ToSocketAddrs + Clone>> {
+    ) -> impl Stream<Item = AdbDevice<impl ToSocketAddrs + Clone + Debug>> {
         let devices = self
             .list_devices()
             .await
@@ -65,16 +327,79 @@ impl AdbClient {
         stream::iter(devices)
     }
 
+    /// 关闭与 adb server 的连接。drop 时连接同样会被关闭，需要确认关闭结果时使用本方法。
+    pub async fn close(mut self) -> AdbResult<()> {
+        match self.stream.shutdown().await {
+            Err(e) if e.kind() != std::io::ErrorKind::NotConnected => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+
+    /// 获取序列号为 `serial` 的设备，设备使用与客户端相同的 adb server 地址。
+    pub fn device(&mut self, serial: &str) -> AdbResult<AdbDevice<SocketAddr>> {
+        Ok(AdbDevice::new(serial, self.stream.peer_addr()?))
+    }
+
+    /// 获取传输 ID 为 `transport_id` 的设备，设备使用与客户端相同的 adb server 地址。
+    pub fn device_by_transport_id(
+        &mut self,
+        transport_id: u64,
+    ) -> AdbResult<AdbDevice<SocketAddr>> {
+        Ok(AdbDevice::with_transport_id(
+            transport_id,
+            self.stream.peer_addr()?,
+        ))
+    }
+
+    /// 返回唯一连接的设备；没有设备或有多个设备时返回错误，错误信息中包含设备数量。
+    pub async fn only_device(&mut self) -> AdbResult<AdbDevice<SocketAddr>> {
+        let mut devices = self.list_devices().await?;
+        if devices.len() == 1 {
+            return Ok(devices.remove(0));
+        }
+        let serials: Vec<_> = devices.iter().filter_map(|d| d.serial.as_deref()).collect();
+        Err(AdbError::unknown(format!(
+            "expected exactly one device, found {}: {:?}",
+            devices.len(),
+            serials
+        )))
+    }
+
     /// 获取 ADB 服务器的版本号。
     ///
     /// # 返回值
     /// 返回服务器的版本号字符串，如果获取失败，则返回错误。
     pub async fn server_version(&mut self) -> Result<String> {
-        let command = "host:version";
-        self.stream.send_cmd_then_check_okay(command).await?;
-        let version_string = self.stream.read_string_block().await?;
-        let version = usize::from_str_radix(&version_string, 16)?;
-        Ok(version.to_string())
+        Ok(self.server_version_u32().await?.to_string())
+    }
+
+    /// 获取 ADB 服务器的版本号，返回数值形式，便于与最低版本比较。
+    pub async fn server_version_u32(&mut self) -> AdbResult<u32> {
+        self.stream.send_cmd_then_check_okay("host:version").await?;
+        let version = self.stream.read_string_block().await?;
+        u32::from_str_radix(version.trim(), 16)
+            .map_err(|_| AdbError::unknown(format!("invalid server version {:#?}", version)))
+    }
+
+    /// 通过 `host:host-features` 获取 adb server 支持的特性，例如 `shell_v2`、`cmd`、`push_sync`，
+    /// 用于在使用较新的协议前检查是否可用。
+    pub async fn host_features(&mut self) -> AdbResult<Vec<String>> {
+        self.stream
+            .send_cmd_then_check_okay("host:host-features")
+            .await?;
+        let features = self.stream.read_string_block().await?;
+        Ok(parse_features(&features))
+    }
+
+    /// 执行任意 host 服务，返回服务端以长度前缀（4 位十六进制）返回的响应。
+    ///
+    /// host 服务由 adb server 自己处理，例如 `host:version`、`host:devices-l`、
+    /// `host-serial:<serial>:get-state`；`shell:`、`sync:`、`jdwp` 等 transport 服务
+    /// 由设备上的 adbd 处理，需要先切换到设备的 transport，请使用 `AdbDevice::exec_service`。
+    /// 只适用于返回一个长度前缀响应的服务，`host:track-devices` 等持续推送的服务请使用对应的方法。
+    pub async fn exec_host_service(&mut self, service: &str) -> AdbResult<String> {
+        self.stream.send_cmd_then_check_okay(service).await?;
+        Ok(self.stream.read_string_block().await?)
     }
 
     /// 关闭 ADB 服务器。
@@ -117,21 +442,172 @@ impl AdbClient {
         Ok(self.stream.read_string_block().await?)
     }
 
-    pub async fn list_devices(&mut self) -> Result<Vec<AdbDevice<impl ToSocketAddrs + Clone>>> {
-        self.stream.send_cmd_then_check_okay("host:devices").await?;
+    /// 通过 `host:devices-l` 列出所有设备，并填充设备的 `transport_id`、`state` 与 `extra`。
+    pub async fn list_devices(&mut self) -> Result<Vec<AdbDevice<SocketAddr>>> {
+        self.stream
+            .send_cmd_then_check_okay("host:devices-l")
+            .await?;
+        let resp = self.stream.read_string_block().await?;
+        Ok(Self::parse_device_list_long_lines(
+            &resp,
+            self.stream.peer_addr()?,
+        ))
+    }
+
+    /// 列出所有设备并读取型号、品牌、Android 版本等属性，序列化为 JSON，便于 CI 展示设备状态。
+    ///
+    /// 属性读取是尽力而为的：离线或未授权的设备只包含 `host:devices-l` 中的信息，
+    /// 单个设备读取失败不会影响其他设备。
+    #[cfg(feature = "serde")]
+    pub async fn list_devices_json(&mut self) -> AdbResult<String> {
+        self.stream
+            .send_cmd_then_check_okay("host:devices-l")
+            .await?;
         let resp = self.stream.read_string_block().await?;
-        Self::parse_device_list_lines(&resp, self.stream.local_addr()?.clone())
+        let addr = self.stream.peer_addr()?;
+        let mut summaries = parse_device_list_long(&resp);
+        for summary in summaries.iter_mut().filter(|s| s.state == "device") {
+            let mut device = AdbDevice::new(summary.serial.as_str(), addr);
+            let mut values: [Option<String>; 3] = Default::default();
+            for (value, key) in values.iter_mut().zip(DeviceSummary::PROPS) {
+                *value = device.get_prop(key).await.ok().filter(|v| !v.is_empty());
+            }
+            summary.set_props(values);
+        }
+        serde_json::to_string_pretty(&summaries).map_err(AdbError::unknown)
+    }
+
+    /// 通过 `host:track-devices` 持续跟踪设备列表的变化，断线后按默认策略自动重连。
+    ///
+    /// 服务端每次设备列表变化都会推送一份完整快照，流中的每一项即为当时的全部设备。
+    /// 该方法会消耗客户端本身的连接，跟踪期间该连接不能再用于其他命令。
+    pub async fn track_devices(
+        self,
+    ) -> Result<impl Stream<Item = DeviceSnapshot<impl ToSocketAddrs + Clone + Debug>>> {
+        self.track_devices_with(Some(TrackReconnect::default()))
+            .await
+    }
+
+    /// 同 `track_devices`，可以指定重连策略。
+    ///
+    /// # 参数
+    /// - `reconnect`: 重连策略，为 `None` 时不重连，服务端关闭连接后流随之结束。
+    pub async fn track_devices_with(
+        mut self,
+        reconnect: Option<TrackReconnect>,
+    ) -> Result<impl Stream<Item = DeviceSnapshot<impl ToSocketAddrs + Clone + Debug>>> {
+        self.stream
+            .send_cmd_then_check_okay("host:track-devices")
+            .await?;
+        let addr = self.stream.peer_addr()?;
+        let mut stream = self.stream;
+        Ok(stream! {
+            let mut resynced = false;
+            loop {
+                if let Ok(resp) = stream.read_string_block().await {
+                    if let Ok(devices) = Self::parse_device_list_lines(&resp, addr) {
+                        yield DeviceSnapshot { devices, resynced };
+                        resynced = false;
+                        continue;
+                    }
+                }
+                let Some(policy) = reconnect.as_ref() else {
+                    break;
+                };
+                let mut backoff = policy.initial_backoff;
+                let mut subscribed = None;
+                for _ in 0..policy.max_attempts {
+                    tokio::time::sleep(backoff).await;
+                    if let Ok(mut conn) = TcpStream::connect(addr).await {
+                        if conn
+                            .send_cmd_then_check_okay("host:track-devices")
+                            .await
+                            .is_ok()
+                        {
+                            subscribed = Some(conn);
+                            break;
+                        }
+                    }
+                    backoff = (backoff * 2).min(policy.max_backoff);
+                }
+                match subscribed {
+                    Some(conn) => {
+                        stream = conn;
+                        resynced = true;
+                    }
+                    None => break,
+                }
+            }
+        })
     }
 }
 
 #[cfg(feature = "blocking")]
 impl AdbClient {
+    /// 连接 `addr` 上的 adb server。
+    ///
+    /// # Panics
+    /// 连接失败时 panic，推荐使用 [`try_new`](Self::try_new)。
     pub fn new<T>(addr: T) -> Self
     where
         T: ToSocketAddrs,
     {
-        let stream = TcpStream::connect(addr).unwrap();
-        Self { stream }
+        Self::try_new(addr).expect("failed to connect to adb server")
+    }
+
+    /// 连接 `addr` 上的 adb server，连接失败时返回 `AdbError::ConnectionFailed`。
+    pub fn try_new<T>(addr: T) -> AdbResult<Self>
+    where
+        T: ToSocketAddrs,
+    {
+        let addrs: Vec<SocketAddr> = addr
+            .to_socket_addrs()
+            .map_err(|e| connection_failed("adb server", e))?
+            .collect();
+        let stream =
+            TcpStream::connect(addrs.as_slice()).map_err(|e| connection_failed(&addrs, e))?;
+        let config = ClientConfig {
+            addr: stream.peer_addr()?.to_string(),
+            ..Default::default()
+        };
+        Ok(Self { stream, config })
+    }
+
+    /// 按 `config` 连接 adb server 并将读写超时应用到连接上。
+    ///
+    /// 连接超时返回 `AdbError::Timeout`，其他连接失败返回 `AdbError::ConnectionFailed`。
+    pub fn with_config(config: ClientConfig) -> AdbResult<Self> {
+        let stream = match config.connect_timeout {
+            Some(timeout) => connect_timeout(&config.addr, timeout),
+            None => TcpStream::connect(config.addr.as_str()),
+        }
+        .map_err(|e| match e.kind() {
+            std::io::ErrorKind::TimedOut => {
+                AdbError::timeout(format!("connect to {}", config.addr))
+            }
+            _ => connection_failed(&config.addr, e),
+        })?;
+        stream.set_read_timeout(config.read_timeout)?;
+        stream.set_write_timeout(config.write_timeout)?;
+        Ok(AdbClient { stream, config })
+    }
+
+    /// 使用相同的配置打开一条新连接，返回新的客户端，原客户端不受影响。
+    pub fn reconnect(&self) -> AdbResult<Self> {
+        Self::with_config(self.config.clone())
+    }
+
+    /// 同 [`reconnect`](Self::reconnect)。
+    pub fn try_clone(&self) -> AdbResult<Self> {
+        self.reconnect()
+    }
+
+    /// 连接本机默认的 adb server（`127.0.0.1:5037`），连接失败时执行 `adb start-server` 后重试一次。
+    pub fn try_default() -> AdbResult<Self> {
+        Self::try_new(DEFAULT_ADB_ADDR).or_else(|err| {
+            try_start_adb_server().map_err(|_| err)?;
+            Self::try_new(DEFAULT_ADB_ADDR)
+        })
     }
 
     /// 以迭代器的形式列出所有连接的 ADB 设备。
@@ -144,10 +620,142 @@ impl AdbClient {
         Ok(self.list_devices()?.into_iter())
     }
 
-    pub fn list_devices(&mut self) -> Result<Vec<AdbDevice<impl ToSocketAddrs + Clone + Debug>>> {
-        self.stream.send_cmd_then_check_okay("host:devices")?;
+    /// 通过 `host:devices-l` 列出所有设备，并填充设备的 `transport_id`、`state` 与 `extra`。
+    pub fn list_devices(&mut self) -> Result<Vec<AdbDevice<SocketAddr>>> {
+        self.stream.send_cmd_then_check_okay("host:devices-l")?;
         let resp = self.stream.read_string_block()?;
-        Self::parse_device_list_lines(&resp, self.stream.peer_addr()?.clone())
+        Ok(Self::parse_device_list_long_lines(
+            &resp,
+            self.stream.peer_addr()?,
+        ))
+    }
+
+    /// 列出所有设备并读取型号、品牌、Android 版本等属性，序列化为 JSON，便于 CI 展示设备状态。
+    ///
+    /// 属性读取是尽力而为的：离线或未授权的设备只包含 `host:devices-l` 中的信息，
+    /// 单个设备读取失败不会影响其他设备。
+    #[cfg(feature = "serde")]
+    pub fn list_devices_json(&mut self) -> AdbResult<String> {
+        self.stream.send_cmd_then_check_okay("host:devices-l")?;
+        let resp = self.stream.read_string_block()?;
+        let addr = self.stream.peer_addr()?;
+        let mut summaries = parse_device_list_long(&resp);
+        for summary in summaries.iter_mut().filter(|s| s.state == "device") {
+            let mut device = AdbDevice::new(summary.serial.as_str(), addr);
+            let mut values: [Option<String>; 3] = Default::default();
+            for (value, key) in values.iter_mut().zip(DeviceSummary::PROPS) {
+                *value = device.get_prop(key).ok().filter(|v| !v.is_empty());
+            }
+            summary.set_props(values);
+        }
+        serde_json::to_string_pretty(&summaries).map_err(AdbError::unknown)
+    }
+
+    /// 通过 `host:track-devices` 持续跟踪设备列表的变化，断线后按默认策略自动重连。
+    ///
+    /// 服务端每次设备列表变化都会推送一份完整快照，迭代器的每一项即为当时的全部设备。
+    /// 该方法会消耗客户端本身的连接，跟踪期间该连接不能再用于其他命令。
+    pub fn track_devices(
+        self,
+    ) -> Result<impl Iterator<Item = DeviceSnapshot<impl ToSocketAddrs + Clone + Debug>>> {
+        self.track_devices_with(Some(TrackReconnect::default()))
+    }
+
+    /// 同 `track_devices`，可以指定重连策略。
+    ///
+    /// # 参数
+    /// - `reconnect`: 重连策略，为 `None` 时不重连，服务端关闭连接后迭代结束。
+    pub fn track_devices_with(
+        mut self,
+        reconnect: Option<TrackReconnect>,
+    ) -> Result<impl Iterator<Item = DeviceSnapshot<impl ToSocketAddrs + Clone + Debug>>> {
+        self.stream.send_cmd_then_check_okay("host:track-devices")?;
+        let addr = self.stream.peer_addr()?;
+        let mut stream = self.stream;
+        let mut resynced = false;
+        // 放弃重连后不再尝试，保证迭代结束后一直返回 None
+        let mut done = false;
+        Ok(std::iter::from_fn(move || loop {
+            if done {
+                return None;
+            }
+            if let Ok(resp) = stream.read_string_block() {
+                if let Ok(devices) = Self::parse_device_list_lines(&resp, addr) {
+                    let snapshot = DeviceSnapshot { devices, resynced };
+                    resynced = false;
+                    return Some(snapshot);
+                }
+            }
+            match reconnect
+                .as_ref()
+                .and_then(|policy| Self::resubscribe_track_devices(addr, policy))
+            {
+                Some(new_stream) => {
+                    stream = new_stream;
+                    resynced = true;
+                }
+                None => {
+                    done = true;
+                    return None;
+                }
+            }
+        }))
+    }
+
+    /// 按重连策略重新连接 adb server 并订阅 `host:track-devices`，重试耗尽时返回 `None`。
+    fn resubscribe_track_devices(addr: SocketAddr, policy: &TrackReconnect) -> Option<TcpStream> {
+        let mut backoff = policy.initial_backoff;
+        for _ in 0..policy.max_attempts {
+            std::thread::sleep(backoff);
+            if let Ok(mut stream) = TcpStream::connect(addr) {
+                if stream
+                    .send_cmd_then_check_okay("host:track-devices")
+                    .is_ok()
+                {
+                    return Some(stream);
+                }
+            }
+            backoff = (backoff * 2).min(policy.max_backoff);
+        }
+        None
+    }
+
+    /// 关闭与 adb server 的连接。drop 时连接同样会被关闭，需要确认关闭结果时使用本方法。
+    pub fn close(self) -> AdbResult<()> {
+        match self.stream.shutdown(std::net::Shutdown::Both) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotConnected => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+
+    /// 获取序列号为 `serial` 的设备，设备使用与客户端相同的 adb server 地址。
+    pub fn device(&mut self, serial: &str) -> AdbResult<AdbDevice<SocketAddr>> {
+        Ok(AdbDevice::new(serial, self.stream.peer_addr()?))
+    }
+
+    /// 获取传输 ID 为 `transport_id` 的设备，设备使用与客户端相同的 adb server 地址。
+    pub fn device_by_transport_id(
+        &mut self,
+        transport_id: u64,
+    ) -> AdbResult<AdbDevice<SocketAddr>> {
+        Ok(AdbDevice::with_transport_id(
+            transport_id,
+            self.stream.peer_addr()?,
+        ))
+    }
+
+    /// 返回唯一连接的设备；没有设备或有多个设备时返回错误，错误信息中包含设备数量。
+    pub fn only_device(&mut self) -> AdbResult<AdbDevice<SocketAddr>> {
+        let mut devices = self.list_devices()?;
+        if devices.len() == 1 {
+            return Ok(devices.remove(0));
+        }
+        let serials: Vec<_> = devices.iter().filter_map(|d| d.serial.as_deref()).collect();
+        Err(AdbError::unknown(format!(
+            "expected exactly one device, found {}: {:?}",
+            devices.len(),
+            serials
+        )))
     }
 
     /// 获取 ADB 服务器的版本号。
@@ -155,11 +763,34 @@ impl AdbClient {
     /// # 返回值
     /// 返回服务器的版本号字符串，如果获取失败，则返回错误。
     pub fn server_version(&mut self) -> Result<String> {
-        let command = "host:version";
-        self.stream.send_cmd_then_check_okay(command)?;
-        let version_string = self.stream.read_string_block()?;
-        let version = usize::from_str_radix(&version_string, 16)?;
-        Ok(version.to_string())
+        Ok(self.server_version_u32()?.to_string())
+    }
+
+    /// 获取 ADB 服务器的版本号，返回数值形式，便于与最低版本比较。
+    pub fn server_version_u32(&mut self) -> AdbResult<u32> {
+        self.stream.send_cmd_then_check_okay("host:version")?;
+        let version = self.stream.read_string_block()?;
+        u32::from_str_radix(version.trim(), 16)
+            .map_err(|_| AdbError::unknown(format!("invalid server version {:#?}", version)))
+    }
+
+    /// 通过 `host:host-features` 获取 adb server 支持的特性，例如 `shell_v2`、`cmd`、`push_sync`，
+    /// 用于在使用较新的协议前检查是否可用。
+    pub fn host_features(&mut self) -> AdbResult<Vec<String>> {
+        self.stream.send_cmd_then_check_okay("host:host-features")?;
+        let features = self.stream.read_string_block()?;
+        Ok(parse_features(&features))
+    }
+
+    /// 执行任意 host 服务，返回服务端以长度前缀（4 位十六进制）返回的响应。
+    ///
+    /// host 服务由 adb server 自己处理，例如 `host:version`、`host:devices-l`、
+    /// `host-serial:<serial>:get-state`；`shell:`、`sync:`、`jdwp` 等 transport 服务
+    /// 由设备上的 adbd 处理，需要先切换到设备的 transport，请使用 `AdbDevice::exec_service`。
+    /// 只适用于返回一个长度前缀响应的服务，`host:track-devices` 等持续推送的服务请使用对应的方法。
+    pub fn exec_host_service(&mut self, service: &str) -> AdbResult<String> {
+        self.stream.send_cmd_then_check_okay(service)?;
+        Ok(self.stream.read_string_block()?)
     }
 
     /// 关闭 ADB 服务器。
@@ -199,13 +830,17 @@ impl AdbClient {
         }
         let command = format!("host:disconnect:{}", serial);
         self.stream.send_cmd_then_check_okay(&command)?;
-        Ok(self.stream.read_string_block()?)
+        self.stream.read_string_block()
     }
 }
 
+/// 连接本机默认的 adb server。
+///
+/// # Panics
+/// 连接失败时 panic，推荐使用 [`AdbClient::try_default`]。
 #[cfg(feature = "blocking")]
 impl Default for AdbClient {
     fn default() -> Self {
-        Self::new("127.0.0.1:5037")
+        Self::new(DEFAULT_ADB_ADDR)
     }
 }
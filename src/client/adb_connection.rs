@@ -0,0 +1,92 @@
+use std::io;
+use std::net::Shutdown;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use tokio::io::{AsyncRead, AsyncWrite, AsyncWriteExt, ReadBuf};
+use tokio::net::TcpStream;
+
+use crate::errors::AdbResult;
+
+/// 异步模式下与设备之间的一条 adb 连接。
+///
+/// 由 `open_transport`、`shell_stream`、`create_connection` 返回，实现了
+/// `AsyncRead`/`AsyncWrite`，可以直接使用 `AdbProtocol` 的全部方法。
+///
+/// 生命周期：
+/// - 写入完成后调用 `shutdown_write` 半关闭写端，设备会读到 EOF，但仍可继续读取输出；
+/// - 调用 `close` 会先 flush 再关闭写端，随后释放连接；
+/// - 直接 drop 时也会尽力关闭写端，保证已写入的数据在 FIN 之前发出。
+///   drop 无法等待异步操作，需要确认结果时请使用 `close`。
+#[derive(Debug)]
+pub struct AdbConnection {
+    stream: Option<TcpStream>,
+}
+
+impl AdbConnection {
+    pub(crate) fn new(stream: TcpStream) -> Self {
+        AdbConnection {
+            stream: Some(stream),
+        }
+    }
+
+    fn stream_mut(&mut self) -> &mut TcpStream {
+        self.stream.as_mut().expect("connection already released")
+    }
+
+    /// 半关闭连接：flush 后关闭写端，设备侧读到 EOF，读端保持可用。
+    pub async fn shutdown_write(&mut self) -> AdbResult<()> {
+        let stream = self.stream_mut();
+        stream.flush().await?;
+        stream.shutdown().await?;
+        Ok(())
+    }
+
+    /// flush 并关闭写端后释放连接。
+    pub async fn close(mut self) -> AdbResult<()> {
+        self.shutdown_write().await
+    }
+
+    /// 取出底层的 `TcpStream`，之后 drop 时不再自动关闭写端。
+    pub fn into_inner(mut self) -> TcpStream {
+        self.stream.take().expect("connection already released")
+    }
+}
+
+impl Drop for AdbConnection {
+    fn drop(&mut self) {
+        if let Some(stream) = self.stream.take() {
+            if let Ok(stream) = stream.into_std() {
+                let _ = stream.shutdown(Shutdown::Write);
+            }
+        }
+    }
+}
+
+impl AsyncRead for AdbConnection {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        Pin::new(self.get_mut().stream_mut()).poll_read(cx, buf)
+    }
+}
+
+impl AsyncWrite for AdbConnection {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(self.get_mut().stream_mut()).poll_write(cx, buf)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(self.get_mut().stream_mut()).poll_flush(cx)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(self.get_mut().stream_mut()).poll_shutdown(cx)
+    }
+}
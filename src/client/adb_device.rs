@@ -1,28 +1,32 @@
 use std::collections::HashMap;
-use std::fmt::{Debug, Display};
+use std::fmt::Debug;
+use std::fmt::Display;
 use std::fs::File;
-use std::{fs, thread, time};
+use std::net::Ipv6Addr;
+use std::time;
+use std::time::Duration;
 
 #[cfg(feature = "blocking")]
-use std::io::{BufRead, BufReader, Read, Write};
+use std::io::Read;
+use std::io::Write;
 #[cfg(feature = "blocking")]
 use std::net::{TcpStream, ToSocketAddrs};
 #[cfg(feature = "blocking")]
 use std::sync::{Arc, RwLock};
 
 use anyhow::{anyhow, Context};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
-use std::thread::sleep;
-use std::time::Duration;
 
 #[cfg(feature = "tokio_async")]
 use async_stream::stream;
-use chrono::DateTime;
+use chrono::{DateTime, Utc};
 
 #[cfg(feature = "tokio_async")]
 use futures_core::Stream;
 #[cfg(feature = "tokio_async")]
+use futures_util::future::BoxFuture;
+#[cfg(feature = "tokio_async")]
 use futures_util::pin_mut;
 #[cfg(feature = "tokio_async")]
 use futures_util::StreamExt;
@@ -31,30 +35,101 @@ use tokio::net::{TcpStream, ToSocketAddrs};
 #[cfg(feature = "tokio_async")]
 use tokio::process::Command;
 
-use log::{error, info};
+use log::{debug, error, info};
 
-use crate::beans::file_info::{parse_file_info, FileInfo};
-use crate::beans::forward_item::ForwardItem;
+use crate::beans::file_info::{
+    parse_dent, parse_file_info, parse_file_info_v2, FileInfo, STAT_V2_LEN, S_IFMT, S_IFREG,
+};
+use crate::beans::forward_item::{parse_forward_list, ForwardItem};
+use crate::beans::install_options::{parse_pm_failure, InstallOptions};
+use crate::beans::jdwp::parse_jdwp_pids;
+use crate::beans::key_code::{keyevent_args, KeyCode};
+use crate::beans::keyguard::{next_unlock_step, parse_keyguard_locked, UnlockStep};
+use crate::beans::logcat::{LogEntry, LogcatFilter};
+use crate::beans::mount_info::{parse_df_output, MountInfo, StorageInfo};
 use crate::beans::net_info::NetworkType;
+use crate::beans::net_info::{parse_ipv4, parse_ipv6_addrs, WLAN_IP_QUERIES};
+use crate::beans::package_filter::{parse_package_list, PackageFilter};
+use crate::beans::proc_meminfo::{available_memory, parse_proc_meminfo};
+use crate::beans::process_info::{parse_pidof, parse_ps_output, ProcessInfo};
+use crate::beans::reboot_mode::RebootMode;
+use crate::beans::screen_capture::{
+    rotate_upright, screenshot_format, ScreenCapture, PNG_SIGNATURE,
+};
+use crate::beans::screen_record::ScreenRecordOptions;
+use crate::beans::settings::{parse_settings_value, setting_enabled, SettingsNamespace};
+use crate::beans::shell_result::ShellResult;
+#[cfg(feature = "blocking")]
+use crate::client::emulator_console::{console_port, read_auth_token};
+#[cfg(feature = "tokio_async")]
+use crate::client::AdbConnection;
+#[cfg(feature = "blocking")]
+use crate::client::EmulatorConsole;
+#[cfg(feature = "blocking")]
+use crate::client::LogcatIterator;
+#[cfg(feature = "blocking")]
+use crate::client::PersistentShell;
+use crate::client::ScreenRecordHandle;
+#[cfg(feature = "blocking")]
+use crate::client::ShellLines;
 
+use crate::beans::activity_info::{
+    parse_current_focus, parse_resolve_activity, parse_resumed_activity, ActivityInfo,
+};
 use crate::beans::app_info::AppInfo;
-use crate::utils::{adb_path, get_free_port, init_logger};
-use image::{io::Reader as ImageReader, RgbImage};
+use crate::beans::battery_info::BatteryInfo;
+use crate::beans::checksum::{parse_checksum, MD5_COMMANDS, SHA256_COMMANDS};
+use crate::beans::command::{is_command_missing, shell_escape_arg, AdbCommand};
+use crate::beans::command_log::{CommandLog, ReplayErrorPolicy, ReplayOptions};
+use crate::beans::device_state::{DeviceState, TransportKind};
+use crate::beans::device_time::{date_set_args, is_permission_error};
+use crate::beans::display_info::{
+    parse_display_rotation, parse_screen_on, parse_wm_density, parse_wm_size,
+};
+use crate::beans::dumpsys::{parse_battery, GfxInfo, GpuInfo, MemInfo, PowerInfo};
+use crate::beans::ui_element::{parse_ui_elements, By, UiElement};
+use crate::beans::volume::{media_volume_args, parse_volume_range, VolumeStream};
+use crate::errors::{AdbError, AdbResult};
+#[cfg(feature = "metrics")]
+use crate::metrics::Timer;
+#[cfg(feature = "blocking")]
+use crate::utils::poll_until;
+use crate::utils::{adb_path, md5_file, sha256_file};
+use image::{ImageFormat, RgbImage};
 
 #[cfg(feature = "tokio_async")]
-use tokio::io::{AsyncBufReadExt, AsyncReadExt, BufStream};
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncReadExt, AsyncWriteExt, BufStream};
 
 use crate::protocols::AdbProtocol;
 
+/// sync 协议单个 DATA 数据包的最大长度。
+const SYNC_DATA_MAX: usize = 64 * 1024;
+/// 推送文件时默认使用的文件模式（S_IFREG | 0o755）。
+const DEFAULT_PUSH_MODE: u32 = 0o100755;
+/// `uiautomator dump` 在设备上的临时输出路径。
+const UI_DUMP_PATH: &str = "/sdcard/window_dump.xml";
+/// `shell_with_status` 用来标记退出码的前缀。
+const EXIT_STATUS_MARKER: &str = ":RADB_EXIT_STATUS:";
+/// 粘贴按键，`send_keys` 通过剪贴板输入非 ASCII 文本时使用。
+const KEYCODE_PASTE: u32 = 279;
+
+/// adb server 上的一个设备。
+///
+/// 本身不持有连接：每个操作各自建立连接，操作结束或返回的流被 drop 时关闭，
+/// 因此大量创建、丢弃设备对象不会遗留连接。需要复用一条连接时使用 `shell_persistent`。
 #[derive(Debug)]
 pub struct AdbDevice<T>
 where
     T: ToSocketAddrs + Clone + Debug,
 {
-    pub serial: Option<String>,   // 设备的序列号，唯一标识一个设备。
-    pub transport_id: Option<u8>, // 设备的传输ID，用于识别设备在系统中的传输方式。
+    pub serial: Option<String>,    // 设备的序列号，唯一标识一个设备。
+    pub transport_id: Option<u64>, // 设备的传输ID，用于识别设备在系统中的传输方式。
     pub properties: HashMap<String, String>, // 设备的属性，以键值对形式存储，可包含多种设备信息。
     pub addr: T,
+    pub forwards: Vec<String>, // 通过 forward_remote_port 创建的端口转发（local），可用 forward_cleanup 清理。
+    pub features: Option<Vec<String>>, // get-features 的缓存，首次使用 sync 协议时查询，用于选择 v1/v2 命令。
+    pub state: Option<DeviceState>, // `host:devices-l` 中的设备状态，例如 device、offline、unauthorized。
+    pub extra: HashMap<String, String>, // `host:devices-l` 中的其他限定信息，例如 product、model、device、usb。
 }
 
 impl<T> AdbDevice<T>
@@ -70,6 +145,38 @@ where
             transport_id: None,
             properties: HashMap::new(),
             addr,
+            forwards: vec![],
+            features: None,
+            state: None,
+            extra: HashMap::new(),
+        }
+    }
+
+    /// 通过传输 ID 指定设备，适合序列号相同或未知的设备（例如多个 `????????????` 设备）。
+    pub fn with_transport_id(transport_id: u64, addr: T) -> Self {
+        AdbDevice {
+            serial: None,
+            transport_id: Some(transport_id),
+            properties: HashMap::new(),
+            addr,
+            forwards: vec![],
+            features: None,
+            state: None,
+            extra: HashMap::new(),
+        }
+    }
+
+    /// 创建一个指向同一设备的新对象，不携带属性缓存和端口转发记录。
+    pub(crate) fn fork(&self) -> AdbDevice<T> {
+        AdbDevice {
+            serial: self.serial.clone(),
+            transport_id: self.transport_id,
+            properties: HashMap::new(),
+            addr: self.addr.clone(),
+            forwards: vec![],
+            features: self.features.clone(),
+            state: self.state,
+            extra: self.extra.clone(),
         }
     }
 
@@ -77,7 +184,7 @@ where
     ///
     /// 根据提供的命令和设备的序列号或传输ID，构建并返回一个特定格式的字符串。
     /// 如果提供了命令，则格式为 `host-transport-id:传输ID:命令` 或 `host-serial:序列号:命令`。
-    /// 如果没有提供命令，则格式为 `host-transport-id:传输ID` 或 `host:transport:序列号`。
+    /// 如果没有提供命令，则格式为 `host:transport-id:传输ID` 或 `host:transport:序列号`。
     ///
     /// - `command`：可选的命令字符串，如果提供，将被添加到返回的字符串中。
     /// - 返回值：构建好的字符串，或者在某些条件下返回错误。
@@ -101,39 +208,46 @@ where
             }
         } else {
             if let Some(ref transport_id) = self.transport_id {
-                Ok(format!("host-transport-id:{}", transport_id))
+                Ok(format!("host:transport-id:{}", transport_id))
             } else {
                 Ok(format!("host:transport:{}", self.serial.clone().unwrap()))
             }
         }
     }
 
+    /// adb 命令行中选择该设备的参数，与 [`get_open_transport_prefix`](Self::get_open_transport_prefix)
+    /// 一样优先使用传输 ID：有传输 ID 时为 `-t <transport_id>`，否则为 `-s <serial>`，
+    /// 两者都没有时返回 `AdbError::DeviceNotFound`。
+    fn adb_target_args(&self) -> AdbResult<[String; 2]> {
+        match (self.transport_id, &self.serial) {
+            (Some(transport_id), _) => Ok(["-t".to_string(), transport_id.to_string()]),
+            (None, Some(serial)) => Ok(["-s".to_string(), serial.clone()]),
+            (None, None) => Err(AdbError::DeviceNotFound(
+                "device has neither serial nor transport_id".to_string(),
+            )),
+        }
+    }
+
+    /// 把参数列表拼成命令行，每个参数按 [`shell_escape_arg`] 转义。
     pub fn list2cmdline(args: &[&str]) -> String {
         args.iter()
-            .map(|arg| {
-                let mut quoted_arg = String::new();
-                for c in arg.chars() {
-                    if c == '"' {
-                        quoted_arg.push_str("\\\"");
-                    } else if c == '\\' {
-                        quoted_arg.push_str("\\\\");
-                    } else {
-                        quoted_arg.push(c);
-                    }
-                }
-                format!("\"{}\"", quoted_arg)
-            })
+            .map(|arg| shell_escape_arg(arg))
             .collect::<Vec<String>>()
             .join(" ")
     }
+
+    /// 把 `AdbCommand` 转为命令行字符串：整条命令原样使用，参数列表逐个转义。
+    pub fn command_line(cmd: &AdbCommand) -> String {
+        cmd.display()
+    }
 }
 
 #[cfg(feature = "tokio_async")]
 impl<T> AdbDevice<T>
 where
-    T: ToSocketAddrs + Clone,
+    T: ToSocketAddrs + Clone + Debug,
 {
-    pub async fn open_transport(&mut self, command: Option<&str>) -> anyhow::Result<TcpStream> {
+    pub async fn open_transport(&mut self, command: Option<&str>) -> anyhow::Result<AdbConnection> {
         // 获取打开传输的前缀，基于是否提供了命令和设备的序列号或传输ID。
         let prefix = self
             .get_open_transport_prefix(command)
@@ -142,11 +256,12 @@ where
         stream
             .send_cmd_then_check_okay(&prefix)
             .await
+            .map_err(transport_failed)
             .context(format!(
                 "Send Command >> {:#?} and Check Okay Failed",
                 &prefix
             ))?;
-        Ok(stream)
+        Ok(AdbConnection::new(stream))
     }
 
     async fn get_with_command(&mut self, command: &str) -> anyhow::Result<String> {
@@ -161,6 +276,13 @@ where
         self.get_with_command("get-state").await
     }
 
+    /// 与 `get_state` 相同，但解析为 [`DeviceState`]。
+    ///
+    /// 设备已断开时 adb server 返回 `device '...' not found`，此时返回 `AdbError::DeviceNotFound`。
+    pub async fn state(&mut self) -> AdbResult<DeviceState> {
+        self.get_state().await?.parse()
+    }
+
     ///
     /// adb get-serialno => emulator-5554
     pub async fn get_serialno(&mut self) -> anyhow::Result<String> {
@@ -176,6 +298,87 @@ where
         self.get_with_command("get-features").await
     }
 
+    /// 设备是否支持 `feature`，首次调用时查询并缓存 `get-features`。
+    ///
+    /// 查询失败视为不支持，但不缓存结果，下次调用时重新查询。
+    pub async fn has_feature(&mut self, feature: &str) -> bool {
+        if self.features.is_none() {
+            match self.get_features().await {
+                Ok(features) => self.features = Some(parse_features(&features)),
+                Err(_) => return false,
+            }
+        }
+        self.features
+            .as_ref()
+            .is_some_and(|features| features.iter().any(|f| f == feature))
+    }
+
+    /// 阻塞等待设备进入指定状态，与 `adb wait-for-<transport>-<state>` 相同。
+    ///
+    /// # 参数
+    /// - `transport`: 设备的连接方式（`usb`/`local`/`any`）。
+    /// - `state`: 需要等待的设备状态。
+    /// - `timeout`: 最长等待时间。
+    ///
+    /// # 返回值
+    /// - 设备进入该状态时返回 `Ok(())`，超过 `timeout` 返回 `AdbError::Timeout`。
+    /// - `state` 不能用于 `wait-for`（见 [`DeviceState::is_waitable`]）时直接返回错误，不连接 adb server。
+    pub async fn wait_for(
+        &mut self,
+        transport: TransportKind,
+        state: DeviceState,
+        timeout: Duration,
+    ) -> AdbResult<()> {
+        check_waitable(state)?;
+        let command = format!("wait-for-{}-{}", transport, state);
+        let wait = async {
+            let mut conn = self.open_transport(Some(&command)).await?;
+            conn.check_okay().await
+        };
+        match tokio::time::timeout(timeout, wait).await {
+            Ok(result) => Ok(result?),
+            Err(_) => Err(AdbError::timeout(format!(
+                "device not in state {} within {:?}",
+                state, timeout
+            ))),
+        }
+    }
+
+    /// 执行 `op`，遇到可重试的错误（[`AdbError::is_retryable`]）时按指数退避重试。
+    ///
+    /// 适合包裹在设备刚启动、短暂繁忙时可能失败的 `shell`、`open_transport` 等操作外层。
+    ///
+    /// # 参数
+    /// - `attempts`: 最多执行的次数（包括第一次），为 0 时按 1 处理。
+    /// - `backoff`: 第一次重试前的等待时间，之后每次翻倍。
+    /// - `op`: 需要执行的操作，参数为当前设备，返回装箱的 future，例如
+    ///   `|d| Box::pin(async move { Ok(d.shell_trim(&["getprop", "sys.boot_completed"]).await?) })`。
+    ///
+    /// # 返回值
+    /// - 第一次成功的结果；遇到不可重试的错误或次数用尽时返回最后一次的错误。
+    pub async fn with_retry<F, R>(
+        &mut self,
+        attempts: usize,
+        backoff: Duration,
+        mut op: F,
+    ) -> AdbResult<R>
+    where
+        F: for<'a> FnMut(&'a mut Self) -> BoxFuture<'a, AdbResult<R>>,
+    {
+        let mut backoff = backoff;
+        let mut remaining = attempts.max(1);
+        loop {
+            remaining -= 1;
+            match op(self).await {
+                Err(e) if remaining > 0 && e.is_retryable() => {
+                    tokio::time::sleep(backoff).await;
+                    backoff *= 2;
+                }
+                result => return result,
+            }
+        }
+    }
+
     /// 执行通过ADB shell命令流，并返回一个AdbConnection的实例。
     ///
     /// # 参数
@@ -184,15 +387,29 @@ where
     /// # 返回值
     /// - `anyhow::Result<AdbConnection>`: 如果命令成功执行，则返回一个AdbConnection的实例；
     ///                                  如果执行过程中出现错误，则返回错误信息。
-    pub async fn shell_stream(&mut self, command: &[&str]) -> anyhow::Result<TcpStream> {
-        // 打开与设备的传输通道
-        let mut conn = self.open_transport(None).await?;
-
+    pub async fn shell_stream(&mut self, command: &[&str]) -> anyhow::Result<AdbConnection> {
         // 将命令切片数组转换为命令行字符串
         let cmd = Self::list2cmdline(command);
+        self.shell_stream_raw(&cmd).await
+    }
+
+    /// 以原样的命令行字符串执行 shell 命令，不做参数转义。
+    async fn shell_stream_raw(&mut self, cmd: &str) -> anyhow::Result<AdbConnection> {
+        self.shell_stream_logged(cmd, cmd).await
+    }
+
+    /// 执行 shell 命令 `cmd`，日志和错误信息中只出现 `shown`，用于隐藏敏感参数。
+    async fn shell_stream_logged(
+        &mut self,
+        cmd: &str,
+        shown: &str,
+    ) -> anyhow::Result<AdbConnection> {
+        // 打开与设备的传输通道
+        let mut conn = self.open_transport(None).await?;
 
         // 构造完整的ADB shell命令字符串
         let send_cmd = format!("shell:{}", cmd);
+        debug!("shell:{}", shown);
 
         // 发送命令并检查是否执行成功
         let _ = conn
@@ -200,7 +417,7 @@ where
             .await
             .context(format!(
                 "Send Command >> {:#?} and Check Okay Failed",
-                &send_cmd
+                format!("shell:{}", shown)
             ))?;
 
         // 返回成功的AdbConnection实例
@@ -215,6 +432,8 @@ where
     /// # 返回值
     /// - `anyhow::Result<String>`: 命令执行成功则返回命令的输出结果，如果执行过程中出现错误则返回错误信息。
     pub async fn shell(&mut self, command: &[&str]) -> anyhow::Result<String> {
+        #[cfg(feature = "metrics")]
+        let _timer = Timer::start("shell");
         // 通过`shell_stream`方法执行命令，获取命令的输出流
         let mut s = self.shell_stream(command).await?;
 
@@ -225,17 +444,121 @@ where
         Ok(output)
     }
 
+    /// 同 [`shell`](AdbDevice::shell)，但日志和错误信息中 `redact` 下标对应的参数显示为 `***`。
+    ///
+    /// 设备收到的仍是完整命令，适合传递密码、令牌等不应写入日志的参数。
+    pub async fn shell_redacted(
+        &mut self,
+        command: &[&str],
+        redact: &[usize],
+    ) -> anyhow::Result<String> {
+        let cmd = AdbCommand::from(command);
+        let mut s = self
+            .shell_stream_logged(&cmd.display(), &cmd.display_redacted(redact))
+            .await?;
+        s.read_until_close().await
+    }
+
     pub async fn shell_trim(&mut self, command: &[&str]) -> anyhow::Result<String> {
         let s = self.shell(command).await?;
         Ok(s.trim().to_string())
     }
 
+    /// 执行 shell 命令并返回输出和退出码。
+    ///
+    /// 通过在命令后追加 `; echo $?` 获取退出码，stdout 与 stderr 合并在输出中。
+    pub async fn shell_with_status(&mut self, command: &[&str]) -> AdbResult<(String, i32)> {
+        let cmd = format!(
+            "{}; echo \"{}$?\"",
+            Self::list2cmdline(command),
+            EXIT_STATUS_MARKER
+        );
+        let output = self
+            .shell_stream_raw(&cmd)
+            .await?
+            .read_until_close()
+            .await?;
+        split_exit_status(&output)
+    }
+
+    /// 执行 shell 命令，退出码非 0 时返回 `AdbError::CommandFailed`，否则返回输出。
+    pub async fn shell_checked(&mut self, command: &[&str]) -> AdbResult<String> {
+        let (output, code) = self.shell_with_status(command).await?;
+        check_exit_status(command, output, code)
+    }
+
+    /// 查找设备上可执行文件的路径，不存在时返回 `None`。
+    pub async fn device_which(&mut self, tool: &str) -> AdbResult<Option<String>> {
+        let (output, code) = self.shell_with_status(&["command", "-v", tool]).await?;
+        let path = output.trim();
+        Ok((code == 0 && !path.is_empty()).then(|| path.to_string()))
+    }
+
+    /// 确认设备上存在 `tool`，否则返回说明缺少哪个工具的错误。
+    async fn require_tool(&mut self, tool: &str) -> AdbResult<()> {
+        match self.device_which(tool).await? {
+            Some(_) => Ok(()),
+            None => Err(missing_tool(&[tool])),
+        }
+    }
+
+    /// 按顺序在当前设备上重新执行录制的命令。
+    ///
+    /// 每条命令都会读取退出码，连接失败或退出码非 0（`AdbError::CommandFailed`）都视为该命令失败。
+    ///
+    /// # 参数
+    /// - `log`: 录制的命令日志。
+    /// - `options`: 是否保留录制时的命令间隔，以及命令失败时中止还是继续。
+    ///
+    /// # 返回值
+    /// - 本次回放的命令日志；`ReplayErrorPolicy::Abort` 下遇到失败直接返回该错误。
+    pub async fn replay(
+        &mut self,
+        log: &CommandLog,
+        options: ReplayOptions,
+    ) -> AdbResult<CommandLog> {
+        let mut replayed = CommandLog::new();
+        for (index, record) in log.records.iter().enumerate() {
+            if options.keep_timing {
+                tokio::time::sleep(log.delay_before(index)).await;
+            }
+            // 按录制时的命令行原样发送，避免引号、管道等被重新拆分
+            let cmdline = record.command.display();
+            let result = match self.shell_stream_raw(&replay_cmdline(&cmdline)).await {
+                Ok(mut conn) => conn.read_until_close().await.map_err(AdbError::from),
+                Err(e) => Err(e.into()),
+            }
+            .and_then(|output| replay_output(&cmdline, &output));
+            replayed.record(record.command.clone(), &result);
+            if let Err(e) = result {
+                error!("Replay {:#?} Failed: {}", cmdline, e);
+                if options.on_error == ReplayErrorPolicy::Abort {
+                    return Err(e);
+                }
+            }
+        }
+        Ok(replayed)
+    }
+
     pub async fn forward(
         &mut self,
         local: &str,
         remote: &str,
         norebind: bool,
     ) -> anyhow::Result<()> {
+        self.forward_port(local, remote, norebind)
+            .await
+            .map(|_| ())
+            .map_err(|e| anyhow!("Failed To Forward Port, Err >>> {}", e))
+    }
+
+    /// 创建端口转发；`local` 为 `tcp:0` 时返回 adb server 分配的本地端口。
+    async fn forward_port(
+        &mut self,
+        local: &str,
+        remote: &str,
+        norebind: bool,
+    ) -> AdbResult<Option<u16>> {
         let mut args = vec!["forward"];
         if norebind {
             args.push("norebind");
@@ -243,43 +566,73 @@ where
         let forward_str = format!("{};{}", local, remote);
         args.push(&forward_str);
         let full_cmd = args.join(":");
-        if let Ok(_) = self.open_transport(Some(&full_cmd)).await {
-            return Ok(());
+        let mut conn = self.open_transport(Some(&full_cmd)).await?;
+        // 第一个 OKAY 表示连接成功，第二个 OKAY 表示转发已建立
+        conn.check_okay().await?;
+        if local != "tcp:0" {
+            return Ok(None);
         }
-        Err(anyhow!("Failed To Forward Port"))
+        let port = conn.read_string_block().await?;
+        port.trim()
+            .parse()
+            .map(Some)
+            .map_err(|_| AdbError::unknown(format!("invalid forward port {:#?}", port)))
+    }
+
+    /// 由 adb server 分配一个空闲的本地端口转发到 `remote`，返回该本地端口。
+    ///
+    /// # 参数
+    /// - `remote`: 设备端地址，例如 `tcp:9999`。
+    pub async fn forward_tcp(&mut self, remote: &str) -> AdbResult<u16> {
+        let port = self
+            .forward_port("tcp:0", remote, false)
+            .await?
+            .ok_or_else(|| AdbError::unknown("adb server did not return a port"))?;
+        self.forwards.push(format!("tcp:{}", port));
+        Ok(port)
     }
 
     pub async fn forward_list(&mut self) -> anyhow::Result<Vec<ForwardItem>> {
         let mut connection = self.open_transport(Some("list-forward")).await?;
         let content = connection.read_string_block().await?;
-        let objs = content
-            .lines()
-            .map(|x| {
-                let mut s = x.split(" ");
-                if let (3, Some(3)) = s.size_hint() {
-                    let (serial, local, remote) = (
-                        s.nth(0).unwrap_or(""),
-                        s.nth(1).unwrap_or(""),
-                        s.nth(2).unwrap_or(""),
-                    );
-                    Some(ForwardItem::new(serial, local, remote))
-                } else {
-                    None
-                }
-            })
-            .filter(|x| x.is_some())
-            .map(|x| x.unwrap())
-            .collect();
-        Ok(objs)
+        Ok(parse_forward_list(&content))
     }
+    /// 返回转发到设备端 `tcp:<remote>` 的本地端口，已有该设备的转发时直接复用，否则新建一个。
     pub async fn forward_remote_port(&mut self, remote: u16) -> anyhow::Result<u16> {
         let remote = format!("tcp:{}", remote);
-        let local_port = get_free_port()?;
-        let local = format!("tcp:{}", local_port);
-        match self.forward(&local, &remote, false).await {
-            Ok(_) => Ok(local_port),
-            Err(e) => Err(anyhow!("Failed To Forward Port, Err >>> {}", e)),
+        for x in self.forward_list().await? {
+            if self.serial.as_deref() == Some(x.serial.as_str()) && x.remote == remote {
+                if let Some(port) = x.local_port() {
+                    return Ok(port);
+                }
+            }
+        }
+        self.forward_tcp(&remote)
+            .await
+            .map_err(|e| anyhow!("Failed To Forward Port, Err >>> {}", e))
+    }
+
+    /// 移除一个端口转发，与 `adb forward --remove <local>` 相同。
+    pub async fn forward_remove(&mut self, local: &str) -> AdbResult<()> {
+        self.open_transport(Some(&format!("killforward:{}", local)))
+            .await?;
+        self.forwards.retain(|x| x != local);
+        Ok(())
+    }
+
+    /// 移除该设备的所有端口转发，与 `adb forward --remove-all` 相同。
+    pub async fn forward_remove_all(&mut self) -> AdbResult<()> {
+        self.open_transport(Some("killforward-all")).await?;
+        self.forwards.clear();
+        Ok(())
+    }
+
+    /// 移除通过 `forward_remote_port` 创建的端口转发，其他转发保持不变。
+    pub async fn forward_cleanup(&mut self) -> AdbResult<()> {
+        for local in self.forwards.clone() {
+            self.forward_remove(&local).await?;
         }
+        Ok(())
     }
     pub async fn reverse(
         &mut self,
@@ -303,7 +656,7 @@ where
         &mut self,
         network_type: NetworkType,
         address: S,
-    ) -> anyhow::Result<TcpStream> {
+    ) -> anyhow::Result<AdbConnection> {
         let mut connection = self.open_transport(None).await?;
         let cmd = match network_type {
             NetworkType::LocalAbstrcat | NetworkType::Unix => {
@@ -317,18 +670,17 @@ where
             .map_err(|e| anyhow!("Send Command >> {:#?} and Check Okay Failed {} ", &cmd, e))?;
         Ok(connection)
     }
+    /// 通过本机的 adb 可执行文件对该设备执行命令，返回标准输出，设备选择规则见 `adb_target_args`。
     pub async fn adb_output(&mut self, command: &[&str]) -> anyhow::Result<String> {
+        let target = self.adb_target_args()?;
         let adb_ = adb_path()?;
         if adb_.exists() {
-            if let Some(ref serial) = self.serial {
-                let cmd = Command::new(adb_)
-                    .arg("-s")
-                    .arg(serial)
-                    .args(command)
-                    .output()
-                    .await?;
-                return Ok(String::from_utf8_lossy(&cmd.stdout).parse()?);
-            }
+            let cmd = Command::new(adb_)
+                .args(&target)
+                .args(command)
+                .output()
+                .await?;
+            return Ok(String::from_utf8_lossy(&cmd.stdout).parse()?);
         };
         Err(anyhow!("adb not found"))
     }
@@ -347,24 +699,283 @@ where
         Ok(resp)
     }
 
+    /// 重启设备到指定模式，使用 `reboot:<mode>` 服务，不依赖设备上的 shell。
+    ///
+    /// # 参数
+    /// - `mode`: 重启的目标模式，`RebootMode::System` 为正常重启。
+    pub async fn reboot(&mut self, mode: RebootMode) -> AdbResult<()> {
+        let mut connection = self.open_transport(None).await?;
+        let cmd = format!("reboot:{}", mode);
+        connection.send_cmd_then_check_okay(&cmd).await?;
+        connection.read_until_close().await?;
+        Ok(())
+    }
+
+    /// 在设备的 transport 上打开任意服务，返回已确认 `OKAY` 的连接，之后的读写由调用方按该服务的协议处理。
+    ///
+    /// transport 服务由设备上的 adbd 处理，例如 `shell:`、`exec:`、`sync:`、`jdwp`、`track-jdwp`
+    /// 以及厂商自定义的服务；`host:version` 等由 adb server 处理的 host 服务请使用
+    /// `AdbClient::exec_host_service`。
+    pub async fn exec_service(&mut self, service: &str) -> AdbResult<AdbConnection> {
+        let mut connection = self.open_transport(None).await?;
+        connection.send_cmd_then_check_okay(service).await?;
+        Ok(connection)
+    }
+
+    /// 获取当前可调试（JDWP）进程的 PID 列表，与 `adb jdwp` 相同，没有可调试进程时返回空列表。
+    ///
+    /// adbd 的 `jdwp` 服务发送列表后并不关闭连接，这里改为读取 `track-jdwp` 的第一份列表后立即返回。
+    pub async fn jdwp(&mut self) -> AdbResult<Vec<u32>> {
+        let mut connection = self.exec_service("track-jdwp").await?;
+        let data = connection.read_string_block().await?;
+        Ok(parse_jdwp_pids(&data))
+    }
+
+    /// 通过 `track-jdwp` 持续跟踪可调试进程，列表每次变化时产出一份完整的 PID 列表，连接关闭后结束。
+    pub async fn jdwp_track(&mut self) -> AdbResult<impl Stream<Item = Vec<u32>>> {
+        let mut conn = self.exec_service("track-jdwp").await?;
+        Ok(stream! {
+            while let Ok(data) = conn.read_string_block().await {
+                yield parse_jdwp_pids(&data);
+            }
+        })
+    }
+
+    /// 执行一个无参数的 transport 服务（如 `root:`），返回服务端的文本响应。
+    async fn run_transport_service(&mut self, service: &str) -> AdbResult<String> {
+        let mut connection = self.exec_service(service).await?;
+        Ok(connection.read_until_close().await?)
+    }
+
+    /// 以 root 权限重启 adbd，与 `adb root` 相同。
+    ///
+    /// 成功后 adbd 会重启，当前 transport 随之断开，继续使用设备前请先调用 `wait_for`。
+    ///
+    /// # 返回值
+    /// - 成功返回服务端响应；生产版本（user build）拒绝 root 时返回 `AdbError::PermissionDenied`。
+    pub async fn root(&mut self) -> AdbResult<String> {
+        let resp = self.run_transport_service("root:").await?;
+        check_root_response(resp)
+    }
+
+    /// 以普通权限重启 adbd，与 `adb unroot` 相同。
+    ///
+    /// 成功后 transport 同样会断开，继续使用设备前请先调用 `wait_for`。
+    pub async fn unroot(&mut self) -> AdbResult<String> {
+        self.run_transport_service("unroot:").await
+    }
+
+    /// 以可写方式重新挂载系统分区，与 `adb remount` 相同，需要先 `root`。
+    pub async fn remount(&mut self) -> AdbResult<String> {
+        self.run_transport_service("remount:").await
+    }
+
     pub async fn push(&mut self, local: &str, remote: &str) -> anyhow::Result<()> {
-        if self.adb_output(&["push", local, remote]).await.is_ok() {
-            info!("push {} to {} success", local, remote);
-            return Ok(());
+        self.push_with_progress(local, remote, |_, _| {}).await
+    }
+
+    /// 推送本地文件到设备，并在每个同步数据块发送后回调进度。
+    ///
+    /// # 参数
+    /// - `local`: 本地文件路径。
+    /// - `remote`: 设备上的目标路径。
+    /// - `progress`: 进度回调，参数为已传输字节数和文件总大小（取自本地文件长度）。
+    pub async fn push_with_progress<F>(
+        &mut self,
+        local: &str,
+        remote: &str,
+        progress: F,
+    ) -> anyhow::Result<()>
+    where
+        F: FnMut(u64, Option<u64>),
+    {
+        let file = tokio::fs::File::open(local)
+            .await
+            .context(format!("Open Local File {} Failed", local))?;
+        let total = file.metadata().await?.len();
+        self.sync_send(file, remote, DEFAULT_PUSH_MODE, Some(total), progress)
+            .await?;
+        info!("push {} to {} success", local, remote);
+        Ok(())
+    }
+
+    /// 通过 sync 协议将任意 `AsyncRead` 的内容推送到设备，不依赖本地 `adb` 可执行文件。
+    ///
+    /// # 参数
+    /// - `reader`: 数据来源。
+    /// - `remote`: 设备上的目标路径。
+    /// - `mode`: 目标文件权限，例如 `0o644`；未包含文件类型位时按普通文件处理。
+    pub async fn push_from_reader(
+        &mut self,
+        reader: impl AsyncRead + Unpin,
+        remote: &str,
+        mode: u32,
+    ) -> AdbResult<()> {
+        self.sync_send(reader, remote, mode, None, |_, _| {}).await
+    }
+
+    /// 将内存中的数据推送到设备，参见 [`push_from_reader`](Self::push_from_reader)。
+    pub async fn push_bytes(&mut self, data: &[u8], remote: &str, mode: u32) -> AdbResult<()> {
+        self.sync_send(data, remote, mode, Some(data.len() as u64), |_, _| {})
+            .await
+    }
+
+    /// 推送本地文件并校验设备上的内容，不一致时返回 `AdbError::FileOperationFailed`。
+    ///
+    /// 优先比较 SHA-256（参见 [`remote_sha256`](Self::remote_sha256)），设备没有 `sha256sum` 时退回 MD5，
+    /// 两者都没有时只比较文件大小。
+    pub async fn push_verified(&mut self, local: &Path, remote: &str) -> AdbResult<()> {
+        let local_str = local
+            .to_str()
+            .ok_or_else(|| AdbError::unknown(format!("invalid local path {}", local.display())))?;
+        self.push(local_str, remote).await?;
+        let (expected, actual) =
+            if let Some(digest) = self.remote_digest(&SHA256_COMMANDS, 64, remote).await? {
+                (sha256_file(local)?, digest)
+            } else if let Some(digest) = self.remote_digest(&MD5_COMMANDS, 32, remote).await? {
+                (md5_file(local)?, digest)
+            } else {
+                let size = self.stat(remote).await?.size as u64;
+                (local.metadata()?.len().to_string(), size.to_string())
+            };
+        if expected != actual {
+            return Err(AdbError::FileOperationFailed(format!(
+                "checksum mismatch after pushing {} to {}: local {}, device {}",
+                local.display(),
+                remote,
+                expected,
+                actual
+            )));
+        }
+        Ok(())
+    }
+
+    /// 计算设备上文件的 SHA-256，返回小写的十六进制摘要，依次尝试 `sha256sum` 与 `toybox sha256sum`。
+    ///
+    /// 设备上没有这两个命令时返回 `AdbError::Unknown`，文件不存在等其他失败返回 `AdbError::FileOperationFailed`。
+    pub async fn remote_sha256(&mut self, path: &str) -> AdbResult<String> {
+        self.remote_digest(&SHA256_COMMANDS, 64, path)
+            .await?
+            .ok_or_else(|| missing_tool(&["sha256sum"]))
+    }
+
+    /// 依次尝试 `commands` 计算 `path` 的摘要，所有命令都不存在时返回 `None`。
+    async fn remote_digest(
+        &mut self,
+        commands: &[&[&str]],
+        hex_len: usize,
+        path: &str,
+    ) -> AdbResult<Option<String>> {
+        for command in commands {
+            let mut args = command.to_vec();
+            args.push(path);
+            let (output, code) = self.shell_with_status(&args).await?;
+            if code == 0 {
+                return parse_checksum(&output, hex_len).map(Some).ok_or_else(|| {
+                    AdbError::unknown(format!(
+                        "invalid {} output: {}",
+                        args.join(" "),
+                        output.trim()
+                    ))
+                });
+            }
+            if !is_command_missing(&output, code) {
+                // 退出码非 0，一定返回错误
+                file_op_result(&args, &output, code)?;
+            }
+        }
+        Ok(None)
+    }
+
+    async fn sync_send<R, F>(
+        &mut self,
+        mut reader: R,
+        remote: &str,
+        mode: u32,
+        total: Option<u64>,
+        mut progress: F,
+    ) -> AdbResult<()>
+    where
+        R: AsyncRead + Unpin,
+        F: FnMut(u64, Option<u64>),
+    {
+        #[cfg(feature = "metrics")]
+        let _timer = Timer::start("push");
+        let mut conn = if self.has_feature("sendrecv_v2").await {
+            let mut conn = self.prepare_sync(remote, "SND2").await?;
+            conn.send(&send_v2_setup(mode)).await?;
+            conn
+        } else {
+            let path = format!("{},{}", remote, file_mode_with_type(mode));
+            self.prepare_sync(&path, "SEND").await?
+        };
+        let mut buffer = vec![0; SYNC_DATA_MAX];
+        let mut transferred = 0;
+        loop {
+            let size = reader.read(&mut buffer).await?;
+            if size == 0 {
+                break;
+            }
+            conn.write_all(&sync_packet("DATA", &buffer[..size]))
+                .await?;
+            transferred += size as u64;
+            progress(transferred, total);
+        }
+        let mtime = time::SystemTime::now()
+            .duration_since(time::UNIX_EPOCH)
+            .map_err(AdbError::unknown)?
+            .as_secs() as u32;
+        conn.write_all(&sync_header("DONE", mtime)).await?;
+        match conn.read_string(4).await?.as_str() {
+            "OKAY" => Ok(()),
+            "FAIL" => {
+                let size = conn.read_sync_length().await?;
+                let error_message = conn.read_string(size).await?;
+                error!(
+                    "Push {} Failed With Error Message >>> {}",
+                    remote, &error_message
+                );
+                Err(AdbError::FileOperationFailed(error_message))
+            }
+            other => Err(AdbError::unknown(format!(
+                "push error, unexpected response {:#?}",
+                other
+            ))),
         }
-        Err(anyhow!("push error"))
     }
+
     pub async fn pull(&mut self, src: &str, dest: &PathBuf) -> anyhow::Result<usize> {
+        self.pull_with_progress(src, dest, |_, _| {}).await
+    }
+
+    /// 从设备拉取文件到本地，并在每个同步数据块写入后回调进度。
+    ///
+    /// # 参数
+    /// - `src`: 设备上的文件路径。
+    /// - `dest`: 本地目标路径。
+    /// - `progress`: 进度回调，参数为已传输字节数和文件总大小（取自 `stat`）。
+    pub async fn pull_with_progress<F>(
+        &mut self,
+        src: &str,
+        dest: &PathBuf,
+        mut progress: F,
+    ) -> anyhow::Result<usize>
+    where
+        F: FnMut(u64, Option<u64>),
+    {
+        #[cfg(feature = "metrics")]
+        let _timer = Timer::start("pull");
+        let total = self.stat(src).await.ok().map(|info| info.size as u64);
         let mut size = 0;
-        let mut file = match File::open(dest) {
-            Ok(mut file) => file,
-            Err(_) => File::create(dest)?,
-        };
-        let _ = self.iter_content(src).await?.map(|x| {
-            let data = x.unwrap();
-            file.write_all(&data).unwrap();
+        let mut file = File::create(dest)?;
+        let stream = self.iter_content(src).await?;
+        pin_mut!(stream);
+        while let Some(data) = stream.next().await {
+            let data = data?;
+            file.write_all(&data)?;
             size += data.len();
-        });
+            progress(size as u64, total);
+        }
         Ok(size)
     }
 
@@ -372,41 +983,76 @@ where
         &mut self,
         path: &str,
     ) -> anyhow::Result<impl Stream<Item = anyhow::Result<(Vec<u8>, String)>>> {
-        let mut conn = self.prepare_sync(path, "LIST").await?;
+        let v2 = self.has_feature("ls_v2").await;
+        let mut conn = self
+            .prepare_sync(path, if v2 { "LIS2" } else { "LIST" })
+            .await?;
         Ok(stream! {
             loop {
-                match conn.read_string(4).await{
-                Ok(data) => {
-                    if data.eq("DONE") {
+                match read_dent_async(&mut conn, v2).await {
+                    Ok(Some(entry)) => yield Ok(entry),
+                    Ok(None) => break,
+                    Err(e) => {
+                        yield Err(e);
                         break
-                    } else {
-                        let mut current_data = conn.recv(16).await?;
-                        let name_length_bytes = &current_data[12..=15];
-                        let name_length = u32::from_le_bytes(name_length_bytes.try_into().unwrap());
-                        let path = conn.read_string(name_length as usize).await?;
-                        yield Ok((current_data, path))
                     }
-                },
-                Err(e) => {
-                    yield Err(e);
-                    break
                 }
             }
-
-        }
         })
     }
 
+    /// 路径是否存在。sync STAT 对不存在的路径返回全 0，因此以 mode 是否为 0 判断。
     pub async fn exists(&mut self, path: &str) -> anyhow::Result<bool> {
         let file_info = self.stat(path).await?;
-        if file_info.mtime != 0 {
-            Ok(true)
-        } else {
-            Ok(false)
+        Ok(file_info.mode != 0)
+    }
+
+    /// 反复检查路径是否存在，直到其存在状态等于 `exists`，用于等待设备端进程生成（或删除）文件。
+    ///
+    /// # 参数
+    /// - `exists`: `true` 等待文件出现，`false` 等待文件消失。
+    /// - `timeout`: 最长等待时间，超时返回 `AdbError::Timeout`。
+    /// - `poll`: 两次检查之间的间隔，第一次检查立即执行。
+    pub async fn wait_for_file(
+        &mut self,
+        path: &str,
+        exists: bool,
+        timeout: Duration,
+        poll: Duration,
+    ) -> AdbResult<()> {
+        let deadline = tokio::time::Instant::now() + timeout;
+        loop {
+            if self.exists(path).await? == exists {
+                return Ok(());
+            }
+            let now = tokio::time::Instant::now();
+            if now >= deadline {
+                return Err(file_wait_timeout(path, exists, timeout));
+            }
+            tokio::time::sleep(poll.min(deadline - now)).await;
         }
     }
 
+    /// 路径是否为目录，不存在时返回 `false`。
+    pub async fn is_dir(&mut self, path: &str) -> anyhow::Result<bool> {
+        Ok(self.stat(path).await?.is_dir())
+    }
+
+    /// 路径是否为普通文件，不存在时返回 `false`。
+    pub async fn is_file(&mut self, path: &str) -> anyhow::Result<bool> {
+        Ok(self.stat(path).await?.is_file())
+    }
+
     pub async fn stat(&mut self, path: &str) -> anyhow::Result<FileInfo> {
+        #[cfg(feature = "metrics")]
+        let _timer = Timer::start("stat");
+        if self.has_feature("stat_v2").await {
+            let mut conn = self.prepare_sync(path, "STA2").await?;
+            return match conn.read_string(4).await?.as_str() {
+                "STA2" => parse_file_info_v2(&conn.recv_exact(STAT_V2_LEN).await?, path),
+                other => Err(anyhow!("unexpected STA2 response {:#?}", other)),
+            };
+        }
         let mut conn = self.prepare_sync(path, "STAT").await?;
         let data = conn.read_string(4).await?;
         if data.eq("STAT") {
@@ -417,20 +1063,17 @@ where
     }
 
     pub async fn list(&mut self, path: &str) -> anyhow::Result<Vec<FileInfo>> {
-        let mut stream = self.iter_directory(path).await?;
+        #[cfg(feature = "metrics")]
+        let _timer = Timer::start("list");
+        let stream = self
+            .iter_directory(path)
+            .await
+            .context("Iter Directory Error")?;
         let mut files = vec![];
         pin_mut!(stream);
         while let Some(data) = stream.next().await {
-            match data {
-                Ok((binary_data, path)) => {
-                    if let Ok(file_info) = parse_file_info(binary_data, path) {
-                        files.push(file_info);
-                    }
-                }
-                Err(e) => {
-                    error!("发生异常 {:#?}", e)
-                }
-            }
+            let (binary_data, path) = data?;
+            files.push(parse_dent(binary_data, path)?);
         }
         Ok(files)
     }
@@ -439,7 +1082,7 @@ where
         &mut self,
         path: &str,
     ) -> anyhow::Result<impl Stream<Item = anyhow::Result<String>>> {
-        let mut stream = self.iter_content(path).await?;
+        let stream = self.iter_content(path).await?;
         Ok(stream! {
             pin_mut!(stream);
             while let Some(data)  = stream.next().await{
@@ -456,18 +1099,49 @@ where
         })
     }
 
-    pub async fn prepare_sync(&mut self, path: &str, command: &str) -> anyhow::Result<TcpStream> {
-        info!("Start Sync Path {:#?} With Command {:#?}", path, command);
-        let mut conn = self.open_transport(None).await?;
-        conn.send_cmd_then_check_okay("sync:")
-            .await
+    /// 读取设备上的整个文本文件，非法的 UTF-8 字节会被替换，与阻塞版的 `read_text` 相同。
+    ///
+    /// 整个文件读完后再解码，不会像逐块解码的 [`read_text`](Self::read_text) 那样拆开跨块的多字节字符。
+    pub async fn read_to_string(&mut self, path: &str) -> AdbResult<String> {
+        let data = self.pull_bytes(path).await?;
+        Ok(String::from_utf8_lossy(&data).into_owned())
+    }
+
+    /// 读取设备上的整个文件，与 [`pull_bytes`](Self::pull_bytes) 相同。
+    pub async fn read_to_bytes(&mut self, path: &str) -> AdbResult<Vec<u8>> {
+        self.pull_bytes(path).await
+    }
+
+    /// 将设备上的文件内容直接读取到内存中，不经过本地文件系统。
+    ///
+    /// # 参数
+    /// - `remote`: 设备上的文件路径。
+    ///
+    /// # 返回值
+    /// - 成功返回文件的全部字节内容，失败返回错误。
+    pub async fn pull_bytes(&mut self, remote: &str) -> AdbResult<Vec<u8>> {
+        #[cfg(feature = "metrics")]
+        let _timer = Timer::start("pull");
+        let stream = self.iter_content(remote).await?;
+        pin_mut!(stream);
+        let mut content = vec![];
+        while let Some(data) = stream.next().await {
+            content.extend_from_slice(&data?);
+        }
+        Ok(content)
+    }
+
+    pub async fn prepare_sync(
+        &mut self,
+        path: &str,
+        command: &str,
+    ) -> anyhow::Result<AdbConnection> {
+        info!("Start Sync Path {:#?} With Command {:#?}", path, command);
+        let mut conn = self.open_transport(None).await?;
+        conn.send_cmd_then_check_okay("sync:")
+            .await
             .context("Start Sync Error")?;
-        let path_len = path.as_bytes().len() as u32;
-        let mut total_byte = vec![];
-        total_byte.extend_from_slice(command.as_bytes());
-        total_byte.extend_from_slice(&path_len.to_le_bytes());
-        total_byte.extend_from_slice(path.as_bytes());
-        conn.send(&total_byte).await?;
+        conn.send(&sync_packet(command, path.as_bytes())).await?;
         Ok(conn)
     }
 
@@ -475,7 +1149,13 @@ where
         &mut self,
         path: &str,
     ) -> anyhow::Result<impl Stream<Item = anyhow::Result<Vec<u8>>>> {
-        let mut connection = self.prepare_sync(path, "RECV").await?;
+        let mut connection = if self.has_feature("sendrecv_v2").await {
+            let mut connection = self.prepare_sync(path, "RCV2").await?;
+            connection.send(&sync_header("RCV2", 0)).await?;
+            connection
+        } else {
+            self.prepare_sync(path, "RECV").await?
+        };
         Ok(stream! {
                         loop{
                             match connection.read_string(4).await {
@@ -485,24 +1165,20 @@ where
                                 },
                                 Ok(data) =>  {
                                     let match_resp = match data.as_str() {
-                                    "FAIL" => match connection.recv(4).await {
+                                    "FAIL" => match connection.read_sync_length().await {
                                         Err(e) => {
                                             Err(anyhow!("Read String Error {}", e))
                                         },
-                                        Ok(data) => {
-                                            let str_size = u32::from_le_bytes(data.try_into().ok().unwrap()) as usize;
+                                        Ok(str_size) => {
                                             let error_message = connection.read_string(str_size).await.ok().unwrap();
                                             error!("Sync Error With Error Message >>> {}", &error_message);
-                                            Err(anyhow!("Read String Error {}", error_message))
+                                            Err(AdbError::FileOperationFailed(error_message).into())
 
                                         }
                                     },
-                                    "DONE" => {
-                                        Err(anyhow!("Read Done"))
-                                    }
-                                    "DATA" => match connection.recv(4).await {
-                                        Ok(size) => {
-                                            let str_size = u32::from_le_bytes(size.try_into().ok().unwrap()) as usize;
+                                    "DONE" => break,
+                                    "DATA" => match connection.read_sync_length().await {
+                                        Ok(str_size) => {
                                             let mut buffer = vec![0; str_size];
                                             match connection.read_exact(& mut buffer).await {
                                                 Ok(data) => Ok(buffer[..data].to_vec()),
@@ -524,18 +1200,231 @@ where
             })
     }
 
+    /// 通过 `exec:` 服务执行命令并返回原始 stdout，不经过 shell 的换行转换，适合二进制输出。
+    ///
+    /// # 参数
+    /// - `cmd`: 要执行的命令，参数列表会按 `list2cmdline` 转义。
+    pub async fn exec_out<T2: Into<AdbCommand>>(&mut self, cmd: T2) -> AdbResult<Vec<u8>> {
+        let service = format!("exec:{}", Self::command_line(&cmd.into()));
+        let mut conn = self.open_transport(None).await?;
+        conn.send_cmd_then_check_okay(&service).await?;
+        let mut output = vec![];
+        conn.read_to_end(&mut output).await?;
+        Ok(output)
+    }
+
+    /// 通过 shell v2 协议执行命令，分别返回 stdout、stderr 和退出码。
+    ///
+    /// 设备不支持 `shell_v2` 时退回普通 `shell:`，输出合并在 `stdout` 中，`exit_code` 为 `None`。
+    ///
+    /// # 参数
+    /// - `cmd`: 要执行的命令，参数列表会按 `list2cmdline` 转义。
+    pub async fn shell_v2<T2: Into<AdbCommand>>(&mut self, cmd: T2) -> AdbResult<ShellResult> {
+        let cmdline = Self::command_line(&cmd.into());
+        let v2 = self.has_feature("shell_v2").await;
+        let service = if v2 {
+            format!("shell,v2,raw:{}", cmdline)
+        } else {
+            format!("shell:{}", cmdline)
+        };
+        let mut conn = self.open_transport(None).await?;
+        conn.send_cmd_then_check_okay(&service).await?;
+        let mut output = vec![];
+        conn.read_to_end(&mut output).await?;
+        if v2 {
+            ShellResult::parse_v2(&output)
+        } else {
+            Ok(ShellResult {
+                stdout: output,
+                ..Default::default()
+            })
+        }
+    }
+
+    /// 执行 shell 命令，把 `stdin` 写入命令的标准输入后半关闭写端，再读取输出直到连接关闭。
+    ///
+    /// 适合 `sh`、`sqlite3`、`cmd` 等从标准输入读取内容的命令。
+    ///
+    /// # 参数
+    /// - `cmd`: 要执行的命令，参数列表会按 `list2cmdline` 转义。
+    /// - `stdin`: 写入命令标准输入的数据。
+    pub async fn shell_with_input<T2: Into<AdbCommand>>(
+        &mut self,
+        cmd: T2,
+        stdin: &[u8],
+    ) -> AdbResult<String> {
+        let mut conn = self
+            .shell_stream_raw(&Self::command_line(&cmd.into()))
+            .await?;
+        conn.write_all(stdin).await?;
+        conn.shutdown_write().await?;
+        let mut output = vec![];
+        conn.read_to_end(&mut output).await?;
+        Ok(String::from_utf8_lossy(&output).to_string())
+    }
+
+    /// 截图，优先使用 `exec-out screencap -p`，设备不支持 `exec:` 时回退到 `screenshot_via_file`。
     pub async fn screenshot(&mut self) -> anyhow::Result<RgbImage> {
+        match self.exec_out(["screencap", "-p"]).await {
+            Ok(data) => match image::load_from_memory(&data) {
+                Ok(image) => return Ok(image.into_rgb8()),
+                Err(e) => info!("Decode exec-out Screenshot Failed {}, Fallback To File", e),
+            },
+            Err(e) => info!("exec-out Screenshot Failed {}, Fallback To File", e),
+        }
+        self.screenshot_via_file().await
+    }
+
+    pub async fn screenrecord(
+        &mut self,
+        remote_tmp: &str,
+        opts: ScreenRecordOptions,
+    ) -> AdbResult<ScreenRecordHandle<T>>
+    where
+        T: Send + Sync + 'static,
+    {
+        Ok(ScreenRecordHandle::start(self, remote_tmp, opts))
+    }
+
+    /// 截图并按当前屏幕旋转角度转为正向，参见 [`rotate_upright`]；[`screenshot`](Self::screenshot) 保持原始截图。
+    ///
+    /// 较新的系统 `screencap` 已按屏幕方向输出，旋转 90/270 度时若截图的宽高已与 `wm size`
+    /// （自然方向）相反，则不再旋转。
+    pub async fn screenshot_rotated(&mut self) -> anyhow::Result<RgbImage> {
+        let image = self.screenshot().await?;
+        let rotation = self.display_rotation().await?;
+        if rotation == 90 || rotation == 270 {
+            let (width, height) = self.screen_size().await?;
+            if width != height && image.dimensions() == (height, width) {
+                return Ok(image);
+            }
+        }
+        Ok(rotate_upright(image, rotation))
+    }
+
+    /// 获取主屏当前的旋转角度（0、90、180、270），解析自 `dumpsys input`。
+    pub async fn display_rotation(&mut self) -> AdbResult<u32> {
+        let output = self.dumpsys("input").await?;
+        parse_display_rotation(&output)
+            .ok_or_else(|| AdbError::unknown("no display orientation found in dumpsys input"))
+    }
+
+    /// 获取未经 PNG 编码的原始帧，适合逐像素比较。
+    pub async fn screencap_raw(&mut self) -> AdbResult<ScreenCapture> {
+        let data = self.exec_out("screencap").await?;
+        ScreenCapture::parse(&data)
+    }
+
+    /// 先在设备上生成 PNG 文件再拉取的截图方式，兼容不支持 `exec:` 的旧设备。
+    pub async fn screenshot_via_file(&mut self) -> anyhow::Result<RgbImage> {
+        let data = self.screencap_png_via_file().await?;
+        let image = image::load_from_memory(&data)?;
+        Ok(image.into_rgb8())
+    }
+
+    /// 获取 PNG 编码的截图数据，不解码为图片；`exec:` 不可用或输出不是 PNG 时回退到先生成文件再拉取。
+    pub async fn screenshot_png_bytes(&mut self) -> AdbResult<Vec<u8>> {
+        match self.exec_out(["screencap", "-p"]).await {
+            Ok(data) if data.starts_with(PNG_SIGNATURE) => return Ok(data),
+            Ok(_) => info!("exec-out Screenshot Is Not PNG, Fallback To File"),
+            Err(e) => info!("exec-out Screenshot Failed {}, Fallback To File", e),
+        }
+        self.screencap_png_via_file().await
+    }
+
+    /// 截图并保存到本地文件，格式由扩展名决定：`.png` 或 `.jpg`/`.jpeg`，其他扩展名在截图前返回错误。
+    ///
+    /// PNG 直接写入设备返回的数据，不重新编码。
+    pub async fn screenshot_to(&mut self, path: &Path) -> AdbResult<()> {
+        let format = screenshot_format(path)?;
+        if format == ImageFormat::Png {
+            let data = self.screenshot_png_bytes().await?;
+            std::fs::write(path, data)?;
+            return Ok(());
+        }
+        let image = self.screenshot().await?;
+        image
+            .save_with_format(path, format)
+            .map_err(|e| AdbError::unknown(format!("save screenshot to {}: {}", path.display(), e)))
+    }
+
+    async fn screencap_png_via_file(&mut self) -> AdbResult<Vec<u8>> {
         let src = "/sdcard/screen.png";
         self.shell(&["screencap", "-p", src]).await?;
-        let tmpdir = tempfile::tempdir().expect("Failed to create temporary directory");
-        let target_path = tmpdir.path().join("tmp001.png");
-        info!("Pull Image To {:#?}", &target_path);
-        self.pull(src, &target_path).await?;
+        let data = self.pull_bytes(src).await?;
         self.shell(&["rm", src]).await?;
+        Ok(data)
+    }
 
-        let image = ImageReader::open(&target_path)?.decode()?;
-        fs::remove_file(target_path).expect("Failed to remove file");
-        Ok(image.into_rgb8())
+    /// 通过 `uiautomator dump` 获取当前界面的控件层级 XML。
+    pub async fn ui_hierarchy(&mut self) -> AdbResult<String> {
+        let output = self.shell(&["uiautomator", "dump", UI_DUMP_PATH]).await?;
+        if output.contains("ERROR") {
+            return Err(AdbError::unknown(format!(
+                "uiautomator dump failed: {}",
+                output.trim()
+            )));
+        }
+        let data = self.pull_bytes(UI_DUMP_PATH).await?;
+        let xml = String::from_utf8_lossy(&data).to_string();
+        self.shell(&["rm", UI_DUMP_PATH]).await?;
+        Ok(xml)
+    }
+
+    /// 获取当前界面的控件列表，由 [`ui_hierarchy`](Self::ui_hierarchy) 解析而来。
+    pub async fn ui_elements(&mut self) -> AdbResult<Vec<UiElement>> {
+        let xml = self.ui_hierarchy().await?;
+        Ok(parse_ui_elements(&xml))
+    }
+
+    /// 查找所有满足条件的控件。
+    pub async fn find_elements(&mut self, by: &By) -> AdbResult<Vec<UiElement>> {
+        let elements = self.ui_elements().await?;
+        Ok(elements.into_iter().filter(|e| by.matches(e)).collect())
+    }
+
+    /// 查找第一个满足条件的控件，返回其中心点坐标；未找到时返回 `AdbError::ElementNotFound`。
+    pub async fn find_element(&mut self, by: &By) -> AdbResult<(i32, i32)> {
+        self.find_elements(by)
+            .await?
+            .first()
+            .map(|e| e.center())
+            .ok_or_else(|| AdbError::ElementNotFound(by.to_string()))
+    }
+
+    /// 查找第一个满足条件的控件并点击其中心点。
+    pub async fn tap_element(&mut self, by: &By) -> AdbResult<(i32, i32)> {
+        let (x, y) = self.find_element(by).await?;
+        self.click(x, y).await?;
+        Ok((x, y))
+    }
+
+    /// 反复获取控件层级，直到出现满足条件的控件。
+    ///
+    /// # 参数
+    /// - `by`: 查找条件。
+    /// - `timeout`: 最长等待时间，超时返回 `AdbError::Timeout`。
+    /// - `poll`: 两次获取之间的间隔，第一次获取立即执行。
+    pub async fn wait_for_element(
+        &mut self,
+        by: &By,
+        timeout: Duration,
+        poll: Duration,
+    ) -> AdbResult<UiElement> {
+        let deadline = tokio::time::Instant::now() + timeout;
+        loop {
+            if let Some(element) = self.find_elements(by).await?.into_iter().next() {
+                return Ok(element);
+            }
+            let now = tokio::time::Instant::now();
+            if now >= deadline {
+                return Err(AdbError::timeout(format!(
+                    "element {} not found within {:?}",
+                    by, timeout
+                )));
+            }
+            tokio::time::sleep(poll.min(deadline - now)).await;
+        }
     }
 
     pub async fn keyevent(&mut self, keycode: &str) -> anyhow::Result<String> {
@@ -543,91 +1432,383 @@ where
     }
 
     pub async fn switch_screen(&mut self, status: bool) -> anyhow::Result<String> {
-        if status == true {
-            self.keyevent("224").await
+        if status {
+            self.keyevent(&KeyCode::Wakeup.to_string()).await
         } else {
-            self.keyevent("223").await
-        }
-    }
-
-    pub async fn install(&mut self, path_or_url: &str) -> anyhow::Result<(), anyhow::Error> {
-        let target_path =
-            if path_or_url.starts_with("http://") || path_or_url.starts_with("https://") {
-                let mut resp = reqwest::get(path_or_url).await?;
-                let response_bytes = resp.bytes().await?;
-                let temp_dir = tempfile::tempdir()?.path().join("tmp001.apk");
-                let mut fd = File::create(&temp_dir)?;
-                fd.write_all(&response_bytes)?;
-                let target_path = temp_dir.to_str().ok_or(anyhow!("fail to get path"))?;
-                info!(
-                    "Save Http/s file to  <{:#?}> => dst: <{:#?}>",
-                    &path_or_url, &target_path
-                );
-                target_path.to_string()
-            } else {
-                path_or_url.to_string()
-            };
-        let dst = format!(
-            "/data/local/tmp/tmp-{}.apk",
-            (time::SystemTime::now()
-                .duration_since(time::UNIX_EPOCH)?
-                .as_millis())
-        );
-        info!("Pushing src: <{:#?}> => dst: <{:#?}> ", &path_or_url, &dst);
-        self.push(&target_path, &dst).await?;
-        match self.install_remote(&dst, true).await {
+            self.keyevent(&KeyCode::Sleep.to_string()).await
+        }
+    }
+
+    /// 在一条 `input keyevent` 命令中依次发送多个按键，按键码可以是数字或 `KEYCODE_*` 名称。
+    ///
+    /// 按键是依次按下并抬起的，不会同时按住。
+    pub async fn key_combo(&mut self, codes: &[&str]) -> AdbResult<String> {
+        let args = keyevent_args(codes);
+        Ok(self
+            .shell(&args.iter().map(|s| s.as_str()).collect::<Vec<_>>())
+            .await?)
+    }
+
+    /// 同 `key_combo`，使用 [`KeyCode`] 指定按键。
+    pub async fn press_keys(&mut self, codes: &[KeyCode]) -> AdbResult<String> {
+        let args = keyevent_args(codes);
+        Ok(self
+            .shell(&args.iter().map(|s| s.as_str()).collect::<Vec<_>>())
+            .await?)
+    }
+
+    /// 推送本地 APK 到设备临时目录后通过 `pm install -r -t` 安装，安装完成后删除临时文件。
+    pub async fn install(&mut self, path: impl AsRef<Path>) -> anyhow::Result<()> {
+        Ok(self.install_with(path, &InstallOptions::default()).await?)
+    }
+
+    /// 按 `opts` 安装本地 APK，`pm` 拒绝安装时返回 `AdbError::ApplicationError`。
+    pub async fn install_with(
+        &mut self,
+        path: impl AsRef<Path>,
+        opts: &InstallOptions,
+    ) -> AdbResult<()> {
+        let path = path.as_ref();
+        let local = path
+            .to_str()
+            .ok_or_else(|| AdbError::unknown("fail to get path"))?;
+        let dst = remote_apk_path()?;
+        info!("Pushing src: <{:#?}> => dst: <{:#?}> ", path, &dst);
+        self.push(local, &dst).await?;
+        match self.install_remote_with(&dst, true, opts).await {
             Ok(resp) => {
                 info!("Install Apk Successed >> <{:#?}>", &resp);
-                return Ok(());
+                Ok(())
             }
             Err(e) => {
-                let error_string = format!("fail to install apk >>> {}", e);
-                error!("{}", &error_string);
-                Err(anyhow!(e))
+                error!("fail to install apk >>> {}", e);
+                Err(e)
             }
         }
     }
-    pub async fn install_remote(&mut self, path: &str, clean: bool) -> anyhow::Result<String> {
-        let args = ["pm", "install", "-r", "-t", path];
+
+    /// 下载 http/https 地址的 APK 到本地临时目录后安装，参见 [`install`](Self::install)。
+    pub async fn install_url(&mut self, url: &str) -> anyhow::Result<()> {
+        Ok(self
+            .install_url_with(url, &InstallOptions::default())
+            .await?)
+    }
+
+    /// 按 `opts` 安装 http/https 地址的 APK。
+    ///
+    /// `opts.stream_download` 为 `true` 且响应带有 `Content-Length` 时，下载的数据边收边通过 sync 协议
+    /// 写入设备，不在本地落临时文件；长度未知（例如 chunked 响应）时仍先下载到本地临时目录。
+    pub async fn install_url_with(&mut self, url: &str, opts: &InstallOptions) -> AdbResult<()> {
+        let mut resp = reqwest::get(url)
+            .await
+            .and_then(|resp| resp.error_for_status())
+            .map_err(AdbError::unknown)?;
+        if opts.stream_download && resp.content_length().is_some() {
+            let dst = remote_apk_path()?;
+            info!("Streaming <{:#?}> => dst: <{:#?}>", url, &dst);
+            let (mut writer, reader) = tokio::io::duplex(SYNC_DATA_MAX);
+            let download = async move {
+                while let Some(chunk) = resp.chunk().await.map_err(AdbError::unknown)? {
+                    writer.write_all(&chunk).await?;
+                }
+                // writer 在这里 drop，推送端读到 EOF 后结束
+                Ok::<_, AdbError>(())
+            };
+            let (downloaded, pushed) = tokio::join!(
+                download,
+                self.push_from_reader(reader, &dst, DEFAULT_PUSH_MODE)
+            );
+            if let Err(e) = downloaded.and(pushed) {
+                let _ = self.shell(&["rm", &dst]).await;
+                return Err(e);
+            }
+            return self.install_remote_with(&dst, true, opts).await.map(|_| ());
+        }
+        let response_bytes = resp.bytes().await.map_err(AdbError::unknown)?;
+        // 临时目录在安装完成前必须保持存在，drop 时才删除
+        let temp_dir = tempfile::tempdir()?;
+        let target_path = temp_dir.path().join("tmp001.apk");
+        std::fs::write(&target_path, &response_bytes)?;
+        info!(
+            "Save Http/s file to  <{:#?}> => dst: <{:#?}>",
+            url, &target_path
+        );
+        self.install_with(&target_path, opts).await
+    }
+
+    /// 通过 `pm install-create` / `install-write` / `install-commit` 会话一起安装 base 与 split APK。
+    pub async fn install_multiple<P>(&mut self, apks: &[P], opts: &InstallOptions) -> AdbResult<()>
+    where
+        P: AsRef<Path>,
+    {
+        self.install_multiple_with_progress(apks, opts, |_, _, _| {})
+            .await
+            .map(|_| ())
+    }
+
+    /// 与 [`install_multiple`](Self::install_multiple) 相同，并在每个 split 写入数据后回调进度。
+    ///
+    /// # 参数
+    /// - `apks`: 本地 APK 路径，多个时作为同一应用的 split APK 一起安装。
+    /// - `opts`: 安装会话的选项。
+    /// - `progress`: 进度回调，参数为 split 序号、该 split 已写入字节数和该 split 总大小。
+    ///
+    /// # 返回值
+    /// `pm install-commit` 的输出。写入失败时放弃会话；提交被拒绝时返回 `AdbError::ApplicationError`。
+    pub async fn install_multiple_with_progress<P, F>(
+        &mut self,
+        apks: &[P],
+        opts: &InstallOptions,
+        mut progress: F,
+    ) -> AdbResult<String>
+    where
+        P: AsRef<Path>,
+        F: FnMut(usize, u64, u64),
+    {
+        let mut sizes = vec![];
+        for apk in apks {
+            sizes.push(tokio::fs::metadata(apk).await?.len());
+        }
+        let total = sizes.iter().sum::<u64>().to_string();
+        let options = opts.to_args();
+        let mut args = vec!["pm", "install-create"];
+        args.extend(options.iter().map(|s| s.as_str()));
+        args.extend(["-S", &total]);
         let output = self.shell(&args).await?;
-        if !output.contains("Success") {
-            return Err(anyhow!("fail to install"));
-        };
-        if clean {
-            self.shell(&["rm", path]).await?;
+        let session = parse_install_session(&output)?;
+        for (index, (apk, size)) in apks.iter().zip(sizes).enumerate() {
+            let written = self
+                .install_write(&session, index, apk.as_ref(), size, &mut progress)
+                .await;
+            if let Err(e) = written {
+                let _ = self.shell(&["pm", "install-abandon", &session]).await;
+                return Err(e);
+            }
         }
-        Ok(output)
+        let output = self.shell(&["pm", "install-commit", &session]).await?;
+        check_pm_output(output)
+    }
+
+    async fn install_write<F>(
+        &mut self,
+        session: &str,
+        index: usize,
+        apk: &Path,
+        size: u64,
+        progress: &mut F,
+    ) -> AdbResult<()>
+    where
+        F: FnMut(usize, u64, u64),
+    {
+        let name = install_split_name(index, apk);
+        let size_arg = size.to_string();
+        let command = Self::command_line(&AdbCommand::from(
+            &["pm", "install-write", "-S", &size_arg, session, &name, "-"][..],
+        ));
+        let mut file = tokio::fs::File::open(apk).await?;
+        let mut conn = self.open_transport(None).await?;
+        conn.send_cmd_then_check_okay(&format!("exec:{}", command))
+            .await?;
+        let mut buffer = vec![0; SYNC_DATA_MAX];
+        let mut written = 0;
+        loop {
+            let read = file.read(&mut buffer).await?;
+            if read == 0 {
+                break;
+            }
+            conn.write_all(&buffer[..read]).await?;
+            written += read as u64;
+            progress(index, written, size);
+        }
+        let mut output = String::new();
+        conn.read_to_string(&mut output).await?;
+        check_install_output(&command, output)?;
+        Ok(())
+    }
+
+    pub async fn install_remote(&mut self, path: &str, clean: bool) -> anyhow::Result<String> {
+        Ok(self
+            .install_remote_with(path, clean, &InstallOptions::default())
+            .await?)
+    }
+
+    /// 按 `opts` 安装设备上已有的 APK，`clean` 为真时无论成功与否都删除该文件。
+    pub async fn install_remote_with(
+        &mut self,
+        path: &str,
+        clean: bool,
+        opts: &InstallOptions,
+    ) -> AdbResult<String> {
+        let options = opts.to_args();
+        let mut args = vec!["pm", "install"];
+        args.extend(options.iter().map(|s| s.as_str()));
+        args.push(path);
+        // 先执行清理再返回安装的错误，安装命令本身失败时也不会遗留 APK
+        let output = self.shell(&args).await;
+        let removed = match clean {
+            true => self.shell(&["rm", path]).await.map(|_| ()),
+            false => Ok(()),
+        };
+        let output = output?;
+        removed?;
+        check_pm_output(output)
+    }
+
+    /// 读取设置项，对应 `settings get <ns> <key>`，键未设置（输出 `null`）时返回 `None`。
+    pub async fn settings_get(
+        &mut self,
+        ns: SettingsNamespace,
+        key: &str,
+    ) -> AdbResult<Option<String>> {
+        let output = self
+            .shell_checked(&["settings", "get", &ns.to_string(), key])
+            .await?;
+        Ok(parse_settings_value(&output))
+    }
+
+    /// 写入设置项，对应 `settings put <ns> <key> <value>`。
+    pub async fn settings_put(
+        &mut self,
+        ns: SettingsNamespace,
+        key: &str,
+        value: &str,
+    ) -> AdbResult<()> {
+        self.shell_checked(&["settings", "put", &ns.to_string(), key, value])
+            .await?;
+        Ok(())
+    }
+
+    /// 删除设置项，对应 `settings delete <ns> <key>`。
+    pub async fn settings_delete(&mut self, ns: SettingsNamespace, key: &str) -> AdbResult<()> {
+        self.shell_checked(&["settings", "delete", &ns.to_string(), key])
+            .await?;
+        Ok(())
     }
 
+    /// 开关飞行模式：写入 `airplane_mode_on` 后广播 `AIRPLANE_MODE`，返回广播命令的输出。
     pub async fn switch_airplane_mode(&mut self, status: bool) -> anyhow::Result<String> {
-        let mut base_setting_cmd = vec!["settings", "put", "global", "airplane_mode_on"];
-        let mut base_am_cmd = vec![
+        let value = if status { "1" } else { "0" };
+        self.settings_put(SettingsNamespace::Global, "airplane_mode_on", value)
+            .await?;
+        self.shell(&[
             "am",
             "broadcast",
             "-a",
             "android.intent.action.AIRPLANE_MODE",
             "--ez",
             "state",
-        ];
-        if status == true {
-            base_setting_cmd.push("1");
-            base_am_cmd.push("true");
-        } else {
-            base_setting_cmd.push("0");
-            base_am_cmd.push("false");
-        }
-        self.shell(&base_setting_cmd).await?;
-        self.shell(&base_am_cmd).await
+            &status.to_string(),
+        ])
+        .await
     }
 
     pub async fn switch_wifi(&mut self, status: bool) -> anyhow::Result<String> {
-        let mut args = vec!["svc", "wifi"];
-        if status == true {
-            args.push("enable");
-        } else {
-            args.push("disable");
+        self.switch_service("wifi", "wifi_on", status).await
+    }
+
+    pub async fn switch_bluetooth(&mut self, status: bool) -> anyhow::Result<String> {
+        self.switch_service("bluetooth", "bluetooth_on", status)
+            .await
+    }
+
+    pub async fn switch_mobile_data(&mut self, status: bool) -> anyhow::Result<String> {
+        self.switch_service("data", "mobile_data", status).await
+    }
+
+    /// 通过 `svc <service> enable|disable` 开关服务，返回 `svc` 的输出。
+    ///
+    /// `svc` 需要 root 或系统不支持该服务时会输出错误信息或以非 0 退出，
+    /// 此时退回到直接写入 `settings global <key>`。
+    async fn switch_service(
+        &mut self,
+        service: &str,
+        key: &str,
+        status: bool,
+    ) -> anyhow::Result<String> {
+        let action = if status { "enable" } else { "disable" };
+        let (output, code) = self.shell_with_status(&["svc", service, action]).await?;
+        if code != 0 || !output.trim().is_empty() {
+            let value = if status { "1" } else { "0" };
+            self.settings_put(SettingsNamespace::Global, key, value)
+                .await?;
+        }
+        Ok(output)
+    }
+
+    /// 设置屏幕亮度（0-255），对应 `settings put system screen_brightness`，返回实际写入的值。
+    ///
+    /// 开启自动亮度时系统会覆盖该值，需要固定亮度时先调用 `set_auto_brightness(false)`。
+    pub async fn set_brightness(&mut self, level: u8) -> AdbResult<u8> {
+        self.settings_put(
+            SettingsNamespace::System,
+            "screen_brightness",
+            &level.to_string(),
+        )
+        .await?;
+        Ok(level)
+    }
+
+    /// 读取屏幕亮度，解析自 `settings get system screen_brightness`，超过 255 的值按 255 返回。
+    pub async fn get_brightness(&mut self) -> AdbResult<u8> {
+        let value = self
+            .settings_get(SettingsNamespace::System, "screen_brightness")
+            .await?
+            .unwrap_or_default();
+        let level = value
+            .trim()
+            .parse::<u32>()
+            .map_err(|_| unexpected_output("settings get system screen_brightness", &value))?;
+        Ok(level.min(u8::MAX as u32) as u8)
+    }
+
+    /// 开关自动亮度，对应 `settings put system screen_brightness_mode 1|0`。
+    pub async fn set_auto_brightness(&mut self, enabled: bool) -> AdbResult<()> {
+        let mode = if enabled { "1" } else { "0" };
+        self.settings_put(SettingsNamespace::System, "screen_brightness_mode", mode)
+            .await
+    }
+
+    /// 设置音量，对应 `media volume --stream <n> --set <level>`，返回实际设置的值。
+    ///
+    /// 先通过 `--get` 查询该音量类型的取值范围，`level` 超出范围时取最近的边界值；
+    /// 查询不到范围时按原值设置。
+    pub async fn set_volume(&mut self, stream: VolumeStream, level: u8) -> AdbResult<u8> {
+        let args = media_volume_args(stream, None);
+        let output = self
+            .shell_checked(&args.iter().map(String::as_str).collect::<Vec<_>>())
+            .await?;
+        let level = match parse_volume_range(&output) {
+            Some((min, max)) => level.clamp(min, max),
+            None => level,
         };
-        self.shell(&args).await
+        let args = media_volume_args(stream, Some(level));
+        self.shell_checked(&args.iter().map(String::as_str).collect::<Vec<_>>())
+            .await?;
+        Ok(level)
+    }
+
+    /// WiFi 是否开启，解析自 `settings get global wifi_on`。
+    pub async fn wifi_enabled(&mut self) -> AdbResult<bool> {
+        let value = self
+            .settings_get(SettingsNamespace::Global, "wifi_on")
+            .await?;
+        Ok(setting_enabled(value.as_deref()))
+    }
+
+    /// 蓝牙是否开启，解析自 `settings get global bluetooth_on`。
+    pub async fn bluetooth_enabled(&mut self) -> AdbResult<bool> {
+        let value = self
+            .settings_get(SettingsNamespace::Global, "bluetooth_on")
+            .await?;
+        Ok(setting_enabled(value.as_deref()))
+    }
+
+    /// 移动数据是否开启，解析自 `settings get global mobile_data`。
+    pub async fn mobile_data_enabled(&mut self) -> AdbResult<bool> {
+        let value = self
+            .settings_get(SettingsNamespace::Global, "mobile_data")
+            .await?;
+        Ok(setting_enabled(value.as_deref()))
     }
 
     pub async fn click(&mut self, x: i32, y: i32) -> anyhow::Result<String> {
@@ -655,36 +1836,124 @@ where
         .await
     }
 
+    /// 在 (x, y) 处长按 `duration_ms` 毫秒，实现为起点与终点相同的 `input swipe`。
+    pub async fn long_click(&mut self, x: i32, y: i32, duration_ms: i32) -> anyhow::Result<String> {
+        self.swipe(x, y, x, y, duration_ms).await
+    }
+
+    /// 在 (x, y) 处连续点击两次，两次 `input tap` 在同一条 shell 命令中执行以缩短间隔。
+    pub async fn double_click(&mut self, x: i32, y: i32) -> anyhow::Result<String> {
+        let tap = Self::list2cmdline(&["input", "tap", &x.to_string(), &y.to_string()]);
+        self.shell_stream_raw(&format!("{} && {}", tap, tap))
+            .await?
+            .read_until_close()
+            .await
+    }
+
+    /// 按住 (x1, y1) 拖动到 (x2, y2)，用时 `duration_ms` 毫秒。
+    ///
+    /// 与 `swipe` 发送的是同一条 `input swipe` 命令，区别在于用途：`swipe` 用于滑动翻页，
+    /// 时长较短时会被识别为 fling 并带有惯性；拖动图标、滑块等需要精确落点时应使用 `drag`
+    /// 并给出足够长的时长（通常不少于 500 毫秒），使手指在终点停下时没有惯性。
+    pub async fn drag(
+        &mut self,
+        x1: i32,
+        y1: i32,
+        x2: i32,
+        y2: i32,
+        duration_ms: i32,
+    ) -> anyhow::Result<String> {
+        self.swipe(x1, y1, x2, y2, duration_ms).await
+    }
+
+    /// 输入文本，支持空格、`&`、`"` 等 shell 特殊字符以及非 ASCII 字符。
+    ///
+    /// `input text` 只能输入 ASCII 字符，非 ASCII 片段会先通过 `cmd clipboard set` 写入剪贴板，
+    /// 再发送粘贴按键（`KEYCODE_PASTE`），需要设备的 `cmd clipboard` 支持 `set` 且当前焦点控件可以粘贴。
+    /// 另外 `input` 会把文本中的 `%s` 解释为空格。
     pub async fn send_keys(&mut self, keys: &str) -> anyhow::Result<String> {
-        self.shell(&["input", "text", keys]).await
+        self.shell_stream_raw(&send_keys_cmdline(keys))
+            .await?
+            .read_until_close()
+            .await
     }
 
+    /// 获取设备的 IPv4 地址，依次尝试 `ifconfig wlan0`、`ip addr`、`ip route get` 与 `ifconfig eth0`，
+    /// 返回第一个合法且不是回环地址的结果。
     pub async fn wlan_ip(&mut self) -> anyhow::Result<String> {
-        let mut result = self.shell(&["ifconfig", "wlan0"]).await?;
-        let re = regex::Regex::new(r"inet\s*addr:(.*?)\s").unwrap();
-        if let Some(captures) = re.captures(&result) {
-            return Ok(captures.get(1).unwrap().as_str().to_string());
-        }
-        result = self.shell(&["ip", "addr", "show", "dev", "wlan0"]).await?;
-        let re = regex::Regex::new(r"inet (\d+.*?)/\d+").unwrap();
-        if let Some(captures) = re.captures(&result) {
-            return Ok(captures.get(1).unwrap().as_str().to_string());
+        let mut found_tool = false;
+        for (tool, command, pattern) in WLAN_IP_QUERIES {
+            if self.device_which(tool).await?.is_none() {
+                continue;
+            }
+            found_tool = true;
+            let result = self.shell(command).await?;
+            if let Some(ip) = parse_ipv4(&result, pattern) {
+                return Ok(ip.to_string());
+            }
         }
-
-        result = self.shell(&["ifconfig", "eth0"]).await?;
-        let re = regex::Regex::new(r"inet\s*addr:(.*?)\s").unwrap();
-        if let Some(captures) = re.captures(&result) {
-            return Ok(captures.get(1).unwrap().as_str().to_string());
+        if !found_tool {
+            return Err(missing_tool(&["ifconfig", "ip"]).into());
         }
         Err(anyhow!("fail to parse wlan ip"))
     }
 
+    /// 获取 wlan0 上的全局 IPv6 地址，解析自 `ip -6 addr show dev wlan0`，不包含链路本地地址。
+    pub async fn wlan_ipv6(&mut self) -> AdbResult<Vec<Ipv6Addr>> {
+        self.wlan_ipv6_with(false).await
+    }
+
+    /// 同 `wlan_ipv6`，`include_link_local` 为 `true` 时同时返回 `fe80::` 链路本地地址。
+    pub async fn wlan_ipv6_with(&mut self, include_link_local: bool) -> AdbResult<Vec<Ipv6Addr>> {
+        self.require_tool("ip").await?;
+        let output = self
+            .shell(&["ip", "-6", "addr", "show", "dev", "wlan0"])
+            .await?;
+        Ok(parse_ipv6_addrs(&output, include_link_local))
+    }
+
     pub async fn uninstall(&mut self, package_name: &str) -> anyhow::Result<String> {
         self.shell(&["am", "uninstall", package_name]).await
     }
 
+    /// 列出已安装的应用包名，解析自 `pm list packages`。
+    pub async fn list_packages(&mut self, filter: PackageFilter) -> AdbResult<Vec<String>> {
+        let args = filter.to_args();
+        let args: Vec<&str> = args.iter().map(|s| s.as_str()).collect();
+        let output = self.shell(&args).await?;
+        Ok(parse_package_list(&output))
+    }
+
+    /// 判断应用是否已安装。
+    pub async fn is_installed(&mut self, package_name: &str) -> AdbResult<bool> {
+        let packages = self
+            .list_packages(PackageFilter {
+                name: Some(package_name.to_string()),
+                ..Default::default()
+            })
+            .await?;
+        Ok(packages.iter().any(|p| p == package_name))
+    }
+
     pub async fn app_start(&mut self, package_name: &str) -> anyhow::Result<String> {
-        self.shell(&["am", "start", "-n", package_name]).await
+        if package_name.contains('/') {
+            return self.shell(&["am", "start", "-n", package_name]).await;
+        }
+        match self.resolve_launch_activity(package_name).await? {
+            Some(info) => self.shell(&["am", "start", "-n", &info.component()]).await,
+            None => self.shell(&launcher_monkey_args(package_name)).await,
+        }
+    }
+
+    /// 查询应用的启动 Activity，应用没有可启动的 Activity 时返回 `None`。
+    pub async fn resolve_launch_activity(
+        &mut self,
+        package_name: &str,
+    ) -> AdbResult<Option<ActivityInfo>> {
+        let output = self
+            .shell(&resolve_launch_activity_args(package_name))
+            .await?;
+        Ok(parse_resolve_activity(&output))
     }
 
     pub async fn app_stop(&mut self, package_name: &str) -> anyhow::Result<String> {
@@ -695,6 +1964,34 @@ where
         self.shell(&["pm", "clear", package_name]).await
     }
 
+    /// 通过 `pm path` 获取应用安装包在设备上的路径，split 应用会返回 base 与多个 split APK。
+    pub async fn apk_paths(&mut self, package_name: &str) -> AdbResult<Vec<String>> {
+        let output = self.shell(&["pm", "path", package_name]).await?;
+        Ok(parse_package_list(&output))
+    }
+
+    /// 将应用的全部安装包拉取到 `dest_dir`，返回本地文件路径。
+    pub async fn pull_apk(
+        &mut self,
+        package_name: &str,
+        dest_dir: &Path,
+    ) -> AdbResult<Vec<PathBuf>> {
+        let paths = self.apk_paths(package_name).await?;
+        if paths.is_empty() {
+            return Err(AdbError::ApplicationError(format!(
+                "package {} is not installed",
+                package_name
+            )));
+        }
+        let mut files = vec![];
+        for remote in paths {
+            let local = dest_dir.join(remote.rsplit('/').next().unwrap_or(&remote));
+            self.pull(&remote, &local).await?;
+            files.push(local);
+        }
+        Ok(files)
+    }
+
     pub async fn app_info(&mut self, package_name: &str) -> Option<AppInfo> {
         let output = self.shell(&["pm", "list", "package", "-3"]).await.ok()?;
         if !output.contains(&format!("package:{}", package_name)) {
@@ -705,6 +2002,9 @@ where
             .await
             .ok()?;
         let mut app_info = AppInfo::new(package_name);
+        if let Ok(paths) = self.apk_paths(package_name).await {
+            app_info.set_apk_paths(paths);
+        }
         let version_name_regex = regex::Regex::new(r"versionName=(?P<name>\S+)").unwrap();
         if let Some(cap) = version_name_regex.captures(&app_info_output) {
             let version_name = cap.get(1).unwrap().as_str();
@@ -749,41 +2049,245 @@ where
         Some(app_info)
     }
 
+    /// 根据 `dumpsys power` 判断屏幕是否点亮，兼容不同系统版本的字段，
+    /// 输出中没有任何可识别的字段时返回错误。
     pub async fn if_screen_on(&mut self) -> anyhow::Result<bool> {
-        let resp = self.shell(&["dumpsys", "power"]).await?;
-        Ok(resp.contains("mHoldingDisplaySuspendBlocker=true"))
+        let resp = self.dumpsys("power").await?;
+        Ok(parse_screen_on(&resp).ok_or_else(unknown_screen_state)?)
     }
 
+    /// 删除文件，`path` 是目录时返回 `AdbError::FileOperationFailed`，目录请用 [`remove_dir`](Self::remove_dir) 删除。
     pub async fn remove(&mut self, path: &str) -> anyhow::Result<String> {
+        check_remove_path(path)?;
+        if self.is_dir(path).await? {
+            return Err(AdbError::FileOperationFailed(format!(
+                "{} is a directory, use remove_dir to delete it",
+                path
+            ))
+            .into());
+        }
         self.shell_trim(&["rm", path]).await
     }
 
-    pub async fn get_sdk_version(&mut self) -> anyhow::Result<String> {
-        self.shell_trim(&["getprop", "ro.build.version.sdk"]).await
+    /// 删除目录。`recursive` 为 `false` 时使用 `rmdir`，只能删除空目录；
+    /// 为 `true` 时使用 `rm -rf`，连同目录中的内容一起删除。
+    ///
+    /// 为防止误删，`path` 为空或为 `/` 时直接返回错误。其他错误的映射规则见 [`mv`](Self::mv)。
+    pub async fn remove_dir(&mut self, path: &str, recursive: bool) -> AdbResult<()> {
+        check_remove_path(path)?;
+        if recursive {
+            self.file_op(&["rm", "-rf", path]).await
+        } else {
+            self.file_op(&["rmdir", path]).await
+        }
     }
 
-    pub async fn get_android_version(&mut self) -> anyhow::Result<String> {
-        self.shell_trim(&["getprop", "ro.build.version.release"])
-            .await
+    /// 创建目录，`recursive` 为 `true` 时使用 `mkdir -p`，同时创建不存在的父目录且目录已存在时不报错。
+    ///
+    /// 错误的映射规则见 [`mv`](Self::mv)。
+    pub async fn mkdir(&mut self, path: &str, recursive: bool) -> AdbResult<()> {
+        if recursive {
+            self.file_op(&["mkdir", "-p", path]).await
+        } else {
+            self.file_op(&["mkdir", path]).await
+        }
+    }
+
+    /// 在设备上移动或重命名文件。
+    ///
+    /// 输出中包含 `Permission denied` 等权限错误时返回 `AdbError::PermissionDenied`，
+    /// 其他失败（退出码非 0 或有错误输出，例如 `No such file or directory`）返回 `AdbError::FileOperationFailed`。
+    pub async fn mv(&mut self, src: &str, dst: &str) -> AdbResult<()> {
+        self.file_op(&["mv", src, dst]).await
+    }
+
+    /// 在设备上复制文件，复制目录时需要 `recursive`（`cp -r`）。错误的映射规则见 [`mv`](Self::mv)。
+    pub async fn cp(&mut self, src: &str, dst: &str, recursive: bool) -> AdbResult<()> {
+        if recursive {
+            self.file_op(&["cp", "-r", src, dst]).await
+        } else {
+            self.file_op(&["cp", src, dst]).await
+        }
+    }
+
+    /// 创建空文件，文件已存在时更新其修改时间。错误的映射规则见 [`mv`](Self::mv)。
+    pub async fn touch(&mut self, path: &str) -> AdbResult<()> {
+        self.file_op(&["touch", path]).await
+    }
+
+    /// 修改文件权限，`mode` 以八进制传给 `chmod`（例如 `0o755`），之后通过 [`stat`](Self::stat) 确认已生效。
+    ///
+    /// 部分文件系统（例如 `/sdcard`）会静默忽略 `chmod`，此时与命令报告权限错误一样返回 `AdbError::PermissionDenied`。
+    pub async fn chmod(&mut self, path: &str, mode: u32) -> AdbResult<()> {
+        let mode = mode & 0o7777;
+        self.file_op(&["chmod", &format!("{:o}", mode), path])
+            .await?;
+        let actual = self.stat(path).await?.permissions() as u32;
+        if actual != mode {
+            return Err(AdbError::PermissionDenied(format!(
+                "mode of {} is {:o} after chmod {:o}",
+                path, actual, mode
+            )));
+        }
+        Ok(())
+    }
+
+    /// 修改文件所有者，`owner` 为 `user` 或 `user:group`，通常需要 root。错误的映射规则见 [`mv`](Self::mv)。
+    pub async fn chown(&mut self, path: &str, owner: &str) -> AdbResult<()> {
+        self.file_op(&["chown", owner, path]).await
+    }
+
+    async fn file_op(&mut self, command: &[&str]) -> AdbResult<()> {
+        let (output, code) = self.shell_with_status(command).await?;
+        file_op_result(command, &output, code)
+    }
+
+    /// 执行一次 `getprop` 读取全部属性并刷新属性缓存。
+    pub async fn load_all_props(&mut self) -> AdbResult<&HashMap<String, String>> {
+        let output = self.shell(&["getprop"]).await?;
+        self.properties = parse_getprop(&output);
+        Ok(&self.properties)
+    }
+
+    /// 读取单个属性，只读属性（`ro.` 前缀）优先使用属性缓存，其他属性总是执行 `getprop <key>`。
+    pub async fn get_prop(&mut self, key: &str) -> AdbResult<String> {
+        if key.starts_with("ro.") {
+            if let Some(value) = self.properties.get(key) {
+                return Ok(value.clone());
+            }
+        }
+        let value = self.shell_trim(&["getprop", key]).await?;
+        if key.starts_with("ro.") {
+            self.properties.insert(key.to_string(), value.clone());
+        }
+        Ok(value)
+    }
+
+    /// 执行 `setprop <key> <value>` 并读回确认，值未生效时返回 `AdbError::PermissionDenied`。
+    pub async fn set_prop(&mut self, key: &str, value: &str) -> AdbResult<()> {
+        self.shell(&["setprop", key, value]).await?;
+        self.properties.remove(key);
+        let actual = self.get_prop(key).await?;
+        check_prop_value(key, value, &actual)
+    }
+
+    /// 获取设备时区，例如 `Asia/Shanghai`，读取自 `persist.sys.timezone`。
+    pub async fn get_timezone(&mut self) -> AdbResult<String> {
+        let timezone = self.get_prop("persist.sys.timezone").await?;
+        if timezone.is_empty() {
+            return Err(AdbError::unknown("persist.sys.timezone is not set"));
+        }
+        Ok(timezone)
+    }
+
+    /// 通过 `service call alarm 3 s16 <timezone>`（`IAlarmManager.setTimeZone`）设置时区并读回确认，
+    /// 时区未生效时返回 `AdbError::PermissionDenied`。
+    pub async fn set_timezone(&mut self, timezone: &str) -> AdbResult<()> {
+        self.shell(&["service", "call", "alarm", "3", "s16", timezone])
+            .await?;
+        self.properties.remove("persist.sys.timezone");
+        let actual = self.get_prop("persist.sys.timezone").await?;
+        check_prop_value("persist.sys.timezone", timezone, &actual)
+    }
+
+    /// 获取系统语言，例如 `zh-CN`。
+    ///
+    /// 依次读取 `persist.sys.locale`、旧版本的 `persist.sys.language` 与 `persist.sys.country`、
+    /// 出厂设置 `ro.product.locale`，取第一个非空的值。
+    pub async fn get_locale(&mut self) -> AdbResult<String> {
+        let locale = self.get_prop("persist.sys.locale").await?;
+        if !locale.is_empty() {
+            return Ok(locale);
+        }
+        let language = self.get_prop("persist.sys.language").await?;
+        if !language.is_empty() {
+            let country = self.get_prop("persist.sys.country").await?;
+            return Ok(if country.is_empty() {
+                language
+            } else {
+                format!("{}-{}", language, country)
+            });
+        }
+        let locale = self.get_prop("ro.product.locale").await?;
+        if locale.is_empty() {
+            return Err(AdbError::unknown("no locale property is set"));
+        }
+        Ok(locale)
+    }
+
+    /// 设置设备时间，按系统版本选择 toybox 或 toolbox 的 `date` 格式，时间以 UTC 写入。
+    ///
+    /// 修改系统时间通常需要 root，shell 报告没有权限时返回 `AdbError::PermissionDenied`。
+    pub async fn set_device_time(&mut self, dt: DateTime<Utc>) -> AdbResult<()> {
+        let sdk = self.get_sdk_version().await?.parse().unwrap_or(23);
+        let args = date_set_args(sdk, &dt);
+        let args: Vec<&str> = args.iter().map(String::as_str).collect();
+        let (output, code) = self.shell_with_status(&args).await?;
+        if is_permission_error(&output) {
+            return Err(AdbError::PermissionDenied(output.trim().to_string()));
+        }
+        check_exit_status(&args, output, code)?;
+        Ok(())
+    }
+
+    pub async fn get_sdk_version(&mut self) -> anyhow::Result<String> {
+        Ok(self.get_prop("ro.build.version.sdk").await?)
+    }
+
+    pub async fn get_android_version(&mut self) -> anyhow::Result<String> {
+        Ok(self.get_prop("ro.build.version.release").await?)
     }
 
     pub async fn get_device_model(&mut self) -> anyhow::Result<String> {
-        self.shell_trim(&["getprop", "ro.product.model"]).await
+        Ok(self.get_prop("ro.product.model").await?)
     }
 
     pub async fn get_device_brand(&mut self) -> anyhow::Result<String> {
-        self.shell_trim(&["getprop", "ro.product.brand"]).await
+        Ok(self.get_prop("ro.product.brand").await?)
     }
     pub async fn get_device_manufacturer(&mut self) -> anyhow::Result<String> {
-        self.shell_trim(&["getprop", "ro.product.manufacturer"])
-            .await
+        Ok(self.get_prop("ro.product.manufacturer").await?)
     }
     pub async fn get_device_product(&mut self) -> anyhow::Result<String> {
-        self.shell_trim(&["getprop", "ro.product.product"]).await
+        Ok(self.get_prop("ro.product.product").await?)
     }
 
     pub async fn get_device_abi(&mut self) -> anyhow::Result<String> {
-        self.shell_trim(&["getprop", "ro.product.cpu.abi"]).await
+        Ok(self.get_prop("ro.product.cpu.abi").await?)
+    }
+
+    /// 获取设备支持的全部 ABI，按优先级排列，读取自 `ro.product.cpu.abilist`。
+    ///
+    /// Android 5.0 之前没有该属性，回退到 `ro.product.cpu.abi` 与 `ro.product.cpu.abi2`。
+    /// ABI 属性都从属性缓存读取，缓存中没有时先通过 [`load_all_props`](Self::load_all_props)
+    /// 一次读取全部属性。
+    pub async fn supported_abis(&mut self) -> AdbResult<Vec<String>> {
+        let abis = self.abi_list("ro.product.cpu.abilist").await?;
+        if !abis.is_empty() {
+            return Ok(abis);
+        }
+        let mut abis = self.abi_list("ro.product.cpu.abi").await?;
+        abis.extend(self.abi_list("ro.product.cpu.abi2").await?);
+        Ok(abis)
+    }
+
+    /// 获取设备支持的 32 位 ABI，读取自 `ro.product.cpu.abilist32`，没有时返回空列表。
+    pub async fn abilist32(&mut self) -> AdbResult<Vec<String>> {
+        self.abi_list("ro.product.cpu.abilist32").await
+    }
+
+    /// 获取设备支持的 64 位 ABI，读取自 `ro.product.cpu.abilist64`，只支持 32 位的设备返回空列表。
+    pub async fn abilist64(&mut self) -> AdbResult<Vec<String>> {
+        self.abi_list("ro.product.cpu.abilist64").await
+    }
+
+    async fn abi_list(&mut self, key: &str) -> AdbResult<Vec<String>> {
+        if !self.properties.contains_key(key) {
+            self.load_all_props().await?;
+        }
+        Ok(split_abi_list(
+            self.properties.get(key).map_or("", String::as_str),
+        ))
     }
 
     pub async fn get_device_gpu(&mut self) -> anyhow::Result<String> {
@@ -800,14 +2304,228 @@ where
         }
         Err(anyhow!("fail to get gpu"))
     }
+
+    /// 获取 GPU 的厂商、渲染器、驱动版本和扩展列表，解析自 `dumpsys SurfaceFlinger`；
+    /// 原始的 `GLES:` 行见 [`get_device_gpu`](Self::get_device_gpu)。
+    pub async fn gpu_info(&mut self) -> AdbResult<GpuInfo> {
+        let output = self.dumpsys("SurfaceFlinger").await?;
+        GpuInfo::parse(&output)
+    }
+
+    /// 获取前台 Activity，优先解析 `dumpsys activity activities`，失败时回退到 `dumpsys window`。
+    pub async fn current_activity(&mut self) -> AdbResult<ActivityInfo> {
+        let output = self.shell(&["dumpsys", "activity", "activities"]).await?;
+        if let Some(info) = parse_resumed_activity(&output) {
+            return Ok(info);
+        }
+        let output = self.shell(&["dumpsys", "window"]).await?;
+        parse_current_focus(&output).ok_or_else(no_focused_activity)
+    }
+
+    /// 屏幕熄灭时按 `KEYCODE_WAKEUP` 点亮，已点亮时不做任何操作。
+    pub async fn wake(&mut self) -> AdbResult<()> {
+        if !self.if_screen_on().await? {
+            self.keyevent(&KeyCode::Wakeup.to_string()).await?;
+        }
+        Ok(())
+    }
+
+    /// 是否显示锁屏界面，解析自 `dumpsys window`。
+    pub async fn is_locked(&mut self) -> AdbResult<bool> {
+        let output = self.shell(&["dumpsys", "window"]).await?;
+        parse_keyguard_locked(&output)
+            .ok_or_else(|| AdbError::unknown("dumpsys window has no known keyguard field"))
+    }
+
+    /// 点亮屏幕并解锁：上滑关闭锁屏界面，提供 `pin` 时输入 PIN 并按回车。
+    ///
+    /// 已解锁时不做任何操作，可以重复调用。只支持无密码和 PIN/密码锁，
+    /// 图案锁与生物识别无法通过该方法解锁；方法不检查解锁是否成功，需要时调用 `is_locked` 确认。
+    pub async fn unlock(&mut self, pin: Option<&str>) -> AdbResult<()> {
+        let mut prev = None;
+        loop {
+            let screen_on = self.if_screen_on().await?;
+            let locked = screen_on && self.is_locked().await?;
+            let step = next_unlock_step(prev, screen_on, locked, pin.is_some());
+            match step {
+                UnlockStep::Wake => {
+                    self.keyevent(&KeyCode::Wakeup.to_string()).await?;
+                }
+                UnlockStep::Swipe => {
+                    let (width, height) = self.screen_size().await?;
+                    let x = (width / 2) as i32;
+                    self.swipe(x, (height * 4 / 5) as i32, x, (height / 5) as i32, 300)
+                        .await?;
+                }
+                UnlockStep::EnterPin => {
+                    self.shell(&["input", "text", pin.unwrap_or_default()])
+                        .await?;
+                    self.keyevent(&KeyCode::Enter.to_string()).await?;
+                }
+                UnlockStep::Done => return Ok(()),
+            }
+            prev = Some(step);
+        }
+    }
+
+    /// 获取主屏分辨率 `(宽, 高)`，解析自 `wm size`，存在 `Override size` 时优先使用。
+    pub async fn screen_size(&mut self) -> AdbResult<(u32, u32)> {
+        let output = self.shell(&["wm", "size"]).await?;
+        parse_wm_size(&output).ok_or_else(|| unexpected_output("wm size", &output))
+    }
+
+    /// 获取主屏 dpi，解析自 `wm density`，存在 `Override density` 时优先使用。
+    pub async fn screen_density(&mut self) -> AdbResult<u32> {
+        let output = self.shell(&["wm", "density"]).await?;
+        parse_wm_density(&output).ok_or_else(|| unexpected_output("wm density", &output))
+    }
+
+    /// 执行 `dumpsys <service>` 并返回输出，`service` 按空白拆分，可以带参数，例如 `meminfo com.example`。
+    ///
+    /// 服务不存在（`Can't find service`）时返回 `AdbError::Unknown`。
+    pub async fn dumpsys(&mut self, service: &str) -> AdbResult<String> {
+        let mut args = vec!["dumpsys"];
+        args.extend(service.split_whitespace());
+        let output = self.shell(&args).await?;
+        if output.starts_with("Can't find service") {
+            return Err(AdbError::unknown(output.trim()));
+        }
+        Ok(output)
+    }
+
+    /// 获取电池电量、充电状态等信息，解析自 `dumpsys battery`。
+    pub async fn battery(&mut self) -> AdbResult<BatteryInfo> {
+        let output = self.dumpsys("battery").await?;
+        parse_battery(&output)
+    }
+
+    /// 获取电源状态，解析自 `dumpsys power`。
+    pub async fn power_info(&mut self) -> AdbResult<PowerInfo> {
+        let output = self.dumpsys("power").await?;
+        PowerInfo::parse(&output)
+    }
+
+    /// 获取应用的内存占用，解析自 `dumpsys meminfo <package>`，应用未运行时返回错误。
+    pub async fn meminfo(&mut self, package: &str) -> AdbResult<MemInfo> {
+        let output = self.shell(&["dumpsys", "meminfo", package]).await?;
+        MemInfo::parse(&output)
+    }
+
+    /// 获取应用自启动（或上次 `reset`）以来的帧耗时统计，解析自 `dumpsys gfxinfo <package>`。
+    pub async fn gfxinfo(&mut self, package: &str) -> AdbResult<GfxInfo> {
+        let output = self.shell(&["dumpsys", "gfxinfo", package]).await?;
+        GfxInfo::parse(&output)
+    }
+
+    /// 获取所有挂载点的存储使用情况，解析自 `df -k`。
+    pub async fn mounts(&mut self) -> AdbResult<Vec<MountInfo>> {
+        self.require_tool("df").await?;
+        let output = self.shell(&["df", "-k"]).await?;
+        Ok(parse_df_output(&output))
+    }
+
+    /// 获取 `path` 所在文件系统的容量，解析自 `df -k <path>`，固定 1K 块输出以免不同版本的 `df` 默认格式不同。
+    ///
+    /// 路径不存在时返回 `AdbError::FileOperationFailed`。
+    pub async fn storage(&mut self, path: &str) -> AdbResult<StorageInfo> {
+        self.require_tool("df").await?;
+        let output = self.shell(&["df", "-k", path]).await?;
+        parse_df_output(&output)
+            .first()
+            .map(StorageInfo::from)
+            .ok_or_else(|| AdbError::FileOperationFailed(format!("df {}: {}", path, output.trim())))
+    }
+
+    /// 获取名为 `process` 的进程的 PID，进程不存在时返回空列表。
+    ///
+    /// 优先使用 `pidof`，旧设备没有 `pidof` 时从 [`ps`](Self::ps) 的结果中按进程名查找。
+    pub async fn pidof(&mut self, process: &str) -> AdbResult<Vec<u32>> {
+        let (output, code) = self.shell_with_status(&["pidof", process]).await?;
+        if !is_command_missing(&output, code) {
+            return Ok(parse_pidof(&output));
+        }
+        Ok(self
+            .ps()
+            .await?
+            .into_iter()
+            .filter(|p| p.name == process)
+            .map(|p| p.pid)
+            .collect())
+    }
+
+    /// 列出设备上的所有进程，使用 toybox 的 `ps -A -o PID,PPID,USER,NAME`；
+    /// 旧设备的 toolbox `ps` 不支持这些参数，把它们当成进程名过滤而输出为空，此时退回不带参数的 `ps`。
+    pub async fn ps(&mut self) -> AdbResult<Vec<ProcessInfo>> {
+        let output = self
+            .shell(&["ps", "-A", "-o", "PID,PPID,USER,NAME"])
+            .await?;
+        let processes = parse_ps_output(&output);
+        if !processes.is_empty() {
+            return Ok(processes);
+        }
+        let output = self.shell(&["ps"]).await?;
+        Ok(parse_ps_output(&output))
+    }
+
+    /// 向进程发送信号，与 `kill -<signal> <pid>` 相同。
+    ///
+    /// 没有权限（`Operation not permitted`）时返回 `AdbError::PermissionDenied`，
+    /// 其他失败（例如进程不存在）返回 `AdbError::ExitStatus`。
+    pub async fn kill_pid(&mut self, pid: u32, signal: i32) -> AdbResult<()> {
+        let signal = format!("-{}", signal);
+        let pid = pid.to_string();
+        let command = ["kill", signal.as_str(), pid.as_str()];
+        let (output, code) = self.shell_with_status(&command).await?;
+        if is_permission_error(&output) {
+            return Err(AdbError::PermissionDenied(output.trim().to_string()));
+        }
+        if code != 0 {
+            return Err(AdbError::exit_status(
+                command.join(" "),
+                code,
+                output.trim(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// 通过 [`pidof`](Self::pidof) 找到名为 `name` 的所有进程并发送 `SIGKILL`，返回被结束的 PID。
+    ///
+    /// 可以结束 `am force-stop` 无法停止的后台服务进程；没有匹配的进程时返回空列表。
+    pub async fn kill_process(&mut self, name: &str) -> AdbResult<Vec<u32>> {
+        let pids = self.pidof(name).await?;
+        for &pid in &pids {
+            self.kill_pid(pid, 9).await?;
+        }
+        Ok(pids)
+    }
+
+    /// 获取设备内存总量（字节），读取自 `/proc/meminfo` 的 `MemTotal`。
+    pub async fn mem_total(&mut self) -> AdbResult<u64> {
+        let meminfo = self.proc_meminfo().await?;
+        meminfo
+            .get("MemTotal")
+            .copied()
+            .ok_or_else(|| AdbError::unknown("MemTotal not found in /proc/meminfo"))
+    }
+
+    /// 获取设备可用内存（字节），计算方式见 [`available_memory`]。
+    pub async fn mem_available(&mut self) -> AdbResult<u64> {
+        let meminfo = self.proc_meminfo().await?;
+        available_memory(&meminfo)
+            .ok_or_else(|| AdbError::unknown("MemAvailable not found in /proc/meminfo"))
+    }
+
+    async fn proc_meminfo(&mut self) -> AdbResult<HashMap<String, u64>> {
+        let output = self.shell(&["cat", "/proc/meminfo"]).await?;
+        Ok(parse_proc_meminfo(&output))
+    }
+
     pub async fn logcat(
         &mut self,
         flush_exist: bool,
         extra_command: Option<&[&str]>,
     ) -> anyhow::Result<impl Stream<Item = anyhow::Result<String>>> {
-        if flush_exist {
-            self.shell(&["logcat", "-c"]).await?;
-        };
         let cmd = if let Some(extra_cmd) = extra_command {
             let mut default_cmd = vec!["logcat"];
             default_cmd.extend_from_slice(extra_cmd);
@@ -815,7 +2533,55 @@ where
         } else {
             vec!["logcat", "-v", "time"]
         };
-        let conn = self.shell_stream(&cmd).await?;
+        self.logcat_stream(flush_exist, &cmd).await
+    }
+
+    /// 按 `filter` 过滤读取日志，参见 [`LogcatFilter`]。
+    pub async fn logcat_filtered(
+        &mut self,
+        flush_exist: bool,
+        filter: &LogcatFilter,
+    ) -> anyhow::Result<impl Stream<Item = anyhow::Result<String>>> {
+        let args = filter.to_args();
+        let cmd: Vec<&str> = args.iter().map(|s| s.as_str()).collect();
+        self.logcat_stream(flush_exist, &cmd).await
+    }
+
+    /// 以 `-v threadtime` 格式读取日志并逐行解析为 [`LogEntry`]。
+    ///
+    /// `filter` 中的输出格式会被忽略；`skip_markers` 为真时丢弃 `--------- beginning of main` 等非日志行。
+    pub async fn logcat_parsed(
+        &mut self,
+        flush_exist: bool,
+        filter: &LogcatFilter,
+        skip_markers: bool,
+    ) -> AdbResult<impl Stream<Item = AdbResult<LogEntry>>> {
+        let filter = LogcatFilter {
+            format: Some("threadtime".to_string()),
+            ..filter.clone()
+        };
+        let lines = self.logcat_filtered(flush_exist, &filter).await?;
+        Ok(lines.filter_map(move |line| {
+            let entry = match line {
+                Ok(line) => match LogEntry::parse(&line) {
+                    LogEntry::Marker(_) if skip_markers => None,
+                    entry => Some(Ok(entry)),
+                },
+                Err(e) => Some(Err(AdbError::from(e))),
+            };
+            std::future::ready(entry)
+        }))
+    }
+
+    async fn logcat_stream(
+        &mut self,
+        flush_exist: bool,
+        cmd: &[&str],
+    ) -> anyhow::Result<impl Stream<Item = anyhow::Result<String>>> {
+        if flush_exist {
+            self.shell(&["logcat", "-c"]).await?;
+        };
+        let conn = self.shell_stream(cmd).await?;
         Ok(stream! {
                     let mut reader = BufStream::new(conn);
                     let mut buffer = String::new();
@@ -852,10 +2618,13 @@ where
             .context("Get Open Transport Prefix Failed")?;
         // 获取一个Adb连接。
         let mut stream = TcpStream::connect(&self.addr)?;
-        stream.send_cmd_then_check_okay(&prefix).context(format!(
-            "Send Command >> {:#?} and Check Okay Failed",
-            &prefix
-        ))?;
+        stream
+            .send_cmd_then_check_okay(&prefix)
+            .map_err(transport_failed)
+            .context(format!(
+                "Send Command >> {:#?} and Check Okay Failed",
+                &prefix
+            ))?;
         Ok(stream)
     }
 
@@ -869,6 +2638,13 @@ where
         self.get_with_command("get-state")
     }
 
+    /// 与 `get_state` 相同，但解析为 [`DeviceState`]。
+    ///
+    /// 设备已断开时 adb server 返回 `device '...' not found`，此时返回 `AdbError::DeviceNotFound`。
+    pub fn state(&mut self) -> AdbResult<DeviceState> {
+        self.get_state()?.parse()
+    }
+
     pub fn get_serialno(&mut self) -> anyhow::Result<String> {
         self.get_with_command("get-serialno")
     }
@@ -881,6 +2657,95 @@ where
         self.get_with_command("get-features")
     }
 
+    /// 设备是否支持 `feature`，例如 `stat_v2`、`ls_v2`、`sendrecv_v2`。
+    ///
+    /// 首次调用时查询 `get-features` 并缓存到 `features`；查询失败视为不支持，本次 sync 操作使用 v1 命令，
+    /// 失败的结果不缓存，下次调用时重新查询。
+    pub fn has_feature(&mut self, feature: &str) -> bool {
+        if self.features.is_none() {
+            match self.get_features() {
+                Ok(features) => self.features = Some(parse_features(&features)),
+                Err(_) => return false,
+            }
+        }
+        self.features
+            .as_ref()
+            .is_some_and(|features| features.iter().any(|f| f == feature))
+    }
+
+    /// 阻塞等待设备进入指定状态，与 `adb wait-for-<transport>-<state>` 相同。
+    ///
+    /// # 参数
+    /// - `transport`: 设备的连接方式（`usb`/`local`/`any`）。
+    /// - `state`: 需要等待的设备状态。
+    /// - `timeout`: 最长等待时间。
+    ///
+    /// # 返回值
+    /// - 设备进入该状态时返回 `Ok(())`，超过 `timeout` 返回 `AdbError::Timeout`。
+    /// - `state` 不能用于 `wait-for`（见 [`DeviceState::is_waitable`]）时直接返回错误，不连接 adb server。
+    pub fn wait_for(
+        &mut self,
+        transport: TransportKind,
+        state: DeviceState,
+        timeout: Duration,
+    ) -> AdbResult<()> {
+        check_waitable(state)?;
+        let command = format!("wait-for-{}-{}", transport, state);
+        let deadline = time::Instant::now() + timeout;
+        let mut conn = self.open_transport(Some(&command))?;
+        // 服务端在设备进入目标状态后才会再回一个 OKAY
+        let remaining = deadline.saturating_duration_since(time::Instant::now());
+        conn.set_read_timeout(Some(remaining.max(Duration::from_millis(1))))?;
+        match conn.check_okay().map_err(AdbError::from) {
+            Err(AdbError::Io(e))
+                if matches!(
+                    e.kind(),
+                    std::io::ErrorKind::WouldBlock | std::io::ErrorKind::TimedOut
+                ) =>
+            {
+                Err(AdbError::timeout(format!(
+                    "device not in state {} within {:?}",
+                    state, timeout
+                )))
+            }
+            result => result,
+        }
+    }
+
+    /// 执行 `op`，遇到可重试的错误（[`AdbError::is_retryable`]）时按指数退避重试。
+    ///
+    /// 适合包裹在设备刚启动、短暂繁忙时可能失败的 `shell`、`open_transport` 等操作外层。
+    ///
+    /// # 参数
+    /// - `attempts`: 最多执行的次数（包括第一次），为 0 时按 1 处理。
+    /// - `backoff`: 第一次重试前的等待时间，之后每次翻倍。
+    /// - `op`: 需要执行的操作，参数为当前设备。
+    ///
+    /// # 返回值
+    /// - 第一次成功的结果；遇到不可重试的错误或次数用尽时返回最后一次的错误。
+    pub fn with_retry<F, R>(
+        &mut self,
+        attempts: usize,
+        backoff: Duration,
+        mut op: F,
+    ) -> AdbResult<R>
+    where
+        F: FnMut(&mut Self) -> AdbResult<R>,
+    {
+        let mut backoff = backoff;
+        let mut remaining = attempts.max(1);
+        loop {
+            remaining -= 1;
+            match op(self) {
+                Err(e) if remaining > 0 && e.is_retryable() => {
+                    std::thread::sleep(backoff);
+                    backoff *= 2;
+                }
+                result => return result,
+            }
+        }
+    }
+
     /// 执行通过ADB shell命令流，并返回一个AdbConnection的实例。
     ///
     /// # 参数
@@ -888,21 +2753,31 @@ where
     ///
     /// # 返回值
     /// - `anyhow::Result<AdbConnection>`: 如果命令成功执行，则返回一个AdbConnection的实例；
-    ///                                  如果执行过程中出现错误，则返回错误信息。
+    ///   如果执行过程中出现错误，则返回错误信息。
     pub fn shell_stream(&mut self, command: &[&str]) -> anyhow::Result<TcpStream> {
-        // 打开与设备的传输通道
-        let mut conn = self.open_transport(None)?;
-
         // 将命令切片数组转换为命令行字符串
         let cmd = Self::list2cmdline(command);
+        self.shell_stream_raw(&cmd)
+    }
+
+    /// 以原样的命令行字符串执行 shell 命令，不做参数转义。
+    fn shell_stream_raw(&mut self, cmd: &str) -> anyhow::Result<TcpStream> {
+        self.shell_stream_logged(cmd, cmd)
+    }
+
+    /// 执行 shell 命令 `cmd`，日志和错误信息中只出现 `shown`，用于隐藏敏感参数。
+    fn shell_stream_logged(&mut self, cmd: &str, shown: &str) -> anyhow::Result<TcpStream> {
+        // 打开与设备的传输通道
+        let mut conn = self.open_transport(None)?;
 
         // 构造完整的ADB shell命令字符串
         let send_cmd = format!("shell:{}", cmd);
+        debug!("shell:{}", shown);
 
         // 发送命令并检查是否执行成功
         conn.send_cmd_then_check_okay(&send_cmd).context(format!(
             "Send Command >> {:#?} and Check Okay Failed",
-            &send_cmd
+            format!("shell:{}", shown)
         ))?;
 
         // 返回成功的AdbConnection实例
@@ -917,6 +2792,8 @@ where
     /// # 返回值
     /// - `anyhow::Result<String>`: 命令执行成功则返回命令的输出结果，如果执行过程中出现错误则返回错误信息。
     pub fn shell(&mut self, command: &[&str]) -> anyhow::Result<String> {
+        #[cfg(feature = "metrics")]
+        let _timer = Timer::start("shell");
         // 通过`shell_stream`方法执行命令，获取命令的输出流
         let mut s = self.shell_stream(command)?;
 
@@ -926,13 +2803,136 @@ where
         // 将读取到的命令输出返回
         Ok(output)
     }
+    /// 同 [`shell`](AdbDevice::shell)，但日志和错误信息中 `redact` 下标对应的参数显示为 `***`。
+    ///
+    /// 设备收到的仍是完整命令，适合传递密码、令牌等不应写入日志的参数。
+    pub fn shell_redacted(&mut self, command: &[&str], redact: &[usize]) -> anyhow::Result<String> {
+        let cmd = AdbCommand::from(command);
+        let mut s = self.shell_stream_logged(&cmd.display(), &cmd.display_redacted(redact))?;
+        s.read_until_close()
+    }
+
     pub fn shell_trim(&mut self, command: &[&str]) -> anyhow::Result<String> {
-        let mut s = self.shell_stream(command)?;
-        let output = s.read_until_close()?;
+        let output = self.shell(command)?;
         Ok(output.trim().to_string())
     }
 
+    /// 执行 shell 命令，按行返回输出，不等待命令结束。
+    ///
+    /// 适合 `top -d 1`、`logcat` 等持续输出的命令，调用方可以边读边处理；
+    /// 需要从其他线程结束时使用 [`ShellLines::stop_handle`]。
+    pub fn shell_lines<T2: Into<AdbCommand>>(&mut self, cmd: T2) -> AdbResult<ShellLines> {
+        let conn = self.shell_stream_raw(&Self::command_line(&cmd.into()))?;
+        Ok(ShellLines::new(conn))
+    }
+
+    /// 打开一个持久 shell 会话，之后的命令都在同一条连接上执行。
+    ///
+    /// `shell` 每次调用都要新建连接并切换传输，连续执行大量短命令时这部分开销占主导，
+    /// 此时可改用会话的 [`PersistentShell::run`]。
+    /// 只有 shell 命令可以共享连接：adb server 处理完 `host:`、`host-serial:` 请求
+    /// （`get-state`、`get-serialno`、`forward` 等）后会关闭连接，这些请求仍需各自建立连接。
+    pub fn shell_persistent(&mut self) -> AdbResult<PersistentShell> {
+        let conn = self.shell_stream_raw("sh")?;
+        Ok(PersistentShell::new(conn))
+    }
+
+    /// 打开模拟器控制台，用于控制 GPS、电池、屏幕方向等模拟器专有功能。
+    ///
+    /// 只支持序列号为 `emulator-<port>` 的模拟器，连接 `127.0.0.1:<port>`，
+    /// 需要认证时使用 `~/.emulator_console_auth_token` 中的令牌；其他设备返回 `AdbError::Unknown`。
+    pub fn emulator_console(&mut self) -> AdbResult<EmulatorConsole> {
+        let serial = self.serial.as_deref().unwrap_or_default();
+        let port = console_port(serial)
+            .ok_or_else(|| AdbError::unknown(format!("{:#?} is not an emulator", serial)))?;
+        EmulatorConsole::connect(("127.0.0.1", port), read_auth_token().as_deref())
+    }
+
+    /// 执行 shell 命令并返回输出和退出码。
+    ///
+    /// 通过在命令后追加 `; echo $?` 获取退出码，stdout 与 stderr 合并在输出中。
+    pub fn shell_with_status(&mut self, command: &[&str]) -> AdbResult<(String, i32)> {
+        let cmd = format!(
+            "{}; echo \"{}$?\"",
+            Self::list2cmdline(command),
+            EXIT_STATUS_MARKER
+        );
+        let output = self.shell_stream_raw(&cmd)?.read_until_close()?;
+        split_exit_status(&output)
+    }
+
+    /// 执行 shell 命令，退出码非 0 时返回 `AdbError::CommandFailed`，否则返回输出。
+    pub fn shell_checked(&mut self, command: &[&str]) -> AdbResult<String> {
+        let (output, code) = self.shell_with_status(command)?;
+        check_exit_status(command, output, code)
+    }
+
+    /// 查找设备上可执行文件的路径，与 `command -v` 相同。
+    ///
+    /// # 返回值
+    /// 可执行文件路径；设备上不存在该工具时返回 `None`
+    pub fn device_which(&mut self, tool: &str) -> AdbResult<Option<String>> {
+        let (output, code) = self.shell_with_status(&["command", "-v", tool])?;
+        let path = output.trim();
+        Ok((code == 0 && !path.is_empty()).then(|| path.to_string()))
+    }
+
+    /// 确认设备上存在 `tool`，否则返回说明缺少哪个工具的错误，
+    /// 供依赖 `df`、`ifconfig` 等设备端工具的方法在执行前检查。
+    fn require_tool(&mut self, tool: &str) -> AdbResult<()> {
+        match self.device_which(tool)? {
+            Some(_) => Ok(()),
+            None => Err(missing_tool(&[tool])),
+        }
+    }
+
+    /// 按顺序在当前设备上重新执行录制的命令。
+    ///
+    /// 每条命令都会读取退出码，连接失败或退出码非 0（`AdbError::CommandFailed`）都视为该命令失败。
+    ///
+    /// # 参数
+    /// - `log`: 录制的命令日志。
+    /// - `options`: 是否保留录制时的命令间隔，以及命令失败时中止还是继续。
+    ///
+    /// # 返回值
+    /// - 本次回放的命令日志；`ReplayErrorPolicy::Abort` 下遇到失败直接返回该错误。
+    pub fn replay(&mut self, log: &CommandLog, options: ReplayOptions) -> AdbResult<CommandLog> {
+        let mut replayed = CommandLog::new();
+        for (index, record) in log.records.iter().enumerate() {
+            if options.keep_timing {
+                std::thread::sleep(log.delay_before(index));
+            }
+            // 按录制时的命令行原样发送，避免引号、管道等被重新拆分
+            let cmdline = record.command.display();
+            let result = self
+                .shell_stream_raw(&replay_cmdline(&cmdline))
+                .and_then(|mut conn| conn.read_until_close())
+                .map_err(AdbError::from)
+                .and_then(|output| replay_output(&cmdline, &output));
+            replayed.record(record.command.clone(), &result);
+            if let Err(e) = result {
+                error!("Replay {:#?} Failed: {}", cmdline, e);
+                if options.on_error == ReplayErrorPolicy::Abort {
+                    return Err(e);
+                }
+            }
+        }
+        Ok(replayed)
+    }
+
     pub fn forward(&mut self, local: &str, remote: &str, norebind: bool) -> anyhow::Result<()> {
+        self.forward_port(local, remote, norebind)
+            .map(|_| ())
+            .map_err(|e| anyhow!("Failed To Forward Port, Err >>> {}", e))
+    }
+
+    /// 创建端口转发；`local` 为 `tcp:0` 时返回 adb server 分配的本地端口。
+    fn forward_port(
+        &mut self,
+        local: &str,
+        remote: &str,
+        norebind: bool,
+    ) -> AdbResult<Option<u16>> {
         let mut args = vec!["forward"];
         if norebind {
             args.push("norebind");
@@ -940,42 +2940,70 @@ where
         let forward_str = format!("{};{}", local, remote);
         args.push(&forward_str);
         let full_cmd = args.join(":");
-        if let Ok(_) = self.open_transport(Some(&full_cmd)) {
-            return Ok(());
+        let mut conn = self.open_transport(Some(&full_cmd))?;
+        // 第一个 OKAY 表示连接成功，第二个 OKAY 表示转发已建立
+        conn.check_okay()?;
+        if local != "tcp:0" {
+            return Ok(None);
         }
-        Err(anyhow!("Failed To Forward Port"))
+        let port = conn.read_string_block()?;
+        port.trim()
+            .parse()
+            .map(Some)
+            .map_err(|_| AdbError::unknown(format!("invalid forward port {:#?}", port)))
     }
 
-    pub fn forward_list(&mut self) -> anyhow::Result<Vec<ForwardItem>> {
-        let mut connection = self.open_transport(Some("list-forward"))?;
-        let content = connection.read_string_block()?;
-        let mut forward_iterms = vec![];
-        for x in content.lines() {
-            let mut current_parts: Vec<&str> = x.split(" ").collect();
-            if current_parts.len() == 3 {
-                let (serial, local, remote) =
-                    (current_parts[0], current_parts[1], current_parts[2]);
-                forward_iterms.push(ForwardItem::new(serial, local, remote))
-            }
-        }
-        Ok(forward_iterms)
+    /// 由 adb server 分配一个空闲的本地端口转发到 `remote`，返回该本地端口。
+    ///
+    /// # 参数
+    /// - `remote`: 设备端地址，例如 `tcp:9999`。
+    pub fn forward_tcp(&mut self, remote: &str) -> AdbResult<u16> {
+        let port = self
+            .forward_port("tcp:0", remote, false)?
+            .ok_or_else(|| AdbError::unknown("adb server did not return a port"))?;
+        self.forwards.push(format!("tcp:{}", port));
+        Ok(port)
+    }
+
+    pub fn forward_list(&mut self) -> anyhow::Result<Vec<ForwardItem>> {
+        let mut connection = self.open_transport(Some("list-forward"))?;
+        let content = connection.read_string_block()?;
+        Ok(parse_forward_list(&content))
     }
+    /// 返回转发到设备端 `tcp:<remote>` 的本地端口，已有该设备的转发时直接复用，否则新建一个。
     pub fn forward_remote_port(&mut self, remote: u16) -> anyhow::Result<u16> {
         let remote = format!("tcp:{}", remote);
         for x in self.forward_list()? {
-            if x.serial.eq(self.serial.clone().unwrap().as_str())
-                & x.remote.eq(&remote)
-                & x.local.starts_with("tcp:")
-            {
-                u16::from_str(x.local.split("tcp:").last().unwrap()).unwrap();
+            if self.serial.as_deref() == Some(x.serial.as_str()) && x.remote == remote {
+                if let Some(port) = x.local_port() {
+                    return Ok(port);
+                }
             }
         }
-        let local_port = get_free_port()?;
-        let local = format!("tcp:{}", local_port);
-        match self.forward(&local, &remote, false) {
-            Ok(_) => Ok(local_port),
-            Err(_) => Err(anyhow!("Failed To Forward Port")),
+        self.forward_tcp(&remote)
+            .map_err(|e| anyhow!("Failed To Forward Port, Err >>> {}", e))
+    }
+
+    /// 移除一个端口转发，与 `adb forward --remove <local>` 相同。
+    pub fn forward_remove(&mut self, local: &str) -> AdbResult<()> {
+        self.open_transport(Some(&format!("killforward:{}", local)))?;
+        self.forwards.retain(|x| x != local);
+        Ok(())
+    }
+
+    /// 移除该设备的所有端口转发，与 `adb forward --remove-all` 相同。
+    pub fn forward_remove_all(&mut self) -> AdbResult<()> {
+        self.open_transport(Some("killforward-all"))?;
+        self.forwards.clear();
+        Ok(())
+    }
+
+    /// 移除通过 `forward_remote_port` 创建的端口转发，其他转发保持不变。
+    pub fn forward_cleanup(&mut self) -> AdbResult<()> {
+        for local in self.forwards.clone() {
+            self.forward_remove(&local)?;
         }
+        Ok(())
     }
 
     pub fn reverse(&mut self, remote: &str, local: &str, norebind: bool) -> anyhow::Result<()> {
@@ -991,17 +3019,38 @@ where
         Ok(())
     }
 
+    /// 连接设备上的 socket，成功后返回可以直接读写的 `TcpStream`。
+    ///
+    /// # 参数
+    /// - `network_type`: socket 类型，`Unix` 与 `LocalAbstrcat` 都按 `localabstract:` 处理。
+    /// - `address`: 端口号或 socket 名称，例如 `8080`、`minicap`。
+    pub fn create_connection<S: Display>(
+        &mut self,
+        network_type: NetworkType,
+        address: S,
+    ) -> anyhow::Result<TcpStream> {
+        let mut connection = self.open_transport(None)?;
+        let cmd = match network_type {
+            NetworkType::LocalAbstrcat | NetworkType::Unix => {
+                format!("{}{}", "localabstract:", address)
+            }
+            _ => format!("{}{}", network_type, address),
+        };
+        connection
+            .send_cmd_then_check_okay(&cmd)
+            .map_err(|e| anyhow!("Send Command >> {:#?} and Check Okay Failed {} ", &cmd, e))?;
+        Ok(connection)
+    }
+
+    /// 通过本机的 adb 可执行文件对该设备执行命令，返回标准输出，设备选择规则见 `adb_target_args`。
     pub fn adb_output(&mut self, command: &[&str]) -> anyhow::Result<String> {
+        let target = self.adb_target_args()?;
         let adb_ = adb_path()?;
         if adb_.exists() {
-            let mut cmd = std::process::Command::new(adb_.to_str().unwrap());
-            cmd.arg("-s");
-            cmd.arg(self.serial.as_ref().unwrap());
-            for x in command {
-                cmd.arg(x);
-            }
+            let mut cmd = std::process::Command::new(adb_);
+            cmd.args(&target).args(command);
             info!("{:?}", &cmd);
-            let output = cmd.output().expect("failed to execute process");
+            let output = cmd.output()?;
             return Ok(String::from_utf8_lossy(&output.stdout).parse()?);
         };
         Err(anyhow!("adb not found"))
@@ -1018,77 +3067,403 @@ where
             .context("Read Until Close Failed")?;
         Ok(resp)
     }
+
+    /// 重启设备到指定模式，使用 `reboot:<mode>` 服务，不依赖设备上的 shell。
+    ///
+    /// # 参数
+    /// - `mode`: 重启的目标模式，`RebootMode::System` 为正常重启。
+    ///
+    /// # 示例
+    /// ```no_run
+    /// use radb::beans::RebootMode;
+    /// use radb::client::AdbDevice;
+    ///
+    /// let mut device = AdbDevice::new("emulator-5554", "127.0.0.1:5037");
+    /// device.reboot(RebootMode::Bootloader).unwrap();
+    /// ```
+    pub fn reboot(&mut self, mode: RebootMode) -> AdbResult<()> {
+        let mut connection = self.open_transport(None)?;
+        let cmd = format!("reboot:{}", mode);
+        connection.send_cmd_then_check_okay(&cmd)?;
+        connection.read_until_close()?;
+        Ok(())
+    }
+
+    /// 在设备的 transport 上打开任意服务，返回已确认 `OKAY` 的连接，之后的读写由调用方按该服务的协议处理。
+    ///
+    /// transport 服务由设备上的 adbd 处理，例如 `shell:`、`exec:`、`sync:`、`jdwp`、`track-jdwp`
+    /// 以及厂商自定义的服务；`host:version` 等由 adb server 处理的 host 服务请使用
+    /// `AdbClient::exec_host_service`。
+    pub fn exec_service(&mut self, service: &str) -> AdbResult<TcpStream> {
+        let mut connection = self.open_transport(None)?;
+        connection.send_cmd_then_check_okay(service)?;
+        Ok(connection)
+    }
+
+    /// 获取当前可调试（JDWP）进程的 PID 列表，与 `adb jdwp` 相同，没有可调试进程时返回空列表。
+    ///
+    /// adbd 的 `jdwp` 服务发送列表后并不关闭连接，这里改为读取 `track-jdwp` 的第一份列表后立即返回。
+    pub fn jdwp(&mut self) -> AdbResult<Vec<u32>> {
+        let mut connection = self.exec_service("track-jdwp")?;
+        let data = connection.read_string_block()?;
+        Ok(parse_jdwp_pids(&data))
+    }
+
+    /// 通过 `track-jdwp` 持续跟踪可调试进程，列表每次变化时产出一份完整的 PID 列表，连接关闭后结束。
+    pub fn jdwp_track(&mut self) -> AdbResult<impl Iterator<Item = Vec<u32>>> {
+        let mut conn = self.exec_service("track-jdwp")?;
+        Ok(std::iter::from_fn(move || {
+            conn.read_string_block()
+                .ok()
+                .map(|data| parse_jdwp_pids(&data))
+        }))
+    }
+
+    /// 执行一个无参数的 transport 服务（如 `root:`），返回服务端的文本响应。
+    fn run_transport_service(&mut self, service: &str) -> AdbResult<String> {
+        let mut connection = self.exec_service(service)?;
+        Ok(connection.read_until_close()?)
+    }
+
+    /// 以 root 权限重启 adbd，与 `adb root` 相同。
+    ///
+    /// 成功后 adbd 会重启，当前 transport 随之断开，继续使用设备前请先调用 `wait_for`。
+    ///
+    /// # 返回值
+    /// - 成功返回服务端响应；生产版本（user build）拒绝 root 时返回 `AdbError::PermissionDenied`。
+    pub fn root(&mut self) -> AdbResult<String> {
+        let resp = self.run_transport_service("root:")?;
+        check_root_response(resp)
+    }
+
+    /// 以普通权限重启 adbd，与 `adb unroot` 相同。
+    ///
+    /// 成功后 transport 同样会断开，继续使用设备前请先调用 `wait_for`。
+    pub fn unroot(&mut self) -> AdbResult<String> {
+        self.run_transport_service("unroot:")
+    }
+
+    /// 以可写方式重新挂载系统分区，与 `adb remount` 相同，需要先 `root`。
+    pub fn remount(&mut self) -> AdbResult<String> {
+        self.run_transport_service("remount:")
+    }
     pub fn push(&mut self, local: &str, remote: &str) -> anyhow::Result<()> {
-        if self.adb_output(&["push", local, remote]).is_ok() {
-            info!("push {} to {} success", local, remote);
-            return Ok(());
+        self.push_with_progress(local, remote, |_, _| {})
+    }
+
+    /// 推送本地文件到设备，并在每个同步数据块发送后回调进度。
+    ///
+    /// # 参数
+    /// - `local`: 本地文件路径。
+    /// - `remote`: 设备上的目标路径。
+    /// - `progress`: 进度回调，参数为已传输字节数和文件总大小（取自本地文件长度）。
+    pub fn push_with_progress<F>(
+        &mut self,
+        local: &str,
+        remote: &str,
+        progress: F,
+    ) -> anyhow::Result<()>
+    where
+        F: FnMut(u64, Option<u64>),
+    {
+        let file = File::open(local).context(format!("Open Local File {} Failed", local))?;
+        let total = file.metadata()?.len();
+        self.sync_send(file, remote, DEFAULT_PUSH_MODE, Some(total), progress)?;
+        info!("push {} to {} success", local, remote);
+        Ok(())
+    }
+
+    /// 通过 sync 协议将任意 `Read` 的内容推送到设备，不依赖本地 `adb` 可执行文件。
+    ///
+    /// # 参数
+    /// - `reader`: 数据来源。
+    /// - `remote`: 设备上的目标路径。
+    /// - `mode`: 目标文件权限，例如 `0o644`；未包含文件类型位时按普通文件处理。
+    pub fn push_from_reader(
+        &mut self,
+        reader: impl Read,
+        remote: &str,
+        mode: u32,
+    ) -> AdbResult<()> {
+        self.sync_send(reader, remote, mode, None, |_, _| {})
+    }
+
+    /// 将内存中的数据推送到设备，参见 [`push_from_reader`](Self::push_from_reader)。
+    pub fn push_bytes(&mut self, data: &[u8], remote: &str, mode: u32) -> AdbResult<()> {
+        self.sync_send(data, remote, mode, Some(data.len() as u64), |_, _| {})
+    }
+
+    /// 推送本地文件并校验设备上的内容，不一致时返回 `AdbError::FileOperationFailed`。
+    ///
+    /// 优先比较 SHA-256（参见 [`remote_sha256`](Self::remote_sha256)），设备没有 `sha256sum` 时退回 MD5，
+    /// 两者都没有时只比较文件大小。
+    pub fn push_verified(&mut self, local: &Path, remote: &str) -> AdbResult<()> {
+        let local_str = local
+            .to_str()
+            .ok_or_else(|| AdbError::unknown(format!("invalid local path {}", local.display())))?;
+        self.push(local_str, remote)?;
+        let (expected, actual) =
+            if let Some(digest) = self.remote_digest(&SHA256_COMMANDS, 64, remote)? {
+                (sha256_file(local)?, digest)
+            } else if let Some(digest) = self.remote_digest(&MD5_COMMANDS, 32, remote)? {
+                (md5_file(local)?, digest)
+            } else {
+                let size = self.stat(remote)?.size as u64;
+                (local.metadata()?.len().to_string(), size.to_string())
+            };
+        if expected != actual {
+            return Err(AdbError::FileOperationFailed(format!(
+                "checksum mismatch after pushing {} to {}: local {}, device {}",
+                local.display(),
+                remote,
+                expected,
+                actual
+            )));
+        }
+        Ok(())
+    }
+
+    /// 计算设备上文件的 SHA-256，返回小写的十六进制摘要，依次尝试 `sha256sum` 与 `toybox sha256sum`。
+    ///
+    /// 设备上没有这两个命令时返回 `AdbError::Unknown`，文件不存在等其他失败返回 `AdbError::FileOperationFailed`。
+    pub fn remote_sha256(&mut self, path: &str) -> AdbResult<String> {
+        self.remote_digest(&SHA256_COMMANDS, 64, path)?
+            .ok_or_else(|| missing_tool(&["sha256sum"]))
+    }
+
+    /// 依次尝试 `commands` 计算 `path` 的摘要，所有命令都不存在时返回 `None`。
+    fn remote_digest(
+        &mut self,
+        commands: &[&[&str]],
+        hex_len: usize,
+        path: &str,
+    ) -> AdbResult<Option<String>> {
+        for command in commands {
+            let mut args = command.to_vec();
+            args.push(path);
+            let (output, code) = self.shell_with_status(&args)?;
+            if code == 0 {
+                return parse_checksum(&output, hex_len).map(Some).ok_or_else(|| {
+                    AdbError::unknown(format!(
+                        "invalid {} output: {}",
+                        args.join(" "),
+                        output.trim()
+                    ))
+                });
+            }
+            if !is_command_missing(&output, code) {
+                // 退出码非 0，一定返回错误
+                file_op_result(&args, &output, code)?;
+            }
         }
-        Err(anyhow!("push error"))
+        Ok(None)
     }
-    pub fn pull(&mut self, src: &str, dest: &PathBuf) -> anyhow::Result<usize> {
-        let mut size = 0;
-        let mut file = match File::open(dest) {
-            Ok(mut file) => file,
-            Err(_) => File::create(dest)?,
+
+    fn sync_send<R, F>(
+        &mut self,
+        mut reader: R,
+        remote: &str,
+        mode: u32,
+        total: Option<u64>,
+        mut progress: F,
+    ) -> AdbResult<()>
+    where
+        R: Read,
+        F: FnMut(u64, Option<u64>),
+    {
+        #[cfg(feature = "metrics")]
+        let _timer = Timer::start("push");
+        let mut conn = if self.has_feature("sendrecv_v2") {
+            let mut conn = self.prepare_sync(remote, "SND2")?;
+            conn.send(&send_v2_setup(mode))?;
+            conn
+        } else {
+            let path = format!("{},{}", remote, file_mode_with_type(mode));
+            self.prepare_sync(&path, "SEND")?
         };
-        self.iter_content(src)?.for_each(|content| match content {
-            Ok(content) => {
-                file.write_all(content.as_bytes()).unwrap();
-                size += content.len();
+        let mut buffer = vec![0; SYNC_DATA_MAX];
+        let mut transferred = 0;
+        loop {
+            let size = reader.read(&mut buffer)?;
+            if size == 0 {
+                break;
+            }
+            conn.write_all(&sync_packet("DATA", &buffer[..size]))?;
+            transferred += size as u64;
+            progress(transferred, total);
+        }
+        let mtime = time::SystemTime::now()
+            .duration_since(time::UNIX_EPOCH)
+            .map_err(AdbError::unknown)?
+            .as_secs() as u32;
+        conn.write_all(&sync_header("DONE", mtime))?;
+        match conn.read_string(4)?.as_str() {
+            "OKAY" => Ok(()),
+            "FAIL" => {
+                let size = conn.read_sync_length()?;
+                let error_message = conn.read_string(size)?;
+                error!(
+                    "Push {} Failed With Error Message >>> {}",
+                    remote, &error_message
+                );
+                Err(AdbError::FileOperationFailed(error_message))
             }
-            Err(_) => {}
-        });
+            other => Err(AdbError::unknown(format!(
+                "push error, unexpected response {:#?}",
+                other
+            ))),
+        }
+    }
+
+    pub fn pull(&mut self, src: &str, dest: &PathBuf) -> anyhow::Result<usize> {
+        self.pull_with_progress(src, dest, |_, _| {})
+    }
+
+    /// 从设备拉取文件到本地，并在每个同步数据块写入后回调进度。
+    ///
+    /// # 参数
+    /// - `src`: 设备上的文件路径。
+    /// - `dest`: 本地目标路径。
+    /// - `progress`: 进度回调，参数为已传输字节数和文件总大小（取自 `stat`）。
+    pub fn pull_with_progress<F>(
+        &mut self,
+        src: &str,
+        dest: &PathBuf,
+        mut progress: F,
+    ) -> anyhow::Result<usize>
+    where
+        F: FnMut(u64, Option<u64>),
+    {
+        #[cfg(feature = "metrics")]
+        let _timer = Timer::start("pull");
+        let total = self.stat(src).ok().map(|info| info.size as u64);
+        let mut size = 0;
+        let mut file = File::create(dest)?;
+        for content in self.iter_content(src)? {
+            let content = content?;
+            file.write_all(&content)?;
+            size += content.len();
+            progress(size as u64, total);
+        }
         Ok(size)
     }
 
-    pub fn iter_directory(&mut self, path: &str) -> anyhow::Result<impl Iterator<Item = FileInfo>> {
-        let mut conn = self.prepare_sync(path, "LIST")?;
+    /// 列出目录内容，遇到 `DONE` 结束；读取出错或数据包不完整时产出一个错误后结束。
+    pub fn iter_directory(
+        &mut self,
+        path: &str,
+    ) -> anyhow::Result<impl Iterator<Item = anyhow::Result<FileInfo>>> {
+        let v2 = self.has_feature("ls_v2");
+        let mut conn = self.prepare_sync(path, if v2 { "LIS2" } else { "LIST" })?;
+        let mut finished = false;
         Ok(std::iter::from_fn(move || {
-            let data = conn.read_string(4).ok()?;
-            return if data.eq("DONE") {
-                None
-            } else {
-                let mut current_data = conn.recv(16).ok()?;
-                let name_length_bytes = &current_data[12..=15];
-                let name_length = u32::from_le_bytes(name_length_bytes.try_into().unwrap());
-                let path = conn.read_string(name_length as usize).ok()?;
-                Some(parse_file_info(current_data, path).ok()?)
-            };
+            if finished {
+                return None;
+            }
+            let entry = read_dent(&mut conn, v2);
+            if !matches!(entry, Ok(Some(_))) {
+                finished = true;
+            }
+            entry.transpose()
         }))
     }
 
+    /// 路径是否存在。sync STAT 对不存在的路径返回全 0，因此以 mode 是否为 0 判断。
     pub fn exists(&mut self, path: &str) -> anyhow::Result<bool> {
         let file_info = self.stat(path)?;
-        if file_info.mtime != 0 {
-            Ok(true)
-        } else {
-            Ok(false)
-        }
+        Ok(file_info.mode != 0)
+    }
+
+    /// 反复检查路径是否存在，直到其存在状态等于 `exists`，用于等待设备端进程生成（或删除）文件。
+    ///
+    /// # 参数
+    /// - `exists`: `true` 等待文件出现，`false` 等待文件消失。
+    /// - `timeout`: 最长等待时间，超时返回 `AdbError::Timeout`。
+    /// - `poll`: 两次检查之间的间隔，第一次检查立即执行。
+    pub fn wait_for_file(
+        &mut self,
+        path: &str,
+        exists: bool,
+        timeout: Duration,
+        poll: Duration,
+    ) -> AdbResult<()> {
+        poll_until(timeout, poll, || {
+            Ok((self.exists(path)? == exists).then_some(()))
+        })
+        .map_err(|e| match e {
+            AdbError::Timeout(_) => file_wait_timeout(path, exists, timeout),
+            e => e,
+        })
+    }
+
+    /// 路径是否为目录，不存在时返回 `false`。
+    pub fn is_dir(&mut self, path: &str) -> anyhow::Result<bool> {
+        Ok(self.stat(path)?.is_dir())
+    }
+
+    /// 路径是否为普通文件，不存在时返回 `false`。
+    pub fn is_file(&mut self, path: &str) -> anyhow::Result<bool> {
+        Ok(self.stat(path)?.is_file())
     }
 
     pub fn stat(&mut self, path: &str) -> anyhow::Result<FileInfo> {
+        #[cfg(feature = "metrics")]
+        let _timer = Timer::start("stat");
+        if self.has_feature("stat_v2") {
+            let mut conn = self.prepare_sync(path, "STA2")?;
+            return match conn.read_string(4)?.as_str() {
+                "STA2" => parse_file_info_v2(&conn.recv_exact(STAT_V2_LEN)?, path),
+                other => Err(anyhow!("unexpected STA2 response {:#?}", other)),
+            };
+        }
         let mut conn = self.prepare_sync(path, "STAT")?;
         let data = conn.read_string(4)?;
         if data.eq("STAT") {
             let current_data = conn.recv(12)?;
-            return Ok(parse_file_info(current_data, path)?);
+            return parse_file_info(current_data, path);
         };
         Err(anyhow!("stat error"))
     }
 
     pub fn list(&mut self, path: &str) -> anyhow::Result<Vec<FileInfo>> {
-        Ok(self
-            .iter_directory(path)
+        #[cfg(feature = "metrics")]
+        let _timer = Timer::start("list");
+        self.iter_directory(path)
             .context("Iter Directory Error")?
-            .collect::<Vec<FileInfo>>())
+            .collect::<anyhow::Result<Vec<FileInfo>>>()
     }
 
     pub fn read_text(&mut self, path: &str) -> anyhow::Result<String> {
-        let data = self
-            .iter_content(path)?
-            .map(|x| x.unwrap_or_else(|_| "".to_string()))
-            .collect::<Vec<String>>();
-        Ok(data.join(""))
+        let data = self.pull_bytes(path)?;
+        Ok(String::from_utf8_lossy(&data).to_string())
+    }
+
+    /// 与 [`read_text`](Self::read_text) 相同，和异步版的同名方法对应。
+    pub fn read_to_string(&mut self, path: &str) -> AdbResult<String> {
+        let data = self.pull_bytes(path)?;
+        Ok(String::from_utf8_lossy(&data).into_owned())
+    }
+
+    /// 读取设备上的整个文件，与 [`pull_bytes`](Self::pull_bytes) 相同。
+    pub fn read_to_bytes(&mut self, path: &str) -> AdbResult<Vec<u8>> {
+        self.pull_bytes(path)
+    }
+
+    /// 将设备上的文件内容直接读取到内存中，不经过本地文件系统。
+    ///
+    /// # 参数
+    /// - `remote`: 设备上的文件路径。
+    ///
+    /// # 返回值
+    /// - 成功返回文件的全部字节内容，失败返回错误。
+    pub fn pull_bytes(&mut self, remote: &str) -> AdbResult<Vec<u8>> {
+        #[cfg(feature = "metrics")]
+        let _timer = Timer::start("pull");
+        let mut content = vec![];
+        for data in self.iter_content(remote)? {
+            content.extend_from_slice(&data?);
+        }
+        Ok(content)
     }
 
     pub fn prepare_sync(&mut self, path: &str, command: &str) -> anyhow::Result<TcpStream> {
@@ -1096,160 +3471,601 @@ where
         let mut conn = self.open_transport(None)?;
         conn.send_cmd_then_check_okay("sync:")
             .context("Start Sync Error")?;
-        let path_len = path.as_bytes().len() as u32;
-        let mut total_byte = vec![];
-        total_byte.extend_from_slice(command.as_bytes());
-        total_byte.extend_from_slice(&path_len.to_le_bytes());
-        total_byte.extend_from_slice(path.as_bytes());
-        conn.send(&total_byte)?;
+        conn.send(&sync_packet(command, path.as_bytes()))?;
         Ok(conn)
     }
 
     pub fn iter_content(
         &mut self,
         path: &str,
-    ) -> anyhow::Result<impl Iterator<Item = anyhow::Result<String>>> {
-        if let Ok(mut connection) = self.prepare_sync(path, "RECV") {
-            let mut done = false;
-            return Ok(std::iter::from_fn(move || {
-                if done {
-                    return None;
-                }
-                return match connection.read_string(4) {
-                    Err(_) => None,
-                    Ok(data) => match data.as_str() {
-                        "FAIL" => match connection.recv(4) {
-                            Err(_) => None,
-                            Ok(data) => {
-                                let str_size = u32::from_le_bytes(data.try_into().ok()?) as usize;
-                                let error_message = connection.read_string(str_size).ok()?;
-                                error!("Sync Error With Error Message >>> {:#?}", error_message);
-                                None
-                            }
-                        },
-                        "DONE" => {
-                            done = true;
-                            None
-                        }
-                        "DATA" => match connection.recv(4) {
-                            Ok(size) => {
-                                let str_size = u32::from_le_bytes(size.try_into().ok()?) as usize;
-                                match connection.read_string(str_size) {
-                                    Ok(data) => Some(Ok(data)),
-                                    Err(_) => None,
-                                }
-                            }
-                            Err(_) => None,
-                        },
-                        _ => None,
-                    },
-                };
-            }));
+    ) -> anyhow::Result<impl Iterator<Item = anyhow::Result<Vec<u8>>>> {
+        let mut connection = if self.has_feature("sendrecv_v2") {
+            let mut connection = self
+                .prepare_sync(path, "RCV2")
+                .context("iter_content error")?;
+            connection.send(&sync_header("RCV2", 0))?;
+            connection
+        } else {
+            self.prepare_sync(path, "RECV")
+                .context("iter_content error")?
+        };
+        let mut done = false;
+        Ok(std::iter::from_fn(move || {
+            if done {
+                return None;
+            }
+            let chunk = match connection.read_string(4) {
+                Err(e) => Err(e),
+                Ok(data) => match data.as_str() {
+                    "DATA" => connection.read_sync_length().and_then(|size| {
+                        let mut buffer = vec![0; size];
+                        connection.read_exact(&mut buffer)?;
+                        Ok(buffer)
+                    }),
+                    "DONE" => {
+                        done = true;
+                        return None;
+                    }
+                    "FAIL" => connection.read_sync_length().and_then(|size| {
+                        let error_message = connection.read_string(size)?;
+                        error!("Sync Error With Error Message >>> {:#?}", error_message);
+                        Err(AdbError::FileOperationFailed(error_message).into())
+                    }),
+                    other => Err(anyhow!("Unexpected Sync Response {:#?}", other)),
+                },
+            };
+            if chunk.is_err() {
+                done = true;
+            }
+            Some(chunk)
+        }))
+    }
+
+    /// 通过 `exec:` 服务执行命令并返回原始 stdout，不经过 shell 的换行转换，适合二进制输出。
+    ///
+    /// # 参数
+    /// - `cmd`: 要执行的命令，参数列表会按 `list2cmdline` 转义。
+    pub fn exec_out<T2: Into<AdbCommand>>(&mut self, cmd: T2) -> AdbResult<Vec<u8>> {
+        let service = format!("exec:{}", Self::command_line(&cmd.into()));
+        let mut conn = self.open_transport(None)?;
+        conn.send_cmd_then_check_okay(&service)?;
+        let mut output = vec![];
+        conn.read_to_end(&mut output)?;
+        Ok(output)
+    }
+
+    /// 通过 shell v2 协议执行命令，分别返回 stdout、stderr 和退出码。
+    ///
+    /// 设备不支持 `shell_v2` 时退回普通 `shell:`，输出合并在 `stdout` 中，`exit_code` 为 `None`。
+    ///
+    /// # 参数
+    /// - `cmd`: 要执行的命令，参数列表会按 `list2cmdline` 转义。
+    pub fn shell_v2<T2: Into<AdbCommand>>(&mut self, cmd: T2) -> AdbResult<ShellResult> {
+        let cmdline = Self::command_line(&cmd.into());
+        let v2 = self.has_feature("shell_v2");
+        let service = if v2 {
+            format!("shell,v2,raw:{}", cmdline)
+        } else {
+            format!("shell:{}", cmdline)
+        };
+        let mut conn = self.open_transport(None)?;
+        conn.send_cmd_then_check_okay(&service)?;
+        let mut output = vec![];
+        conn.read_to_end(&mut output)?;
+        if v2 {
+            ShellResult::parse_v2(&output)
+        } else {
+            Ok(ShellResult {
+                stdout: output,
+                ..Default::default()
+            })
         }
-        Err(anyhow!("iter_content error"))
     }
 
+    /// 执行 shell 命令，把 `stdin` 写入命令的标准输入后半关闭写端，再读取输出直到连接关闭。
+    ///
+    /// 适合 `sh`、`sqlite3`、`cmd` 等从标准输入读取内容的命令。
+    ///
+    /// # 参数
+    /// - `cmd`: 要执行的命令，参数列表会按 `list2cmdline` 转义。
+    /// - `stdin`: 写入命令标准输入的数据。
+    pub fn shell_with_input<T2: Into<AdbCommand>>(
+        &mut self,
+        cmd: T2,
+        stdin: &[u8],
+    ) -> AdbResult<String> {
+        let mut conn = self.shell_stream_raw(&Self::command_line(&cmd.into()))?;
+        conn.write_all(stdin)?;
+        conn.shutdown(std::net::Shutdown::Write)?;
+        Ok(conn.read_until_close()?)
+    }
+
+    /// 截图，优先使用 `exec-out screencap -p`，设备不支持 `exec:` 时回退到 `screenshot_via_file`。
     pub fn screenshot(&mut self) -> anyhow::Result<RgbImage> {
-        let src = "/sdcard/screen.png";
-        self.shell(&["screencap", "-p", src])?;
-        let tmpdir = tempfile::tempdir().expect("Failed to create temporary directory");
-        let target_path = tmpdir.path().join("tmp001.png");
-        info!("Pull Image To {:#?}", &target_path);
-        self.pull(src, &target_path)?;
-        self.shell(&["rm", src])?;
+        match self.exec_out(["screencap", "-p"]) {
+            Ok(data) => match image::load_from_memory(&data) {
+                Ok(image) => return Ok(image.into_rgb8()),
+                Err(e) => info!("Decode exec-out Screenshot Failed {}, Fallback To File", e),
+            },
+            Err(e) => info!("exec-out Screenshot Failed {}, Fallback To File", e),
+        }
+        self.screenshot_via_file()
+    }
+
+    /// 在后台开始录屏，返回的句柄可用于停止录制并拉取视频。
+    ///
+    /// # 参数
+    /// - `remote_tmp`: 设备上的临时 mp4 路径，自动分段时后续分段会在扩展名前加序号
+    /// - `opts`: 录制参数，`screenrecord` 单段最长 180 秒，需要更长时设置 `auto_restart`
+    ///
+    /// # 返回值
+    /// 录制句柄，调用 [`ScreenRecordHandle::stop`] 返回本地文件列表
+    pub fn screenrecord(
+        &mut self,
+        remote_tmp: &str,
+        opts: ScreenRecordOptions,
+    ) -> AdbResult<ScreenRecordHandle<T>>
+    where
+        T: Send + 'static,
+    {
+        Ok(ScreenRecordHandle::start(self, remote_tmp, opts))
+    }
+
+    /// 截图并按当前屏幕旋转角度转为正向，参见 [`rotate_upright`]；[`screenshot`](Self::screenshot) 保持原始截图。
+    ///
+    /// 较新的系统 `screencap` 已按屏幕方向输出，旋转 90/270 度时若截图的宽高已与 `wm size`
+    /// （自然方向）相反，则不再旋转。
+    pub fn screenshot_rotated(&mut self) -> anyhow::Result<RgbImage> {
+        let image = self.screenshot()?;
+        let rotation = self.display_rotation()?;
+        if rotation == 90 || rotation == 270 {
+            let (width, height) = self.screen_size()?;
+            if width != height && image.dimensions() == (height, width) {
+                return Ok(image);
+            }
+        }
+        Ok(rotate_upright(image, rotation))
+    }
+
+    /// 获取主屏当前的旋转角度（0、90、180、270），解析自 `dumpsys input`。
+    pub fn display_rotation(&mut self) -> AdbResult<u32> {
+        let output = self.dumpsys("input")?;
+        parse_display_rotation(&output)
+            .ok_or_else(|| AdbError::unknown("no display orientation found in dumpsys input"))
+    }
+
+    /// 获取未经 PNG 编码的原始帧，适合逐像素比较。
+    pub fn screencap_raw(&mut self) -> AdbResult<ScreenCapture> {
+        let data = self.exec_out("screencap")?;
+        ScreenCapture::parse(&data)
+    }
 
-        let image = ImageReader::open(&target_path)?.decode()?;
-        fs::remove_file(target_path).expect("Failed to remove file");
+    /// 先在设备上生成 PNG 文件再拉取的截图方式，兼容不支持 `exec:` 的旧设备。
+    pub fn screenshot_via_file(&mut self) -> anyhow::Result<RgbImage> {
+        let data = self.screencap_png_via_file()?;
+        let image = image::load_from_memory(&data)?;
         Ok(image.into_rgb8())
     }
 
-    pub fn keyevent(&mut self, keycode: &str) -> anyhow::Result<String> {
-        self.shell(&["input", "keyevent", keycode])
+    /// 获取 PNG 编码的截图数据，不解码为图片；`exec:` 不可用或输出不是 PNG 时回退到先生成文件再拉取。
+    pub fn screenshot_png_bytes(&mut self) -> AdbResult<Vec<u8>> {
+        match self.exec_out(["screencap", "-p"]) {
+            Ok(data) if data.starts_with(PNG_SIGNATURE) => return Ok(data),
+            Ok(_) => info!("exec-out Screenshot Is Not PNG, Fallback To File"),
+            Err(e) => info!("exec-out Screenshot Failed {}, Fallback To File", e),
+        }
+        self.screencap_png_via_file()
     }
 
-    pub fn switch_screen(&mut self, status: bool) -> anyhow::Result<String> {
-        if status == true {
-            self.keyevent("224")
-        } else {
-            self.keyevent("223")
-        }
-    }
-
-    pub fn install(&mut self, path_or_url: &str) -> anyhow::Result<(), anyhow::Error> {
-        let target_path =
-            if path_or_url.starts_with("http://") || path_or_url.starts_with("https://") {
-                let mut resp = reqwest::blocking::get(path_or_url)?;
-                let mut buffer = Vec::new();
-                resp.read_to_end(&mut buffer)?;
-                let temp_dir = tempfile::tempdir()?.path().join("tmp001.apk");
-                let mut fd = File::create(&temp_dir)?;
-                fd.write_all(&buffer)?;
-                let target_path = temp_dir.to_str().ok_or(anyhow!("fail to get path"))?;
-                info!(
-                    "Save Http/s file to  <{:#?}> => dst: <{:#?}>",
-                    &path_or_url, &target_path
-                );
-                target_path.to_string()
-            } else {
-                path_or_url.to_string()
-            };
-        let dst = format!(
-            "/data/local/tmp/tmp-{}.apk",
-            (time::SystemTime::now()
-                .duration_since(time::UNIX_EPOCH)?
-                .as_millis())
-        );
-        info!("Pushing src: <{:#?}> => dst: <{:#?}> ", &path_or_url, &dst);
-        self.push(&target_path, &dst)?;
-        let install_resp = self.install_remote(&dst, true);
-        info!("Install Apk Result {:#?}", &install_resp);
-        if let Ok(resp) = install_resp {
-            info!("Install Apk Successed >> <{:#?}>", &resp);
+    /// 截图并保存到本地文件，格式由扩展名决定：`.png` 或 `.jpg`/`.jpeg`，其他扩展名在截图前返回错误。
+    ///
+    /// PNG 直接写入设备返回的数据，不重新编码。
+    pub fn screenshot_to(&mut self, path: &Path) -> AdbResult<()> {
+        let format = screenshot_format(path)?;
+        if format == ImageFormat::Png {
+            let data = self.screenshot_png_bytes()?;
+            std::fs::write(path, data)?;
             return Ok(());
         }
-        Err(anyhow!("fail to install apk"))
+        let image = self.screenshot()?;
+        image
+            .save_with_format(path, format)
+            .map_err(|e| AdbError::unknown(format!("save screenshot to {}: {}", path.display(), e)))
     }
-    pub fn install_remote(&mut self, path: &str, clean: bool) -> anyhow::Result<String> {
-        let args = ["pm", "install", "-r", "-t", path];
-        let output = self.shell(&args)?;
-        if !output.contains("Success") {
-            return Err(anyhow!("fail to install"));
-        };
-        if clean {
-            self.shell(&["rm", path])?;
-        }
-        Ok(output)
+
+    fn screencap_png_via_file(&mut self) -> AdbResult<Vec<u8>> {
+        let src = "/sdcard/screen.png";
+        self.shell(&["screencap", "-p", src])?;
+        let data = self.pull_bytes(src)?;
+        self.shell(&["rm", src])?;
+        Ok(data)
     }
 
-    pub fn switch_airplane_mode(&mut self, status: bool) -> anyhow::Result<String> {
-        let mut base_setting_cmd = vec!["settings", "put", "global", "airplane_mode_on"];
-        let mut base_am_cmd = vec![
-            "am",
-            "broadcast",
-            "-a",
-            "android.intent.action.AIRPLANE_MODE",
-            "--ez",
-            "state",
-        ];
-        if status == true {
-            base_setting_cmd.push("1");
-            base_am_cmd.push("true");
-        } else {
-            base_setting_cmd.push("0");
-            base_am_cmd.push("false");
+    /// 通过 `uiautomator dump` 获取当前界面的控件层级 XML。
+    pub fn ui_hierarchy(&mut self) -> AdbResult<String> {
+        let output = self.shell(&["uiautomator", "dump", UI_DUMP_PATH])?;
+        if output.contains("ERROR") {
+            return Err(AdbError::unknown(format!(
+                "uiautomator dump failed: {}",
+                output.trim()
+            )));
         }
-        self.shell(&base_setting_cmd)?;
-        self.shell(&base_am_cmd)
+        let xml = self.read_text(UI_DUMP_PATH)?;
+        self.shell(&["rm", UI_DUMP_PATH])?;
+        Ok(xml)
     }
 
-    pub fn switch_wifi(&mut self, status: bool) -> anyhow::Result<String> {
-        let mut args = vec!["svc", "wifi"];
-        if status == true {
-            args.push("enable");
-        } else {
-            args.push("disable");
-        };
-        self.shell(&args)
+    /// 获取当前界面的控件列表，由 [`ui_hierarchy`](Self::ui_hierarchy) 解析而来。
+    pub fn ui_elements(&mut self) -> AdbResult<Vec<UiElement>> {
+        let xml = self.ui_hierarchy()?;
+        Ok(parse_ui_elements(&xml))
+    }
+
+    /// 查找所有满足条件的控件。
+    pub fn find_elements(&mut self, by: &By) -> AdbResult<Vec<UiElement>> {
+        let elements = self.ui_elements()?;
+        Ok(elements.into_iter().filter(|e| by.matches(e)).collect())
+    }
+
+    /// 查找第一个满足条件的控件，返回其中心点坐标；未找到时返回 `AdbError::ElementNotFound`。
+    pub fn find_element(&mut self, by: &By) -> AdbResult<(i32, i32)> {
+        self.find_elements(by)?
+            .first()
+            .map(|e| e.center())
+            .ok_or_else(|| AdbError::ElementNotFound(by.to_string()))
+    }
+
+    /// 查找第一个满足条件的控件并点击其中心点。
+    pub fn tap_element(&mut self, by: &By) -> AdbResult<(i32, i32)> {
+        let (x, y) = self.find_element(by)?;
+        self.click(x, y)?;
+        Ok((x, y))
+    }
+
+    /// 反复获取控件层级，直到出现满足条件的控件。
+    ///
+    /// # 参数
+    /// - `by`: 查找条件。
+    /// - `timeout`: 最长等待时间，超时返回 `AdbError::Timeout`。
+    /// - `poll`: 两次获取之间的间隔，第一次获取立即执行。
+    pub fn wait_for_element(
+        &mut self,
+        by: &By,
+        timeout: Duration,
+        poll: Duration,
+    ) -> AdbResult<UiElement> {
+        poll_until(timeout, poll, || {
+            Ok(self.find_elements(by)?.into_iter().next())
+        })
+    }
+
+    pub fn keyevent(&mut self, keycode: &str) -> anyhow::Result<String> {
+        self.shell(&["input", "keyevent", keycode])
+    }
+
+    pub fn switch_screen(&mut self, status: bool) -> anyhow::Result<String> {
+        if status {
+            self.keyevent(&KeyCode::Wakeup.to_string())
+        } else {
+            self.keyevent(&KeyCode::Sleep.to_string())
+        }
+    }
+
+    /// 在一条 `input keyevent` 命令中依次发送多个按键，按键码可以是数字或 `KEYCODE_*` 名称。
+    ///
+    /// 按键是依次按下并抬起的，不会同时按住。
+    pub fn key_combo(&mut self, codes: &[&str]) -> AdbResult<String> {
+        let args = keyevent_args(codes);
+        Ok(self.shell(&args.iter().map(|s| s.as_str()).collect::<Vec<_>>())?)
+    }
+
+    /// 同 `key_combo`，使用 [`KeyCode`] 指定按键。
+    pub fn press_keys(&mut self, codes: &[KeyCode]) -> AdbResult<String> {
+        let args = keyevent_args(codes);
+        Ok(self.shell(&args.iter().map(|s| s.as_str()).collect::<Vec<_>>())?)
+    }
+
+    /// 推送本地 APK 到设备临时目录后通过 `pm install -r -t` 安装，安装完成后删除临时文件。
+    pub fn install(&mut self, path: impl AsRef<Path>) -> anyhow::Result<()> {
+        Ok(self.install_with(path, &InstallOptions::default())?)
+    }
+
+    /// 按 `opts` 安装本地 APK。
+    ///
+    /// # 参数
+    /// - `path`: 本地 APK 路径，远程地址请使用 [`install_url`](Self::install_url)
+    /// - `opts`: `pm install` 的选项，例如 `-g` 授予运行时权限、`-d` 允许降级
+    ///
+    /// # 返回值
+    /// `pm` 拒绝安装时返回 `AdbError::ApplicationError`，携带 `INSTALL_FAILED_*` 等失败原因
+    pub fn install_with(&mut self, path: impl AsRef<Path>, opts: &InstallOptions) -> AdbResult<()> {
+        let path = path.as_ref();
+        let local = path
+            .to_str()
+            .ok_or_else(|| AdbError::unknown("fail to get path"))?;
+        let dst = remote_apk_path()?;
+        info!("Pushing src: <{:#?}> => dst: <{:#?}> ", path, &dst);
+        self.push(local, &dst)?;
+        let install_resp = self.install_remote_with(&dst, true, opts);
+        info!("Install Apk Result {:#?}", &install_resp);
+        install_resp.map(|_| ())
+    }
+
+    /// 下载 http/https 地址的 APK 到本地临时目录后安装，参见 [`install`](Self::install)。
+    pub fn install_url(&mut self, url: &str) -> anyhow::Result<()> {
+        Ok(self.install_url_with(url, &InstallOptions::default())?)
+    }
+
+    /// 按 `opts` 安装 http/https 地址的 APK。
+    ///
+    /// `opts.stream_download` 为 `true` 且响应带有 `Content-Length` 时，响应体直接作为
+    /// [`push_from_reader`](Self::push_from_reader) 的数据来源写入设备，不在本地落临时文件；
+    /// 长度未知（例如 chunked 响应）时仍先下载到本地临时目录。
+    pub fn install_url_with(&mut self, url: &str, opts: &InstallOptions) -> AdbResult<()> {
+        let mut resp = reqwest::blocking::get(url)
+            .and_then(|resp| resp.error_for_status())
+            .map_err(AdbError::unknown)?;
+        if opts.stream_download && resp.content_length().is_some() {
+            let dst = remote_apk_path()?;
+            info!("Streaming <{:#?}> => dst: <{:#?}>", url, &dst);
+            if let Err(e) = self.push_from_reader(resp, &dst, DEFAULT_PUSH_MODE) {
+                let _ = self.shell(&["rm", &dst]);
+                return Err(e);
+            }
+            return self.install_remote_with(&dst, true, opts).map(|_| ());
+        }
+        let mut buffer = Vec::new();
+        resp.read_to_end(&mut buffer)?;
+        // 临时目录在安装完成前必须保持存在，drop 时才删除
+        let temp_dir = tempfile::tempdir()?;
+        let target_path = temp_dir.path().join("tmp001.apk");
+        std::fs::write(&target_path, &buffer)?;
+        info!(
+            "Save Http/s file to  <{:#?}> => dst: <{:#?}>",
+            url, &target_path
+        );
+        self.install_with(&target_path, opts)
+    }
+
+    /// 通过 `pm install-create` / `install-write` / `install-commit` 会话一起安装 base 与 split APK，
+    /// 数据经 `exec:` 直接写入安装会话，不在设备上落临时文件。
+    ///
+    /// # 参数
+    /// - `apks`: 本地 APK 路径，通常为 `base.apk` 加若干 `split_*.apk`
+    /// - `opts`: 安装会话的选项，与 [`install_with`](Self::install_with) 相同
+    ///
+    /// # 返回值
+    /// 任一 split 写入失败时放弃会话并返回该错误；提交被拒绝时返回 `AdbError::ApplicationError`，
+    /// 携带 `INSTALL_FAILED_*` 等失败原因
+    pub fn install_multiple<P>(&mut self, apks: &[P], opts: &InstallOptions) -> AdbResult<()>
+    where
+        P: AsRef<Path>,
+    {
+        self.install_multiple_with_progress(apks, opts, |_, _, _| {})
+            .map(|_| ())
+    }
+
+    /// 与 [`install_multiple`](Self::install_multiple) 相同，并在每个 split 写入数据后回调进度。
+    ///
+    /// # 参数
+    /// - `progress`: 进度回调，参数为 split 序号、该 split 已写入字节数和该 split 总大小
+    ///
+    /// # 返回值
+    /// `pm install-commit` 的输出
+    pub fn install_multiple_with_progress<P, F>(
+        &mut self,
+        apks: &[P],
+        opts: &InstallOptions,
+        mut progress: F,
+    ) -> AdbResult<String>
+    where
+        P: AsRef<Path>,
+        F: FnMut(usize, u64, u64),
+    {
+        let sizes = apks
+            .iter()
+            .map(|apk| std::fs::metadata(apk).map(|meta| meta.len()))
+            .collect::<Result<Vec<_>, _>>()?;
+        let total = sizes.iter().sum::<u64>().to_string();
+        let options = opts.to_args();
+        let mut args = vec!["pm", "install-create"];
+        args.extend(options.iter().map(|s| s.as_str()));
+        args.extend(["-S", &total]);
+        let output = self.shell(&args)?;
+        let session = parse_install_session(&output)?;
+        for (index, (apk, size)) in apks.iter().zip(sizes).enumerate() {
+            if let Err(e) = self.install_write(&session, index, apk.as_ref(), size, &mut progress) {
+                let _ = self.shell(&["pm", "install-abandon", &session]);
+                return Err(e);
+            }
+        }
+        let output = self.shell(&["pm", "install-commit", &session])?;
+        check_pm_output(output)
+    }
+
+    fn install_write<F>(
+        &mut self,
+        session: &str,
+        index: usize,
+        apk: &Path,
+        size: u64,
+        progress: &mut F,
+    ) -> AdbResult<()>
+    where
+        F: FnMut(usize, u64, u64),
+    {
+        let name = install_split_name(index, apk);
+        let size_arg = size.to_string();
+        let command = Self::command_line(&AdbCommand::from(
+            &["pm", "install-write", "-S", &size_arg, session, &name, "-"][..],
+        ));
+        let mut file = File::open(apk)?;
+        let mut conn = self.open_transport(None)?;
+        conn.send_cmd_then_check_okay(&format!("exec:{}", command))?;
+        let mut buffer = vec![0; SYNC_DATA_MAX];
+        let mut written = 0;
+        loop {
+            let read = file.read(&mut buffer)?;
+            if read == 0 {
+                break;
+            }
+            conn.write_all(&buffer[..read])?;
+            written += read as u64;
+            progress(index, written, size);
+        }
+        let mut output = String::new();
+        conn.read_to_string(&mut output)?;
+        check_install_output(&command, output)?;
+        Ok(())
+    }
+
+    pub fn install_remote(&mut self, path: &str, clean: bool) -> anyhow::Result<String> {
+        Ok(self.install_remote_with(path, clean, &InstallOptions::default())?)
+    }
+
+    /// 按 `opts` 安装设备上已有的 APK，`clean` 为真时无论成功与否都删除该文件。
+    pub fn install_remote_with(
+        &mut self,
+        path: &str,
+        clean: bool,
+        opts: &InstallOptions,
+    ) -> AdbResult<String> {
+        let options = opts.to_args();
+        let mut args = vec!["pm", "install"];
+        args.extend(options.iter().map(|s| s.as_str()));
+        args.push(path);
+        // 先执行清理再返回安装的错误，安装命令本身失败时也不会遗留 APK
+        let output = self.shell(&args);
+        let removed = match clean {
+            true => self.shell(&["rm", path]).map(|_| ()),
+            false => Ok(()),
+        };
+        let output = output?;
+        removed?;
+        check_pm_output(output)
+    }
+
+    /// 读取设置项，对应 `settings get <ns> <key>`，键未设置（输出 `null`）时返回 `None`。
+    pub fn settings_get(&mut self, ns: SettingsNamespace, key: &str) -> AdbResult<Option<String>> {
+        let output = self.shell_checked(&["settings", "get", &ns.to_string(), key])?;
+        Ok(parse_settings_value(&output))
+    }
+
+    /// 写入设置项，对应 `settings put <ns> <key> <value>`。
+    pub fn settings_put(&mut self, ns: SettingsNamespace, key: &str, value: &str) -> AdbResult<()> {
+        self.shell_checked(&["settings", "put", &ns.to_string(), key, value])?;
+        Ok(())
+    }
+
+    /// 删除设置项，对应 `settings delete <ns> <key>`。
+    pub fn settings_delete(&mut self, ns: SettingsNamespace, key: &str) -> AdbResult<()> {
+        self.shell_checked(&["settings", "delete", &ns.to_string(), key])?;
+        Ok(())
+    }
+
+    /// 开关飞行模式：写入 `airplane_mode_on` 后广播 `AIRPLANE_MODE`，返回广播命令的输出。
+    pub fn switch_airplane_mode(&mut self, status: bool) -> anyhow::Result<String> {
+        let value = if status { "1" } else { "0" };
+        self.settings_put(SettingsNamespace::Global, "airplane_mode_on", value)?;
+        self.shell(&[
+            "am",
+            "broadcast",
+            "-a",
+            "android.intent.action.AIRPLANE_MODE",
+            "--ez",
+            "state",
+            &status.to_string(),
+        ])
+    }
+
+    pub fn switch_wifi(&mut self, status: bool) -> anyhow::Result<String> {
+        self.switch_service("wifi", "wifi_on", status)
+    }
+
+    pub fn switch_bluetooth(&mut self, status: bool) -> anyhow::Result<String> {
+        self.switch_service("bluetooth", "bluetooth_on", status)
+    }
+
+    pub fn switch_mobile_data(&mut self, status: bool) -> anyhow::Result<String> {
+        self.switch_service("data", "mobile_data", status)
+    }
+
+    /// 通过 `svc <service> enable|disable` 开关服务，返回 `svc` 的输出。
+    ///
+    /// `svc` 需要 root 或系统不支持该服务时会输出错误信息或以非 0 退出，
+    /// 此时退回到直接写入 `settings global <key>`。
+    fn switch_service(&mut self, service: &str, key: &str, status: bool) -> anyhow::Result<String> {
+        let action = if status { "enable" } else { "disable" };
+        let (output, code) = self.shell_with_status(&["svc", service, action])?;
+        if code != 0 || !output.trim().is_empty() {
+            let value = if status { "1" } else { "0" };
+            self.settings_put(SettingsNamespace::Global, key, value)?;
+        }
+        Ok(output)
+    }
+
+    /// 设置屏幕亮度（0-255），对应 `settings put system screen_brightness`，返回实际写入的值。
+    ///
+    /// 开启自动亮度时系统会覆盖该值，需要固定亮度时先调用 `set_auto_brightness(false)`。
+    pub fn set_brightness(&mut self, level: u8) -> AdbResult<u8> {
+        self.settings_put(
+            SettingsNamespace::System,
+            "screen_brightness",
+            &level.to_string(),
+        )?;
+        Ok(level)
+    }
+
+    /// 读取屏幕亮度，解析自 `settings get system screen_brightness`，超过 255 的值按 255 返回。
+    pub fn get_brightness(&mut self) -> AdbResult<u8> {
+        let value = self
+            .settings_get(SettingsNamespace::System, "screen_brightness")?
+            .unwrap_or_default();
+        let level = value
+            .trim()
+            .parse::<u32>()
+            .map_err(|_| unexpected_output("settings get system screen_brightness", &value))?;
+        Ok(level.min(u8::MAX as u32) as u8)
+    }
+
+    /// 开关自动亮度，对应 `settings put system screen_brightness_mode 1|0`。
+    pub fn set_auto_brightness(&mut self, enabled: bool) -> AdbResult<()> {
+        let mode = if enabled { "1" } else { "0" };
+        self.settings_put(SettingsNamespace::System, "screen_brightness_mode", mode)
+    }
+
+    /// 设置音量，对应 `media volume --stream <n> --set <level>`，返回实际设置的值。
+    ///
+    /// 先通过 `--get` 查询该音量类型的取值范围，`level` 超出范围时取最近的边界值；
+    /// 查询不到范围时按原值设置。
+    pub fn set_volume(&mut self, stream: VolumeStream, level: u8) -> AdbResult<u8> {
+        let args = media_volume_args(stream, None);
+        let output = self.shell_checked(&args.iter().map(String::as_str).collect::<Vec<_>>())?;
+        let level = match parse_volume_range(&output) {
+            Some((min, max)) => level.clamp(min, max),
+            None => level,
+        };
+        let args = media_volume_args(stream, Some(level));
+        self.shell_checked(&args.iter().map(String::as_str).collect::<Vec<_>>())?;
+        Ok(level)
+    }
+
+    /// WiFi 是否开启，解析自 `settings get global wifi_on`。
+    pub fn wifi_enabled(&mut self) -> AdbResult<bool> {
+        let value = self.settings_get(SettingsNamespace::Global, "wifi_on")?;
+        Ok(setting_enabled(value.as_deref()))
+    }
+
+    /// 蓝牙是否开启，解析自 `settings get global bluetooth_on`。
+    pub fn bluetooth_enabled(&mut self) -> AdbResult<bool> {
+        let value = self.settings_get(SettingsNamespace::Global, "bluetooth_on")?;
+        Ok(setting_enabled(value.as_deref()))
+    }
+
+    /// 移动数据是否开启，解析自 `settings get global mobile_data`。
+    pub fn mobile_data_enabled(&mut self) -> AdbResult<bool> {
+        let value = self.settings_get(SettingsNamespace::Global, "mobile_data")?;
+        Ok(setting_enabled(value.as_deref()))
     }
 
     pub fn click(&mut self, x: i32, y: i32) -> anyhow::Result<String> {
@@ -1275,36 +4091,126 @@ where
         ])
     }
 
+    /// 在 (x, y) 处长按 `duration_ms` 毫秒，实现为起点与终点相同的 `input swipe`。
+    pub fn long_click(&mut self, x: i32, y: i32, duration_ms: i32) -> anyhow::Result<String> {
+        self.swipe(x, y, x, y, duration_ms)
+    }
+
+    /// 在 (x, y) 处连续点击两次，两次 `input tap` 在同一条 shell 命令中执行以缩短间隔。
+    pub fn double_click(&mut self, x: i32, y: i32) -> anyhow::Result<String> {
+        let tap = Self::list2cmdline(&["input", "tap", &x.to_string(), &y.to_string()]);
+        self.shell_stream_raw(&format!("{} && {}", tap, tap))?
+            .read_until_close()
+    }
+
+    /// 按住 (x1, y1) 拖动到 (x2, y2)，用时 `duration_ms` 毫秒。
+    ///
+    /// 与 `swipe` 发送的是同一条 `input swipe` 命令，区别在于用途：`swipe` 用于滑动翻页，
+    /// 时长较短时会被识别为 fling 并带有惯性；拖动图标、滑块等需要精确落点时应使用 `drag`
+    /// 并给出足够长的时长（通常不少于 500 毫秒），使手指在终点停下时没有惯性。
+    pub fn drag(
+        &mut self,
+        x1: i32,
+        y1: i32,
+        x2: i32,
+        y2: i32,
+        duration_ms: i32,
+    ) -> anyhow::Result<String> {
+        self.swipe(x1, y1, x2, y2, duration_ms)
+    }
+
+    /// 输入文本，支持空格、`&`、`"` 等 shell 特殊字符以及非 ASCII 字符。
+    ///
+    /// `input text` 只能输入 ASCII 字符，非 ASCII 片段会先通过 `cmd clipboard set` 写入剪贴板，
+    /// 再发送粘贴按键（`KEYCODE_PASTE`），需要设备的 `cmd clipboard` 支持 `set` 且当前焦点控件可以粘贴。
+    /// 另外 `input` 会把文本中的 `%s` 解释为空格。
     pub fn send_keys(&mut self, keys: &str) -> anyhow::Result<String> {
-        self.shell(&["input", "text", keys])
+        self.shell_stream_raw(&send_keys_cmdline(keys))?
+            .read_until_close()
     }
 
+    /// 获取设备的 IPv4 地址，依次尝试 `ifconfig wlan0`、`ip addr`、`ip route get` 与 `ifconfig eth0`，
+    /// 返回第一个合法且不是回环地址的结果。
     pub fn wlan_ip(&mut self) -> anyhow::Result<String> {
-        let mut result = self.shell(&["ifconfig", "wlan0"])?;
-        let re = regex::Regex::new(r"inet\s*addr:(.*?)\s").unwrap();
-        if let Some(captures) = re.captures(&result) {
-            return Ok(captures.get(1).unwrap().as_str().to_string());
+        let mut found_tool = false;
+        for (tool, command, pattern) in WLAN_IP_QUERIES {
+            if self.device_which(tool)?.is_none() {
+                continue;
+            }
+            found_tool = true;
+            let result = self.shell(command)?;
+            if let Some(ip) = parse_ipv4(&result, pattern) {
+                return Ok(ip.to_string());
+            }
         }
-        result = self.shell(&["ip", "addr", "show", "dev", "wlan0"])?;
-        let re = regex::Regex::new(r"inet (\d+.*?)/\d+").unwrap();
-        if let Some(captures) = re.captures(&result) {
-            return Ok(captures.get(1).unwrap().as_str().to_string());
-        }
-
-        result = self.shell(&["ifconfig", "eth0"])?;
-        let re = regex::Regex::new(r"inet\s*addr:(.*?)\s").unwrap();
-        if let Some(captures) = re.captures(&result) {
-            return Ok(captures.get(1).unwrap().as_str().to_string());
+        if !found_tool {
+            return Err(missing_tool(&["ifconfig", "ip"]).into());
         }
         Err(anyhow!("fail to parse wlan ip"))
     }
 
+    /// 获取 wlan0 上的全局 IPv6 地址，解析自 `ip -6 addr show dev wlan0`，不包含链路本地地址。
+    pub fn wlan_ipv6(&mut self) -> AdbResult<Vec<Ipv6Addr>> {
+        self.wlan_ipv6_with(false)
+    }
+
+    /// 同 `wlan_ipv6`，`include_link_local` 为 `true` 时同时返回 `fe80::` 链路本地地址。
+    pub fn wlan_ipv6_with(&mut self, include_link_local: bool) -> AdbResult<Vec<Ipv6Addr>> {
+        self.require_tool("ip")?;
+        let output = self.shell(&["ip", "-6", "addr", "show", "dev", "wlan0"])?;
+        Ok(parse_ipv6_addrs(&output, include_link_local))
+    }
+
     pub fn uninstall(&mut self, package_name: &str) -> anyhow::Result<String> {
         self.shell(&["am", "uninstall", package_name])
     }
 
+    /// 列出已安装的应用包名，解析自 `pm list packages`。
+    ///
+    /// # 参数
+    /// - `filter`: 过滤条件，默认列出全部应用
+    pub fn list_packages(&mut self, filter: PackageFilter) -> AdbResult<Vec<String>> {
+        let args = filter.to_args();
+        let args: Vec<&str> = args.iter().map(|s| s.as_str()).collect();
+        let output = self.shell(&args)?;
+        Ok(parse_package_list(&output))
+    }
+
+    /// 判断应用是否已安装。`pm list packages` 按子串过滤，这里再比较完整包名。
+    pub fn is_installed(&mut self, package_name: &str) -> AdbResult<bool> {
+        let packages = self.list_packages(PackageFilter {
+            name: Some(package_name.to_string()),
+            ..Default::default()
+        })?;
+        Ok(packages.iter().any(|p| p == package_name))
+    }
+
+    /// 启动应用。
+    ///
+    /// # 参数
+    /// - `package_name`: `包名/Activity` 形式的组件名直接交给 `am start -n`；
+    ///   只有包名时先通过 [`resolve_launch_activity`](Self::resolve_launch_activity) 查询启动 Activity，
+    ///   查询不到时回退到 `monkey -p <包名> -c android.intent.category.LAUNCHER 1`
     pub fn app_start(&mut self, package_name: &str) -> anyhow::Result<String> {
-        self.shell(&["am", "start", "-n", package_name])
+        if package_name.contains('/') {
+            return self.shell(&["am", "start", "-n", package_name]);
+        }
+        match self.resolve_launch_activity(package_name)? {
+            Some(info) => self.shell(&["am", "start", "-n", &info.component()]),
+            None => self.shell(&launcher_monkey_args(package_name)),
+        }
+    }
+
+    /// 通过 `cmd package resolve-activity` 查询应用的启动 Activity。
+    ///
+    /// # 返回值
+    /// 应用的 LAUNCHER Activity；应用不存在或没有可启动的 Activity 时返回 `None`
+    pub fn resolve_launch_activity(
+        &mut self,
+        package_name: &str,
+    ) -> AdbResult<Option<ActivityInfo>> {
+        let output = self.shell(&resolve_launch_activity_args(package_name))?;
+        Ok(parse_resolve_activity(&output))
     }
 
     pub fn app_stop(&mut self, package_name: &str) -> anyhow::Result<String> {
@@ -1315,6 +4221,37 @@ where
         self.shell(&["pm", "clear", package_name])
     }
 
+    /// 通过 `pm path` 获取应用安装包在设备上的路径，split 应用会返回 base 与多个 split APK。
+    pub fn apk_paths(&mut self, package_name: &str) -> AdbResult<Vec<String>> {
+        let output = self.shell(&["pm", "path", package_name])?;
+        Ok(parse_package_list(&output))
+    }
+
+    /// 将应用的全部安装包拉取到 `dest_dir`，返回本地文件路径。
+    ///
+    /// # 参数
+    /// - `package_name`: 应用包名
+    /// - `dest_dir`: 本地目录，文件名与设备上相同（`base.apk`、`split_config.*.apk` 等）
+    ///
+    /// # 返回值
+    /// 本地文件路径；应用未安装时返回 `AdbError::ApplicationError`
+    pub fn pull_apk(&mut self, package_name: &str, dest_dir: &Path) -> AdbResult<Vec<PathBuf>> {
+        let paths = self.apk_paths(package_name)?;
+        if paths.is_empty() {
+            return Err(AdbError::ApplicationError(format!(
+                "package {} is not installed",
+                package_name
+            )));
+        }
+        let mut files = vec![];
+        for remote in paths {
+            let local = dest_dir.join(remote.rsplit('/').next().unwrap_or(&remote));
+            self.pull(&remote, &local)?;
+            files.push(local);
+        }
+        Ok(files)
+    }
+
     pub fn app_info(&mut self, package_name: &str) -> Option<AppInfo> {
         let output = self.shell(&["pm", "list", "package", "-3"]).ok()?;
         if !output.contains(&format!("package:{}", package_name)) {
@@ -1322,6 +4259,9 @@ where
         }
         let app_info_output = self.shell(&["dumpsys", "pacakge", package_name]).ok()?;
         let mut app_info = AppInfo::new(package_name);
+        if let Ok(paths) = self.apk_paths(package_name) {
+            app_info.set_apk_paths(paths);
+        }
         let version_name_regex = regex::Regex::new(r"versionName=(?P<name>\S+)").unwrap();
         if let Some(cap) = version_name_regex.captures(&app_info_output) {
             let version_name = cap.get(1).unwrap().as_str();
@@ -1366,76 +4306,935 @@ where
         Some(app_info)
     }
 
+    /// 根据 `dumpsys power` 判断屏幕是否点亮，兼容不同系统版本的字段，
+    /// 输出中没有任何可识别的字段时返回错误。
     pub fn if_screen_on(&mut self) -> anyhow::Result<bool> {
-        let resp = self.shell(&["dumpsys", "power"])?;
-        Ok(resp.contains("mHoldingDisplaySuspendBlocker=true"))
+        let resp = self.dumpsys("power")?;
+        Ok(parse_screen_on(&resp).ok_or_else(unknown_screen_state)?)
     }
 
+    /// 删除文件，`path` 是目录时返回 `AdbError::FileOperationFailed`，目录请用 [`remove_dir`](Self::remove_dir) 删除。
     pub fn remove(&mut self, path: &str) -> anyhow::Result<String> {
+        check_remove_path(path)?;
+        if self.is_dir(path)? {
+            return Err(AdbError::FileOperationFailed(format!(
+                "{} is a directory, use remove_dir to delete it",
+                path
+            ))
+            .into());
+        }
         self.shell_trim(&["rm", path])
     }
 
+    /// 删除目录。`recursive` 为 `false` 时使用 `rmdir`，只能删除空目录；
+    /// 为 `true` 时使用 `rm -rf`，连同目录中的内容一起删除。
+    ///
+    /// 为防止误删，`path` 为空或为 `/` 时直接返回错误。其他错误的映射规则见 [`mv`](Self::mv)。
+    pub fn remove_dir(&mut self, path: &str, recursive: bool) -> AdbResult<()> {
+        check_remove_path(path)?;
+        if recursive {
+            self.file_op(&["rm", "-rf", path])
+        } else {
+            self.file_op(&["rmdir", path])
+        }
+    }
+
+    /// 创建目录，`recursive` 为 `true` 时使用 `mkdir -p`，同时创建不存在的父目录且目录已存在时不报错。
+    ///
+    /// 错误的映射规则见 [`mv`](Self::mv)。
+    pub fn mkdir(&mut self, path: &str, recursive: bool) -> AdbResult<()> {
+        if recursive {
+            self.file_op(&["mkdir", "-p", path])
+        } else {
+            self.file_op(&["mkdir", path])
+        }
+    }
+
+    /// 在设备上移动或重命名文件。
+    ///
+    /// 输出中包含 `Permission denied` 等权限错误时返回 `AdbError::PermissionDenied`，
+    /// 其他失败（退出码非 0 或有错误输出，例如 `No such file or directory`）返回 `AdbError::FileOperationFailed`。
+    pub fn mv(&mut self, src: &str, dst: &str) -> AdbResult<()> {
+        self.file_op(&["mv", src, dst])
+    }
+
+    /// 在设备上复制文件，复制目录时需要 `recursive`（`cp -r`）。错误的映射规则见 [`mv`](Self::mv)。
+    pub fn cp(&mut self, src: &str, dst: &str, recursive: bool) -> AdbResult<()> {
+        if recursive {
+            self.file_op(&["cp", "-r", src, dst])
+        } else {
+            self.file_op(&["cp", src, dst])
+        }
+    }
+
+    /// 创建空文件，文件已存在时更新其修改时间。错误的映射规则见 [`mv`](Self::mv)。
+    pub fn touch(&mut self, path: &str) -> AdbResult<()> {
+        self.file_op(&["touch", path])
+    }
+
+    /// 修改文件权限，`mode` 以八进制传给 `chmod`（例如 `0o755`），之后通过 [`stat`](Self::stat) 确认已生效。
+    ///
+    /// 部分文件系统（例如 `/sdcard`）会静默忽略 `chmod`，此时与命令报告权限错误一样返回 `AdbError::PermissionDenied`。
+    pub fn chmod(&mut self, path: &str, mode: u32) -> AdbResult<()> {
+        let mode = mode & 0o7777;
+        self.file_op(&["chmod", &format!("{:o}", mode), path])?;
+        let actual = self.stat(path)?.permissions() as u32;
+        if actual != mode {
+            return Err(AdbError::PermissionDenied(format!(
+                "mode of {} is {:o} after chmod {:o}",
+                path, actual, mode
+            )));
+        }
+        Ok(())
+    }
+
+    /// 修改文件所有者，`owner` 为 `user` 或 `user:group`，通常需要 root。错误的映射规则见 [`mv`](Self::mv)。
+    pub fn chown(&mut self, path: &str, owner: &str) -> AdbResult<()> {
+        self.file_op(&["chown", owner, path])
+    }
+
+    fn file_op(&mut self, command: &[&str]) -> AdbResult<()> {
+        let (output, code) = self.shell_with_status(command)?;
+        file_op_result(command, &output, code)
+    }
+
+    /// 执行一次 `getprop` 读取全部属性并刷新属性缓存。
+    ///
+    /// # 返回值
+    /// 刷新后的属性缓存，之后的 [`get_prop`](Self::get_prop) 直接从缓存读取只读属性
+    pub fn load_all_props(&mut self) -> AdbResult<&HashMap<String, String>> {
+        let output = self.shell(&["getprop"])?;
+        self.properties = parse_getprop(&output);
+        Ok(&self.properties)
+    }
+
+    /// 读取单个属性，只读属性（`ro.` 前缀）优先使用属性缓存，未命中时执行 `getprop <key>` 并加入缓存。
+    ///
+    /// 其他属性可能随时变化，即使已经通过 [`load_all_props`](Self::load_all_props) 读入缓存，
+    /// 也总是重新执行 `getprop <key>`。
+    pub fn get_prop(&mut self, key: &str) -> AdbResult<String> {
+        if key.starts_with("ro.") {
+            if let Some(value) = self.properties.get(key) {
+                return Ok(value.clone());
+            }
+        }
+        let value = self.shell_trim(&["getprop", key])?;
+        if key.starts_with("ro.") {
+            self.properties.insert(key.to_string(), value.clone());
+        }
+        Ok(value)
+    }
+
+    /// 执行 `setprop <key> <value>` 并通过 [`get_prop`](Self::get_prop) 读回确认。
+    ///
+    /// 只读属性或没有权限的属性会被 `setprop` 静默忽略，此时返回 `AdbError::PermissionDenied`。
+    /// 无论结果如何，属性缓存中的旧值都会被丢弃。
+    pub fn set_prop(&mut self, key: &str, value: &str) -> AdbResult<()> {
+        self.shell(&["setprop", key, value])?;
+        self.properties.remove(key);
+        let actual = self.get_prop(key)?;
+        check_prop_value(key, value, &actual)
+    }
+
+    /// 获取设备时区，例如 `Asia/Shanghai`，读取自 `persist.sys.timezone`。
+    pub fn get_timezone(&mut self) -> AdbResult<String> {
+        let timezone = self.get_prop("persist.sys.timezone")?;
+        if timezone.is_empty() {
+            return Err(AdbError::unknown("persist.sys.timezone is not set"));
+        }
+        Ok(timezone)
+    }
+
+    /// 通过 `service call alarm 3 s16 <timezone>`（`IAlarmManager.setTimeZone`）设置时区并读回确认，
+    /// 时区未生效时返回 `AdbError::PermissionDenied`。
+    pub fn set_timezone(&mut self, timezone: &str) -> AdbResult<()> {
+        self.shell(&["service", "call", "alarm", "3", "s16", timezone])?;
+        self.properties.remove("persist.sys.timezone");
+        let actual = self.get_prop("persist.sys.timezone")?;
+        check_prop_value("persist.sys.timezone", timezone, &actual)
+    }
+
+    /// 获取系统语言，例如 `zh-CN`。
+    ///
+    /// 依次读取 `persist.sys.locale`、旧版本的 `persist.sys.language` 与 `persist.sys.country`、
+    /// 出厂设置 `ro.product.locale`，取第一个非空的值。
+    pub fn get_locale(&mut self) -> AdbResult<String> {
+        let locale = self.get_prop("persist.sys.locale")?;
+        if !locale.is_empty() {
+            return Ok(locale);
+        }
+        let language = self.get_prop("persist.sys.language")?;
+        if !language.is_empty() {
+            let country = self.get_prop("persist.sys.country")?;
+            return Ok(if country.is_empty() {
+                language
+            } else {
+                format!("{}-{}", language, country)
+            });
+        }
+        let locale = self.get_prop("ro.product.locale")?;
+        if locale.is_empty() {
+            return Err(AdbError::unknown("no locale property is set"));
+        }
+        Ok(locale)
+    }
+
+    /// 设置设备时间，按系统版本选择 toybox 或 toolbox 的 `date` 格式，时间以 UTC 写入。
+    ///
+    /// 修改系统时间通常需要 root，shell 报告没有权限时返回 `AdbError::PermissionDenied`。
+    pub fn set_device_time(&mut self, dt: DateTime<Utc>) -> AdbResult<()> {
+        let sdk = self.get_sdk_version()?.parse().unwrap_or(23);
+        let args = date_set_args(sdk, &dt);
+        let args: Vec<&str> = args.iter().map(String::as_str).collect();
+        let (output, code) = self.shell_with_status(&args)?;
+        if is_permission_error(&output) {
+            return Err(AdbError::PermissionDenied(output.trim().to_string()));
+        }
+        check_exit_status(&args, output, code)?;
+        Ok(())
+    }
+
     pub fn get_sdk_version(&mut self) -> anyhow::Result<String> {
-        self.shell_trim(&["getprop", "ro.build.version.sdk"])
+        Ok(self.get_prop("ro.build.version.sdk")?)
     }
 
     pub fn get_android_version(&mut self) -> anyhow::Result<String> {
-        self.shell_trim(&["getprop", "ro.build.version.release"])
+        Ok(self.get_prop("ro.build.version.release")?)
     }
 
     pub fn get_device_model(&mut self) -> anyhow::Result<String> {
-        self.shell_trim(&["getprop", "ro.product.model"])
+        Ok(self.get_prop("ro.product.model")?)
     }
 
     pub fn get_device_brand(&mut self) -> anyhow::Result<String> {
-        self.shell_trim(&["getprop", "ro.product.brand"])
+        Ok(self.get_prop("ro.product.brand")?)
     }
     pub fn get_device_manufacturer(&mut self) -> anyhow::Result<String> {
-        self.shell_trim(&["getprop", "ro.product.manufacturer"])
+        Ok(self.get_prop("ro.product.manufacturer")?)
     }
     pub fn get_device_product(&mut self) -> anyhow::Result<String> {
-        self.shell_trim(&["getprop", "ro.product.product"])
+        Ok(self.get_prop("ro.product.product")?)
     }
 
     pub fn get_device_abi(&mut self) -> anyhow::Result<String> {
-        self.shell_trim(&["getprop", "ro.product.cpu.abi"])
+        Ok(self.get_prop("ro.product.cpu.abi")?)
+    }
+
+    /// 获取设备支持的全部 ABI，按优先级排列，读取自 `ro.product.cpu.abilist`。
+    ///
+    /// Android 5.0 之前没有该属性，回退到 `ro.product.cpu.abi` 与 `ro.product.cpu.abi2`。
+    /// ABI 属性都从属性缓存读取，缓存中没有时先通过 [`load_all_props`](Self::load_all_props)
+    /// 一次读取全部属性。
+    pub fn supported_abis(&mut self) -> AdbResult<Vec<String>> {
+        let abis = self.abi_list("ro.product.cpu.abilist")?;
+        if !abis.is_empty() {
+            return Ok(abis);
+        }
+        let mut abis = self.abi_list("ro.product.cpu.abi")?;
+        abis.extend(self.abi_list("ro.product.cpu.abi2")?);
+        Ok(abis)
+    }
+
+    /// 获取设备支持的 32 位 ABI，读取自 `ro.product.cpu.abilist32`，没有时返回空列表。
+    pub fn abilist32(&mut self) -> AdbResult<Vec<String>> {
+        self.abi_list("ro.product.cpu.abilist32")
+    }
+
+    /// 获取设备支持的 64 位 ABI，读取自 `ro.product.cpu.abilist64`，只支持 32 位的设备返回空列表。
+    pub fn abilist64(&mut self) -> AdbResult<Vec<String>> {
+        self.abi_list("ro.product.cpu.abilist64")
+    }
+
+    fn abi_list(&mut self, key: &str) -> AdbResult<Vec<String>> {
+        if !self.properties.contains_key(key) {
+            self.load_all_props()?;
+        }
+        Ok(split_abi_list(
+            self.properties.get(key).map_or("", String::as_str),
+        ))
     }
 
     pub fn get_device_gpu(&mut self) -> anyhow::Result<String> {
         let resp = self.shell(&["dumpsys", "SurfaceFlinger"]);
-        match resp {
-            Ok(data) => {
-                for x in data.split("\n") {
-                    if x.starts_with("GLES:") {
-                        return Ok(x.to_string());
-                    }
+        if let Ok(data) = resp {
+            for x in data.split('\n') {
+                if x.starts_with("GLES:") {
+                    return Ok(x.to_string());
                 }
             }
-            _ => {}
         }
         Err(anyhow!("fail to get gpu"))
     }
+
+    /// 获取 GPU 的厂商、渲染器、驱动版本和扩展列表，解析自 `dumpsys SurfaceFlinger`；
+    /// 原始的 `GLES:` 行见 [`get_device_gpu`](Self::get_device_gpu)。
+    pub fn gpu_info(&mut self) -> AdbResult<GpuInfo> {
+        let output = self.dumpsys("SurfaceFlinger")?;
+        GpuInfo::parse(&output)
+    }
+
+    /// 获取前台 Activity，优先解析 `dumpsys activity activities`，失败时回退到 `dumpsys window`。
+    ///
+    /// 锁屏、息屏或没有 Activity 获得焦点时返回 `AdbError::Unknown`。
+    pub fn current_activity(&mut self) -> AdbResult<ActivityInfo> {
+        let output = self.shell(&["dumpsys", "activity", "activities"])?;
+        if let Some(info) = parse_resumed_activity(&output) {
+            return Ok(info);
+        }
+        let output = self.shell(&["dumpsys", "window"])?;
+        parse_current_focus(&output).ok_or_else(no_focused_activity)
+    }
+
+    /// 屏幕熄灭时按 `KEYCODE_WAKEUP` 点亮，已点亮时不做任何操作。
+    pub fn wake(&mut self) -> AdbResult<()> {
+        if !self.if_screen_on()? {
+            self.keyevent(&KeyCode::Wakeup.to_string())?;
+        }
+        Ok(())
+    }
+
+    /// 是否显示锁屏界面，解析自 `dumpsys window`。
+    pub fn is_locked(&mut self) -> AdbResult<bool> {
+        let output = self.shell(&["dumpsys", "window"])?;
+        parse_keyguard_locked(&output)
+            .ok_or_else(|| AdbError::unknown("dumpsys window has no known keyguard field"))
+    }
+
+    /// 点亮屏幕并解锁：上滑关闭锁屏界面，提供 `pin` 时输入 PIN 并按回车。
+    ///
+    /// 已解锁时不做任何操作，可以重复调用。只支持无密码和 PIN/密码锁，
+    /// 图案锁与生物识别无法通过该方法解锁；方法不检查解锁是否成功，需要时调用 `is_locked` 确认。
+    pub fn unlock(&mut self, pin: Option<&str>) -> AdbResult<()> {
+        let mut prev = None;
+        loop {
+            let screen_on = self.if_screen_on()?;
+            let locked = screen_on && self.is_locked()?;
+            let step = next_unlock_step(prev, screen_on, locked, pin.is_some());
+            match step {
+                UnlockStep::Wake => {
+                    self.keyevent(&KeyCode::Wakeup.to_string())?;
+                }
+                UnlockStep::Swipe => {
+                    let (width, height) = self.screen_size()?;
+                    let x = (width / 2) as i32;
+                    self.swipe(x, (height * 4 / 5) as i32, x, (height / 5) as i32, 300)?;
+                }
+                UnlockStep::EnterPin => {
+                    self.shell(&["input", "text", pin.unwrap_or_default()])?;
+                    self.keyevent(&KeyCode::Enter.to_string())?;
+                }
+                UnlockStep::Done => return Ok(()),
+            }
+            prev = Some(step);
+        }
+    }
+
+    /// 获取主屏分辨率 `(宽, 高)`，解析自 `wm size`，存在 `Override size` 时优先使用。
+    ///
+    /// 多屏设备只返回 0 号屏的分辨率。
+    pub fn screen_size(&mut self) -> AdbResult<(u32, u32)> {
+        let output = self.shell(&["wm", "size"])?;
+        parse_wm_size(&output).ok_or_else(|| unexpected_output("wm size", &output))
+    }
+
+    /// 获取主屏 dpi，解析自 `wm density`，存在 `Override density` 时优先使用。
+    pub fn screen_density(&mut self) -> AdbResult<u32> {
+        let output = self.shell(&["wm", "density"])?;
+        parse_wm_density(&output).ok_or_else(|| unexpected_output("wm density", &output))
+    }
+
+    /// 执行 `dumpsys <service>` 并返回输出，`service` 按空白拆分，可以带参数，例如 `meminfo com.example`。
+    ///
+    /// 服务不存在（`Can't find service`）时返回 `AdbError::Unknown`。
+    pub fn dumpsys(&mut self, service: &str) -> AdbResult<String> {
+        let mut args = vec!["dumpsys"];
+        args.extend(service.split_whitespace());
+        let output = self.shell(&args)?;
+        if output.starts_with("Can't find service") {
+            return Err(AdbError::unknown(output.trim()));
+        }
+        Ok(output)
+    }
+
+    /// 获取电池电量、充电状态等信息，解析自 `dumpsys battery`。
+    pub fn battery(&mut self) -> AdbResult<BatteryInfo> {
+        let output = self.dumpsys("battery")?;
+        parse_battery(&output)
+    }
+
+    /// 获取电源状态，解析自 `dumpsys power`。
+    pub fn power_info(&mut self) -> AdbResult<PowerInfo> {
+        let output = self.dumpsys("power")?;
+        PowerInfo::parse(&output)
+    }
+
+    /// 获取应用的内存占用，解析自 `dumpsys meminfo <package>`，应用未运行时返回错误。
+    pub fn meminfo(&mut self, package: &str) -> AdbResult<MemInfo> {
+        let output = self.shell(&["dumpsys", "meminfo", package])?;
+        MemInfo::parse(&output)
+    }
+
+    /// 获取应用自启动（或上次 `reset`）以来的帧耗时统计，解析自 `dumpsys gfxinfo <package>`。
+    pub fn gfxinfo(&mut self, package: &str) -> AdbResult<GfxInfo> {
+        let output = self.shell(&["dumpsys", "gfxinfo", package])?;
+        GfxInfo::parse(&output)
+    }
+
+    /// 获取所有挂载点的存储使用情况，解析自 `df -k`。
+    pub fn mounts(&mut self) -> AdbResult<Vec<MountInfo>> {
+        self.require_tool("df")?;
+        let output = self.shell(&["df", "-k"])?;
+        Ok(parse_df_output(&output))
+    }
+
+    /// 获取 `path` 所在文件系统的容量，解析自 `df -k <path>`，固定 1K 块输出以免不同版本的 `df` 默认格式不同。
+    ///
+    /// 路径不存在时返回 `AdbError::FileOperationFailed`。
+    pub fn storage(&mut self, path: &str) -> AdbResult<StorageInfo> {
+        self.require_tool("df")?;
+        let output = self.shell(&["df", "-k", path])?;
+        parse_df_output(&output)
+            .first()
+            .map(StorageInfo::from)
+            .ok_or_else(|| AdbError::FileOperationFailed(format!("df {}: {}", path, output.trim())))
+    }
+
+    /// 获取名为 `process` 的进程的 PID，进程不存在时返回空列表。
+    ///
+    /// 优先使用 `pidof`，旧设备没有 `pidof` 时从 [`ps`](Self::ps) 的结果中按进程名查找。
+    pub fn pidof(&mut self, process: &str) -> AdbResult<Vec<u32>> {
+        let (output, code) = self.shell_with_status(&["pidof", process])?;
+        if !is_command_missing(&output, code) {
+            return Ok(parse_pidof(&output));
+        }
+        Ok(self
+            .ps()?
+            .into_iter()
+            .filter(|p| p.name == process)
+            .map(|p| p.pid)
+            .collect())
+    }
+
+    /// 列出设备上的所有进程，使用 toybox 的 `ps -A -o PID,PPID,USER,NAME`；
+    /// 旧设备的 toolbox `ps` 不支持这些参数，把它们当成进程名过滤而输出为空，此时退回不带参数的 `ps`。
+    pub fn ps(&mut self) -> AdbResult<Vec<ProcessInfo>> {
+        let output = self.shell(&["ps", "-A", "-o", "PID,PPID,USER,NAME"])?;
+        let processes = parse_ps_output(&output);
+        if !processes.is_empty() {
+            return Ok(processes);
+        }
+        let output = self.shell(&["ps"])?;
+        Ok(parse_ps_output(&output))
+    }
+
+    /// 向进程发送信号，与 `kill -<signal> <pid>` 相同。
+    ///
+    /// 没有权限（`Operation not permitted`）时返回 `AdbError::PermissionDenied`，
+    /// 其他失败（例如进程不存在）返回 `AdbError::ExitStatus`。
+    pub fn kill_pid(&mut self, pid: u32, signal: i32) -> AdbResult<()> {
+        let signal = format!("-{}", signal);
+        let pid = pid.to_string();
+        let command = ["kill", signal.as_str(), pid.as_str()];
+        let (output, code) = self.shell_with_status(&command)?;
+        if is_permission_error(&output) {
+            return Err(AdbError::PermissionDenied(output.trim().to_string()));
+        }
+        if code != 0 {
+            return Err(AdbError::exit_status(
+                command.join(" "),
+                code,
+                output.trim(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// 通过 [`pidof`](Self::pidof) 找到名为 `name` 的所有进程并发送 `SIGKILL`，返回被结束的 PID。
+    ///
+    /// 可以结束 `am force-stop` 无法停止的后台服务进程；没有匹配的进程时返回空列表。
+    pub fn kill_process(&mut self, name: &str) -> AdbResult<Vec<u32>> {
+        let pids = self.pidof(name)?;
+        for &pid in &pids {
+            self.kill_pid(pid, 9)?;
+        }
+        Ok(pids)
+    }
+
+    /// 获取设备内存总量（字节），读取自 `/proc/meminfo` 的 `MemTotal`。
+    pub fn mem_total(&mut self) -> AdbResult<u64> {
+        let meminfo = self.proc_meminfo()?;
+        meminfo
+            .get("MemTotal")
+            .copied()
+            .ok_or_else(|| AdbError::unknown("MemTotal not found in /proc/meminfo"))
+    }
+
+    /// 获取设备可用内存（字节），计算方式见 [`available_memory`]。
+    pub fn mem_available(&mut self) -> AdbResult<u64> {
+        let meminfo = self.proc_meminfo()?;
+        available_memory(&meminfo)
+            .ok_or_else(|| AdbError::unknown("MemAvailable not found in /proc/meminfo"))
+    }
+
+    fn proc_meminfo(&mut self) -> AdbResult<HashMap<String, u64>> {
+        let output = self.shell(&["cat", "/proc/meminfo"])?;
+        Ok(parse_proc_meminfo(&output))
+    }
+
     pub fn logcat(
         &mut self,
         flush_exist: bool,
         command: Option<&str>,
         lock: Arc<RwLock<bool>>,
-    ) -> anyhow::Result<impl Iterator<Item = String>> {
+    ) -> anyhow::Result<LogcatIterator> {
+        let mut cmd = vec!["logcat"];
+        match command {
+            Some(command) => cmd.extend(command.split_whitespace()),
+            None => cmd.extend(["-v", "time"]),
+        }
+        self.logcat_iter(flush_exist, &cmd, lock)
+    }
+
+    /// 按 `filter` 过滤读取日志。
+    ///
+    /// # 参数
+    /// - `flush_exist`: 是否先清空已有日志
+    /// - `filter`: 缓冲区、`TAG:LEVEL` 规则、起始时间与输出格式，参见 [`LogcatFilter`]
+    /// - `lock`: 置为 `false` 后迭代器在读到下一行时结束
+    pub fn logcat_filtered(
+        &mut self,
+        flush_exist: bool,
+        filter: &LogcatFilter,
+        lock: Arc<RwLock<bool>>,
+    ) -> anyhow::Result<LogcatIterator> {
+        let args = filter.to_args();
+        let cmd: Vec<&str> = args.iter().map(|s| s.as_str()).collect();
+        self.logcat_iter(flush_exist, &cmd, lock)
+    }
+
+    /// 以 `-v threadtime` 格式读取日志并逐行解析为 [`LogEntry`]，需要原始文本时使用
+    /// [`logcat_filtered`](Self::logcat_filtered)。
+    ///
+    /// # 参数
+    /// - `filter`: 过滤条件，其中的输出格式会被忽略
+    /// - `skip_markers`: 为真时丢弃 `--------- beginning of main` 等非日志行，否则作为
+    ///   `LogEntry::Marker` 产出
+    pub fn logcat_parsed(
+        &mut self,
+        flush_exist: bool,
+        filter: &LogcatFilter,
+        skip_markers: bool,
+        lock: Arc<RwLock<bool>>,
+    ) -> AdbResult<impl Iterator<Item = AdbResult<LogEntry>>> {
+        let filter = LogcatFilter {
+            format: Some("threadtime".to_string()),
+            ..filter.clone()
+        };
+        let lines = self.logcat_filtered(flush_exist, &filter, lock)?;
+        Ok(lines.filter_map(move |line| match LogEntry::parse(&line) {
+            LogEntry::Marker(_) if skip_markers => None,
+            entry => Some(Ok(entry)),
+        }))
+    }
+
+    fn logcat_iter(
+        &mut self,
+        flush_exist: bool,
+        cmd: &[&str],
+        lock: Arc<RwLock<bool>>,
+    ) -> anyhow::Result<LogcatIterator> {
         if flush_exist {
             self.shell(&["logcat", "-c"])?;
         }
-        let mut conn = self.shell_stream(&["logcat", "-v", "time"])?;
-        Ok(std::iter::from_fn(move || {
-            let mut bufreader = BufReader::new(&conn);
+        let conn = self.shell_stream(cmd)?;
+        Ok(LogcatIterator::new(conn, lock))
+    }
+}
 
-            while *(lock.read().unwrap()) {
-                let mut string = String::new();
-                let data = bufreader.read_line(&mut string);
-                if data.is_ok() {
-                    return Some(string);
-                }
+/// 构造 sync 协议数据包：4 字节命令 + 小端长度 + 数据。
+fn sync_packet(id: &str, data: &[u8]) -> Vec<u8> {
+    let mut packet = sync_header(id, data.len() as u32);
+    packet.extend_from_slice(data);
+    packet
+}
+
+/// 构造 sync 协议头：4 字节命令 + 小端 u32 参数，例如 `DONE` + mtime。
+fn sync_header(id: &str, value: u32) -> Vec<u8> {
+    let mut packet = Vec::with_capacity(8);
+    packet.extend_from_slice(id.as_bytes());
+    packet.extend_from_slice(&value.to_le_bytes());
+    packet
+}
+
+/// 未携带文件类型位的权限补充为普通文件（S_IFREG）。
+fn file_mode_with_type(mode: u32) -> u32 {
+    if mode & S_IFMT == 0 {
+        mode | S_IFREG
+    } else {
+        mode
+    }
+}
+
+/// 解析 `root:` 服务的响应：已是 root 或正在以 root 重启视为成功，生产版本拒绝则为权限错误。
+fn check_root_response(resp: String) -> AdbResult<String> {
+    if resp.contains("production builds") {
+        Err(AdbError::PermissionDenied(resp.trim().to_string()))
+    } else if resp.contains("already running as root") || resp.contains("restarting adbd as root") {
+        Ok(resp)
+    } else {
+        Err(AdbError::unknown(resp.trim()))
+    }
+}
+
+/// 设备上缺少所需工具时的错误，多个候选工具都不存在时一并列出。
+fn missing_tool(tools: &[&str]) -> AdbError {
+    let names: Vec<String> = tools.iter().map(|tool| format!("'{}'", tool)).collect();
+    AdbError::unknown(format!(
+        "required tool {} not found on device",
+        names.join(" or ")
+    ))
+}
+
+fn resolve_launch_activity_args(package_name: &str) -> [&str; 9] {
+    [
+        "cmd",
+        "package",
+        "resolve-activity",
+        "--brief",
+        "-a",
+        "android.intent.action.MAIN",
+        "-c",
+        "android.intent.category.LAUNCHER",
+        package_name,
+    ]
+}
+
+fn launcher_monkey_args(package_name: &str) -> [&str; 6] {
+    [
+        "monkey",
+        "-p",
+        package_name,
+        "-c",
+        "android.intent.category.LAUNCHER",
+        "1",
+    ]
+}
+
+/// 设备命令的输出无法解析时的错误。
+fn unknown_screen_state() -> AdbError {
+    AdbError::unknown(
+        "dumpsys power has none of Display Power, mWakefulness or mHoldingDisplaySuspendBlocker",
+    )
+}
+
+fn unexpected_output(command: &str, output: &str) -> AdbError {
+    AdbError::unknown(format!(
+        "unexpected output from {}: {}",
+        command,
+        output.trim()
+    ))
+}
+
+fn no_focused_activity() -> AdbError {
+    AdbError::unknown("no focused activity, the screen may be locked or off")
+}
+
+/// `setprop` 后读回的值与期望不一致时视为没有权限修改该属性。
+fn check_prop_value(key: &str, expected: &str, actual: &str) -> AdbResult<()> {
+    if actual == expected {
+        Ok(())
+    } else {
+        Err(AdbError::PermissionDenied(format!(
+            "property {} is {:?} after setting it to {:?}",
+            key, actual, expected
+        )))
+    }
+}
+
+/// 解析 `getprop` 输出中 `[key]: [value]` 格式的行。
+fn parse_getprop(output: &str) -> HashMap<String, String> {
+    let re = regex::Regex::new(r"(?m)^\[(.+?)\]:\s*\[(.*)\]\s*$").unwrap();
+    re.captures_iter(output)
+        .map(|cap| (cap[1].to_string(), cap[2].to_string()))
+        .collect()
+}
+
+fn check_waitable(state: DeviceState) -> AdbResult<()> {
+    if state.is_waitable() {
+        return Ok(());
+    }
+    Err(AdbError::unknown(format!(
+        "cannot wait for device state {}, expected device, recovery, rescue, sideload, bootloader or disconnect",
+        state
+    )))
+}
+
+fn file_wait_timeout(path: &str, exists: bool, timeout: Duration) -> AdbError {
+    let state = if exists { "appear" } else { "disappear" };
+    AdbError::timeout(format!("{} did not {} within {:?}", path, state, timeout))
+}
+
+/// 拒绝删除空路径和根目录。
+fn check_remove_path(path: &str) -> AdbResult<()> {
+    let path = path.trim();
+    if path.is_empty() || path.trim_end_matches('/').is_empty() {
+        return Err(AdbError::FileOperationFailed(format!(
+            "refusing to remove {:?}",
+            path
+        )));
+    }
+    Ok(())
+}
+
+/// 文件操作命令成功时没有输出，有输出或退出码非 0 都视为失败，权限错误单独区分。
+fn file_op_result(command: &[&str], output: &str, code: i32) -> AdbResult<()> {
+    let output = output.trim();
+    if is_permission_error(output) {
+        return Err(AdbError::PermissionDenied(output.to_string()));
+    }
+    if code == 0 && output.is_empty() {
+        return Ok(());
+    }
+    let reason = if output.is_empty() {
+        format!("exit status {}", code)
+    } else {
+        output.to_string()
+    };
+    Err(AdbError::FileOperationFailed(format!(
+        "{}: {}",
+        command.join(" "),
+        reason
+    )))
+}
+
+/// 拆分 `ro.product.cpu.abilist` 等逗号分隔的 ABI 列表，忽略空项。
+fn split_abi_list(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|abi| !abi.is_empty())
+        .map(String::from)
+        .collect()
+}
+
+/// 推送 APK 时在设备上使用的临时路径，以当前毫秒时间戳命名。
+fn remote_apk_path() -> AdbResult<String> {
+    let millis = time::SystemTime::now()
+        .duration_since(time::UNIX_EPOCH)
+        .map_err(AdbError::unknown)?
+        .as_millis();
+    Ok(format!("/data/local/tmp/tmp-{}.apk", millis))
+}
+
+/// 从 `pm install-create` 的输出 `Success: created install session [1234]` 中取出会话 ID。
+fn parse_install_session(output: &str) -> AdbResult<String> {
+    output
+        .split_once('[')
+        .and_then(|(_, rest)| rest.split_once(']'))
+        .map(|(session, _)| session.to_string())
+        .ok_or_else(|| AdbError::CommandFailed {
+            command: "pm install-create".to_string(),
+            reason: output.trim().to_string(),
+        })
+}
+
+/// `pm install` 的输出不含 `Success` 时转换为 `AdbError::ApplicationError`。
+fn check_pm_output(output: String) -> AdbResult<String> {
+    if output.contains("Success") {
+        Ok(output)
+    } else {
+        let reason = parse_pm_failure(&output).unwrap_or_else(|| output.trim().to_string());
+        Err(AdbError::ApplicationError(reason))
+    }
+}
+
+/// `pm install-*` 的输出不含 `Success` 时转换为 `AdbError::CommandFailed`。
+fn check_install_output(command: &str, output: String) -> AdbResult<String> {
+    if output.contains("Success") {
+        Ok(output)
+    } else {
+        Err(AdbError::CommandFailed {
+            command: command.to_string(),
+            reason: output.trim().to_string(),
+        })
+    }
+}
+
+/// 安装会话中 split 的名称，加上序号避免多个 APK 同名。
+fn install_split_name(index: usize, apk: &Path) -> String {
+    let file_name = apk
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "base.apk".to_string());
+    format!("{}_{}", index, file_name)
+}
+
+/// 从 `shell_with_status` 的输出中拆出命令输出和退出码。
+fn split_exit_status(output: &str) -> AdbResult<(String, i32)> {
+    let (stdout, status) = output
+        .rsplit_once(EXIT_STATUS_MARKER)
+        .ok_or_else(|| AdbError::unknown("exit status not found in shell output"))?;
+    let code = status
+        .trim()
+        .parse()
+        .map_err(|_| AdbError::unknown(format!("invalid exit status {:#?}", status)))?;
+    Ok((stdout.to_string(), code))
+}
+
+/// 回放时实际发送的命令行：在录制的命令行后追加退出码标记，与 `shell_with_status` 相同。
+fn replay_cmdline(cmdline: &str) -> String {
+    format!("{}; echo \"{}$?\"", cmdline, EXIT_STATUS_MARKER)
+}
+
+/// 拆出回放命令的输出，退出码非 0 时返回 `AdbError::CommandFailed`。
+fn replay_output(cmdline: &str, output: &str) -> AdbResult<String> {
+    let (output, code) = split_exit_status(output)?;
+    if code == 0 {
+        return Ok(output);
+    }
+    Err(AdbError::CommandFailed {
+        command: cmdline.to_string(),
+        reason: format!("exit status {}: {}", code, output.trim()),
+    })
+}
+
+/// 退出码非 0 时转换为 `AdbError::CommandFailed`。
+fn check_exit_status(command: &[&str], output: String, code: i32) -> AdbResult<String> {
+    if code == 0 {
+        Ok(output)
+    } else {
+        Err(AdbError::CommandFailed {
+            command: command.join(" "),
+            reason: format!("exit status {}: {}", code, output.trim()),
+        })
+    }
+}
+
+/// 把 `send_keys` 的文本转换为设备上执行的命令行。
+///
+/// 文本按 ASCII / 非 ASCII 切分：ASCII 片段使用 `input text`，空格替换为 `%s`；
+/// 非 ASCII 片段写入剪贴板后发送粘贴按键。各片段的命令用 `&&` 连接，按顺序执行。
+fn send_keys_cmdline(keys: &str) -> String {
+    let mut runs: Vec<(bool, String)> = vec![];
+    for c in keys.chars() {
+        match runs.last_mut() {
+            Some((ascii, run)) if *ascii == c.is_ascii() => run.push(c),
+            _ => runs.push((c.is_ascii(), c.to_string())),
+        }
+    }
+    if runs.is_empty() {
+        runs.push((true, String::new()));
+    }
+    runs.iter()
+        .map(|(ascii, run)| {
+            if *ascii {
+                format!("input text {}", shell_escape_arg(&run.replace(' ', "%s")))
+            } else {
+                format!(
+                    "cmd clipboard set {} && input keyevent {}",
+                    shell_escape_arg(run),
+                    KEYCODE_PASTE
+                )
             }
-            None
-        }))
+        })
+        .collect::<Vec<_>>()
+        .join(" && ")
+}
+
+/// `SND2` 的第二个数据包：命令 + 文件模式 + 压缩标志（不压缩）。
+fn send_v2_setup(mode: u32) -> Vec<u8> {
+    let mut packet = sync_header("SND2", file_mode_with_type(mode));
+    packet.extend_from_slice(&0u32.to_le_bytes());
+    packet
+}
+
+/// 把切换传输时 `FAIL` 的原因按 [`AdbError::from_fail_message`] 转换为设备离线、未授权、未找到等错误。
+fn transport_failed(err: anyhow::Error) -> anyhow::Error {
+    match err.downcast::<AdbError>() {
+        Ok(AdbError::Unknown(message)) => AdbError::from_fail_message(message).into(),
+        Ok(err) => err.into(),
+        Err(err) => err,
+    }
+}
+
+/// 解析 `get-features`、`host:host-features` 返回的逗号分隔列表。
+pub(crate) fn parse_features(features: &str) -> Vec<String> {
+    features
+        .split(',')
+        .map(str::trim)
+        .filter(|f| !f.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// 目录项命令之后、文件名之前的定长部分：v1 `DENT` 为 16 字节，v2 `DNT2` 为 72 字节。
+fn dent_header_length(v2: bool) -> usize {
+    if v2 {
+        STAT_V2_LEN + 4
+    } else {
+        16
+    }
+}
+
+/// 目录项中文件名的长度，位于定长部分的最后 4 字节，数据不完整时返回 `None`。
+fn dent_name_length(data: &[u8], v2: bool) -> Option<usize> {
+    let end = dent_header_length(v2);
+    let bytes = data.get(end - 4..end)?;
+    Some(u32::from_le_bytes(bytes.try_into().ok()?) as usize)
+}
+
+fn truncated_dent_error(data: &[u8], v2: bool) -> anyhow::Error {
+    anyhow!(
+        "truncated LIST entry: expected {} bytes, got {}",
+        dent_header_length(v2),
+        data.len()
+    )
+}
+
+/// 读取 `LIST` 返回的一个目录项。
+///
+/// `DENT`（`v2` 时为 `DNT2`）返回目录项，`DONE` 返回 `None`，
+/// `FAIL` 读取原因后返回 `AdbError::FileOperationFailed`。
+#[cfg(feature = "blocking")]
+fn read_dent(conn: &mut TcpStream, v2: bool) -> anyhow::Result<Option<FileInfo>> {
+    match conn.read_string(4)?.as_str() {
+        "DENT" | "DNT2" => {
+            let current_data = conn.recv_exact(dent_header_length(v2))?;
+            let name_length = dent_name_length(&current_data, v2)
+                .ok_or_else(|| truncated_dent_error(&current_data, v2))?;
+            let path = conn.read_string(name_length)?;
+            Ok(Some(parse_dent(current_data, path)?))
+        }
+        "DONE" => Ok(None),
+        "FAIL" => {
+            let size = conn.read_sync_length()?;
+            let error_message = conn.read_string(size)?;
+            Err(AdbError::FileOperationFailed(error_message).into())
+        }
+        other => Err(anyhow!("unexpected LIST response {:#?}", other)),
+    }
+}
+
+/// `read_dent` 的异步版本，返回目录项的原始数据和文件名。
+#[cfg(feature = "tokio_async")]
+async fn read_dent_async(
+    conn: &mut AdbConnection,
+    v2: bool,
+) -> anyhow::Result<Option<(Vec<u8>, String)>> {
+    match conn.read_string(4).await?.as_str() {
+        "DENT" | "DNT2" => {
+            let current_data = conn.recv_exact(dent_header_length(v2)).await?;
+            let name_length = dent_name_length(&current_data, v2)
+                .ok_or_else(|| truncated_dent_error(&current_data, v2))?;
+            let path = conn.read_string(name_length).await?;
+            Ok(Some((current_data, path)))
+        }
+        "DONE" => Ok(None),
+        "FAIL" => {
+            let size = conn.read_sync_length().await?;
+            let error_message = conn.read_string(size).await?;
+            Err(AdbError::FileOperationFailed(error_message).into())
+        }
+        other => Err(anyhow!("unexpected LIST response {:#?}", other)),
     }
 }
@@ -0,0 +1,143 @@
+use std::io::{BufRead, BufReader, Write};
+use std::net::{Shutdown, TcpStream, ToSocketAddrs};
+use std::path::PathBuf;
+
+use crate::errors::{AdbError, AdbResult};
+
+/// 控制台认证令牌文件，位于用户主目录下。
+const AUTH_TOKEN_FILE: &str = ".emulator_console_auth_token";
+
+/// 模拟器控制台（telnet 协议）的一个会话，由 `AdbDevice::emulator_console` 打开。
+///
+/// 控制台监听在序列号 `emulator-<port>` 中的端口上，每条命令以换行结束，
+/// 响应以单独一行 `OK` 结束，失败时为 `KO: <原因>`。
+/// drop 时与 [`close`](EmulatorConsole::close) 相同，会发送 `quit` 并关闭连接。
+pub struct EmulatorConsole {
+    reader: BufReader<TcpStream>,
+    closed: bool,
+}
+
+impl EmulatorConsole {
+    /// 连接 `addr` 上的模拟器控制台，读取欢迎信息，控制台要求认证时用 `auth_token` 认证。
+    ///
+    /// 控制台要求认证但未提供令牌时返回 `AdbError::Unknown`。
+    pub fn connect<A: ToSocketAddrs>(addr: A, auth_token: Option<&str>) -> AdbResult<Self> {
+        let conn = TcpStream::connect(addr)
+            .map_err(|e| AdbError::ConnectionFailed(format!("emulator console: {}", e)))?;
+        let mut console = EmulatorConsole {
+            reader: BufReader::new(conn),
+            closed: false,
+        };
+        let banner = console.read_response()?;
+        if banner.contains("Authentication required") {
+            let token = auth_token.ok_or_else(|| {
+                AdbError::unknown("emulator console requires an auth token, but none was found")
+            })?;
+            console.send(&format!("auth {}", token.trim()))?;
+        }
+        Ok(console)
+    }
+
+    /// 发送一条控制台命令，返回 `OK` 之前的输出；控制台返回 `KO` 时返回 `AdbError::CommandFailed`。
+    pub fn send(&mut self, cmd: &str) -> AdbResult<String> {
+        self.reader
+            .get_mut()
+            .write_all(format!("{}\n", cmd.trim_end()).as_bytes())?;
+        self.read_response().map_err(|e| match e {
+            AdbError::CommandFailed { reason, .. } => AdbError::CommandFailed {
+                command: cmd.to_string(),
+                reason,
+            },
+            e => e,
+        })
+    }
+
+    /// 设置模拟的 GPS 位置，对应 `geo fix <经度> <纬度>`（注意控制台命令经度在前）。
+    pub fn geo_fix(&mut self, lat: f64, lon: f64) -> AdbResult<String> {
+        self.send(&format!("geo fix {} {}", lon, lat))
+    }
+
+    /// 将模拟器屏幕逆时针旋转 90 度。
+    pub fn rotate(&mut self) -> AdbResult<String> {
+        self.send("rotate")
+    }
+
+    /// 设置模拟的电池电量（0-100）。
+    pub fn power(&mut self, capacity: u8) -> AdbResult<String> {
+        self.send(&format!("power capacity {}", capacity))
+    }
+
+    /// 发送 `quit` 并关闭连接。
+    pub fn close(mut self) -> AdbResult<()> {
+        Ok(self.shutdown()?)
+    }
+
+    fn read_response(&mut self) -> AdbResult<String> {
+        let mut output = String::new();
+        loop {
+            let mut line = String::new();
+            if self.reader.read_line(&mut line)? == 0 {
+                return Err(AdbError::ConnectionFailed(
+                    "emulator console closed the connection".to_string(),
+                ));
+            }
+            let trimmed = line.trim_end();
+            if trimmed == "OK" {
+                return Ok(output);
+            }
+            if let Some(reason) = trimmed.strip_prefix("KO") {
+                return Err(AdbError::CommandFailed {
+                    command: String::new(),
+                    reason: reason.trim_start_matches(':').trim().to_string(),
+                });
+            }
+            output.push_str(trimmed);
+            output.push('\n');
+        }
+    }
+
+    fn shutdown(&mut self) -> std::io::Result<()> {
+        if self.closed {
+            return Ok(());
+        }
+        self.closed = true;
+        let conn = self.reader.get_mut();
+        conn.write_all(b"quit\n")?;
+        match conn.shutdown(Shutdown::Both) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotConnected => Err(e),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl Drop for EmulatorConsole {
+    fn drop(&mut self) {
+        let _ = self.shutdown();
+    }
+}
+
+/// 解析 `emulator-<port>` 形式的序列号，返回控制台端口。
+pub(crate) fn console_port(serial: &str) -> Option<u16> {
+    serial.strip_prefix("emulator-")?.parse().ok()
+}
+
+/// 读取 `~/.emulator_console_auth_token`，文件不存在或为空时返回 `None`。
+pub(crate) fn read_auth_token() -> Option<String> {
+    let home = std::env::var_os("HOME").or_else(|| std::env::var_os("USERPROFILE"))?;
+    let token = std::fs::read_to_string(PathBuf::from(home).join(AUTH_TOKEN_FILE)).ok()?;
+    let token = token.trim();
+    (!token.is_empty()).then(|| token.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_console_port() {
+        assert_eq!(console_port("emulator-5554"), Some(5554));
+        assert_eq!(console_port("emulator-"), None);
+        assert_eq!(console_port("0123456789ABCDEF"), None);
+        assert_eq!(console_port("192.168.1.20:5555"), None);
+    }
+}
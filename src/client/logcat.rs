@@ -0,0 +1,119 @@
+use std::io::{BufRead, BufReader};
+use std::net::{Shutdown, TcpStream};
+use std::sync::{Arc, RwLock};
+
+use crate::errors::AdbResult;
+
+/// 阻塞版 `shell_lines` 返回的逐行迭代器，设备输出一行就返回一行，行尾的换行符会被去掉。
+///
+/// 连接断开时结束，读取出错时返回一次错误后结束。
+/// 需要从其他线程结束 `top`、`logcat` 等不会自行退出的命令时使用
+/// [`stop_handle`](ShellLines::stop_handle)。
+pub struct ShellLines {
+    reader: BufReader<TcpStream>,
+    done: bool,
+}
+
+impl ShellLines {
+    pub(crate) fn new(conn: TcpStream) -> ShellLines {
+        ShellLines {
+            reader: BufReader::new(conn),
+            done: false,
+        }
+    }
+
+    /// 获取一个可在其他线程中结束本迭代器的句柄。
+    pub fn stop_handle(&self) -> std::io::Result<LogcatStopHandle> {
+        Ok(LogcatStopHandle {
+            stream: self.reader.get_ref().try_clone()?,
+        })
+    }
+
+    /// 读取一行原始输出（保留换行符），连接关闭时返回 `None`。
+    fn read_raw_line(&mut self) -> std::io::Result<Option<String>> {
+        let mut line = String::new();
+        match self.reader.read_line(&mut line)? {
+            0 => Ok(None),
+            _ => Ok(Some(line)),
+        }
+    }
+}
+
+impl Iterator for ShellLines {
+    type Item = AdbResult<String>;
+
+    fn next(&mut self) -> Option<AdbResult<String>> {
+        if self.done {
+            return None;
+        }
+        match self.read_raw_line() {
+            Ok(Some(mut line)) => {
+                if line.ends_with('\n') {
+                    line.pop();
+                    if line.ends_with('\r') {
+                        line.pop();
+                    }
+                }
+                Some(Ok(line))
+            }
+            Ok(None) => {
+                self.done = true;
+                None
+            }
+            Err(e) => {
+                self.done = true;
+                Some(Err(e.into()))
+            }
+        }
+    }
+}
+
+/// 阻塞版 `logcat` 返回的逐行迭代器。
+///
+/// `lock` 被置为 `false`、连接断开或读取出错时结束。`lock` 只在读到下一行后才会被检查，
+/// 日志很少时迭代器可能一直阻塞；从其他线程结束长时间运行的 logcat 请使用
+/// [`stop_handle`](LogcatIterator::stop_handle)。
+pub struct LogcatIterator {
+    lines: ShellLines,
+    lock: Arc<RwLock<bool>>,
+}
+
+impl LogcatIterator {
+    pub(crate) fn new(conn: TcpStream, lock: Arc<RwLock<bool>>) -> LogcatIterator {
+        LogcatIterator {
+            lines: ShellLines::new(conn),
+            lock,
+        }
+    }
+
+    /// 获取一个可在其他线程中结束本迭代器的句柄。
+    pub fn stop_handle(&self) -> std::io::Result<LogcatStopHandle> {
+        self.lines.stop_handle()
+    }
+}
+
+impl Iterator for LogcatIterator {
+    type Item = String;
+
+    fn next(&mut self) -> Option<String> {
+        if !*self.lock.read().unwrap_or_else(|e| e.into_inner()) {
+            return None;
+        }
+        self.lines.read_raw_line().ok().flatten()
+    }
+}
+
+/// 由 [`LogcatIterator::stop_handle`] 或 [`ShellLines::stop_handle`] 创建，用于结束正在阻塞读取的命令。
+pub struct LogcatStopHandle {
+    stream: TcpStream,
+}
+
+impl LogcatStopHandle {
+    /// 关闭底层连接，阻塞中的 `read_line` 返回 `Ok(0)`，迭代器随之结束。
+    pub fn stop(&self) -> std::io::Result<()> {
+        match self.stream.shutdown(Shutdown::Both) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotConnected => Err(e),
+            _ => Ok(()),
+        }
+    }
+}
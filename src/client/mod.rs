@@ -1,5 +1,23 @@
 pub(crate) mod adb_client;
+#[cfg(feature = "tokio_async")]
+pub(crate) mod adb_connection;
 pub(crate) mod adb_device;
+#[cfg(feature = "blocking")]
+pub(crate) mod emulator_console;
+#[cfg(feature = "blocking")]
+pub(crate) mod logcat;
+#[cfg(feature = "blocking")]
+pub(crate) mod persistent_shell;
+pub(crate) mod screen_record;
 
-pub use adb_client::AdbClient;
+pub use adb_client::{AdbClient, AdbClientBuilder, ClientConfig, DeviceSnapshot, TrackReconnect};
+#[cfg(feature = "tokio_async")]
+pub use adb_connection::AdbConnection;
 pub use adb_device::AdbDevice;
+#[cfg(feature = "blocking")]
+pub use emulator_console::EmulatorConsole;
+#[cfg(feature = "blocking")]
+pub use logcat::{LogcatIterator, LogcatStopHandle, ShellLines};
+#[cfg(feature = "blocking")]
+pub use persistent_shell::PersistentShell;
+pub use screen_record::ScreenRecordHandle;
@@ -0,0 +1,108 @@
+use std::io::{BufRead, BufReader, Write};
+use std::net::{Shutdown, TcpStream};
+
+use crate::beans::command::AdbCommand;
+use crate::errors::{AdbError, AdbResult};
+
+/// 每条命令结束后输出的标记前缀，后跟命令序号、`:` 与退出码。
+const SESSION_END_MARKER: &str = ":RADB_SESSION_END:";
+
+/// 阻塞版 `shell_persistent` 返回的持久 shell 会话。
+///
+/// 会话在一条 `shell:sh` 连接上运行设备端的 `sh`，命令通过标准输入依次写入，
+/// 每条命令后输出带序号的结束标记，用来切分输出并取得退出码，
+/// 省去了每次执行命令都重新建立连接和传输的开销。
+///
+/// 命令的标准输入被重定向到 `/dev/null`，不会读走后续命令；
+/// 命令中执行 `exit` 或出现语法错误会结束设备端的 `sh`，之后的调用返回错误，需要重新打开会话。
+/// drop 时与 [`close`](PersistentShell::close) 相同，会让设备端的 `sh` 退出并关闭连接。
+pub struct PersistentShell {
+    reader: BufReader<TcpStream>,
+    counter: u64,
+    closed: bool,
+}
+
+impl PersistentShell {
+    pub(crate) fn new(conn: TcpStream) -> PersistentShell {
+        PersistentShell {
+            reader: BufReader::new(conn),
+            counter: 0,
+            closed: false,
+        }
+    }
+
+    /// 执行一条命令，返回输出（stdout 与 stderr 合并）。
+    pub fn run<C: Into<AdbCommand>>(&mut self, cmd: C) -> AdbResult<String> {
+        Ok(self.run_with_status(cmd)?.0)
+    }
+
+    /// 执行一条命令，返回输出和退出码。
+    pub fn run_with_status<C: Into<AdbCommand>>(&mut self, cmd: C) -> AdbResult<(String, i32)> {
+        self.counter += 1;
+        let marker = format!("{}{}:", SESSION_END_MARKER, self.counter);
+        let script = format!(
+            "{{ {}\n}} </dev/null 2>&1; echo \"{}$?\"\n",
+            cmd.into().display(),
+            marker
+        );
+        self.reader.get_mut().write_all(script.as_bytes())?;
+
+        let mut output = Vec::new();
+        loop {
+            let start = output.len();
+            if self.reader.read_until(b'\n', &mut output)? == 0 {
+                return Err(AdbError::ConnectionFailed(
+                    "persistent shell closed by device".to_string(),
+                ));
+            }
+            let line = String::from_utf8_lossy(&output[start..]);
+            if let Some(pos) = line.find(&marker) {
+                let status = line[pos + marker.len()..].trim();
+                let code = status
+                    .parse()
+                    .map_err(|_| AdbError::unknown(format!("invalid exit status {:#?}", status)))?;
+                let stdout = String::from_utf8_lossy(&output[..start]).to_string() + &line[..pos];
+                return Ok((stdout, code));
+            }
+        }
+    }
+
+    /// 执行一条命令，退出码非 0 时返回 `AdbError::CommandFailed`，否则返回输出。
+    pub fn run_checked<C: Into<AdbCommand>>(&mut self, cmd: C) -> AdbResult<String> {
+        let cmd = cmd.into();
+        let command = cmd.get_command();
+        let (output, code) = self.run_with_status(cmd)?;
+        if code == 0 {
+            Ok(output)
+        } else {
+            Err(AdbError::CommandFailed {
+                command,
+                reason: format!("exit status {}: {}", code, output.trim()),
+            })
+        }
+    }
+
+    /// 让设备端的 `sh` 退出并关闭连接。
+    pub fn close(mut self) -> AdbResult<()> {
+        Ok(self.shutdown()?)
+    }
+
+    fn shutdown(&mut self) -> std::io::Result<()> {
+        if self.closed {
+            return Ok(());
+        }
+        self.closed = true;
+        let conn = self.reader.get_mut();
+        conn.write_all(b"exit\n")?;
+        match conn.shutdown(Shutdown::Both) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotConnected => Err(e),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl Drop for PersistentShell {
+    fn drop(&mut self) {
+        let _ = self.shutdown();
+    }
+}
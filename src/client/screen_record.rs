@@ -0,0 +1,180 @@
+use std::fmt::Debug;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+#[cfg(feature = "blocking")]
+use std::net::ToSocketAddrs;
+#[cfg(feature = "blocking")]
+use std::thread::JoinHandle;
+#[cfg(feature = "tokio_async")]
+use tokio::net::ToSocketAddrs;
+#[cfg(feature = "tokio_async")]
+use tokio::task::JoinHandle;
+
+use crate::beans::screen_record::{segment_path, ScreenRecordOptions};
+use crate::client::AdbDevice;
+use crate::errors::{AdbError, AdbResult};
+
+/// 停止录制时重复发送 SIGINT 的间隔，覆盖分段切换时新进程刚启动的情况。
+const STOP_POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+/// `AdbDevice::screenrecord` 返回的录制句柄。
+///
+/// 录制在后台进行，调用 `stop` 会向 `screenrecord` 发送 SIGINT 使其正常写完 mp4，
+/// 然后把所有分段拉取到本地并删除设备上的临时文件。
+pub struct ScreenRecordHandle<T>
+where
+    T: ToSocketAddrs + Clone + Debug,
+{
+    device: AdbDevice<T>,
+    stopping: Arc<AtomicBool>,
+    segments: Arc<Mutex<Vec<String>>>,
+    worker: Option<JoinHandle<()>>,
+}
+
+impl<T> ScreenRecordHandle<T>
+where
+    T: ToSocketAddrs + Clone + Debug,
+{
+    /// 已经录制完成的分段在设备上的路径。
+    pub fn segments(&self) -> Vec<String> {
+        self.segments
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    fn local_path(local_dir: &Path, remote: &str) -> PathBuf {
+        local_dir.join(remote.rsplit('/').next().unwrap_or(remote))
+    }
+}
+
+#[cfg(feature = "blocking")]
+impl<T> ScreenRecordHandle<T>
+where
+    T: ToSocketAddrs + Clone + Debug + Send + 'static,
+{
+    pub(crate) fn start(
+        device: &AdbDevice<T>,
+        remote_tmp: &str,
+        opts: ScreenRecordOptions,
+    ) -> ScreenRecordHandle<T> {
+        let stopping = Arc::new(AtomicBool::new(false));
+        let segments = Arc::new(Mutex::new(vec![]));
+        let mut recorder = device.fork();
+        let remote_tmp = remote_tmp.to_string();
+        let (worker_stopping, worker_segments) = (stopping.clone(), segments.clone());
+        let worker = std::thread::spawn(move || {
+            for index in 0.. {
+                let remote = segment_path(&remote_tmp, index);
+                let mut args = opts.to_args();
+                args.push(remote.clone());
+                let args: Vec<&str> = args.iter().map(|s| s.as_str()).collect();
+                if recorder.shell(&args).is_err() {
+                    break;
+                }
+                worker_segments
+                    .lock()
+                    .unwrap_or_else(|e| e.into_inner())
+                    .push(remote);
+                if worker_stopping.load(Ordering::SeqCst) || !opts.auto_restart {
+                    break;
+                }
+            }
+        });
+        ScreenRecordHandle {
+            device: device.fork(),
+            stopping,
+            segments,
+            worker: Some(worker),
+        }
+    }
+
+    /// 停止录制，把所有分段拉取到 `local_dir` 并返回本地文件路径。
+    pub fn stop(mut self, local_dir: &Path) -> AdbResult<Vec<PathBuf>> {
+        self.stopping.store(true, Ordering::SeqCst);
+        if let Some(worker) = self.worker.take() {
+            while !worker.is_finished() {
+                let _ = self.device.shell(&["pkill", "-2", "screenrecord"]);
+                std::thread::sleep(STOP_POLL_INTERVAL);
+            }
+            worker
+                .join()
+                .map_err(|_| AdbError::unknown("screenrecord worker panicked"))?;
+        }
+        let mut files = vec![];
+        for remote in self.segments() {
+            let local = Self::local_path(local_dir, &remote);
+            self.device.pull(&remote, &local)?;
+            self.device.remove(&remote)?;
+            files.push(local);
+        }
+        Ok(files)
+    }
+}
+
+#[cfg(feature = "tokio_async")]
+impl<T> ScreenRecordHandle<T>
+where
+    T: ToSocketAddrs + Clone + Debug + Send + Sync + 'static,
+{
+    pub(crate) fn start(
+        device: &AdbDevice<T>,
+        remote_tmp: &str,
+        opts: ScreenRecordOptions,
+    ) -> ScreenRecordHandle<T> {
+        let stopping = Arc::new(AtomicBool::new(false));
+        let segments = Arc::new(Mutex::new(vec![]));
+        let mut recorder = device.fork();
+        let remote_tmp = remote_tmp.to_string();
+        let (worker_stopping, worker_segments) = (stopping.clone(), segments.clone());
+        let worker = tokio::spawn(async move {
+            for index in 0.. {
+                let remote = segment_path(&remote_tmp, index);
+                let mut args = opts.to_args();
+                args.push(remote.clone());
+                let args: Vec<&str> = args.iter().map(|s| s.as_str()).collect();
+                if recorder.shell(&args).await.is_err() {
+                    break;
+                }
+                worker_segments
+                    .lock()
+                    .unwrap_or_else(|e| e.into_inner())
+                    .push(remote);
+                if worker_stopping.load(Ordering::SeqCst) || !opts.auto_restart {
+                    break;
+                }
+            }
+        });
+        ScreenRecordHandle {
+            device: device.fork(),
+            stopping,
+            segments,
+            worker: Some(worker),
+        }
+    }
+
+    /// 停止录制，把所有分段拉取到 `local_dir` 并返回本地文件路径。
+    pub async fn stop(mut self, local_dir: &Path) -> AdbResult<Vec<PathBuf>> {
+        self.stopping.store(true, Ordering::SeqCst);
+        if let Some(worker) = self.worker.take() {
+            while !worker.is_finished() {
+                let _ = self.device.shell(&["pkill", "-2", "screenrecord"]).await;
+                tokio::time::sleep(STOP_POLL_INTERVAL).await;
+            }
+            worker
+                .await
+                .map_err(|_| AdbError::unknown("screenrecord worker panicked"))?;
+        }
+        let mut files = vec![];
+        for remote in self.segments() {
+            let local = Self::local_path(local_dir, &remote);
+            self.device.pull(&remote, &local).await?;
+            self.device.remove(&remote).await?;
+            files.push(local);
+        }
+        Ok(files)
+    }
+}
@@ -0,0 +1,262 @@
+use std::fmt::{Display, Formatter};
+use std::io::ErrorKind;
+
+/// radb 的统一返回类型。
+pub type AdbResult<T> = Result<T, AdbError>;
+
+/// radb 的错误类型。
+#[derive(Debug)]
+pub enum AdbError {
+    Io(std::io::Error),          // 底层 IO 错误
+    ConnectionFailed(String),    // 无法连接 adb server
+    ElementNotFound(String),     // 界面上未找到匹配的控件
+    Timeout(String),             // 等待超时
+    FileOperationFailed(String), // 设备端文件操作失败，携带服务端返回的原因
+    PermissionDenied(String),    // 设备拒绝了需要权限的操作
+    // 命令执行失败（退出码非 0）
+    CommandFailed {
+        command: String,
+        reason: String,
+    },
+    // 设备端命令以非 0 退出码结束，携带退出码和输出；连接仍可继续使用，不可重试
+    ExitStatus {
+        command: String,
+        code: i32,
+        output: String,
+    },
+    ApplicationError(String),   // 包管理操作失败，携带 pm 的失败原因
+    DeviceNotFound(String),     // adb server 找不到指定的设备
+    DeviceOffline(String),      // 设备处于 offline 状态
+    DeviceUnauthorized(String), // 设备未授权调试，需要在设备上确认 RSA 指纹
+    Unknown(String),            // 未归类的错误
+}
+
+impl AdbError {
+    pub fn unknown<T: ToString>(message: T) -> Self {
+        AdbError::Unknown(message.to_string())
+    }
+
+    pub fn timeout<T: ToString>(message: T) -> Self {
+        AdbError::Timeout(message.to_string())
+    }
+
+    /// 把切换传输时 adb server 返回的 `FAIL` 原因转换为对应的错误。
+    ///
+    /// 只识别 adb server 的固定文案：`device offline`、`device unauthorized`（之后可能附带说明）、
+    /// `device '...' not found` 与 `no devices/emulators found`，分别对应 `DeviceOffline`、
+    /// `DeviceUnauthorized`、`DeviceNotFound`，其他原因为 `Unknown`。
+    pub fn from_fail_message<T: ToString>(message: T) -> Self {
+        let message = message.to_string();
+        let reason = message.trim_end();
+        if reason == "device offline" {
+            AdbError::DeviceOffline(message)
+        } else if reason.starts_with("device unauthorized") {
+            AdbError::DeviceUnauthorized(message)
+        } else if reason.starts_with("device '") && reason.ends_with("' not found")
+            || reason == "no devices/emulators found"
+        {
+            AdbError::DeviceNotFound(message)
+        } else {
+            AdbError::Unknown(message)
+        }
+    }
+
+    pub fn exit_status<C: ToString, O: ToString>(command: C, code: i32, output: O) -> Self {
+        AdbError::ExitStatus {
+            command: command.to_string(),
+            code,
+            output: output.to_string(),
+        }
+    }
+
+    /// 返回错误对应的错误码字符串，便于日志和上层统一处理。
+    pub fn error_code(&self) -> &'static str {
+        match self {
+            AdbError::Io(_) => "IO",
+            AdbError::ConnectionFailed(_) => "CONNECTION_FAILED",
+            AdbError::ElementNotFound(_) => "ELEMENT_NOT_FOUND",
+            AdbError::Timeout(_) => "TIMEOUT",
+            AdbError::FileOperationFailed(_) => "FILE_OPERATION_FAILED",
+            AdbError::PermissionDenied(_) => "PERMISSION_DENIED",
+            AdbError::CommandFailed { .. } => "COMMAND_FAILED",
+            AdbError::ExitStatus { .. } => "EXIT_STATUS",
+            AdbError::ApplicationError(_) => "APPLICATION_ERROR",
+            AdbError::DeviceNotFound(_) => "DEVICE_NOT_FOUND",
+            AdbError::DeviceOffline(_) => "DEVICE_OFFLINE",
+            AdbError::DeviceUnauthorized(_) => "DEVICE_UNAUTHORIZED",
+            AdbError::Unknown(_) => "UNKNOWN",
+        }
+    }
+
+    /// 判断该错误是否为可重试的瞬时错误。
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            AdbError::Io(e) => matches!(
+                e.kind(),
+                ErrorKind::ConnectionRefused
+                    | ErrorKind::ConnectionReset
+                    | ErrorKind::ConnectionAborted
+                    | ErrorKind::TimedOut
+                    | ErrorKind::Interrupted
+                    | ErrorKind::WouldBlock
+            ),
+            AdbError::Timeout(_) | AdbError::ConnectionFailed(_) | AdbError::DeviceOffline(_) => {
+                true
+            }
+            AdbError::ElementNotFound(_)
+            | AdbError::FileOperationFailed(_)
+            | AdbError::PermissionDenied(_)
+            | AdbError::CommandFailed { .. }
+            | AdbError::ExitStatus { .. }
+            | AdbError::ApplicationError(_)
+            | AdbError::DeviceNotFound(_)
+            | AdbError::DeviceUnauthorized(_)
+            | AdbError::Unknown(_) => false,
+        }
+    }
+}
+
+impl Display for AdbError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AdbError::Io(e) => write!(f, "io error: {}", e),
+            AdbError::ConnectionFailed(message) => write!(f, "connection failed: {}", message),
+            AdbError::ElementNotFound(by) => write!(f, "element not found: {}", by),
+            AdbError::Timeout(message) => write!(f, "timeout: {}", message),
+            AdbError::FileOperationFailed(message) => {
+                write!(f, "file operation failed: {}", message)
+            }
+            AdbError::PermissionDenied(message) => write!(f, "permission denied: {}", message),
+            AdbError::CommandFailed { command, reason } => {
+                write!(f, "command {:?} failed: {}", command, reason)
+            }
+            AdbError::ExitStatus {
+                command,
+                code,
+                output,
+            } => write!(
+                f,
+                "command {:?} exited with status {}: {}",
+                command,
+                code,
+                output.trim()
+            ),
+            AdbError::ApplicationError(message) => write!(f, "application error: {}", message),
+            AdbError::DeviceNotFound(message) => write!(f, "device not found: {}", message),
+            AdbError::DeviceOffline(message) => write!(f, "device offline: {}", message),
+            AdbError::DeviceUnauthorized(message) => {
+                write!(f, "device unauthorized: {}", message)
+            }
+            AdbError::Unknown(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+impl std::error::Error for AdbError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            AdbError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for AdbError {
+    fn from(value: std::io::Error) -> Self {
+        AdbError::Io(value)
+    }
+}
+
+impl From<anyhow::Error> for AdbError {
+    fn from(value: anyhow::Error) -> Self {
+        match value.downcast::<AdbError>() {
+            Ok(e) => e,
+            Err(e) => match e.downcast::<std::io::Error>() {
+                Ok(e) => AdbError::Io(e),
+                Err(e) => AdbError::Unknown(format!("{:#}", e)),
+            },
+        }
+    }
+}
+
+/// 把 `shell_with_status` 等返回的 `(输出, 退出码)` 转换为输出，退出码非 0 时返回 `AdbError::ExitStatus`。
+pub trait ExitStatusExt {
+    fn ok_or_exit<C: ToString>(self, command: C) -> AdbResult<String>;
+}
+
+impl ExitStatusExt for AdbResult<(String, i32)> {
+    fn ok_or_exit<C: ToString>(self, command: C) -> AdbResult<String> {
+        match self? {
+            (output, 0) => Ok(output),
+            (output, code) => Err(AdbError::exit_status(command, code, output)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_exit_status_display_and_classification() {
+        let err = AdbError::exit_status("ls /nope", 1, "ls: /nope: No such file or directory\n");
+        assert_eq!(
+            err.to_string(),
+            "command \"ls /nope\" exited with status 1: ls: /nope: No such file or directory"
+        );
+        assert_eq!(err.error_code(), "EXIT_STATUS");
+        assert!(!err.is_retryable());
+    }
+
+    #[test]
+    fn test_from_fail_message() {
+        for (message, code, retryable) in [
+            ("device offline", "DEVICE_OFFLINE", true),
+            (
+                "device unauthorized.\nThis adb server's $ADB_VENDOR_KEYS is not set\n",
+                "DEVICE_UNAUTHORIZED",
+                false,
+            ),
+            (
+                "device 'emulator-5556' not found",
+                "DEVICE_NOT_FOUND",
+                false,
+            ),
+            ("no devices/emulators found", "DEVICE_NOT_FOUND", false),
+            ("unknown host service", "UNKNOWN", false),
+            ("cannot bind listener: device offline", "UNKNOWN", false),
+            ("'/sdcard/x' not found", "UNKNOWN", false),
+        ] {
+            let err = AdbError::from_fail_message(message);
+            assert_eq!(err.error_code(), code, "{}", message);
+            assert_eq!(err.is_retryable(), retryable, "{}", message);
+        }
+        assert_eq!(
+            AdbError::from_fail_message("device offline").to_string(),
+            "device offline: device offline"
+        );
+    }
+
+    #[test]
+    fn test_ok_or_exit() {
+        let ok: AdbResult<(String, i32)> = Ok(("/sdcard\n".to_string(), 0));
+        assert_eq!(ok.ok_or_exit("ls -d /sdcard").unwrap(), "/sdcard\n");
+
+        let failed: AdbResult<(String, i32)> = Ok(("denied\n".to_string(), 13));
+        match failed.ok_or_exit("cat /data") {
+            Err(AdbError::ExitStatus {
+                command,
+                code,
+                output,
+            }) => {
+                assert_eq!(command, "cat /data");
+                assert_eq!(code, 13);
+                assert_eq!(output, "denied\n");
+            }
+            other => panic!("unexpected result {:?}", other),
+        }
+
+        let err: AdbResult<(String, i32)> = Err(AdbError::timeout("busy"));
+        assert!(matches!(err.ok_or_exit("ls"), Err(AdbError::Timeout(_))));
+    }
+}
@@ -1,4 +1,7 @@
 pub mod beans;
 pub mod client;
+pub mod errors;
+#[cfg(feature = "metrics")]
+pub mod metrics;
 mod protocols;
 mod utils;
@@ -0,0 +1,122 @@
+//! 命令耗时统计，需要开启 `metrics` feature。
+//!
+//! `shell` 与 sync 相关操作（push/pull/stat/list）执行完成后会记录耗时，
+//! 超过阈值的操作会以 warn 级别输出日志，例如 `shell took 5.2s`。
+
+use std::collections::HashMap;
+use std::sync::{Mutex, MutexGuard, OnceLock};
+use std::time::{Duration, Instant};
+
+use log::warn;
+
+/// 默认的慢操作阈值。
+const DEFAULT_SLOW_THRESHOLD: Duration = Duration::from_secs(5);
+
+/// 单个操作的累计耗时统计。
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct OpStats {
+    pub count: u64,      // 执行次数
+    pub total: Duration, // 累计耗时
+    pub max: Duration,   // 单次最大耗时
+    pub slow: u64,       // 超过慢操作阈值的次数
+}
+
+impl OpStats {
+    /// 平均耗时，未执行过时为 0。
+    pub fn average(&self) -> Duration {
+        if self.count == 0 {
+            Duration::ZERO
+        } else {
+            self.total / self.count as u32
+        }
+    }
+}
+
+/// 某一时刻的统计快照。
+#[derive(Debug, Clone, Default)]
+pub struct MetricsSnapshot {
+    pub slow_threshold: Duration,
+    pub ops: HashMap<String, OpStats>,
+}
+
+impl MetricsSnapshot {
+    pub fn get(&self, op: &str) -> Option<&OpStats> {
+        self.ops.get(op)
+    }
+}
+
+struct Metrics {
+    slow_threshold: Duration,
+    ops: HashMap<&'static str, OpStats>,
+}
+
+fn metrics() -> MutexGuard<'static, Metrics> {
+    static METRICS: OnceLock<Mutex<Metrics>> = OnceLock::new();
+    METRICS
+        .get_or_init(|| {
+            Mutex::new(Metrics {
+                slow_threshold: DEFAULT_SLOW_THRESHOLD,
+                ops: HashMap::new(),
+            })
+        })
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+}
+
+/// 设置慢操作阈值，超过该耗时的操作会输出 warn 日志。
+pub fn set_slow_threshold(threshold: Duration) {
+    metrics().slow_threshold = threshold;
+}
+
+/// 返回当前的统计快照。
+pub fn snapshot() -> MetricsSnapshot {
+    let metrics = metrics();
+    MetricsSnapshot {
+        slow_threshold: metrics.slow_threshold,
+        ops: metrics
+            .ops
+            .iter()
+            .map(|(op, stats)| (op.to_string(), stats.clone()))
+            .collect(),
+    }
+}
+
+/// 清空已记录的统计，阈值保持不变。
+pub fn reset() {
+    metrics().ops.clear();
+}
+
+/// 记录一次操作的耗时。
+pub fn record(op: &'static str, elapsed: Duration) {
+    let mut metrics = metrics();
+    let slow = elapsed > metrics.slow_threshold;
+    let stats = metrics.ops.entry(op).or_default();
+    stats.count += 1;
+    stats.total += elapsed;
+    stats.max = stats.max.max(elapsed);
+    if slow {
+        stats.slow += 1;
+        warn!("{} took {:?}", op, elapsed);
+    }
+}
+
+/// 作用域计时器，drop 时记录从创建到结束的耗时。
+pub(crate) struct Timer {
+    op: &'static str,
+    start: Instant,
+}
+
+impl Timer {
+    pub(crate) fn start(op: &'static str) -> Self {
+        Timer {
+            op,
+            start: Instant::now(),
+        }
+    }
+}
+
+impl Drop for Timer {
+    fn drop(&mut self) {
+        record(self.op, self.start.elapsed());
+    }
+}
@@ -7,6 +7,7 @@ pub use blocking::AdbProtocol;
 #[cfg(feature = "tokio_async")]
 pub use tokio_async::AdbProtocol;
 
+#[allow(dead_code, clippy::upper_case_acronyms)]
 #[derive(Debug)]
 pub enum AdbProtocolRespDataType {
     OKAY,    // 操作成功
@@ -1,19 +1,25 @@
+#[cfg(feature = "tokio_async")]
 use anyhow::{anyhow, Context};
 
-use log::info;
+#[cfg(all(feature = "tokio_async", feature = "protocol-trace"))]
+use log::trace;
 
 #[cfg(feature = "tokio_async")]
 use async_trait::async_trait;
 #[cfg(feature = "tokio_async")]
 use tokio::io::{AsyncReadExt, AsyncWriteExt};
 
+#[cfg(feature = "tokio_async")]
+use crate::errors::AdbError;
+#[cfg(feature = "tokio_async")]
 use crate::protocols::AdbProtocolRespDataType;
 
 #[cfg(feature = "tokio_async")]
 #[async_trait]
 pub trait AdbProtocol: AsyncReadExt + AsyncWriteExt + Unpin {
     async fn send(&mut self, data: &[u8]) -> anyhow::Result<usize> {
-        info!(">>>>>>> Send Size: {:#?} >>>>>>>", data.len());
+        #[cfg(feature = "protocol-trace")]
+        trace!(">>>>>>> Send Size: {:#?} >>>>>>>", data.len());
         let size = self.write(data).await?;
         Ok(size)
     }
@@ -26,13 +32,24 @@ pub trait AdbProtocol: AsyncReadExt + AsyncWriteExt + Unpin {
     /// # 返回值
     /// - 成功返回接收到的数据，失败返回错误。
     async fn recv(&mut self, n: usize) -> anyhow::Result<Vec<u8>> {
-        info!("<<<<<<< Try Recv Size: {:#?} <<<<<<<", n);
+        #[cfg(feature = "protocol-trace")]
+        trace!("<<<<<<< Try Recv Size: {:#?} <<<<<<<", n);
         let mut target = vec![0; n];
         let result = self.read(&mut target).await?;
-        info!("<<<<<<< Recv Size: {:#?} <<<<<<<", result);
+        #[cfg(feature = "protocol-trace")]
+        trace!("<<<<<<< Recv Size: {:#?} <<<<<<<", result);
         Ok(target[..result].to_owned())
     }
 
+    /// 与 [`recv`](Self::recv) 相同，但会反复读取直到读满 `n` 字节，只在连接关闭时提前返回。
+    ///
+    /// 用于读取定长的结构，返回的数据不足 `n` 字节说明连接已关闭，由调用方报告数据不完整。
+    async fn recv_exact(&mut self, n: usize) -> anyhow::Result<Vec<u8>> {
+        let mut target = Vec::with_capacity(n);
+        (&mut *self).take(n as u64).read_to_end(&mut target).await?;
+        Ok(target)
+    }
+
     /// 发送命令到设备。
     ///
     /// # 参数
@@ -41,7 +58,8 @@ pub trait AdbProtocol: AsyncReadExt + AsyncWriteExt + Unpin {
     /// # 返回值
     /// - 成功返回发送的数据大小，失败返回错误。
     async fn send_command(&mut self, command: &str) -> anyhow::Result<usize> {
-        info!("Send COMMAND: <{:#?}>", command);
+        #[cfg(feature = "protocol-trace")]
+        trace!("Send COMMAND: <{:#?}>", command);
         let cmd_bytes = command.as_bytes();
         let length = format!("{:04x}", cmd_bytes.len());
         let mut data = Vec::with_capacity(length.len() + cmd_bytes.len());
@@ -73,32 +91,62 @@ pub trait AdbProtocol: AsyncReadExt + AsyncWriteExt + Unpin {
         self.read_string(string_size).await
     }
 
+    /// 读取 sync 协议中以小端 u32 表示的长度，如 `DATA`、`FAIL` 数据包的长度字段。
+    ///
+    /// 连接在读满 4 字节之前关闭时返回错误，不会把长度当作 0。
+    async fn read_sync_length(&mut self) -> anyhow::Result<usize> {
+        let mut length = [0; 4];
+        self.read_exact(&mut length)
+            .await
+            .context("Failed to read sync length")?;
+        Ok(u32::from_le_bytes(length) as usize)
+    }
+
     /// 读取直到关闭的消息。
     ///
     /// # 返回值
     /// - 成功返回读取的全部内容，失败返回错误。
     async fn read_until_close(&mut self) -> anyhow::Result<String> {
-        let mut content = Vec::new();
-        loop {
-            let bytes_read = self.recv(4096).await?;
-            if bytes_read.is_empty() {
-                break;
-            }
-            content.extend_from_slice(&bytes_read);
-        }
-        Ok(String::from_utf8_lossy(&content).to_string())
+        self.read_until_close_with_capacity(0).await
+    }
+
+    /// 读取直到关闭的消息，数据直接读入预先分配 `capacity` 字节的缓冲区，不再逐块分配。
+    ///
+    /// # 参数
+    /// - `capacity`: 预计的输出大小，`dumpsys`、`ps -A` 等大量输出时可减少扩容次数。
+    ///
+    /// # 返回值
+    /// - 成功返回读取的全部内容，失败返回错误。
+    async fn read_until_close_with_capacity(&mut self, capacity: usize) -> anyhow::Result<String> {
+        let mut content = Vec::with_capacity(capacity);
+        self.read_to_end(&mut content).await?;
+        #[cfg(feature = "protocol-trace")]
+        trace!(
+            "<<<<<<< Recv Until Close Size: {:#?} <<<<<<<",
+            content.len()
+        );
+        Ok(match String::from_utf8(content) {
+            Ok(content) => content,
+            Err(e) => String::from_utf8_lossy(e.as_bytes()).into_owned(),
+        })
     }
 
     /// 检查设备返回是否为"OKAY"。
     ///
     /// # 返回值
-    /// - 成功返回()`，表示检查通过，失败返回错误。
+    /// - 成功返回()`，表示检查通过，失败返回错误。收到 `FAIL` 时返回携带原因的 `AdbError::Unknown`。
     async fn check_okay(&mut self) -> anyhow::Result<()> {
         let data = self.read_string(4).await?;
-        info!("Check Okay Response >>> {:#?}", &data);
+        #[cfg(feature = "protocol-trace")]
+        trace!("Check Okay Response >>> {:#?}", &data);
         if data.eq(AdbProtocolRespDataType::OKAY.as_str()) {
             return Ok(());
         }
+        if data.eq(AdbProtocolRespDataType::FAIL.as_str()) {
+            if let Ok(message) = self.read_string_block().await {
+                return Err(AdbError::unknown(message).into());
+            }
+        }
         Err(anyhow!("Check Okay Failed"))
     }
 
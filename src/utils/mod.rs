@@ -1,16 +1,24 @@
-use anyhow::{anyhow, Context};
+use crate::errors::{AdbError, AdbResult};
+use anyhow::anyhow;
+use sha2::Digest;
+use std::fs::File;
 use std::net::TcpListener;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::process::Command;
+#[cfg(feature = "blocking")]
+use std::thread::sleep;
+#[cfg(feature = "blocking")]
+use std::time::{Duration, Instant};
 use tracing::Level;
 use which::which;
 #[cfg(windows)]
-const ADB_EXECUTE_FILE_NAME: &'static str = "adb.exe";
+const ADB_EXECUTE_FILE_NAME: &str = "adb.exe";
 #[cfg(not(windows))]
-const ADB_EXECUTE_FILE_NAME: &'static str = "adb";
+const ADB_EXECUTE_FILE_NAME: &str = "adb";
 
-const ADBUTILS_ADB_PATH: &'static str = "ADBUTILS_ADB_PATH";
+const ADBUTILS_ADB_PATH: &str = "ADBUTILS_ADB_PATH";
 
+#[allow(dead_code)]
 pub fn init_logger() {
     tracing_subscriber::fmt()
         .with_max_level(Level::INFO)
@@ -21,9 +29,8 @@ pub fn init_logger() {
 }
 
 pub fn adb_path() -> anyhow::Result<PathBuf> {
-    let adb_env = std::env::var(ADBUTILS_ADB_PATH);
-    if adb_env.is_ok() {
-        Ok(PathBuf::from(adb_env.unwrap()))
+    if let Ok(adb_env) = std::env::var(ADBUTILS_ADB_PATH) {
+        Ok(PathBuf::from(adb_env))
     } else {
         match which(ADB_EXECUTE_FILE_NAME) {
             Ok(path) => Ok(path),
@@ -32,11 +39,13 @@ pub fn adb_path() -> anyhow::Result<PathBuf> {
     }
 }
 
+#[allow(dead_code)]
 pub fn get_free_port() -> anyhow::Result<u16> {
     let socket = TcpListener::bind("127.0.0.1:0")?;
     Ok(socket.local_addr()?.port())
 }
 
+#[allow(dead_code)]
 pub fn start_adb_server() {
     match adb_path() {
         Err(_) => {
@@ -51,7 +60,112 @@ pub fn start_adb_server() {
     }
 }
 
+/// 执行 `adb start-server`，找不到 adb 或启动失败时返回错误而不是 panic。
+pub fn try_start_adb_server() -> AdbResult<()> {
+    let output = Command::new(adb_path()?).arg("start-server").output()?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(AdbError::CommandFailed {
+            command: "adb start-server".to_string(),
+            reason: String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        })
+    }
+}
+
+#[allow(dead_code)]
 pub fn vec_to_string(data: &[u8]) -> anyhow::Result<String> {
-    let a = String::from_utf8_lossy(&data.to_vec()).to_string();
+    let a = String::from_utf8_lossy(data).to_string();
     Ok(a)
 }
+
+/// 计算本地文件的 SHA-256，返回小写的十六进制摘要。
+pub fn sha256_file(path: &Path) -> std::io::Result<String> {
+    digest_file::<sha2::Sha256>(path)
+}
+
+/// 计算本地文件的 MD5，返回小写的十六进制摘要。
+pub fn md5_file(path: &Path) -> std::io::Result<String> {
+    digest_file::<md5::Md5>(path)
+}
+
+fn digest_file<D: Digest + std::io::Write>(path: &Path) -> std::io::Result<String> {
+    let mut hasher = D::new();
+    std::io::copy(&mut File::open(path)?, &mut hasher)?;
+    Ok(hasher
+        .finalize()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect())
+}
+
+/// 立即执行一次 `check`，之后每隔 `poll` 重试，直到返回 `Some` 或超过 `timeout`。
+///
+/// # 返回值
+/// - `check` 返回 `Some(value)` 时返回 `value`；超时返回 `AdbError::Timeout`；`check` 出错时直接返回该错误。
+#[cfg(feature = "blocking")]
+pub fn poll_until<T, F>(timeout: Duration, poll: Duration, mut check: F) -> AdbResult<T>
+where
+    F: FnMut() -> AdbResult<Option<T>>,
+{
+    let deadline = Instant::now() + timeout;
+    loop {
+        if let Some(value) = check()? {
+            return Ok(value);
+        }
+        let now = Instant::now();
+        if now >= deadline {
+            return Err(AdbError::timeout(format!(
+                "condition not met within {:?}",
+                timeout
+            )));
+        }
+        sleep(poll.min(deadline - now));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::beans::{parse_ui_elements, By};
+
+    #[cfg(feature = "blocking")]
+    #[test]
+    fn test_poll_until_succeeds_on_nth_check() {
+        let empty = r#"<hierarchy rotation="0"></hierarchy>"#;
+        let ready = r#"<hierarchy rotation="0"><node text="OK" resource-id="android:id/button1" class="android.widget.Button" content-desc="" bounds="[0,0][100,50]" /></hierarchy>"#;
+        let mut dumps = vec![empty, empty, ready].into_iter();
+        let by = By::Text("OK".to_string());
+        let mut calls = 0;
+        let element = poll_until(Duration::from_secs(5), Duration::from_millis(1), || {
+            calls += 1;
+            let xml = dumps.next().unwrap();
+            Ok(parse_ui_elements(xml).into_iter().find(|e| by.matches(e)))
+        })
+        .unwrap();
+        assert_eq!(calls, 3);
+        assert_eq!(element.center(), (50, 25));
+    }
+
+    #[cfg(feature = "blocking")]
+    #[test]
+    fn test_poll_until_times_out() {
+        let result: AdbResult<()> =
+            poll_until(Duration::from_millis(20), Duration::from_millis(5), || {
+                Ok(None)
+            });
+        assert!(matches!(result, Err(AdbError::Timeout(_))));
+    }
+
+    #[test]
+    fn test_file_digests() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("abc.txt");
+        std::fs::write(&path, b"abc").unwrap();
+        assert_eq!(
+            sha256_file(&path).unwrap(),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        assert_eq!(md5_file(&path).unwrap(), "900150983cd24fb0d6963f7d28e17f72");
+    }
+}
@@ -0,0 +1,84 @@
+#![allow(dead_code)]
+//! 测试用的简易 adb server，按脚本应答 smart-socket 与 sync 协议。
+
+use std::io::{Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::thread;
+
+/// 在随机端口启动一个 mock server，`handler` 在后台线程中处理连接，返回监听地址。
+pub fn serve<F>(handler: F) -> String
+where
+    F: FnOnce(TcpListener) + Send + 'static,
+{
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap().to_string();
+    thread::spawn(move || handler(listener));
+    addr
+}
+
+/// 读取一个 `<4 位十六进制长度><内容>` 格式的请求。
+pub fn read_request(stream: &mut TcpStream) -> String {
+    let mut length = [0; 4];
+    stream.read_exact(&mut length).unwrap();
+    let length = usize::from_str_radix(std::str::from_utf8(&length).unwrap(), 16).unwrap();
+    let mut payload = vec![0; length];
+    stream.read_exact(&mut payload).unwrap();
+    String::from_utf8(payload).unwrap()
+}
+
+pub fn write_okay(stream: &mut TcpStream) {
+    stream.write_all(b"OKAY").unwrap();
+}
+
+pub fn write_block(stream: &mut TcpStream, payload: &str) {
+    stream
+        .write_all(format!("{:04x}{}", payload.len(), payload).as_bytes())
+        .unwrap();
+}
+
+pub fn write_fail(stream: &mut TcpStream, message: &str) {
+    stream.write_all(b"FAIL").unwrap();
+    write_block(stream, message);
+}
+
+/// 接受一个连接，应答 `host:transport:<serial>` 与随后的一个服务请求，返回该服务请求。
+pub fn accept_transport(listener: &TcpListener) -> (TcpStream, String) {
+    let (mut stream, _) = listener.accept().unwrap();
+    let transport = read_request(&mut stream);
+    assert!(transport.starts_with("host:transport:"), "{}", transport);
+    write_okay(&mut stream);
+    let service = read_request(&mut stream);
+    write_okay(&mut stream);
+    (stream, service)
+}
+
+/// 读取一个 sync 数据包，返回命令和数据；`DONE` 的 4 字节参数作为数据返回。
+pub fn read_sync_packet(stream: &mut TcpStream) -> (String, Vec<u8>) {
+    let mut header = [0; 8];
+    stream.read_exact(&mut header).unwrap();
+    let id = String::from_utf8(header[..4].to_vec()).unwrap();
+    if id == "DONE" {
+        return (id, header[4..].to_vec());
+    }
+    let length = u32::from_le_bytes(header[4..].try_into().unwrap()) as usize;
+    let mut data = vec![0; length];
+    stream.read_exact(&mut data).unwrap();
+    (id, data)
+}
+
+pub fn write_sync_packet(stream: &mut TcpStream, id: &str, data: &[u8]) {
+    stream.write_all(id.as_bytes()).unwrap();
+    stream
+        .write_all(&(data.len() as u32).to_le_bytes())
+        .unwrap();
+    stream.write_all(data).unwrap();
+}
+
+/// 接受一个连接，应答 `get-features` 请求，返回逗号分隔的 `features`。
+pub fn accept_features(listener: &TcpListener, features: &str) {
+    let (mut stream, _) = listener.accept().unwrap();
+    let request = read_request(&mut stream);
+    assert!(request.ends_with(":get-features"), "{}", request);
+    write_okay(&mut stream);
+    write_block(&mut stream, features);
+}
@@ -1,4 +1,4 @@
-const DEFAULT_ADB_ADDR: &'static str = "127.0.0.1:5037";
+const DEFAULT_ADB_ADDR: &str = "127.0.0.1:5037";
 #[cfg(feature = "blocking")]
 mod test_adb {
     use crate::DEFAULT_ADB_ADDR;
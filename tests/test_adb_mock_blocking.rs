@@ -0,0 +1,378 @@
+mod common;
+
+#[cfg(feature = "blocking")]
+mod test_adb_mock {
+    use crate::common::*;
+    use radb::beans::DeviceState;
+    #[cfg(feature = "serde")]
+    use radb::beans::DeviceSummary;
+    use radb::client::{AdbClient, ClientConfig, TrackReconnect};
+    use radb::errors::AdbError;
+    use std::net::TcpListener;
+    use std::time::Duration;
+
+    #[test]
+    fn test_track_devices() {
+        let addr = serve(|listener| {
+            let (mut stream, _) = listener.accept().unwrap();
+            assert_eq!(read_request(&mut stream), "host:track-devices");
+            write_okay(&mut stream);
+            write_block(&mut stream, "emulator-5554\tdevice\n");
+            write_block(
+                &mut stream,
+                "emulator-5554\tdevice\nemulator-5556\toffline\n",
+            );
+        });
+        let adb = AdbClient::new(addr);
+        let mut snapshots = adb.track_devices_with(None).unwrap();
+        let first = snapshots.next().unwrap();
+        assert_eq!(first.devices.len(), 1);
+        assert_eq!(first.devices[0].serial.as_deref(), Some("emulator-5554"));
+        assert!(!first.resynced);
+        assert_eq!(snapshots.next().unwrap().devices.len(), 2);
+        assert!(snapshots.next().is_none());
+    }
+
+    #[test]
+    fn test_track_devices_resumes_after_server_restart() {
+        let addr = serve(|listener| {
+            let addr = listener.local_addr().unwrap();
+            let (mut stream, _) = listener.accept().unwrap();
+            read_request(&mut stream);
+            write_okay(&mut stream);
+            write_block(&mut stream, "emulator-5554\tdevice\n");
+            // 模拟 adb server 重启：关闭连接和监听，稍后在同一端口重新监听
+            drop(stream);
+            drop(listener);
+            std::thread::sleep(Duration::from_millis(150));
+            let listener = TcpListener::bind(addr).unwrap();
+            let (mut stream, _) = listener.accept().unwrap();
+            assert_eq!(read_request(&mut stream), "host:track-devices");
+            write_okay(&mut stream);
+            write_block(
+                &mut stream,
+                "emulator-5554\tdevice\nemulator-5556\tdevice\n",
+            );
+        });
+        let adb = AdbClient::new(addr);
+        let mut snapshots = adb
+            .track_devices_with(Some(TrackReconnect {
+                max_attempts: 3,
+                initial_backoff: Duration::from_millis(100),
+                max_backoff: Duration::from_millis(200),
+            }))
+            .unwrap();
+        let first = snapshots.next().unwrap();
+        assert!(!first.resynced);
+        assert_eq!(first.devices.len(), 1);
+        let resumed = snapshots.next().unwrap();
+        assert!(resumed.resynced);
+        assert_eq!(resumed.devices.len(), 2);
+        assert!(snapshots.next().is_none());
+    }
+
+    #[test]
+    fn test_track_devices_stays_finished_after_giving_up() {
+        let addr = serve(|listener| {
+            let (mut stream, _) = listener.accept().unwrap();
+            read_request(&mut stream);
+            write_okay(&mut stream);
+            write_block(&mut stream, "emulator-5554\tdevice\n");
+        });
+        let adb = AdbClient::new(&addr);
+        let mut snapshots = adb
+            .track_devices_with(Some(TrackReconnect {
+                max_attempts: 1,
+                initial_backoff: Duration::from_millis(10),
+                max_backoff: Duration::from_millis(10),
+            }))
+            .unwrap();
+        assert!(snapshots.next().is_some());
+        assert!(snapshots.next().is_none());
+        // adb server 恢复后，已经结束的迭代器也不会重新连接
+        let listener = TcpListener::bind(&addr).unwrap();
+        assert!(snapshots.next().is_none());
+        listener.set_nonblocking(true).unwrap();
+        std::thread::sleep(Duration::from_millis(50));
+        assert!(listener.accept().is_err());
+    }
+
+    #[test]
+    fn test_builder_applies_read_timeout() {
+        let addr = serve(|listener| {
+            let (mut stream, _) = listener.accept().unwrap();
+            assert_eq!(read_request(&mut stream), "host:version");
+            // 不应答，等待客户端超时后断开
+            std::thread::sleep(Duration::from_secs(2));
+        });
+        let mut adb = AdbClient::builder()
+            .addr(addr)
+            .read_timeout(Duration::from_millis(200))
+            .build()
+            .unwrap();
+        let start = std::time::Instant::now();
+        assert!(adb.server_version().is_err());
+        assert!(start.elapsed() < Duration::from_secs(1));
+    }
+
+    #[test]
+    fn test_builder_reports_connection_failure() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        drop(listener);
+        let err = AdbClient::builder().addr(addr).build().err().unwrap();
+        assert_eq!(err.error_code(), "CONNECTION_FAILED");
+    }
+
+    #[test]
+    fn test_try_new_returns_error_instead_of_panicking() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        drop(listener);
+        match AdbClient::try_new(addr) {
+            Err(AdbError::ConnectionFailed(message)) => {
+                assert!(message.contains(&addr.to_string()), "{}", message)
+            }
+            other => panic!("unexpected result {:?}", other.err()),
+        }
+
+        let addr = serve(|listener| {
+            let (mut stream, _) = listener.accept().unwrap();
+            assert_eq!(read_request(&mut stream), "host:version");
+            write_okay(&mut stream);
+            write_block(&mut stream, "0029");
+        });
+        let mut adb = AdbClient::try_new(addr).unwrap();
+        assert_eq!(adb.server_version().unwrap(), "41");
+    }
+
+    #[test]
+    fn test_server_version_u32_and_host_features() {
+        let addr = serve(|listener| {
+            let (mut stream, _) = listener.accept().unwrap();
+            assert_eq!(read_request(&mut stream), "host:version");
+            write_okay(&mut stream);
+            write_block(&mut stream, "0029");
+            drop(stream);
+            let (mut stream, _) = listener.accept().unwrap();
+            assert_eq!(read_request(&mut stream), "host:host-features");
+            write_okay(&mut stream);
+            write_block(&mut stream, "shell_v2,cmd,push_sync,");
+        });
+        let mut adb = AdbClient::new(&addr);
+        assert_eq!(adb.server_version_u32().unwrap(), 41);
+        let mut adb = AdbClient::new(&addr);
+        assert_eq!(
+            adb.host_features().unwrap(),
+            vec!["shell_v2", "cmd", "push_sync"]
+        );
+    }
+
+    /// 每个连接应答一次 `host:devices-l`，依次返回 `lists` 中的设备列表。
+    fn serve_device_lists(lists: &'static [&'static str]) -> String {
+        serve(move |listener| {
+            for list in lists {
+                let (mut stream, _) = listener.accept().unwrap();
+                assert_eq!(read_request(&mut stream), "host:devices-l");
+                write_okay(&mut stream);
+                write_block(&mut stream, list);
+            }
+        })
+    }
+
+    #[test]
+    fn test_exec_host_service() {
+        let addr = serve(|listener| {
+            let (mut stream, _) = listener.accept().unwrap();
+            assert_eq!(read_request(&mut stream), "host:version");
+            write_okay(&mut stream);
+            write_block(&mut stream, "0029");
+            assert_eq!(read_request(&mut stream), "host:no-such-service");
+            write_fail(&mut stream, "unknown host service");
+        });
+        let mut adb = AdbClient::new(addr);
+        assert_eq!(adb.exec_host_service("host:version").unwrap(), "0029");
+        assert!(adb.exec_host_service("host:no-such-service").is_err());
+    }
+
+    #[test]
+    fn test_try_clone_opens_new_connection_with_same_config() {
+        let addr = serve_device_lists(&["emulator-5554\tdevice\n", "emulator-5556\tdevice\n"]);
+        let mut adb = AdbClient::builder()
+            .addr(&addr)
+            .timeout(Duration::from_secs(5))
+            .build()
+            .unwrap();
+        let mut cloned = adb.try_clone().unwrap();
+        assert_eq!(cloned.config(), adb.config());
+        assert_eq!(
+            adb.config(),
+            &ClientConfig {
+                addr: addr.clone(),
+                connect_timeout: Some(Duration::from_secs(5)),
+                read_timeout: Some(Duration::from_secs(5)),
+                write_timeout: Some(Duration::from_secs(5)),
+            }
+        );
+
+        let devices = adb.list_devices().unwrap();
+        assert_eq!(devices[0].serial.as_deref(), Some("emulator-5554"));
+        let devices = cloned.list_devices().unwrap();
+        assert_eq!(devices[0].serial.as_deref(), Some("emulator-5556"));
+    }
+
+    #[test]
+    fn test_list_devices_reads_devices_l() {
+        // 两个序列号相同的设备，只能通过 transport_id 区分
+        let addr = serve_device_lists(&[
+            "emulator-5554          device product:sdk_gphone64_x86_64 model:sdk_gphone64_x86_64 device:emu64xa transport_id:1\n\
+             0123456789ABCDEF       unauthorized usb:1-1 transport_id:3\n\
+             0123456789ABCDEF       device usb:1-2 product:oriole model:Pixel_6 device:oriole transport_id:4\n\
+             192.168.1.20:5555      offline\n\
+             FEDCBA9876543210       no permissions (missing udev rules? user is in the plugdev group); see [http://developer.android.com/tools/device.html] usb:1-3 transport_id:300\n",
+        ]);
+        let devices = AdbClient::new(&addr).list_devices().unwrap();
+        assert_eq!(devices.len(), 5);
+        assert_eq!(devices[0].serial.as_deref(), Some("emulator-5554"));
+        assert_eq!(devices[0].state, Some(DeviceState::Device));
+        assert_eq!(devices[0].transport_id, Some(1));
+        assert_eq!(devices[0].extra["model"], "sdk_gphone64_x86_64");
+        assert_eq!(devices[0].extra["device"], "emu64xa");
+        assert!(!devices[0].extra.contains_key("transport_id"));
+        assert_eq!(devices[1].state, Some(DeviceState::Unauthorized));
+        assert_eq!(devices[1].extra["usb"], "1-1");
+        assert_eq!(devices[2].serial, devices[1].serial);
+        assert_eq!(devices[2].transport_id, Some(4));
+        assert_eq!(
+            devices[2].get_open_transport_prefix(None).unwrap(),
+            "host:transport-id:4"
+        );
+        assert_eq!(devices[3].state, Some(DeviceState::Offline));
+        assert_eq!(devices[3].transport_id, None);
+        assert!(devices[3].extra.is_empty());
+        assert_eq!(devices[4].state, Some(DeviceState::NoPermissions));
+        assert_eq!(devices[4].transport_id, Some(300));
+        assert_eq!(devices[4].extra["usb"], "1-3");
+        assert_eq!(devices[4].extra.len(), 1);
+    }
+
+    #[test]
+    fn test_only_device() {
+        let addr = serve_device_lists(&[
+            "",
+            "emulator-5554          device product:sdk model:sdk transport_id:1\n",
+            "emulator-5554          device transport_id:1\n\
+             emulator-5556          device transport_id:2\n",
+        ]);
+        let err = AdbClient::new(&addr).only_device().unwrap_err();
+        assert!(err.to_string().contains("found 0"), "{}", err);
+
+        let device = AdbClient::new(&addr).only_device().unwrap();
+        assert_eq!(device.serial.as_deref(), Some("emulator-5554"));
+        assert_eq!(device.transport_id, Some(1));
+        assert_eq!(device.addr.to_string(), addr);
+
+        let err = AdbClient::new(&addr).only_device().unwrap_err();
+        assert!(err.to_string().contains("found 2"), "{}", err);
+    }
+
+    #[test]
+    fn test_device_selection_uses_client_addr() {
+        let addr = serve(|listener| {
+            listener.accept().unwrap();
+            let (mut stream, _) = listener.accept().unwrap();
+            assert_eq!(read_request(&mut stream), "host:transport-id:3");
+            write_okay(&mut stream);
+            assert_eq!(read_request(&mut stream), r#"shell:"echo" "ok""#);
+            write_okay(&mut stream);
+            std::io::Write::write_all(&mut stream, b"ok\n").unwrap();
+        });
+        let mut adb = AdbClient::new(&addr);
+        let device = adb.device("emulator-5554").unwrap();
+        assert_eq!(device.serial.as_deref(), Some("emulator-5554"));
+        assert_eq!(device.addr.to_string(), addr);
+
+        let mut device = adb.device_by_transport_id(3).unwrap();
+        assert_eq!(device.serial, None);
+        assert_eq!(device.transport_id, Some(3));
+        assert_eq!(device.shell(&["echo", "ok"]).unwrap(), "ok\n");
+    }
+
+    #[cfg(target_os = "linux")]
+    fn open_fds() -> usize {
+        std::fs::read_dir("/proc/self/fd").unwrap().count()
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_short_lived_clients_and_devices_release_connections() {
+        const ROUNDS: usize = 300;
+        let addr = serve(|listener| {
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                std::thread::spawn(move || {
+                    let request = read_request(&mut stream);
+                    write_okay(&mut stream);
+                    if request == "host:devices-l" {
+                        write_block(&mut stream, "emulator-5554 device transport_id:1\n");
+                        return;
+                    }
+                    read_request(&mut stream);
+                    write_okay(&mut stream);
+                    // 等待客户端关闭连接，shell 会话在 drop 时先发送 exit
+                    let mut rest = vec![];
+                    let _ = std::io::Read::read_to_end(&mut stream, &mut rest);
+                });
+            }
+        });
+        let before = open_fds();
+        for _ in 0..ROUNDS {
+            let mut adb = AdbClient::new(&addr);
+            let mut device = adb.only_device().unwrap();
+            adb.close().unwrap();
+            drop(device.shell_persistent().unwrap());
+        }
+        // 其他测试并发运行也会占用少量描述符，泄漏时每轮至少会多出一个
+        let leaked = open_fds().saturating_sub(before);
+        assert!(leaked < ROUNDS / 3, "{} file descriptors leaked", leaked);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_list_devices_json_is_best_effort() {
+        let addr = serve(|listener| {
+            let (mut stream, _) = listener.accept().unwrap();
+            assert_eq!(read_request(&mut stream), "host:devices-l");
+            write_okay(&mut stream);
+            write_block(
+                &mut stream,
+                "emulator-5554 device product:sdk model:sdk transport_id:1
+                 emulator-5556 device transport_id:2
+                 0123456789ABCDEF unauthorized usb:1-1 transport_id:3
+",
+            );
+            drop(stream);
+            for value in ["Pixel 7", "google", "14"] {
+                let (mut stream, service) = accept_transport(&listener);
+                assert!(service.contains("getprop"), "{}", service);
+                std::io::Write::write_all(&mut stream, format!("{}\n", value).as_bytes()).unwrap();
+            }
+            // emulator-5556 在读取属性时断开
+            for _ in 0..3 {
+                let (mut stream, _) = listener.accept().unwrap();
+                read_request(&mut stream);
+                write_fail(&mut stream, "device offline");
+            }
+        });
+        let mut adb = AdbClient::new(addr);
+        let json = adb.list_devices_json().unwrap();
+        let summaries: Vec<DeviceSummary> = serde_json::from_str(&json).unwrap();
+        assert_eq!(summaries.len(), 3);
+        assert_eq!(summaries[0].model.as_deref(), Some("Pixel 7"));
+        assert_eq!(summaries[0].brand.as_deref(), Some("google"));
+        assert_eq!(summaries[0].android_version.as_deref(), Some("14"));
+        assert_eq!(summaries[1].transport_id, Some(2));
+        assert_eq!(summaries[1].model, None);
+        assert_eq!(summaries[2].state, "unauthorized");
+    }
+}
@@ -1,4 +1,5 @@
-const DEFAULT_ADB_ADDR: &'static str = "127.0.0.1:5037";
+#[cfg(feature = "tokio_async")]
+const DEFAULT_ADB_ADDR: &str = "127.0.0.1:5037";
 
 #[cfg(feature = "tokio_async")]
 mod test_adb_s2 {
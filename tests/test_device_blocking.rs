@@ -1,13 +1,12 @@
 #[cfg(feature = "blocking")]
 mod test_device {
     use std::fmt::Debug;
-    use std::io::Write;
     use std::net::ToSocketAddrs;
     use std::sync::{Arc, RwLock};
     use std::thread::sleep;
     use std::time::Duration;
 
-
+    use radb::beans::{DeviceState, LogPriority, LogcatFilter, ScreenRecordOptions, TransportKind};
     use radb::client::{AdbClient, AdbDevice};
 
     fn get_android_emulator_device() -> Option<AdbDevice<impl ToSocketAddrs + Clone + Debug>> {
@@ -32,16 +31,37 @@ mod test_device {
     fn test_device_logcat() {
         let mut device = get_android_emulator_device().unwrap();
         println!("{:#?}", device.addr);
-        let mutex = Arc::new(RwLock::new(true));
-        let mutex_arc = Arc::clone(&mutex);
-        let _ = std::thread::spawn(move || {
-            for i in device.logcat(false, None, mutex_arc).unwrap() {
+        let lines = device
+            .logcat(false, None, Arc::new(RwLock::new(true)))
+            .unwrap();
+        let handle = lines.stop_handle().unwrap();
+        let reader = std::thread::spawn(move || {
+            for i in lines {
                 println!("{:#?}", i)
             }
         });
         sleep(Duration::from_secs(10));
-        let mut target = mutex.write().unwrap();
-        *target = false;
+        handle.stop().unwrap();
+        reader.join().unwrap();
+    }
+
+    #[test]
+    fn test_device_logcat_filtered() {
+        let mut device = get_android_emulator_device().unwrap();
+        let filter = LogcatFilter {
+            specs: vec![("ActivityManager".to_string(), LogPriority::Verbose)],
+            ..Default::default()
+        };
+        let lock = Arc::new(RwLock::new(true));
+        let lines: Vec<String> = device
+            .logcat_filtered(false, &filter, lock)
+            .unwrap()
+            .filter(|line| !line.starts_with("---------"))
+            .take(10)
+            .collect();
+        for line in lines {
+            assert!(line.contains("/ActivityManager"), "{}", line);
+        }
     }
 
     #[test]
@@ -52,4 +72,229 @@ mod test_device {
         let output = device.shell(&["ps", "-ef"]).unwrap();
         assert!(output.contains(pkg))
     }
+
+    #[test]
+    fn test_push_pull_with_progress() {
+        let mut device = get_android_emulator_device().unwrap();
+        let tmpdir = tempfile::tempdir().unwrap();
+        let local = tmpdir.path().join("progress.bin");
+        std::fs::write(&local, vec![7u8; 200 * 1024]).unwrap();
+        let remote = "/data/local/tmp/progress.bin";
+
+        let mut pushed = vec![];
+        device
+            .push_with_progress(local.to_str().unwrap(), remote, |done, total| {
+                pushed.push((done, total))
+            })
+            .unwrap();
+        assert_eq!(pushed.last(), Some(&(200 * 1024, Some(200 * 1024))));
+
+        let mut pulled = vec![];
+        let dest = tmpdir.path().join("progress.pull");
+        device
+            .pull_with_progress(remote, &dest, |done, total| pulled.push((done, total)))
+            .unwrap();
+        assert_eq!(pulled.last(), Some(&(200 * 1024, Some(200 * 1024))));
+        device.remove(remote).unwrap();
+    }
+
+    #[test]
+    fn test_pull_bytes() {
+        let mut device = get_android_emulator_device().unwrap();
+        let tmpdir = tempfile::tempdir().unwrap();
+        let local = tmpdir.path().join("small.txt");
+        let content = b"radb pull_bytes\n\x00\xff".to_vec();
+        std::fs::write(&local, &content).unwrap();
+        let remote = "/data/local/tmp/small.txt";
+        device.push(local.to_str().unwrap(), remote).unwrap();
+        assert_eq!(device.pull_bytes(remote).unwrap(), content);
+        device.remove(remote).unwrap();
+    }
+
+    #[test]
+    fn test_file_ops() {
+        let mut device = get_android_emulator_device().unwrap();
+        let dir = "/data/local/tmp/radb_file_ops/nested";
+        device.mkdir(dir, true).unwrap();
+        let src = format!("{}/a.txt", dir);
+        let dst = format!("{}/b.txt", dir);
+        device.touch(&src).unwrap();
+        device.cp(&src, &dst, false).unwrap();
+        device.remove(&dst).unwrap();
+        device.mv(&src, &dst).unwrap();
+        assert!(!device.exists(&src).unwrap());
+        assert!(device.exists(&dst).unwrap());
+        device.remove(&dst).unwrap();
+        assert!(device.mv(&src, &dst).is_err());
+        assert!(device.remove("/data/local/tmp/radb_file_ops").is_err());
+        device
+            .remove_dir("/data/local/tmp/radb_file_ops", true)
+            .unwrap();
+        assert!(!device.exists("/data/local/tmp/radb_file_ops").unwrap());
+    }
+
+    #[test]
+    fn test_chmod_pushed_file() {
+        let mut device = get_android_emulator_device().unwrap();
+        let remote = "/data/local/tmp/chmod.sh";
+        device
+            .push_bytes(b"#!/system/bin/sh\n", remote, 0o644)
+            .unwrap();
+        device.chmod(remote, 0o755).unwrap();
+        assert_eq!(device.stat(remote).unwrap().permissions(), 0o755);
+        device.remove(remote).unwrap();
+    }
+
+    #[test]
+    fn test_push_verified() {
+        use sha2::Digest;
+        let mut device = get_android_emulator_device().unwrap();
+        let tmpdir = tempfile::tempdir().unwrap();
+        let local = tmpdir.path().join("verified.bin");
+        let content: Vec<u8> = (0..200_000u32).map(|i| (i % 251) as u8).collect();
+        std::fs::write(&local, &content).unwrap();
+        let remote = "/data/local/tmp/verified.bin";
+        device.push_verified(&local, remote).unwrap();
+        let expected: String = sha2::Sha256::digest(&content)
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect();
+        assert_eq!(device.remote_sha256(remote).unwrap(), expected);
+        device.remove(remote).unwrap();
+    }
+
+    #[test]
+    fn test_jdwp() {
+        let mut device = get_android_emulator_device().unwrap();
+        let (tx, rx) = std::sync::mpsc::channel();
+        let mut jdwp_device = get_android_emulator_device().unwrap();
+        std::thread::spawn(move || tx.send(jdwp_device.jdwp()).unwrap());
+        let pids = rx
+            .recv_timeout(Duration::from_secs(10))
+            .expect("jdwp did not return within 10s")
+            .unwrap();
+        let running = device.shell(&["ps", "-A", "-o", "PID"]).unwrap();
+        for pid in pids {
+            assert!(
+                running.split_whitespace().any(|p| p == pid.to_string()),
+                "{} is not a running process",
+                pid
+            );
+        }
+    }
+
+    #[test]
+    fn test_kill_process() {
+        let mut device = get_android_emulator_device().unwrap();
+        device
+            .shell(&["sh", "-c", "nohup sleep 600 >/dev/null 2>&1 &"])
+            .unwrap();
+        assert!(!device.pidof("sleep").unwrap().is_empty());
+        assert!(!device.kill_process("sleep").unwrap().is_empty());
+        assert!(device.pidof("sleep").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_push_bytes() {
+        let mut device = get_android_emulator_device().unwrap();
+        let remote = "/data/local/tmp/push_bytes.txt";
+        let content = b"pushed from memory".to_vec();
+        device.push_bytes(&content, remote, 0o644).unwrap();
+        assert_eq!(device.pull_bytes(remote).unwrap(), content);
+        assert_eq!(device.stat(remote).unwrap().mode & 0o777, 0o644);
+        device.remove(remote).unwrap();
+    }
+
+    #[test]
+    fn test_forward_remove() {
+        let mut device = get_android_emulator_device().unwrap();
+        let has_forward = |device: &mut AdbDevice<_>| {
+            device
+                .forward_list()
+                .unwrap()
+                .iter()
+                .any(|x| x.local == "tcp:8080")
+        };
+        device.forward("tcp:8080", "tcp:8080", false).unwrap();
+        assert!(has_forward(&mut device));
+        device.forward_remove("tcp:8080").unwrap();
+        assert!(!has_forward(&mut device));
+    }
+
+    #[test]
+    fn test_forward_tcp() {
+        let mut device = get_android_emulator_device().unwrap();
+        let port = device.forward_tcp("tcp:9999").unwrap();
+        assert_ne!(port, 0);
+        device.forward_cleanup().unwrap();
+    }
+
+    #[test]
+    fn test_screenshot_exec_out_matches_file() {
+        let mut device = get_android_emulator_device().unwrap();
+        let exec_out = device.screenshot().unwrap();
+        let via_file = device.screenshot_via_file().unwrap();
+        assert_eq!(exec_out.dimensions(), via_file.dimensions());
+    }
+
+    #[test]
+    fn test_wait_for_online_device() {
+        let mut device = get_android_emulator_device().unwrap();
+        let start = std::time::Instant::now();
+        device
+            .wait_for(
+                TransportKind::Any,
+                DeviceState::Device,
+                Duration::from_secs(10),
+            )
+            .unwrap();
+        assert!(start.elapsed() < Duration::from_secs(1));
+    }
+
+    #[test]
+    fn test_app_start_by_package_name() {
+        let mut device = get_android_emulator_device().unwrap();
+        device.app_start("com.android.settings").unwrap();
+        sleep(Duration::from_secs(2));
+        assert_eq!(
+            device.current_activity().unwrap().package,
+            "com.android.settings"
+        );
+        device.app_stop("com.android.settings").unwrap();
+    }
+
+    #[test]
+    fn test_screenrecord() {
+        let mut device = get_android_emulator_device().unwrap();
+        let handle = device
+            .screenrecord(
+                "/sdcard/radb_record.mp4",
+                ScreenRecordOptions {
+                    time_limit: Some(2),
+                    auto_restart: true,
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+        sleep(Duration::from_secs(5));
+        let local_dir = std::env::temp_dir();
+        let files = handle.stop(&local_dir).unwrap();
+        assert!(files.len() >= 2);
+        for file in files {
+            assert!(std::fs::metadata(&file).unwrap().len() > 0);
+            std::fs::remove_file(file).unwrap();
+        }
+    }
+
+    // 需要正在运行的 emulator-5554，使用 RUSTFLAGS="--cfg emulator" cargo test 运行
+    #[cfg(emulator)]
+    #[test]
+    fn test_emulator_console() {
+        let mut device = AdbDevice::new("emulator-5554", "127.0.0.1:5037");
+        let mut console = device.emulator_console().unwrap();
+        console.geo_fix(31.2304, 121.4737).unwrap();
+        console.power(80).unwrap();
+        assert!(console.send("avd name").unwrap().contains('\n'));
+        console.close().unwrap();
+    }
 }
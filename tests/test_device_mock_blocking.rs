@@ -0,0 +1,2352 @@
+mod common;
+
+#[cfg(feature = "blocking")]
+mod test_device_mock {
+    use crate::common::*;
+    use radb::beans::{
+        AdbCommand, CommandLog, DeviceState, InstallOptions, NetworkType, RebootMode,
+        ReplayErrorPolicy, ReplayOptions, SettingsNamespace, TransportKind, VolumeStream,
+    };
+    use radb::client::{AdbDevice, EmulatorConsole};
+    use radb::errors::AdbError;
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpStream;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::{Arc, RwLock};
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn test_push_surfaces_fail_reason() {
+        let addr = serve(|listener| {
+            accept_features(&listener, "shell_v2,cmd");
+            let (mut stream, service) = accept_transport(&listener);
+            assert_eq!(service, "sync:");
+            let (id, path) = read_sync_packet(&mut stream);
+            assert_eq!(id, "SEND");
+            assert_eq!(path, b"/system/readonly.txt,33261");
+            while read_sync_packet(&mut stream).0 != "DONE" {}
+            write_sync_packet(
+                &mut stream,
+                "FAIL",
+                b"couldn't create file: Read-only file system",
+            );
+        });
+        let tmpdir = tempfile::tempdir().unwrap();
+        let local = tmpdir.path().join("readonly.txt");
+        std::fs::write(&local, b"data").unwrap();
+
+        let mut device = AdbDevice::new("emulator-5554", addr);
+        let err = device
+            .push(local.to_str().unwrap(), "/system/readonly.txt")
+            .unwrap_err();
+        match err.downcast_ref::<AdbError>() {
+            Some(AdbError::FileOperationFailed(reason)) => {
+                assert_eq!(reason, "couldn't create file: Read-only file system")
+            }
+            other => panic!("unexpected error {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_push_reports_truncated_fail_length() {
+        let addr = serve(|listener| {
+            accept_features(&listener, "shell_v2,cmd");
+            let (mut stream, _) = accept_transport(&listener);
+            read_sync_packet(&mut stream);
+            while read_sync_packet(&mut stream).0 != "DONE" {}
+            // FAIL 之后只有 2 字节长度就断开
+            std::io::Write::write_all(&mut stream, b"FAIL\x10\x00").unwrap();
+        });
+        let tmpdir = tempfile::tempdir().unwrap();
+        let local = tmpdir.path().join("data.txt");
+        std::fs::write(&local, b"data").unwrap();
+
+        let mut device = AdbDevice::new("emulator-5554", addr);
+        let err = device
+            .push(local.to_str().unwrap(), "/sdcard/data.txt")
+            .unwrap_err();
+        assert!(
+            !matches!(
+                err.downcast_ref::<AdbError>(),
+                Some(AdbError::FileOperationFailed(_))
+            ),
+            "{:?}",
+            err
+        );
+        assert!(
+            format!("{:#}", err).contains("failed to fill whole buffer"),
+            "{:#}",
+            err
+        );
+    }
+
+    #[test]
+    fn test_with_retry_retries_transient_errors() {
+        let mut device = AdbDevice::new("emulator-5554", "127.0.0.1:5037");
+        let mut calls = 0;
+        let result = device.with_retry(3, Duration::from_millis(10), |_| {
+            calls += 1;
+            if calls < 3 {
+                Err(AdbError::timeout("device busy"))
+            } else {
+                Ok(calls)
+            }
+        });
+        assert_eq!(result.unwrap(), 3);
+
+        let mut calls = 0;
+        let result: Result<(), _> = device.with_retry(3, Duration::from_millis(10), |_| {
+            calls += 1;
+            Err(AdbError::PermissionDenied("not root".into()))
+        });
+        assert!(matches!(result, Err(AdbError::PermissionDenied(_))));
+        assert_eq!(calls, 1);
+    }
+
+    #[test]
+    fn test_wait_for_times_out() {
+        let addr = serve(|listener| {
+            let (mut stream, _) = listener.accept().unwrap();
+            assert_eq!(
+                read_request(&mut stream),
+                "host-serial:emulator-5554:wait-for-usb-recovery"
+            );
+            write_okay(&mut stream);
+            // 设备一直未进入目标状态，保持连接直到客户端放弃
+            let _ = std::io::Read::read(&mut stream, &mut [0; 1]);
+        });
+        let mut device = AdbDevice::new("emulator-5554", addr);
+        let err = device
+            .wait_for(
+                TransportKind::Usb,
+                DeviceState::Recovery,
+                Duration::from_millis(200),
+            )
+            .unwrap_err();
+        assert!(matches!(err, AdbError::Timeout(_)), "{:?}", err);
+    }
+
+    #[test]
+    fn test_wait_for_rejects_non_waitable_state() {
+        // 地址上没有 adb server，状态检查必须在连接之前完成
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap().to_string();
+        drop(listener);
+        let mut device = AdbDevice::new("emulator-5554", addr);
+        let err = device
+            .wait_for(
+                TransportKind::Any,
+                DeviceState::Offline,
+                Duration::from_secs(1),
+            )
+            .unwrap_err();
+        assert!(
+            matches!(&err, AdbError::Unknown(reason) if reason.contains("offline")),
+            "{:?}",
+            err
+        );
+    }
+
+    #[test]
+    fn test_state_parses_and_maps_missing_device() {
+        let addr = serve(|listener| {
+            for reply in [Some("offline"), None] {
+                let (mut stream, _) = listener.accept().unwrap();
+                assert_eq!(
+                    read_request(&mut stream),
+                    "host-serial:emulator-5554:get-state"
+                );
+                match reply {
+                    Some(state) => {
+                        write_okay(&mut stream);
+                        write_block(&mut stream, state);
+                    }
+                    None => write_fail(&mut stream, "device 'emulator-5554' not found"),
+                }
+                drop(stream);
+            }
+        });
+        let mut device = AdbDevice::new("emulator-5554", addr);
+        assert_eq!(device.state().unwrap(), DeviceState::Offline);
+        assert!(matches!(
+            device.state().unwrap_err(),
+            AdbError::DeviceNotFound(_)
+        ));
+    }
+
+    #[test]
+    fn test_reboot_uses_reboot_service() {
+        let addr = serve(|listener| {
+            for expected in ["reboot:bootloader", "reboot:"] {
+                let (_, service) = accept_transport(&listener);
+                assert_eq!(service, expected);
+            }
+        });
+        let mut device = AdbDevice::new("emulator-5554", addr);
+        device.reboot(RebootMode::Bootloader).unwrap();
+        device.reboot(RebootMode::System).unwrap();
+    }
+
+    #[test]
+    fn test_root_responses() {
+        let addr = serve(|listener| {
+            for resp in [
+                "restarting adbd as root\n",
+                "adbd is already running as root\n",
+                "adbd cannot run as root in production builds\n",
+            ] {
+                let (mut stream, service) = accept_transport(&listener);
+                assert_eq!(service, "root:");
+                std::io::Write::write_all(&mut stream, resp.as_bytes()).unwrap();
+            }
+        });
+        let mut device = AdbDevice::new("emulator-5554", addr);
+        assert_eq!(device.root().unwrap(), "restarting adbd as root\n");
+        assert!(device.root().is_ok());
+        match device.root().unwrap_err() {
+            AdbError::PermissionDenied(reason) => {
+                assert_eq!(reason, "adbd cannot run as root in production builds")
+            }
+            other => panic!("unexpected error {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_forward_tcp_returns_allocated_port() {
+        let addr = serve(|listener| {
+            let (mut stream, _) = listener.accept().unwrap();
+            assert_eq!(
+                read_request(&mut stream),
+                "host-serial:emulator-5554:forward:tcp:0;tcp:9999"
+            );
+            write_okay(&mut stream);
+            write_okay(&mut stream);
+            write_block(&mut stream, "41237");
+        });
+        let mut device = AdbDevice::new("emulator-5554", addr);
+        assert_eq!(device.forward_tcp("tcp:9999").unwrap(), 41237);
+        assert_eq!(device.forwards, vec!["tcp:41237"]);
+    }
+
+    #[test]
+    fn test_forward_remote_port_reuses_existing_forward() {
+        let addr = serve(|listener| {
+            let mut forwards = String::new();
+            for _ in 0..2 {
+                let (mut stream, _) = listener.accept().unwrap();
+                assert_eq!(
+                    read_request(&mut stream),
+                    "host-serial:emulator-5554:list-forward"
+                );
+                write_okay(&mut stream);
+                write_block(&mut stream, &forwards);
+                if forwards.is_empty() {
+                    drop(stream);
+                    let (mut stream, _) = listener.accept().unwrap();
+                    assert_eq!(
+                        read_request(&mut stream),
+                        "host-serial:emulator-5554:forward:tcp:0;tcp:8080"
+                    );
+                    write_okay(&mut stream);
+                    write_okay(&mut stream);
+                    write_block(&mut stream, "41237");
+                    forwards = "emulator-5556 tcp:40000 tcp:8080\n\
+                                emulator-5554 tcp:41237 tcp:8080\n"
+                        .to_string();
+                }
+            }
+        });
+        let mut device = AdbDevice::new("emulator-5554", addr);
+        assert_eq!(device.forward_remote_port(8080).unwrap(), 41237);
+        assert_eq!(device.forward_remote_port(8080).unwrap(), 41237);
+        assert_eq!(device.forwards, vec!["tcp:41237"]);
+    }
+
+    #[test]
+    fn test_shell_checked_reports_failure() {
+        let addr = serve(|listener| {
+            let (mut stream, service) = accept_transport(&listener);
+            assert_eq!(
+                service,
+                r#"shell:"ls" "/nope"; echo ":RADB_EXIT_STATUS:$?""#
+            );
+            std::io::Write::write_all(
+                &mut stream,
+                b"ls: /nope: No such file or directory\n:RADB_EXIT_STATUS:1\n",
+            )
+            .unwrap();
+            drop(stream);
+            let (mut stream, _) = accept_transport(&listener);
+            std::io::Write::write_all(&mut stream, b"/sdcard\n:RADB_EXIT_STATUS:0\n").unwrap();
+        });
+        let mut device = AdbDevice::new("emulator-5554", addr);
+        match device.shell_checked(&["ls", "/nope"]).unwrap_err() {
+            AdbError::CommandFailed { command, reason } => {
+                assert_eq!(command, "ls /nope");
+                assert_eq!(
+                    reason,
+                    "exit status 1: ls: /nope: No such file or directory"
+                );
+            }
+            other => panic!("unexpected error {:?}", other),
+        }
+        assert_eq!(
+            device.shell_checked(&["ls", "-d", "/sdcard"]).unwrap(),
+            "/sdcard\n"
+        );
+    }
+
+    #[test]
+    fn test_shell_redacted_masks_only_logged_command() {
+        let addr = serve(|listener| {
+            let (mut stream, service) = accept_transport(&listener);
+            assert_eq!(service, r#"shell:"cmd" "login" "hunter2""#);
+            std::io::Write::write_all(&mut stream, b"ok\n").unwrap();
+            drop(stream);
+            let (mut stream, _) = listener.accept().unwrap();
+            read_request(&mut stream);
+            write_okay(&mut stream);
+            read_request(&mut stream);
+            write_fail(&mut stream, "closed");
+        });
+        let mut device = AdbDevice::new("emulator-5554", addr);
+        let output = device
+            .shell_redacted(&["cmd", "login", "hunter2"], &[2])
+            .unwrap();
+        assert_eq!(output, "ok\n");
+        let err = device
+            .shell_redacted(&["cmd", "login", "hunter2"], &[2])
+            .unwrap_err();
+        let message = format!("{:#}", err);
+        assert!(message.contains("***"), "{}", message);
+        assert!(!message.contains("hunter2"), "{}", message);
+    }
+
+    #[test]
+    fn test_shell_with_input_pipes_script() {
+        let addr = serve(|listener| {
+            let (mut stream, service) = accept_transport(&listener);
+            assert_eq!(service, r#"shell:"sh""#);
+            let mut script = String::new();
+            // 客户端半关闭写端后才能读到 EOF
+            std::io::Read::read_to_string(&mut stream, &mut script).unwrap();
+            assert_eq!(script, "echo hello\necho world\n");
+            std::io::Write::write_all(&mut stream, b"hello\nworld\n").unwrap();
+        });
+        let mut device = AdbDevice::new("emulator-5554", addr);
+        let output = device
+            .shell_with_input(["sh"], b"echo hello\necho world\n")
+            .unwrap();
+        assert_eq!(output, "hello\nworld\n");
+    }
+
+    /// 依次应答 shell 请求，把收到的命令行通过 channel 返回。
+    fn serve_shell_commands(count: usize) -> (String, std::sync::mpsc::Receiver<String>) {
+        let (tx, rx) = std::sync::mpsc::channel();
+        let addr = serve(move |listener| {
+            for _ in 0..count {
+                let (_stream, service) = accept_transport(&listener);
+                tx.send(service).unwrap();
+            }
+        });
+        (addr, rx)
+    }
+
+    #[test]
+    fn test_send_keys_escapes_text() {
+        let (addr, rx) = serve_shell_commands(4);
+        let mut device = AdbDevice::new("emulator-5554", addr);
+        device.send_keys("hello world").unwrap();
+        assert_eq!(rx.recv().unwrap(), r#"shell:input text "hello%sworld""#);
+        device.send_keys(r#"a&b "c" $HOME"#).unwrap();
+        assert_eq!(
+            rx.recv().unwrap(),
+            r#"shell:input text "a&b%s\"c\"%s\$HOME""#
+        );
+        device.send_keys("it's").unwrap();
+        assert_eq!(rx.recv().unwrap(), r#"shell:input text "it's""#);
+        device.send_keys("hi 你好!").unwrap();
+        assert_eq!(
+            rx.recv().unwrap(),
+            r#"shell:input text "hi%s" && cmd clipboard set "你好" && input keyevent 279 && input text "!""#
+        );
+    }
+
+    /// 生成约 1MB 的多行输出，每行内容不同，便于发现丢失或错位的数据。
+    fn large_output() -> String {
+        (0..40_000).map(|i| format!("line {:08}\n", i)).collect()
+    }
+
+    #[test]
+    fn test_shell_reads_large_output() {
+        let expected = large_output();
+        let payload = expected.clone();
+        let addr = serve(move |listener| {
+            let (mut stream, _) = accept_transport(&listener);
+            // 分成大小不一的块写出，模拟设备端逐步输出
+            for chunk in payload.as_bytes().chunks(7919) {
+                stream.write_all(chunk).unwrap();
+            }
+        });
+        let mut device = AdbDevice::new("emulator-5554", addr);
+        let output = device.shell(&["dumpsys"]).unwrap();
+        assert_eq!(output.len(), expected.len());
+        assert_eq!(output, expected);
+    }
+
+    #[test]
+    fn test_shell_lines_yields_lines_as_they_arrive() {
+        let (tx, rx) = std::sync::mpsc::channel::<()>();
+        let addr = serve(move |listener| {
+            let (mut stream, service) = accept_transport(&listener);
+            assert_eq!(service, r#"shell:"seq" "1" "100""#);
+            for i in 1..=50 {
+                stream.write_all(format!("{}\n", i).as_bytes()).unwrap();
+            }
+            // 客户端读完前一半后才继续输出，迭代器必须在命令结束前就返回行
+            rx.recv().unwrap();
+            for i in 51..=100 {
+                stream.write_all(format!("{}\r\n", i).as_bytes()).unwrap();
+            }
+        });
+        let mut device = AdbDevice::new("emulator-5554", addr);
+        let mut lines = device.shell_lines(["seq", "1", "100"]).unwrap();
+        let first: Vec<String> = lines.by_ref().take(50).map(Result::unwrap).collect();
+        assert_eq!(first.last().unwrap(), "50");
+        tx.send(()).unwrap();
+        let rest: Vec<String> = lines.map(Result::unwrap).collect();
+        assert_eq!(first.len() + rest.len(), 100);
+        assert_eq!(rest.last().unwrap(), "100");
+    }
+
+    fn shell_v2_packet(id: u8, payload: &[u8]) -> Vec<u8> {
+        let mut data = vec![id];
+        data.extend_from_slice(&(payload.len() as u32).to_le_bytes());
+        data.extend_from_slice(payload);
+        data
+    }
+
+    #[test]
+    fn test_shell_v2_separates_stdout_and_stderr() {
+        let addr = serve(|listener| {
+            accept_features(&listener, "shell_v2,cmd");
+            let (mut stream, service) = accept_transport(&listener);
+            assert_eq!(
+                service,
+                r#"shell,v2,raw:"sh" "-c" "echo out; echo err >&2; exit 3""#
+            );
+            let mut data = shell_v2_packet(1, b"out\n");
+            data.extend(shell_v2_packet(2, b"err\n"));
+            data.extend(shell_v2_packet(3, &[3]));
+            stream.write_all(&data).unwrap();
+        });
+        let mut device = AdbDevice::new("emulator-5554", addr);
+        let result = device
+            .shell_v2(["sh", "-c", "echo out; echo err >&2; exit 3"])
+            .unwrap();
+        assert_eq!(result.stdout_str(), "out\n");
+        assert_eq!(result.stderr_str(), "err\n");
+        assert_eq!(result.exit_code, Some(3));
+    }
+
+    #[test]
+    fn test_shell_v2_falls_back_without_feature() {
+        let addr = serve(|listener| {
+            accept_features(&listener, "cmd");
+            let (mut stream, service) = accept_transport(&listener);
+            assert_eq!(service, r#"shell:"sh" "-c" "echo out; echo err >&2""#);
+            stream.write_all(b"out\nerr\n").unwrap();
+        });
+        let mut device = AdbDevice::new("emulator-5554", addr);
+        let result = device
+            .shell_v2(["sh", "-c", "echo out; echo err >&2"])
+            .unwrap();
+        assert_eq!(result.stdout_str(), "out\nerr\n");
+        assert!(result.stderr.is_empty());
+        assert_eq!(result.exit_code, None);
+    }
+
+    #[test]
+    fn test_open_transport_maps_fail_reasons() {
+        let reasons = [
+            "device offline",
+            "device unauthorized.\nPlease check the confirmation dialog on your device.",
+            "device 'emulator-5554' not found",
+        ];
+        let addr = serve(move |listener| {
+            for reason in reasons {
+                let (mut stream, _) = listener.accept().unwrap();
+                assert_eq!(read_request(&mut stream), "host:transport:emulator-5554");
+                write_fail(&mut stream, reason);
+            }
+        });
+        let mut device = AdbDevice::new("emulator-5554", addr);
+        for code in ["DEVICE_OFFLINE", "DEVICE_UNAUTHORIZED", "DEVICE_NOT_FOUND"] {
+            let err = AdbError::from(device.shell(&["true"]).unwrap_err());
+            assert_eq!(err.error_code(), code, "{}", err);
+        }
+    }
+
+    #[test]
+    fn test_shell_args_are_literal() {
+        let (addr, rx) = serve_shell_commands(1);
+        let mut device = AdbDevice::new("emulator-5554", addr);
+        device.shell(&["echo", "$HOME", "`id`", "a;b"]).unwrap();
+        assert_eq!(
+            rx.recv().unwrap(),
+            r#"shell:"echo" "\$HOME" "\`id\`" "a;b""#
+        );
+    }
+
+    #[test]
+    fn test_emulator_console_authenticates_and_sends_commands() {
+        let (tx, rx) = std::sync::mpsc::channel();
+        let addr = serve(move |listener| {
+            let (stream, _) = listener.accept().unwrap();
+            let mut writer = stream.try_clone().unwrap();
+            writer
+                .write_all(
+                    b"Android Console: Authentication required\r\n\
+                      Android Console: type 'auth <auth_token>' to authenticate\r\n\
+                      OK\r\n",
+                )
+                .unwrap();
+            for line in BufReader::new(stream).lines() {
+                let line = line.unwrap();
+                let reply = match line.as_str() {
+                    "auth secret" => {
+                        "Android Console: type 'help' for a list of commands\r\nOK\r\n"
+                    }
+                    "power capacity 150" => "KO: Usage: \"capacity <percentage>\"\r\n",
+                    "quit" => break,
+                    _ => "OK\r\n",
+                };
+                tx.send(line).unwrap();
+                writer.write_all(reply.as_bytes()).unwrap();
+            }
+            tx.send("closed".to_string()).unwrap();
+        });
+
+        let mut console = EmulatorConsole::connect(addr.as_str(), Some("secret\n")).unwrap();
+        assert_eq!(rx.recv().unwrap(), "auth secret");
+        console.geo_fix(31.2, 121.5).unwrap();
+        assert_eq!(rx.recv().unwrap(), "geo fix 121.5 31.2");
+        console.rotate().unwrap();
+        assert_eq!(rx.recv().unwrap(), "rotate");
+        match console.power(150) {
+            Err(AdbError::CommandFailed { command, reason }) => {
+                assert_eq!(command, "power capacity 150");
+                assert_eq!(reason, r#"Usage: "capacity <percentage>""#);
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+        assert_eq!(rx.recv().unwrap(), "power capacity 150");
+        drop(console);
+        assert_eq!(rx.recv().unwrap(), "closed");
+    }
+
+    #[test]
+    fn test_emulator_console_requires_emulator_serial() {
+        let mut device = AdbDevice::new("0123456789ABCDEF", "127.0.0.1:5037");
+        assert!(matches!(
+            device.emulator_console(),
+            Err(AdbError::Unknown(_))
+        ));
+    }
+
+    /// 模拟设备上的 `settings` 命令，设置项保存在内存中，处理 `requests` 个请求后退出。
+    ///
+    /// `svc` 命令总是失败，模拟需要 root 的设备。
+    fn serve_settings(requests: usize) -> String {
+        serve(move |listener| {
+            let mut settings = std::collections::HashMap::new();
+            for (key, value) in [
+                ("global/airplane_mode_on", "0"),
+                ("global/wifi_on", "1"),
+                ("global/bluetooth_on", "2"),
+                ("global/mobile_data", "0"),
+            ] {
+                settings.insert(key.to_string(), value.to_string());
+            }
+            for _ in 0..requests {
+                let (mut stream, service) = accept_transport(&listener);
+                let (cmd, _) = service
+                    .strip_prefix("shell:")
+                    .unwrap()
+                    .split_once("; echo")
+                    .unwrap();
+                let args = match AdbCommand::parse(cmd) {
+                    AdbCommand::Multiple(args) => args,
+                    AdbCommand::Single(_) => unreachable!(),
+                };
+                let (output, code) = match args[0].as_str() {
+                    "svc" => ("Error: svc requires root\n".to_string(), 1),
+                    "settings" => {
+                        let key = format!("{}/{}", args[2], args[3]);
+                        let output = match args[1].as_str() {
+                            "get" => {
+                                settings.get(&key).cloned().unwrap_or("null".to_string()) + "\n"
+                            }
+                            "put" => {
+                                settings.insert(key, args[4].clone());
+                                String::new()
+                            }
+                            "delete" => {
+                                format!("Deleted {} rows\n", settings.remove(&key).iter().count())
+                            }
+                            _ => unreachable!(),
+                        };
+                        (output, 0)
+                    }
+                    _ => unreachable!("{:?}", args),
+                };
+                let reply = format!("{}:RADB_EXIT_STATUS:{}\n", output, code);
+                stream.write_all(reply.as_bytes()).unwrap();
+            }
+        })
+    }
+
+    #[test]
+    fn test_radio_states() {
+        let addr = serve_settings(3);
+        let mut device = AdbDevice::new("emulator-5554", addr);
+        assert!(device.wifi_enabled().unwrap());
+        // 2：飞行模式下保持开启
+        assert!(device.bluetooth_enabled().unwrap());
+        assert!(!device.mobile_data_enabled().unwrap());
+    }
+
+    #[test]
+    fn test_switch_falls_back_to_settings_without_svc() {
+        let addr = serve_settings(6);
+        let mut device = AdbDevice::new("emulator-5554", addr);
+        device.switch_mobile_data(true).unwrap();
+        assert!(device.mobile_data_enabled().unwrap());
+        device.switch_wifi(false).unwrap();
+        assert!(!device.wifi_enabled().unwrap());
+    }
+
+    #[test]
+    fn test_settings_get_known_key() {
+        let addr = serve_settings(2);
+        let mut device = AdbDevice::new("emulator-5554", addr);
+        assert_eq!(
+            device
+                .settings_get(SettingsNamespace::Global, "airplane_mode_on")
+                .unwrap()
+                .as_deref(),
+            Some("0")
+        );
+        assert_eq!(
+            device
+                .settings_get(SettingsNamespace::System, "missing")
+                .unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_settings_put_get_round_trip() {
+        let addr = serve_settings(4);
+        let mut device = AdbDevice::new("emulator-5554", addr);
+        device
+            .settings_put(SettingsNamespace::System, "screen_off_timeout", "600000")
+            .unwrap();
+        assert_eq!(
+            device
+                .settings_get(SettingsNamespace::System, "screen_off_timeout")
+                .unwrap()
+                .as_deref(),
+            Some("600000")
+        );
+        device
+            .settings_delete(SettingsNamespace::System, "screen_off_timeout")
+            .unwrap();
+        assert_eq!(
+            device
+                .settings_get(SettingsNamespace::System, "screen_off_timeout")
+                .unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_brightness_read_back() {
+        let addr = serve_settings(4);
+        let mut device = AdbDevice::new("emulator-5554", addr);
+        device.set_auto_brightness(false).unwrap();
+        assert_eq!(device.set_brightness(128).unwrap(), 128);
+        assert_eq!(device.get_brightness().unwrap(), 128);
+        assert_eq!(
+            device
+                .settings_get(SettingsNamespace::System, "screen_brightness_mode")
+                .unwrap()
+                .as_deref(),
+            Some("0")
+        );
+    }
+
+    #[test]
+    fn test_set_volume_clamps_to_stream_range() {
+        let addr = serve(|listener| {
+            let (mut stream, service) = accept_transport(&listener);
+            assert_eq!(
+                service,
+                r#"shell:"media" "volume" "--stream" "4" "--get"; echo ":RADB_EXIT_STATUS:$?""#
+            );
+            stream
+                .write_all(
+                    b"[v] will get volume\n[v] stream=4\n[v] volume is 3 in range [1..7]\n:RADB_EXIT_STATUS:0\n",
+                )
+                .unwrap();
+            drop(stream);
+            let (mut stream, service) = accept_transport(&listener);
+            assert_eq!(
+                service,
+                r#"shell:"media" "volume" "--stream" "4" "--set" "7"; echo ":RADB_EXIT_STATUS:$?""#
+            );
+            stream
+                .write_all(b"[v] will set volume\n:RADB_EXIT_STATUS:0\n")
+                .unwrap();
+        });
+        let mut device = AdbDevice::new("emulator-5554", addr);
+        assert_eq!(device.set_volume(VolumeStream::Alarm, 200).unwrap(), 7);
+    }
+
+    /// 按抓取的 `getprop` 输出应答 `getprop <key>` 请求，处理 `requests` 个请求后退出。
+    fn serve_getprop(dump: &'static str, requests: usize) -> String {
+        serve(move |listener| {
+            for _ in 0..requests {
+                let (mut stream, service) = accept_transport(&listener);
+                let args = match AdbCommand::parse(service.strip_prefix("shell:").unwrap()) {
+                    AdbCommand::Multiple(args) => args,
+                    AdbCommand::Single(_) => unreachable!(),
+                };
+                assert_eq!(args[0], "getprop");
+                if args.len() == 1 {
+                    stream.write_all(dump.as_bytes()).unwrap();
+                    continue;
+                }
+                let prefix = format!("[{}]: [", args[1]);
+                let value = dump
+                    .lines()
+                    .find_map(|line| line.strip_prefix(&prefix)?.strip_suffix(']'))
+                    .unwrap_or("");
+                stream.write_all(format!("{}\n", value).as_bytes()).unwrap();
+            }
+        })
+    }
+
+    #[test]
+    fn test_abi_lists_share_one_getprop() {
+        let dump = "[ro.product.cpu.abi]: [arm64-v8a]
+[ro.product.cpu.abilist]: [arm64-v8a,armeabi-v7a,armeabi]
+[ro.product.cpu.abilist32]: [armeabi-v7a,armeabi]
+[ro.product.cpu.abilist64]: [arm64-v8a]
+";
+        let mut device = AdbDevice::new("emulator-5554", serve_getprop(dump, 1));
+        assert_eq!(
+            device.supported_abis().unwrap(),
+            ["arm64-v8a", "armeabi-v7a", "armeabi"]
+        );
+        assert_eq!(device.abilist32().unwrap(), ["armeabi-v7a", "armeabi"]);
+        assert_eq!(device.abilist64().unwrap(), ["arm64-v8a"]);
+        assert_eq!(device.get_device_abi().unwrap(), "arm64-v8a");
+
+        // 只支持 32 位的设备，abilist64 为空
+        let arm32 = "[ro.product.cpu.abi]: [armeabi-v7a]
+[ro.product.cpu.abilist]: [armeabi-v7a,armeabi]
+[ro.product.cpu.abilist32]: [armeabi-v7a,armeabi]
+[ro.product.cpu.abilist64]: []
+";
+        let mut device = AdbDevice::new("emulator-5554", serve_getprop(arm32, 1));
+        assert!(device.abilist64().unwrap().is_empty());
+        assert_eq!(device.supported_abis().unwrap(), ["armeabi-v7a", "armeabi"]);
+
+        // Android 4.4 没有 abilist
+        let kitkat = "[ro.product.cpu.abi2]: [armeabi]
+[ro.product.cpu.abi]: [armeabi-v7a]
+";
+        let mut device = AdbDevice::new("emulator-5554", serve_getprop(kitkat, 1));
+        assert_eq!(device.supported_abis().unwrap(), ["armeabi-v7a", "armeabi"]);
+    }
+
+    #[test]
+    fn test_timezone_and_locale_getters() {
+        let dump = "[persist.sys.locale]: [zh-CN]
+[persist.sys.timezone]: [Asia/Shanghai]
+[ro.product.locale]: [en-US]
+";
+        let mut device = AdbDevice::new("emulator-5554", serve_getprop(dump, 2));
+        assert_eq!(device.get_timezone().unwrap(), "Asia/Shanghai");
+        assert_eq!(device.get_locale().unwrap(), "zh-CN");
+
+        // Android 4.4：语言与地区分开保存
+        let kitkat = "[persist.sys.country]: [TW]
+[persist.sys.language]: [zh]
+[persist.sys.timezone]: [Asia/Taipei]
+";
+        let mut device = AdbDevice::new("emulator-5554", serve_getprop(kitkat, 3));
+        assert_eq!(device.get_locale().unwrap(), "zh-TW");
+
+        // 只有出厂设置
+        let factory = "[ro.product.locale]: [en-US]\n";
+        let mut device = AdbDevice::new("emulator-5554", serve_getprop(factory, 4));
+        assert_eq!(device.get_locale().unwrap(), "en-US");
+        assert!(device.get_timezone().is_err());
+    }
+
+    #[test]
+    fn test_set_device_time_permission_denied() {
+        let addr = serve(|listener| {
+            let (mut stream, service) = accept_transport(&listener);
+            assert_eq!(service, r#"shell:"getprop" "ro.build.version.sdk""#);
+            stream.write_all(b"34\n").unwrap();
+            drop(stream);
+            let (mut stream, service) = accept_transport(&listener);
+            assert_eq!(
+                service,
+                r#"shell:"date" "-u" "061508052024.09"; echo ":RADB_EXIT_STATUS:$?""#
+            );
+            stream
+                .write_all(
+                    b"date: cannot set date: Operation not permitted\nSat Jun 15 08:05:09 UTC 2024\n:RADB_EXIT_STATUS:1\n",
+                )
+                .unwrap();
+        });
+        let mut device = AdbDevice::new("emulator-5554", addr);
+        let dt = chrono::TimeZone::with_ymd_and_hms(&chrono::Utc, 2024, 6, 15, 8, 5, 9).unwrap();
+        assert!(matches!(
+            device.set_device_time(dt),
+            Err(AdbError::PermissionDenied(_))
+        ));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_adb_output_without_serial() {
+        use std::os::unix::fs::PermissionsExt;
+
+        // 用回显参数的脚本代替 adb
+        let dir = tempfile::tempdir().unwrap();
+        let adb = dir.path().join("adb");
+        std::fs::write(&adb, "#!/bin/sh\necho \"$@\"\n").unwrap();
+        std::fs::set_permissions(&adb, std::fs::Permissions::from_mode(0o755)).unwrap();
+        std::env::set_var("ADBUTILS_ADB_PATH", &adb);
+
+        let mut device = AdbDevice::with_transport_id(3, "127.0.0.1:5037");
+        assert_eq!(
+            device.adb_output(&["get-state"]).unwrap(),
+            "-t 3 get-state\n"
+        );
+        let mut device = AdbDevice::new("emulator-5554", "127.0.0.1:5037");
+        assert_eq!(
+            device.adb_output(&["get-state"]).unwrap(),
+            "-s emulator-5554 get-state\n"
+        );
+
+        device.serial = None;
+        let err = device.adb_output(&["get-state"]).unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<AdbError>(),
+            Some(AdbError::DeviceNotFound(_))
+        ));
+    }
+
+    /// 在随机端口启动一个只应答一次 GET 请求的 HTTP server，返回 URL。
+    ///
+    /// `chunked` 为 `true` 时以 chunked 编码分块发送，响应中没有 `Content-Length`。
+    fn serve_http_once(body: &'static [u8], chunked: bool) -> String {
+        let addr = serve(move |listener| {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream);
+            let mut line = String::new();
+            while reader.read_line(&mut line).unwrap() > 2 {
+                line.clear();
+            }
+            let mut stream = reader.into_inner();
+            if chunked {
+                stream
+                    .write_all(b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\nConnection: close\r\n\r\n")
+                    .unwrap();
+                for chunk in body.chunks(7) {
+                    stream
+                        .write_all(format!("{:x}\r\n", chunk.len()).as_bytes())
+                        .unwrap();
+                    stream.write_all(chunk).unwrap();
+                    stream.write_all(b"\r\n").unwrap();
+                }
+                stream.write_all(b"0\r\n\r\n").unwrap();
+            } else {
+                let header = format!(
+                    "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                    body.len()
+                );
+                stream.write_all(header.as_bytes()).unwrap();
+                stream.write_all(body).unwrap();
+            }
+        });
+        format!("http://{}/app.apk", addr)
+    }
+
+    /// 应答一次 APK 安装：sync 推送（检查内容为 `apk`）、`pm install` 与删除临时文件。
+    fn serve_apk_install(apk: &'static [u8]) -> String {
+        serve_apk_install_with(apk, || {})
+    }
+
+    /// 同 `serve_apk_install`，收到 `SEND` 请求后调用 `on_send`。
+    fn serve_apk_install_with<F>(apk: &'static [u8], on_send: F) -> String
+    where
+        F: FnOnce() + Send + 'static,
+    {
+        serve(move |listener| {
+            accept_features(&listener, "shell_v2,cmd");
+            let (mut stream, service) = accept_transport(&listener);
+            assert_eq!(service, "sync:");
+            let (id, path) = read_sync_packet(&mut stream);
+            assert_eq!(id, "SEND");
+            on_send();
+            let path = String::from_utf8(path).unwrap();
+            let (dst, _) = path.split_once(',').unwrap();
+            let mut data = vec![];
+            loop {
+                match read_sync_packet(&mut stream) {
+                    (id, chunk) if id == "DATA" => data.extend(chunk),
+                    (id, _) => {
+                        assert_eq!(id, "DONE");
+                        break;
+                    }
+                }
+            }
+            assert_eq!(data, apk);
+            write_sync_packet(&mut stream, "OKAY", b"");
+            drop(stream);
+
+            let (mut stream, service) = accept_transport(&listener);
+            assert_eq!(
+                service,
+                format!(r#"shell:"pm" "install" "-r" "-t" "{}""#, dst)
+            );
+            stream.write_all(b"Success\n").unwrap();
+            drop(stream);
+            let (_, service) = accept_transport(&listener);
+            assert_eq!(service, format!(r#"shell:"rm" "{}""#, dst));
+        })
+    }
+
+    /// 应答一次 sync SEND，返回推送的内容。
+    fn receive_push(listener: &std::net::TcpListener) -> Vec<u8> {
+        let (mut stream, service) = accept_transport(listener);
+        assert_eq!(service, "sync:");
+        assert_eq!(read_sync_packet(&mut stream).0, "SEND");
+        let mut data = vec![];
+        loop {
+            match read_sync_packet(&mut stream) {
+                (id, chunk) if id == "DATA" => data.extend(chunk),
+                (id, _) => {
+                    assert_eq!(id, "DONE");
+                    break;
+                }
+            }
+        }
+        write_sync_packet(&mut stream, "OKAY", b"");
+        data
+    }
+
+    #[test]
+    fn test_push_verified_compares_digests() {
+        let addr = serve(|listener| {
+            accept_features(&listener, "");
+            assert_eq!(receive_push(&listener), b"abc");
+            let (mut stream, service) = accept_transport(&listener);
+            assert_eq!(
+                service,
+                r#"shell:"sha256sum" "/data/local/tmp/abc.txt"; echo ":RADB_EXIT_STATUS:$?""#
+            );
+            stream
+                .write_all(b"ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad  /data/local/tmp/abc.txt\n:RADB_EXIT_STATUS:0\n")
+                .unwrap();
+            drop(stream);
+
+            // 旧设备没有 sha256sum，退回 md5sum，且设备上的内容已损坏
+            assert_eq!(receive_push(&listener), b"abc");
+            for reply in [
+                "/system/bin/sh: sha256sum: not found\n:RADB_EXIT_STATUS:127\n",
+                "toybox: Unknown command sha256sum\n:RADB_EXIT_STATUS:1\n",
+                "0cc175b9c0f1b6a831c399e269772661  /data/local/tmp/abc.txt\n:RADB_EXIT_STATUS:0\n",
+            ] {
+                let (mut stream, _) = accept_transport(&listener);
+                stream.write_all(reply.as_bytes()).unwrap();
+            }
+        });
+        let tmpdir = tempfile::tempdir().unwrap();
+        let local = tmpdir.path().join("abc.txt");
+        std::fs::write(&local, b"abc").unwrap();
+        let mut device = AdbDevice::new("emulator-5554", addr);
+        device
+            .push_verified(&local, "/data/local/tmp/abc.txt")
+            .unwrap();
+        match device
+            .push_verified(&local, "/data/local/tmp/abc.txt")
+            .unwrap_err()
+        {
+            AdbError::FileOperationFailed(reason) => assert!(
+                reason.ends_with(
+                    "local 900150983cd24fb0d6963f7d28e17f72, device 0cc175b9c0f1b6a831c399e269772661"
+                ),
+                "{}",
+                reason
+            ),
+            other => panic!("unexpected error {:?}", other),
+        }
+    }
+
+    const FAKE_APK: &[u8] = b"PK\x03\x04 fake apk content, long enough for several chunks";
+
+    #[test]
+    fn test_install_remote_removes_apk_when_install_fails() {
+        let (tx, rx) = std::sync::mpsc::channel();
+        let addr = serve(move |listener| {
+            // pm install 的 shell 服务打开失败
+            let (mut stream, _) = listener.accept().unwrap();
+            read_request(&mut stream);
+            write_okay(&mut stream);
+            tx.send(read_request(&mut stream)).unwrap();
+            write_fail(&mut stream, "closed");
+            drop(stream);
+            let (_, service) = accept_transport(&listener);
+            tx.send(service).unwrap();
+        });
+        let mut device = AdbDevice::new("emulator-5554", addr);
+        assert!(device
+            .install_remote_with("/data/local/tmp/app.apk", true, &InstallOptions::default())
+            .is_err());
+        assert_eq!(
+            rx.recv().unwrap(),
+            r#"shell:"pm" "install" "-r" "-t" "/data/local/tmp/app.apk""#
+        );
+        assert_eq!(
+            rx.recv().unwrap(),
+            r#"shell:"rm" "/data/local/tmp/app.apk""#
+        );
+    }
+
+    #[test]
+    fn test_install_url_pushes_downloaded_apk() {
+        let url = serve_http_once(FAKE_APK, false);
+        let mut device = AdbDevice::new("emulator-5554", serve_apk_install(FAKE_APK));
+        device.install_url(&url).unwrap();
+    }
+
+    #[test]
+    fn test_install_url_streams_to_device() {
+        let options = InstallOptions {
+            stream_download: true,
+            ..Default::default()
+        };
+        // 响应体只发出前一半，设备端开始接收推送后才发送剩余部分，
+        // 先下载完整响应再推送的实现会在这里超时
+        let (started_tx, started_rx) = std::sync::mpsc::channel();
+        let http = serve(move |listener| {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream);
+            let mut line = String::new();
+            while reader.read_line(&mut line).unwrap() > 2 {
+                line.clear();
+            }
+            let mut stream = reader.into_inner();
+            let header = format!(
+                "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                FAKE_APK.len()
+            );
+            let (head, tail) = FAKE_APK.split_at(FAKE_APK.len() / 2);
+            stream.write_all(header.as_bytes()).unwrap();
+            stream.write_all(head).unwrap();
+            started_rx
+                .recv_timeout(Duration::from_secs(5))
+                .expect("push did not start before the download finished");
+            stream.write_all(tail).unwrap();
+        });
+        let addr = serve_apk_install_with(FAKE_APK, move || started_tx.send(()).unwrap());
+        let mut device = AdbDevice::new("emulator-5554", addr);
+        device
+            .install_url_with(&format!("http://{}/app.apk", http), &options)
+            .unwrap();
+
+        // chunked 响应没有长度，退回本地临时文件
+        let url = serve_http_once(FAKE_APK, true);
+        let mut device = AdbDevice::new("emulator-5554", serve_apk_install(FAKE_APK));
+        device.install_url_with(&url, &options).unwrap();
+    }
+
+    /// 应答一个 `shell:sh` 会话：每读到一条命令，输出 `output` 并以 `code` 作为退出码结束。
+    fn answer_persistent_shell(reader: BufReader<TcpStream>, output: &str, code: i32) {
+        let mut writer = reader.get_ref().try_clone().unwrap();
+        for line in reader.lines() {
+            let line = line.unwrap();
+            if line == "exit" {
+                break;
+            }
+            if let Some((_, marker)) = line.split_once("; echo \"") {
+                let marker = marker.trim_end_matches("$?\"");
+                let reply = format!("{}{}{}\n", output, marker, code);
+                writer.write_all(reply.as_bytes()).unwrap();
+            }
+        }
+    }
+
+    #[test]
+    fn test_shell_persistent_runs_commands_on_one_connection() {
+        let (tx, rx) = std::sync::mpsc::channel();
+        let addr = serve(move |listener| {
+            let (stream, service) = accept_transport(&listener);
+            tx.send(service).unwrap();
+            let mut writer = stream.try_clone().unwrap();
+            let mut reader = BufReader::new(stream);
+            let mut line = String::new();
+            reader.read_line(&mut line).unwrap();
+            tx.send(line.clone()).unwrap();
+            line.clear();
+            reader.read_line(&mut line).unwrap();
+            tx.send(line.clone()).unwrap();
+            // 输出不以换行结尾时标记紧跟在输出后面
+            writer
+                .write_all(b"no newline:RADB_SESSION_END:1:0\n")
+                .unwrap();
+            answer_persistent_shell(reader, "fail\n", 3);
+        });
+        let mut device = AdbDevice::new("emulator-5554", addr);
+        let mut session = device.shell_persistent().unwrap();
+        assert_eq!(rx.recv().unwrap(), "shell:sh");
+        assert_eq!(session.run(["echo", "-n", "$HOME"]).unwrap(), "no newline");
+        assert_eq!(rx.recv().unwrap(), "{ \"echo\" \"-n\" \"\\$HOME\"\n");
+        assert_eq!(
+            rx.recv().unwrap(),
+            "} </dev/null 2>&1; echo \":RADB_SESSION_END:1:$?\"\n"
+        );
+        assert_eq!(
+            session.run_with_status("false").unwrap(),
+            ("fail\n".to_string(), 3)
+        );
+        match session.run_checked("false").unwrap_err() {
+            AdbError::CommandFailed { command, reason } => {
+                assert_eq!(command, "false");
+                assert_eq!(reason, "exit status 3: fail");
+            }
+            e => panic!("unexpected error {:?}", e),
+        }
+        session.close().unwrap();
+    }
+
+    #[test]
+    fn test_shell_persistent_reuses_one_connection() {
+        const COMMANDS: usize = 10;
+        let per_call = Arc::new(AtomicUsize::new(0));
+        let persistent = Arc::new(AtomicUsize::new(0));
+        let (per_call_count, persistent_count) = (per_call.clone(), persistent.clone());
+        let addr = serve(move |listener| {
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                read_request(&mut stream);
+                write_okay(&mut stream);
+                let service = read_request(&mut stream);
+                write_okay(&mut stream);
+                if service == "shell:sh" {
+                    persistent_count.fetch_add(1, Ordering::SeqCst);
+                    answer_persistent_shell(BufReader::new(stream), "ok\n", 0);
+                } else {
+                    per_call_count.fetch_add(1, Ordering::SeqCst);
+                    stream.write_all(b"ok\n").unwrap();
+                }
+            }
+        });
+        let mut device = AdbDevice::new("emulator-5554", addr);
+
+        for _ in 0..COMMANDS {
+            assert_eq!(device.shell(&["echo", "ok"]).unwrap(), "ok\n");
+        }
+        assert_eq!(per_call.load(Ordering::SeqCst), COMMANDS);
+
+        let mut session = device.shell_persistent().unwrap();
+        for _ in 0..COMMANDS {
+            assert_eq!(session.run(["echo", "ok"]).unwrap(), "ok\n");
+        }
+        session.close().unwrap();
+        assert_eq!(persistent.load(Ordering::SeqCst), 1);
+        assert_eq!(per_call.load(Ordering::SeqCst), COMMANDS);
+    }
+
+    #[test]
+    fn test_gesture_commands() {
+        let (addr, rx) = serve_shell_commands(3);
+        let mut device = AdbDevice::new("emulator-5554", addr);
+        device.long_click(100, 200, 800).unwrap();
+        assert_eq!(
+            rx.recv().unwrap(),
+            r#"shell:"input" "swipe" "100" "200" "100" "200" "800""#
+        );
+        device.drag(10, 20, 300, 400, 1000).unwrap();
+        assert_eq!(
+            rx.recv().unwrap(),
+            r#"shell:"input" "swipe" "10" "20" "300" "400" "1000""#
+        );
+        device.double_click(5, 6).unwrap();
+        assert_eq!(
+            rx.recv().unwrap(),
+            r#"shell:"input" "tap" "5" "6" && "input" "tap" "5" "6""#
+        );
+    }
+
+    #[test]
+    fn test_create_connection_to_forwarded_port() {
+        let addr = serve(|listener| {
+            let (mut stream, service) = accept_transport(&listener);
+            assert_eq!(service, "tcp:8080");
+            let mut request = [0; 4];
+            std::io::Read::read_exact(&mut stream, &mut request).unwrap();
+            assert_eq!(&request, b"ping");
+            std::io::Write::write_all(&mut stream, b"pong").unwrap();
+            let (_, service) = accept_transport(&listener);
+            assert_eq!(service, "localabstract:minicap");
+        });
+        let mut device = AdbDevice::new("emulator-5554", addr);
+        let mut conn = device.create_connection(NetworkType::Tcp, 8080).unwrap();
+        std::io::Write::write_all(&mut conn, b"ping").unwrap();
+        let mut response = [0; 4];
+        std::io::Read::read_exact(&mut conn, &mut response).unwrap();
+        assert_eq!(&response, b"pong");
+        device
+            .create_connection(NetworkType::Unix, "minicap")
+            .unwrap();
+    }
+
+    #[test]
+    fn test_exec_service_returns_open_connection() {
+        let addr = serve(|listener| {
+            let (mut stream, service) = accept_transport(&listener);
+            assert_eq!(service, "jdwp");
+            write_block(&mut stream, "1234\n");
+        });
+        let mut device = AdbDevice::new("emulator-5554", addr);
+        let mut conn = device.exec_service("jdwp").unwrap();
+        let mut response = String::new();
+        std::io::Read::read_to_string(&mut conn, &mut response).unwrap();
+        assert_eq!(response, "00051234\n");
+    }
+
+    #[test]
+    fn test_jdwp_lists_and_tracks_pids() {
+        let addr = serve(|listener| {
+            // 第一份列表之后连接保持打开，jdwp 不应等待连接关闭
+            let (mut held, service) = accept_transport(&listener);
+            assert_eq!(service, "track-jdwp");
+            write_block(&mut held, "1234\n5678\n");
+            let (mut stream, service) = accept_transport(&listener);
+            assert_eq!(service, "track-jdwp");
+            write_block(&mut stream, "1234\n");
+            write_block(&mut stream, "1234\n5678\n");
+            write_block(&mut stream, "");
+        });
+        let mut device = AdbDevice::new("emulator-5554", addr);
+        assert_eq!(device.jdwp().unwrap(), [1234, 5678]);
+        let updates: Vec<_> = device.jdwp_track().unwrap().collect();
+        assert_eq!(updates, [vec![1234], vec![1234, 5678], vec![]]);
+    }
+
+    #[test]
+    fn test_exec_out_returns_raw_bytes() {
+        let addr = serve(|listener| {
+            let (mut stream, service) = accept_transport(&listener);
+            assert_eq!(service, r#"exec:"screencap" "-p""#);
+            std::io::Write::write_all(&mut stream, b"\x89PNG\r\n\x1a\n\x00").unwrap();
+        });
+        let mut device = AdbDevice::new("emulator-5554", addr);
+        assert_eq!(
+            device.exec_out(["screencap", "-p"]).unwrap(),
+            b"\x89PNG\r\n\x1a\n\x00"
+        );
+    }
+
+    #[test]
+    fn test_screenshot_to_saves_png_and_jpeg() {
+        let mut png = Vec::new();
+        image::RgbImage::from_pixel(4, 3, image::Rgb([200, 10, 10]))
+            .write_to(
+                &mut std::io::Cursor::new(&mut png),
+                image::ImageOutputFormat::Png,
+            )
+            .unwrap();
+        let served = png.clone();
+        let addr = serve(move |listener| {
+            for _ in 0..2 {
+                let (mut stream, service) = accept_transport(&listener);
+                assert_eq!(service, r#"exec:"screencap" "-p""#);
+                stream.write_all(&served).unwrap();
+            }
+        });
+        let tmpdir = tempfile::tempdir().unwrap();
+        let mut device = AdbDevice::new("emulator-5554", addr);
+
+        let png_path = tmpdir.path().join("shot.png");
+        device.screenshot_to(&png_path).unwrap();
+        assert_eq!(std::fs::read(&png_path).unwrap(), png);
+        let reloaded = image::open(&png_path).unwrap().into_rgb8();
+        assert_eq!(reloaded.dimensions(), (4, 3));
+        assert_eq!(reloaded.get_pixel(0, 0).0, [200, 10, 10]);
+
+        let jpg_path = tmpdir.path().join("shot.jpg");
+        device.screenshot_to(&jpg_path).unwrap();
+        assert_eq!(
+            image::open(&jpg_path).unwrap().into_rgb8().dimensions(),
+            (4, 3)
+        );
+
+        assert!(device
+            .screenshot_to(&tmpdir.path().join("shot.bmp"))
+            .is_err());
+    }
+
+    #[test]
+    fn test_iter_directory_truncated_entry() {
+        let addr = serve(|listener| {
+            accept_features(&listener, "");
+            let (mut stream, service) = accept_transport(&listener);
+            assert_eq!(service, "sync:");
+            let (id, path) = read_sync_packet(&mut stream);
+            assert_eq!(
+                (id.as_str(), path.as_slice()),
+                ("LIST", b"/sdcard".as_slice())
+            );
+            // 只发送 10 字节的目录项后断开
+            std::io::Write::write_all(&mut stream, b"DENT\xa4\x81\x00\x00\x05\x00\x00\x00\x00\x00")
+                .unwrap();
+        });
+        let mut device = AdbDevice::new("emulator-5554", addr);
+        let entries: Vec<_> = device.iter_directory("/sdcard").unwrap().collect();
+        assert_eq!(entries.len(), 1);
+        assert!(entries[0].is_err());
+    }
+
+    #[test]
+    fn test_iter_directory_reassembles_split_entry() {
+        let addr = serve(|listener| {
+            accept_features(&listener, "");
+            let (mut stream, _) = accept_transport(&listener);
+            read_sync_packet(&mut stream);
+            let mut dent = b"DENT".to_vec();
+            for value in [0o100644u32, 3, 1700000000, 5] {
+                dent.extend_from_slice(&value.to_le_bytes());
+            }
+            dent.extend_from_slice(b"a.txt");
+            // 目录项的定长部分分两次到达
+            let (head, tail) = dent.split_at(10);
+            stream.write_all(head).unwrap();
+            stream.flush().unwrap();
+            std::thread::sleep(Duration::from_millis(50));
+            stream.write_all(tail).unwrap();
+            write_sync_packet(&mut stream, "DONE", b"");
+        });
+        let mut device = AdbDevice::new("emulator-5554", addr);
+        let entries: Vec<_> = device.iter_directory("/sdcard").unwrap().collect();
+        assert_eq!(entries.len(), 1);
+        let entry = entries[0].as_ref().unwrap();
+        assert_eq!((entry.path.as_str(), entry.size), ("a.txt", 3));
+    }
+
+    #[test]
+    fn test_iter_directory_reports_fail_after_entries() {
+        let addr = serve(|listener| {
+            accept_features(&listener, "");
+            let (mut stream, _) = accept_transport(&listener);
+            read_sync_packet(&mut stream);
+            for name in ["a.txt", "b.txt"] {
+                let mut dent = b"DENT".to_vec();
+                for value in [0o100644u32, 3, 1700000000, name.len() as u32] {
+                    dent.extend_from_slice(&value.to_le_bytes());
+                }
+                dent.extend_from_slice(name.as_bytes());
+                std::io::Write::write_all(&mut stream, &dent).unwrap();
+            }
+            write_sync_packet(&mut stream, "FAIL", b"Permission denied");
+        });
+        let mut device = AdbDevice::new("emulator-5554", addr);
+        let entries: Vec<_> = device.iter_directory("/data").unwrap().collect();
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[0].as_ref().unwrap().path, "a.txt");
+        assert_eq!(entries[1].as_ref().unwrap().size, 3);
+        let err = entries[2].as_ref().unwrap_err();
+        match err.downcast_ref::<AdbError>() {
+            Some(AdbError::FileOperationFailed(reason)) => assert_eq!(reason, "Permission denied"),
+            other => panic!("unexpected error {:?}", other),
+        }
+    }
+
+    /// 构造 v2 stat 数据：error、mode、uid、gid、size 与 atime/mtime/ctime。
+    fn stat_v2_frame(mode: u32, size: u64, mtime: i64) -> Vec<u8> {
+        let mut data = vec![0; 68];
+        data[20..24].copy_from_slice(&mode.to_le_bytes());
+        data[28..32].copy_from_slice(&10123u32.to_le_bytes());
+        data[32..36].copy_from_slice(&1078u32.to_le_bytes());
+        data[36..44].copy_from_slice(&size.to_le_bytes());
+        data[44..52].copy_from_slice(&(mtime - 60).to_le_bytes());
+        data[52..60].copy_from_slice(&mtime.to_le_bytes());
+        data[60..68].copy_from_slice(&mtime.to_le_bytes());
+        data
+    }
+
+    #[test]
+    fn test_stat_uses_v2_when_supported() {
+        let addr = serve(|listener| {
+            accept_features(&listener, "shell_v2,cmd,stat_v2,ls_v2");
+            let (mut stream, _) = accept_transport(&listener);
+            let (id, path) = read_sync_packet(&mut stream);
+            assert_eq!(
+                (id.as_str(), path.as_slice()),
+                ("STA2", b"/sdcard/big.bin".as_slice())
+            );
+            let mut response = b"STA2".to_vec();
+            response.extend(stat_v2_frame(0o100644, 5 << 30, 1700000000));
+            // stat 数据分两次到达
+            let (head, tail) = response.split_at(20);
+            stream.write_all(head).unwrap();
+            stream.flush().unwrap();
+            std::thread::sleep(Duration::from_millis(50));
+            stream.write_all(tail).unwrap();
+        });
+        let mut device = AdbDevice::new("emulator-5554", addr);
+        let info = device.stat("/sdcard/big.bin").unwrap();
+        assert_eq!(info.size64, Some(5 << 30));
+        assert_eq!(info.size, u32::MAX);
+        assert_eq!((info.uid, info.gid), (Some(10123), Some(1078)));
+        assert_eq!(info.atime, Some(1700000000 - 60));
+        assert_eq!(info.mtime, 1700000000);
+        assert_eq!(info.error, Some(0));
+    }
+
+    #[test]
+    fn test_stat_falls_back_to_v1() {
+        let addr = serve(|listener| {
+            let (mut stream, _) = listener.accept().unwrap();
+            read_request(&mut stream);
+            write_fail(&mut stream, "unknown host service");
+            drop(stream);
+            let (mut stream, _) = accept_transport(&listener);
+            let (id, _) = read_sync_packet(&mut stream);
+            assert_eq!(id, "STAT");
+            let mut response = b"STAT".to_vec();
+            for value in [0o100644u32, 42, 1700000000] {
+                response.extend_from_slice(&value.to_le_bytes());
+            }
+            std::io::Write::write_all(&mut stream, &response).unwrap();
+            drop(stream);
+            // 查询失败的结果没有缓存，下一次 stat 重新查询
+            accept_features(&listener, "stat_v2");
+            let (mut stream, _) = accept_transport(&listener);
+            let (id, _) = read_sync_packet(&mut stream);
+            assert_eq!(id, "STA2");
+            let mut response = b"STA2".to_vec();
+            response.extend(stat_v2_frame(0o100644, 42, 1700000000));
+            stream.write_all(&response).unwrap();
+        });
+        let mut device = AdbDevice::new("emulator-5554", addr);
+        let info = device.stat("/sdcard/small.txt").unwrap();
+        assert_eq!(info.size, 42);
+        assert_eq!(info.size64, None);
+        assert_eq!(info.uid, None);
+        assert_eq!(device.features, None);
+        let info = device.stat("/sdcard/small.txt").unwrap();
+        assert_eq!(info.size64, Some(42));
+        assert_eq!(device.features, Some(vec!["stat_v2".to_string()]));
+    }
+
+    /// 依次应答 `stats` 中的 v1 STAT 请求，每项为 (路径, mode, mtime)。
+    fn serve_stats(stats: Vec<(&'static str, u32, u32)>) -> String {
+        serve(move |listener| {
+            accept_features(&listener, "");
+            for (expected, mode, mtime) in stats {
+                let (mut stream, _) = accept_transport(&listener);
+                let (id, path) = read_sync_packet(&mut stream);
+                assert_eq!(
+                    (id.as_str(), path.as_slice()),
+                    ("STAT", expected.as_bytes())
+                );
+                let mut response = b"STAT".to_vec();
+                for value in [mode, 0, mtime] {
+                    response.extend_from_slice(&value.to_le_bytes());
+                }
+                std::io::Write::write_all(&mut stream, &response).unwrap();
+            }
+        })
+    }
+
+    #[test]
+    fn test_exists_uses_mode() {
+        let addr = serve_stats(vec![
+            ("/sdcard", 0o040771, 1700000000),
+            ("/epoch.txt", 0o100644, 0),
+            ("/sdcard/missing-radb", 0, 0),
+        ]);
+        let mut device = AdbDevice::new("emulator-5554", addr);
+        assert!(device.exists("/sdcard").unwrap());
+        assert!(device.exists("/epoch.txt").unwrap());
+        assert!(!device.exists("/sdcard/missing-radb").unwrap());
+    }
+
+    #[test]
+    fn test_chmod_verifies_mode_and_chown_maps_errors() {
+        let addr = serve(|listener| {
+            for (path, mode) in [
+                ("/data/local/tmp/run.sh", 0o100755u32),
+                ("/sdcard/run.sh", 0o100660),
+            ] {
+                let (mut stream, service) = accept_transport(&listener);
+                assert_eq!(
+                    service,
+                    format!(
+                        r#"shell:"chmod" "755" "{}"; echo ":RADB_EXIT_STATUS:$?""#,
+                        path
+                    )
+                );
+                stream.write_all(b":RADB_EXIT_STATUS:0\n").unwrap();
+                drop(stream);
+                if mode == 0o100755 {
+                    accept_features(&listener, "");
+                }
+                let (mut stream, _) = accept_transport(&listener);
+                let (id, _) = read_sync_packet(&mut stream);
+                assert_eq!(id, "STAT");
+                let mut response = b"STAT".to_vec();
+                for value in [mode, 0, 0] {
+                    response.extend_from_slice(&value.to_le_bytes());
+                }
+                stream.write_all(&response).unwrap();
+            }
+            let (mut stream, service) = accept_transport(&listener);
+            assert_eq!(
+                service,
+                r#"shell:"chown" "system:system" "/data/local/tmp/run.sh"; echo ":RADB_EXIT_STATUS:$?""#
+            );
+            stream
+                .write_all(b"chown: /data/local/tmp/run.sh: Operation not permitted\n:RADB_EXIT_STATUS:1\n")
+                .unwrap();
+        });
+        let mut device = AdbDevice::new("emulator-5554", addr);
+        device.chmod("/data/local/tmp/run.sh", 0o755).unwrap();
+        match device.chmod("/sdcard/run.sh", 0o755).unwrap_err() {
+            AdbError::PermissionDenied(reason) => {
+                assert_eq!(reason, "mode of /sdcard/run.sh is 660 after chmod 755")
+            }
+            other => panic!("unexpected error {:?}", other),
+        }
+        assert!(matches!(
+            device
+                .chown("/data/local/tmp/run.sh", "system:system")
+                .unwrap_err(),
+            AdbError::PermissionDenied(_)
+        ));
+    }
+
+    #[test]
+    fn test_wait_for_file() {
+        let created = Arc::new(std::sync::atomic::AtomicBool::new(false));
+        let flag = created.clone();
+        let addr = serve(move |listener| {
+            accept_features(&listener, "");
+            loop {
+                let (mut stream, _) = accept_transport(&listener);
+                let (id, path) = read_sync_packet(&mut stream);
+                assert_eq!(
+                    (id.as_str(), path.as_slice()),
+                    ("STAT", b"/data/local/tmp/out.mp4".as_slice())
+                );
+                let mode: u32 = if flag.load(Ordering::SeqCst) {
+                    0o100644
+                } else {
+                    0
+                };
+                let mut response = b"STAT".to_vec();
+                for value in [mode, 0, 0] {
+                    response.extend_from_slice(&value.to_le_bytes());
+                }
+                stream.write_all(&response).unwrap();
+            }
+        });
+        let writer = {
+            let created = created.clone();
+            std::thread::spawn(move || {
+                std::thread::sleep(Duration::from_millis(100));
+                created.store(true, Ordering::SeqCst);
+            })
+        };
+        let mut device = AdbDevice::new("emulator-5554", addr);
+        device
+            .wait_for_file(
+                "/data/local/tmp/out.mp4",
+                true,
+                Duration::from_secs(5),
+                Duration::from_millis(20),
+            )
+            .unwrap();
+        writer.join().unwrap();
+        let start = Instant::now();
+        match device
+            .wait_for_file(
+                "/data/local/tmp/out.mp4",
+                false,
+                Duration::from_millis(100),
+                Duration::from_millis(20),
+            )
+            .unwrap_err()
+        {
+            AdbError::Timeout(reason) => {
+                assert_eq!(
+                    reason,
+                    "/data/local/tmp/out.mp4 did not disappear within 100ms"
+                )
+            }
+            other => panic!("unexpected error {:?}", other),
+        }
+        assert!(start.elapsed() < Duration::from_secs(2));
+    }
+
+    #[test]
+    fn test_remove_dir_and_root_guard() {
+        let addr = serve(|listener| {
+            let (mut stream, service) = accept_transport(&listener);
+            assert_eq!(
+                service,
+                r#"shell:"rm" "-rf" "/data/local/tmp/radb"; echo ":RADB_EXIT_STATUS:$?""#
+            );
+            stream.write_all(b":RADB_EXIT_STATUS:0\n").unwrap();
+            drop(stream);
+            let (mut stream, service) = accept_transport(&listener);
+            assert_eq!(
+                service,
+                r#"shell:"rmdir" "/data/local/tmp/full"; echo ":RADB_EXIT_STATUS:$?""#
+            );
+            stream
+                .write_all(
+                    b"rmdir: '/data/local/tmp/full': Directory not empty\n:RADB_EXIT_STATUS:1\n",
+                )
+                .unwrap();
+            drop(stream);
+            // remove 先 stat 确认不是目录
+            accept_features(&listener, "");
+            let (mut stream, _) = accept_transport(&listener);
+            let (id, _) = read_sync_packet(&mut stream);
+            assert_eq!(id, "STAT");
+            let mut response = b"STAT".to_vec();
+            for value in [0o040771u32, 0, 0] {
+                response.extend_from_slice(&value.to_le_bytes());
+            }
+            stream.write_all(&response).unwrap();
+        });
+        let mut device = AdbDevice::new("emulator-5554", addr);
+        device.remove_dir("/data/local/tmp/radb", true).unwrap();
+        assert!(matches!(
+            device
+                .remove_dir("/data/local/tmp/full", false)
+                .unwrap_err(),
+            AdbError::FileOperationFailed(_)
+        ));
+        for path in ["/", "//", "", " "] {
+            match device.remove_dir(path, true).unwrap_err() {
+                AdbError::FileOperationFailed(reason) => assert!(reason.starts_with("refusing")),
+                other => panic!("unexpected error {:?}", other),
+            }
+            assert!(device.remove(path).is_err());
+        }
+        let err = device.remove("/data/local/tmp/radb").unwrap_err();
+        assert!(err.to_string().contains("use remove_dir"), "{}", err);
+    }
+
+    #[test]
+    fn test_is_dir_and_is_file() {
+        let addr = serve_stats(vec![
+            ("/sdcard", 0o040771, 1700000000),
+            ("/sdcard", 0o040771, 1700000000),
+            ("/system/build.prop", 0o100600, 1230768000),
+            ("/system/build.prop", 0o100600, 1230768000),
+            ("/sdcard/missing-radb", 0, 0),
+            ("/sdcard/missing-radb", 0, 0),
+        ]);
+        let mut device = AdbDevice::new("emulator-5554", addr);
+        assert!(device.is_dir("/sdcard").unwrap());
+        assert!(!device.is_file("/sdcard").unwrap());
+        assert!(device.is_file("/system/build.prop").unwrap());
+        assert!(!device.is_dir("/system/build.prop").unwrap());
+        assert!(!device.is_dir("/sdcard/missing-radb").unwrap());
+        assert!(!device.is_file("/sdcard/missing-radb").unwrap());
+    }
+
+    #[test]
+    fn test_iter_directory_uses_lis2() {
+        let addr = serve(|listener| {
+            accept_features(&listener, "ls_v2");
+            let (mut stream, _) = accept_transport(&listener);
+            let (id, _) = read_sync_packet(&mut stream);
+            assert_eq!(id, "LIS2");
+            let name = "movie.mp4";
+            let mut dent = b"DNT2".to_vec();
+            dent.extend(stat_v2_frame(0o100644, 6 << 30, 1700000000));
+            dent.extend_from_slice(&(name.len() as u32).to_le_bytes());
+            dent.extend_from_slice(name.as_bytes());
+            std::io::Write::write_all(&mut stream, &dent).unwrap();
+            write_sync_packet(&mut stream, "DONE", b"");
+        });
+        let mut device = AdbDevice::new("emulator-5554", addr);
+        let entries = device.list("/sdcard/Movies").unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].path, "movie.mp4");
+        assert_eq!(entries[0].size64, Some(6 << 30));
+        assert_eq!(entries[0].gid, Some(1078));
+    }
+
+    fn recorded_session() -> CommandLog {
+        let mut log = CommandLog::new();
+        log.record("input tap 10 20", &Ok::<_, String>(String::new()));
+        log.record(
+            ["pm", "clear", "com.example"],
+            &Ok::<_, String>("Success".into()),
+        );
+        log.record("input text 'hello world'", &Ok::<_, String>(String::new()));
+        log
+    }
+
+    /// 按顺序应答回放的 shell 命令：`Ok(code)` 以该退出码结束，`Err(reason)` 在打开时返回 FAIL。
+    fn serve_replay(
+        outcomes: Vec<Result<i32, &'static str>>,
+    ) -> (String, std::sync::mpsc::Receiver<String>) {
+        let (tx, rx) = std::sync::mpsc::channel();
+        let addr = serve(move |listener| {
+            for outcome in outcomes {
+                let (mut stream, _) = listener.accept().unwrap();
+                read_request(&mut stream);
+                write_okay(&mut stream);
+                let service = read_request(&mut stream);
+                match outcome {
+                    Ok(code) => {
+                        write_okay(&mut stream);
+                        let output = format!("out\n:RADB_EXIT_STATUS:{}\n", code);
+                        stream.write_all(output.as_bytes()).unwrap();
+                    }
+                    Err(reason) => write_fail(&mut stream, reason),
+                }
+                tx.send(service).unwrap();
+            }
+        });
+        (addr, rx)
+    }
+
+    #[test]
+    fn test_replay_issues_commands_in_order() {
+        let (addr, rx) = serve_replay(vec![Ok(0), Err("closed"), Ok(0)]);
+        let mut device = AdbDevice::new("emulator-5554", addr);
+        let options = ReplayOptions {
+            keep_timing: true,
+            on_error: ReplayErrorPolicy::Continue,
+        };
+        let replayed = device.replay(&recorded_session(), options).unwrap();
+        let issued: Vec<String> = rx.iter().take(3).collect();
+        assert_eq!(
+            issued,
+            vec![
+                r#"shell:input tap 10 20; echo ":RADB_EXIT_STATUS:$?""#,
+                r#"shell:"pm" "clear" "com.example"; echo ":RADB_EXIT_STATUS:$?""#,
+                r#"shell:input text 'hello world'; echo ":RADB_EXIT_STATUS:$?""#,
+            ]
+        );
+        assert_eq!(replayed.records.len(), 3);
+        assert_eq!(replayed.records[0].result, Ok("out\n".to_string()));
+        assert!(replayed.records[1].result.is_err());
+        assert!(replayed.records[2].result.is_ok());
+    }
+
+    #[test]
+    fn test_replay_aborts_on_failure() {
+        let (addr, rx) = serve_replay(vec![Err("closed")]);
+        let mut device = AdbDevice::new("emulator-5554", addr);
+        assert!(device
+            .replay(&recorded_session(), ReplayOptions::default())
+            .is_err());
+        assert_eq!(
+            rx.recv().unwrap(),
+            r#"shell:input tap 10 20; echo ":RADB_EXIT_STATUS:$?""#
+        );
+    }
+
+    #[test]
+    fn test_replay_treats_non_zero_exit_status_as_failure() {
+        // pm clear 在设备上失败（退出码 1），默认策略下回放在此中止
+        let (addr, rx) = serve_replay(vec![Ok(0), Ok(1)]);
+        let mut device = AdbDevice::new("emulator-5554", addr);
+        match device
+            .replay(&recorded_session(), ReplayOptions::default())
+            .unwrap_err()
+        {
+            AdbError::CommandFailed { command, reason } => {
+                assert_eq!(command, r#""pm" "clear" "com.example""#);
+                assert_eq!(reason, "exit status 1: out");
+            }
+            other => panic!("unexpected error {:?}", other),
+        }
+        assert_eq!(rx.iter().count(), 2);
+
+        let (addr, _rx) = serve_replay(vec![Ok(0), Ok(1), Ok(0)]);
+        let mut device = AdbDevice::new("emulator-5554", addr);
+        let options = ReplayOptions {
+            keep_timing: false,
+            on_error: ReplayErrorPolicy::Continue,
+        };
+        let replayed = device.replay(&recorded_session(), options).unwrap();
+        assert!(replayed.records[1].result.is_err());
+        assert!(replayed.records[2].result.is_ok());
+    }
+
+    #[test]
+    fn test_install_multiple_reports_progress_per_split() {
+        let sizes = [200_000usize, 5];
+        let addr = serve(move |listener| {
+            let (mut stream, service) = accept_transport(&listener);
+            assert_eq!(
+                service,
+                r#"shell:"pm" "install-create" "-r" "-t" "-S" "200005""#
+            );
+            std::io::Write::write_all(&mut stream, b"Success: created install session [42]\n")
+                .unwrap();
+            drop(stream);
+            for (index, size) in sizes.into_iter().enumerate() {
+                let (mut stream, service) = accept_transport(&listener);
+                assert_eq!(
+                    service,
+                    format!(
+                        r#"exec:"pm" "install-write" "-S" "{}" "42" "{}_split{}.apk" "-""#,
+                        size, index, index
+                    )
+                );
+                let mut data = vec![0; size];
+                std::io::Read::read_exact(&mut stream, &mut data).unwrap();
+                std::io::Write::write_all(&mut stream, b"Success: streamed bytes\n").unwrap();
+            }
+            let (mut stream, service) = accept_transport(&listener);
+            assert_eq!(service, r#"shell:"pm" "install-commit" "42""#);
+            std::io::Write::write_all(&mut stream, b"Success\n").unwrap();
+        });
+        let tmpdir = tempfile::tempdir().unwrap();
+        let apks: Vec<_> = sizes
+            .iter()
+            .enumerate()
+            .map(|(index, size)| {
+                let apk = tmpdir.path().join(format!("split{}.apk", index));
+                std::fs::write(&apk, vec![7u8; *size]).unwrap();
+                apk
+            })
+            .collect();
+
+        let mut device = AdbDevice::new("emulator-5554", addr);
+        let mut written = vec![0u64; apks.len()];
+        let output = device
+            .install_multiple_with_progress(
+                &apks,
+                &InstallOptions::default(),
+                |index, bytes, total| {
+                    assert_eq!(total, sizes[index] as u64);
+                    assert!(bytes > written[index]);
+                    written[index] = bytes;
+                },
+            )
+            .unwrap();
+        assert_eq!(output, "Success\n");
+        assert_eq!(written, vec![200_000, 5]);
+        assert_eq!(written.iter().sum::<u64>(), 200_005);
+    }
+
+    #[test]
+    fn test_props_are_read_once() {
+        let round_trips = Arc::new(AtomicUsize::new(0));
+        let counter = round_trips.clone();
+        let addr = serve(move |listener| loop {
+            let (mut stream, service) = accept_transport(&listener);
+            counter.fetch_add(1, Ordering::SeqCst);
+            assert_eq!(service, r#"shell:"getprop""#);
+            std::io::Write::write_all(
+                &mut stream,
+                b"[ro.build.version.sdk]: [34]\n[ro.product.model]: [sdk_gphone64_x86_64]\n\
+                  [persist.sys.locale]: []\n",
+            )
+            .unwrap();
+        });
+        let mut device = AdbDevice::new("emulator-5554", addr);
+        let props = device.load_all_props().unwrap();
+        assert_eq!(props.len(), 3);
+        assert_eq!(props["persist.sys.locale"], "");
+        assert_eq!(device.get_sdk_version().unwrap(), "34");
+        assert_eq!(device.get_device_model().unwrap(), "sdk_gphone64_x86_64");
+        assert_eq!(round_trips.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_memory_and_storage() {
+        let addr = serve(|listener| {
+            for _ in 0..2 {
+                let (mut stream, service) = accept_transport(&listener);
+                assert_eq!(service, r#"shell:"cat" "/proc/meminfo""#);
+                stream
+                    .write_all(b"MemTotal:        3969016 kB\nMemFree:          127516 kB\nMemAvailable:    1623064 kB\n")
+                    .unwrap();
+            }
+            for path in ["/data", "/nonexistent"] {
+                let (mut stream, _) = accept_transport(&listener);
+                stream
+                    .write_all(b"/system/bin/df\n:RADB_EXIT_STATUS:0\n")
+                    .unwrap();
+                drop(stream);
+                let (mut stream, service) = accept_transport(&listener);
+                assert_eq!(service, format!(r#"shell:"df" "-k" "{}""#, path));
+                let output = if path == "/data" {
+                    "Filesystem      1K-blocks    Used Available Use% Mounted on\n/dev/block/dm-5  57591052 9143524  48316456  16% /data\n".to_string()
+                } else {
+                    format!("df: {}: No such file or directory\n", path)
+                };
+                stream.write_all(output.as_bytes()).unwrap();
+            }
+        });
+        let mut device = AdbDevice::new("emulator-5554", addr);
+        assert_eq!(device.mem_total().unwrap(), 3969016 * 1024);
+        assert_eq!(device.mem_available().unwrap(), 1623064 * 1024);
+        let storage = device.storage("/data").unwrap();
+        assert_eq!(storage.total, 57591052 * 1024);
+        assert_eq!(storage.used, 9143524 * 1024);
+        assert_eq!(storage.available, 48316456 * 1024);
+        assert!(matches!(
+            device.storage("/nonexistent").unwrap_err(),
+            AdbError::FileOperationFailed(_)
+        ));
+    }
+
+    #[test]
+    fn test_file_ops_map_errors() {
+        let addr = serve(|listener| {
+            for (expected, reply) in [
+                (
+                    r#"shell:"mkdir" "-p" "/data/local/tmp/a/b"; echo ":RADB_EXIT_STATUS:$?""#,
+                    ":RADB_EXIT_STATUS:0\n",
+                ),
+                (
+                    r#"shell:"mv" "/data/local/tmp/x" "/data/local/tmp/y"; echo ":RADB_EXIT_STATUS:$?""#,
+                    "mv: bad '/data/local/tmp/x': No such file or directory\n:RADB_EXIT_STATUS:1\n",
+                ),
+                (
+                    r#"shell:"touch" "/system/x"; echo ":RADB_EXIT_STATUS:$?""#,
+                    "touch: '/system/x': Read-only file system\n:RADB_EXIT_STATUS:1\n",
+                ),
+                (
+                    r#"shell:"cp" "-r" "/data/local/tmp/a" "/data/app/a"; echo ":RADB_EXIT_STATUS:$?""#,
+                    "cp: /data/app/a: Permission denied\n:RADB_EXIT_STATUS:1\n",
+                ),
+            ] {
+                let (mut stream, service) = accept_transport(&listener);
+                assert_eq!(service, expected);
+                stream.write_all(reply.as_bytes()).unwrap();
+            }
+        });
+        let mut device = AdbDevice::new("emulator-5554", addr);
+        device.mkdir("/data/local/tmp/a/b", true).unwrap();
+        match device
+            .mv("/data/local/tmp/x", "/data/local/tmp/y")
+            .unwrap_err()
+        {
+            AdbError::FileOperationFailed(reason) => assert_eq!(
+                reason,
+                "mv /data/local/tmp/x /data/local/tmp/y: \
+                 mv: bad '/data/local/tmp/x': No such file or directory"
+            ),
+            other => panic!("unexpected error {:?}", other),
+        }
+        assert!(matches!(
+            device.touch("/system/x").unwrap_err(),
+            AdbError::FileOperationFailed(_)
+        ));
+        assert!(matches!(
+            device
+                .cp("/data/local/tmp/a", "/data/app/a", true)
+                .unwrap_err(),
+            AdbError::PermissionDenied(_)
+        ));
+    }
+
+    #[test]
+    fn test_pidof_falls_back_to_toolbox_ps() {
+        let addr = serve(|listener| {
+            let (mut stream, service) = accept_transport(&listener);
+            assert_eq!(
+                service,
+                r#"shell:"pidof" "system_server"; echo ":RADB_EXIT_STATUS:$?""#
+            );
+            stream.write_all(b"573\n:RADB_EXIT_STATUS:0\n").unwrap();
+            drop(stream);
+            let (mut stream, _) = accept_transport(&listener);
+            stream
+                .write_all(b"/system/bin/sh: pidof: not found\n:RADB_EXIT_STATUS:127\n")
+                .unwrap();
+            drop(stream);
+            let (mut stream, service) = accept_transport(&listener);
+            assert_eq!(service, r#"shell:"ps" "-A" "-o" "PID,PPID,USER,NAME""#);
+            stream
+                .write_all(b"USER      PID   PPID  VSIZE  RSS   WCHAN              PC  NAME\n")
+                .unwrap();
+            drop(stream);
+            let (mut stream, service) = accept_transport(&listener);
+            assert_eq!(service, r#"shell:"ps""#);
+            stream
+                .write_all(
+                    b"USER      PID   PPID  VSIZE  RSS   WCHAN              PC  NAME\n\
+                      system    573   245   1697880 112640 SyS_epoll_ 00000000 S system_server\n\
+                      u0_a54    1893  245   1541284 49052 SyS_epoll_ 00000000 S com.android.launcher3\n",
+                )
+                .unwrap();
+        });
+        let mut device = AdbDevice::new("emulator-5554", addr);
+        assert_eq!(device.pidof("system_server").unwrap(), [573]);
+        assert_eq!(device.pidof("com.android.launcher3").unwrap(), [1893]);
+    }
+
+    #[test]
+    fn test_kill_process_kills_every_pid() {
+        let addr = serve(|listener| {
+            let (mut stream, _) = accept_transport(&listener);
+            stream
+                .write_all(b"4021 4087\n:RADB_EXIT_STATUS:0\n")
+                .unwrap();
+            drop(stream);
+            for pid in [4021, 4087] {
+                let (mut stream, service) = accept_transport(&listener);
+                assert_eq!(
+                    service,
+                    format!(
+                        r#"shell:"kill" "-9" "{}"; echo ":RADB_EXIT_STATUS:$?""#,
+                        pid
+                    )
+                );
+                stream.write_all(b":RADB_EXIT_STATUS:0\n").unwrap();
+            }
+            let (mut stream, _) = accept_transport(&listener);
+            stream
+                .write_all(
+                    b"/system/bin/sh: kill: 573: Operation not permitted\n:RADB_EXIT_STATUS:1\n",
+                )
+                .unwrap();
+            drop(stream);
+            let (mut stream, _) = accept_transport(&listener);
+            stream
+                .write_all(b"/system/bin/sh: kill: 99999: No such process\n:RADB_EXIT_STATUS:1\n")
+                .unwrap();
+        });
+        let mut device = AdbDevice::new("emulator-5554", addr);
+        assert_eq!(device.kill_process("sleep").unwrap(), [4021, 4087]);
+        assert!(matches!(
+            device.kill_pid(573, 15).unwrap_err(),
+            AdbError::PermissionDenied(_)
+        ));
+        assert!(matches!(
+            device.kill_pid(99999, 15).unwrap_err(),
+            AdbError::ExitStatus { code: 1, .. }
+        ));
+    }
+
+    #[test]
+    fn test_missing_tool_is_reported() {
+        let addr = serve(|listener| {
+            let (mut stream, service) = accept_transport(&listener);
+            assert_eq!(
+                service,
+                r#"shell:"command" "-v" "df"; echo ":RADB_EXIT_STATUS:$?""#
+            );
+            std::io::Write::write_all(&mut stream, b":RADB_EXIT_STATUS:1\n").unwrap();
+            drop(stream);
+            for _ in 0..4 {
+                let (mut stream, _) = accept_transport(&listener);
+                std::io::Write::write_all(&mut stream, b":RADB_EXIT_STATUS:127\n").unwrap();
+            }
+        });
+        let mut device = AdbDevice::new("emulator-5554", addr);
+        assert_eq!(
+            device.mounts().unwrap_err().to_string(),
+            "required tool 'df' not found on device"
+        );
+        assert_eq!(
+            device.wlan_ip().unwrap_err().to_string(),
+            "required tool 'ifconfig' or 'ip' not found on device"
+        );
+    }
+
+    #[test]
+    fn test_set_prop_detects_ignored_value() {
+        let addr = serve(|listener| {
+            let (_, service) = accept_transport(&listener);
+            assert_eq!(service, r#"shell:"setprop" "ro.debuggable" "1""#);
+            let (mut stream, service) = accept_transport(&listener);
+            assert_eq!(service, r#"shell:"getprop" "ro.debuggable""#);
+            std::io::Write::write_all(&mut stream, b"0\n").unwrap();
+        });
+        let mut device = AdbDevice::new("emulator-5554", addr);
+        device
+            .properties
+            .insert("ro.debuggable".to_string(), "1".to_string());
+        match device.set_prop("ro.debuggable", "1").unwrap_err() {
+            AdbError::PermissionDenied(reason) => {
+                assert_eq!(
+                    reason,
+                    r#"property ro.debuggable is "0" after setting it to "1""#
+                )
+            }
+            other => panic!("unexpected error {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_mutable_props_bypass_cache() {
+        let addr = serve(|listener| {
+            let (mut stream, service) = accept_transport(&listener);
+            assert_eq!(service, r#"shell:"getprop""#);
+            std::io::Write::write_all(
+                &mut stream,
+                b"[persist.sys.timezone]: [Asia/Shanghai]\n[persist.sys.locale]: [zh-CN]\n",
+            )
+            .unwrap();
+            drop(stream);
+            let (_, service) = accept_transport(&listener);
+            assert_eq!(
+                service,
+                r#"shell:"service" "call" "alarm" "3" "s16" "Europe/Paris""#
+            );
+            for (expected, reply) in [
+                (
+                    r#"shell:"getprop" "persist.sys.timezone""#,
+                    "Europe/Paris\n",
+                ),
+                (r#"shell:"getprop" "persist.sys.locale""#, "fr-FR\n"),
+            ] {
+                let (mut stream, service) = accept_transport(&listener);
+                assert_eq!(service, expected);
+                std::io::Write::write_all(&mut stream, reply.as_bytes()).unwrap();
+            }
+        });
+        let mut device = AdbDevice::new("emulator-5554", addr);
+        device.load_all_props().unwrap();
+        device.set_timezone("Europe/Paris").unwrap();
+        assert_eq!(device.get_locale().unwrap(), "fr-FR");
+    }
+
+    #[test]
+    fn test_app_start_resolves_launcher_activity() {
+        let addr = serve(|listener| {
+            let (mut stream, service) = accept_transport(&listener);
+            assert_eq!(
+                service,
+                r#"shell:"cmd" "package" "resolve-activity" "--brief" "-a" "android.intent.action.MAIN" "-c" "android.intent.category.LAUNCHER" "com.android.settings""#
+            );
+            std::io::Write::write_all(
+                &mut stream,
+                b"priority=0 preferredOrder=0 match=0x108000 specificIndex=-1 isDefault=true\ncom.android.settings/.Settings\n",
+            )
+            .unwrap();
+            drop(stream);
+            let (_, service) = accept_transport(&listener);
+            assert_eq!(
+                service,
+                r#"shell:"am" "start" "-n" "com.android.settings/com.android.settings.Settings""#
+            );
+        });
+        let mut device = AdbDevice::new("emulator-5554", addr);
+        device.app_start("com.android.settings").unwrap();
+    }
+
+    #[test]
+    fn test_is_installed_matches_full_name() {
+        let addr = serve(|listener| {
+            let (mut stream, service) = accept_transport(&listener);
+            assert_eq!(service, r#"shell:"pm" "list" "packages" "com.example.app""#);
+            std::io::Write::write_all(&mut stream, b"package:com.example.app.debug\n").unwrap();
+        });
+        let mut device = AdbDevice::new("emulator-5554", addr);
+        assert!(!device.is_installed("com.example.app").unwrap());
+    }
+
+    #[test]
+    fn test_install_surfaces_pm_failure() {
+        let addr = serve(|listener| {
+            let (mut stream, service) = accept_transport(&listener);
+            assert_eq!(
+                service,
+                r#"shell:"pm" "install" "-r" "-t" "-g" "/data/local/tmp/app.apk""#
+            );
+            std::io::Write::write_all(&mut stream, b"Failure [INSTALL_FAILED_VERSION_DOWNGRADE]\n")
+                .unwrap();
+        });
+        let mut device = AdbDevice::new("emulator-5554", addr);
+        let options = InstallOptions {
+            grant_permissions: true,
+            ..Default::default()
+        };
+        match device
+            .install_remote_with("/data/local/tmp/app.apk", false, &options)
+            .unwrap_err()
+        {
+            AdbError::ApplicationError(reason) => {
+                assert_eq!(reason, "INSTALL_FAILED_VERSION_DOWNGRADE")
+            }
+            other => panic!("unexpected error {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_install_multiple_rejected_commit() {
+        let addr = serve(|listener| {
+            let (mut stream, service) = accept_transport(&listener);
+            assert_eq!(
+                service,
+                r#"shell:"pm" "install-create" "-r" "-t" "-d" "-S" "8""#
+            );
+            std::io::Write::write_all(&mut stream, b"Success: created install session [7]\n")
+                .unwrap();
+            drop(stream);
+            for name in ["0_base.apk", "1_split_config.arm64_v8a.apk"] {
+                let (mut stream, service) = accept_transport(&listener);
+                assert_eq!(
+                    service,
+                    format!(r#"exec:"pm" "install-write" "-S" "4" "7" "{}" "-""#, name)
+                );
+                std::io::Read::read_exact(&mut stream, &mut [0; 4]).unwrap();
+                std::io::Write::write_all(&mut stream, b"Success: streamed 4 bytes\n").unwrap();
+            }
+            let (mut stream, _) = accept_transport(&listener);
+            std::io::Write::write_all(
+                &mut stream,
+                b"Failure [INSTALL_FAILED_MISSING_SPLIT: Missing split for com.example.app]\n",
+            )
+            .unwrap();
+        });
+        let tmpdir = tempfile::tempdir().unwrap();
+        let base = tmpdir.path().join("base.apk");
+        let split = tmpdir.path().join("split_config.arm64_v8a.apk");
+        std::fs::write(&base, b"base").unwrap();
+        std::fs::write(&split, b"libs").unwrap();
+
+        let mut device = AdbDevice::new("emulator-5554", addr);
+        let options = InstallOptions {
+            allow_downgrade: true,
+            ..Default::default()
+        };
+        match device
+            .install_multiple(&[base.as_path(), split.as_path()], &options)
+            .unwrap_err()
+        {
+            AdbError::ApplicationError(reason) => assert_eq!(
+                reason,
+                "INSTALL_FAILED_MISSING_SPLIT: Missing split for com.example.app"
+            ),
+            other => panic!("unexpected error {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_install_multiple_abandons_on_write_failure() {
+        let (tx, rx) = std::sync::mpsc::channel();
+        let addr = serve(move |listener| {
+            let (mut stream, _) = accept_transport(&listener);
+            std::io::Write::write_all(&mut stream, b"Success: created install session [9]\n")
+                .unwrap();
+            drop(stream);
+            let (mut stream, _) = accept_transport(&listener);
+            std::io::Read::read_exact(&mut stream, &mut [0; 4]).unwrap();
+            std::io::Write::write_all(&mut stream, b"Error: Unable to write split\n").unwrap();
+            drop(stream);
+            let (_, service) = accept_transport(&listener);
+            tx.send(service).unwrap();
+        });
+        let tmpdir = tempfile::tempdir().unwrap();
+        let base = tmpdir.path().join("base.apk");
+        std::fs::write(&base, b"base").unwrap();
+
+        let mut device = AdbDevice::new("emulator-5554", addr);
+        assert!(device
+            .install_multiple(&[base], &InstallOptions::default())
+            .is_err());
+        assert_eq!(rx.recv().unwrap(), r#"shell:"pm" "install-abandon" "9""#);
+    }
+
+    #[test]
+    fn test_pull_apk_returns_all_splits() {
+        let remotes = [
+            "/data/app/~~a1==/com.example.app-b2==/base.apk",
+            "/data/app/~~a1==/com.example.app-b2==/split_config.arm64_v8a.apk",
+            "/data/app/~~a1==/com.example.app-b2==/split_config.xxhdpi.apk",
+        ];
+        let addr = serve(move |listener| {
+            let (mut stream, service) = accept_transport(&listener);
+            assert_eq!(service, r#"shell:"pm" "path" "com.example.app""#);
+            for remote in remotes {
+                std::io::Write::write_all(&mut stream, format!("package:{}\n", remote).as_bytes())
+                    .unwrap();
+            }
+            drop(stream);
+            accept_features(&listener, "");
+            for remote in remotes {
+                // stat 失败时 pull 不报告总大小，直接断开即可
+                let (mut stream, _) = accept_transport(&listener);
+                read_sync_packet(&mut stream);
+                drop(stream);
+                let (mut stream, _) = accept_transport(&listener);
+                let (id, path) = read_sync_packet(&mut stream);
+                assert_eq!((id.as_str(), path.as_slice()), ("RECV", remote.as_bytes()));
+                write_sync_packet(&mut stream, "DATA", b"PK\x03\x04");
+                write_sync_packet(&mut stream, "DONE", b"");
+            }
+        });
+        let tmpdir = tempfile::tempdir().unwrap();
+        let mut device = AdbDevice::new("emulator-5554", addr);
+        let files = device.pull_apk("com.example.app", tmpdir.path()).unwrap();
+        let names: Vec<_> = files
+            .iter()
+            .map(|f| f.file_name().unwrap().to_str().unwrap())
+            .collect();
+        assert_eq!(
+            names,
+            vec![
+                "base.apk",
+                "split_config.arm64_v8a.apk",
+                "split_config.xxhdpi.apk"
+            ]
+        );
+        for file in files {
+            assert_eq!(std::fs::read(file).unwrap(), b"PK\x03\x04");
+        }
+    }
+
+    #[test]
+    fn test_logcat_stop_handle_ends_iterator() {
+        let addr = serve(|listener| {
+            let (mut stream, service) = accept_transport(&listener);
+            assert_eq!(service, r#"shell:"logcat" "-v" "time""#);
+            std::io::Write::write_all(&mut stream, b"10-15 12:00:00.000 I/Tag( 1): first\n")
+                .unwrap();
+            // 之后不再输出日志，客户端只能通过 stop 结束读取
+            let _ = std::io::Read::read(&mut stream, &mut [0; 1]);
+        });
+        let mut device = AdbDevice::new("emulator-5554", addr);
+        let mut lines = device
+            .logcat(false, None, Arc::new(RwLock::new(true)))
+            .unwrap();
+        let handle = lines.stop_handle().unwrap();
+        assert_eq!(
+            lines.next().unwrap(),
+            "10-15 12:00:00.000 I/Tag( 1): first\n"
+        );
+        std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(100));
+            handle.stop().unwrap();
+        });
+        assert_eq!(lines.next(), None);
+    }
+}
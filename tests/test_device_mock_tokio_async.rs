@@ -0,0 +1,213 @@
+mod common;
+
+#[cfg(feature = "tokio_async")]
+mod test_device_mock_tokio_async {
+    use crate::common::*;
+    use radb::beans::DeviceState;
+    use radb::client::AdbDevice;
+    use radb::errors::AdbError;
+    use std::io::Read;
+    use std::sync::mpsc;
+    use std::time::Duration;
+    use tokio::io::AsyncWriteExt;
+
+    #[tokio::test]
+    async fn test_state_parses_and_maps_missing_device() {
+        let addr = serve(|listener| {
+            for reply in [Some("device"), None] {
+                let (mut stream, _) = listener.accept().unwrap();
+                assert_eq!(
+                    read_request(&mut stream),
+                    "host-serial:emulator-5554:get-state"
+                );
+                match reply {
+                    Some(state) => {
+                        write_okay(&mut stream);
+                        write_block(&mut stream, state);
+                    }
+                    None => write_fail(&mut stream, "device 'emulator-5554' not found"),
+                }
+                drop(stream);
+            }
+        });
+        let mut device = AdbDevice::new("emulator-5554", addr);
+        assert_eq!(device.state().await.unwrap(), DeviceState::Device);
+        assert!(matches!(
+            device.state().await.unwrap_err(),
+            AdbError::DeviceNotFound(_)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_dropped_connection_flushes_and_sends_eof() {
+        let (tx, rx) = mpsc::channel();
+        let addr = serve(move |listener| {
+            let (mut stream, service) = accept_transport(&listener);
+            assert_eq!(service, "shell:\"cat\"");
+            let mut received = vec![];
+            stream.read_to_end(&mut received).unwrap();
+            tx.send(received).unwrap();
+        });
+        let mut device = AdbDevice::new("emulator-5554", addr);
+        let mut conn = device.shell_stream(&["cat"]).await.unwrap();
+        conn.write_all(b"streamed payload").await.unwrap();
+        drop(conn);
+        let received = tokio::task::spawn_blocking(move || rx.recv().unwrap())
+            .await
+            .unwrap();
+        assert_eq!(received, b"streamed payload");
+    }
+
+    #[tokio::test]
+    async fn test_forward_remote_port_reuses_existing_forward() {
+        let addr = serve(|listener| {
+            let mut forwards = String::new();
+            for _ in 0..2 {
+                let (mut stream, _) = listener.accept().unwrap();
+                assert_eq!(
+                    read_request(&mut stream),
+                    "host-serial:emulator-5554:list-forward"
+                );
+                write_okay(&mut stream);
+                write_block(&mut stream, &forwards);
+                if forwards.is_empty() {
+                    drop(stream);
+                    let (mut stream, _) = listener.accept().unwrap();
+                    assert_eq!(
+                        read_request(&mut stream),
+                        "host-serial:emulator-5554:forward:tcp:0;tcp:8080"
+                    );
+                    write_okay(&mut stream);
+                    write_okay(&mut stream);
+                    write_block(&mut stream, "41237");
+                    forwards = "emulator-5554 tcp:41237 tcp:8080\n".to_string();
+                }
+            }
+        });
+        let mut device = AdbDevice::new("emulator-5554", addr);
+        assert_eq!(device.forward_remote_port(8080).await.unwrap(), 41237);
+        assert_eq!(device.forward_remote_port(8080).await.unwrap(), 41237);
+        assert_eq!(device.forwards, vec!["tcp:41237"]);
+    }
+
+    #[tokio::test]
+    async fn test_read_to_string_collects_all_chunks() {
+        let content = "你好, radb\nsecond line\n".as_bytes();
+        let addr = serve(move |listener| {
+            accept_features(&listener, "");
+            for _ in 0..2 {
+                let (mut stream, service) = accept_transport(&listener);
+                assert_eq!(service, "sync:");
+                let (id, path) = read_sync_packet(&mut stream);
+                assert_eq!(
+                    (id.as_str(), path.as_slice()),
+                    ("RECV", b"/data/local/tmp/hello.txt".as_slice())
+                );
+                // 第一个块在多字节字符中间截断
+                for chunk in [&content[..1], &content[1..]] {
+                    write_sync_packet(&mut stream, "DATA", chunk);
+                }
+                write_sync_packet(&mut stream, "DONE", b"");
+            }
+        });
+        let mut device = AdbDevice::new("emulator-5554", addr);
+        assert_eq!(
+            device
+                .read_to_string("/data/local/tmp/hello.txt")
+                .await
+                .unwrap(),
+            "你好, radb\nsecond line\n"
+        );
+        assert_eq!(
+            device
+                .read_to_bytes("/data/local/tmp/hello.txt")
+                .await
+                .unwrap(),
+            content
+        );
+    }
+
+    #[tokio::test]
+    async fn test_list_returns_fail_after_entries() {
+        let addr = serve(|listener| {
+            accept_features(&listener, "");
+            let (mut stream, service) = accept_transport(&listener);
+            assert_eq!(service, "sync:");
+            read_sync_packet(&mut stream);
+            let mut dent = b"DENT".to_vec();
+            for value in [0o100644u32, 3, 1700000000, 5] {
+                dent.extend_from_slice(&value.to_le_bytes());
+            }
+            dent.extend_from_slice(b"a.txt");
+            std::io::Write::write_all(&mut stream, &dent).unwrap();
+            write_sync_packet(&mut stream, "FAIL", b"Permission denied");
+        });
+        let mut device = AdbDevice::new("emulator-5554", addr);
+        let err = device.list("/data").await.unwrap_err();
+        match err.downcast_ref::<AdbError>() {
+            Some(AdbError::FileOperationFailed(reason)) => assert_eq!(reason, "Permission denied"),
+            other => panic!("unexpected error {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_shell_reads_large_output() {
+        let expected: String = (0..40_000).map(|i| format!("line {:08}\n", i)).collect();
+        let payload = expected.clone();
+        let addr = serve(move |listener| {
+            let (mut stream, _) = accept_transport(&listener);
+            for chunk in payload.as_bytes().chunks(7919) {
+                std::io::Write::write_all(&mut stream, chunk).unwrap();
+            }
+        });
+        let mut device = AdbDevice::new("emulator-5554", addr);
+        let output = device.shell(&["dumpsys"]).await.unwrap();
+        assert_eq!(output, expected);
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_write_keeps_read_side_open() {
+        let addr = serve(|listener| {
+            let (mut stream, _) = accept_transport(&listener);
+            let mut received = vec![];
+            stream.read_to_end(&mut received).unwrap();
+            std::io::Write::write_all(&mut stream, &received).unwrap();
+        });
+        let mut device = AdbDevice::new("emulator-5554", addr);
+        let mut conn = device.shell_stream(&["cat"]).await.unwrap();
+        conn.write_all(b"echo").await.unwrap();
+        conn.shutdown_write().await.unwrap();
+        let mut echoed = String::new();
+        tokio::io::AsyncReadExt::read_to_string(&mut conn, &mut echoed)
+            .await
+            .unwrap();
+        assert_eq!(echoed, "echo");
+        conn.close().await.ok();
+    }
+
+    #[tokio::test]
+    async fn test_with_retry_retries_until_shell_succeeds() {
+        let addr = serve(|listener| {
+            let (mut stream, service) = accept_transport(&listener);
+            assert_eq!(service, r#"shell:"getprop" "sys.boot_completed""#);
+            std::io::Write::write_all(&mut stream, b"1\n").unwrap();
+        });
+        let mut device = AdbDevice::new("emulator-5554", addr);
+        let mut calls = 0;
+        let booted = device
+            .with_retry(3, Duration::from_millis(10), |d| {
+                calls += 1;
+                let call = calls;
+                Box::pin(async move {
+                    if call < 3 {
+                        return Err(AdbError::timeout("device busy"));
+                    }
+                    Ok(d.shell_trim(&["getprop", "sys.boot_completed"]).await?)
+                })
+            })
+            .await
+            .unwrap();
+        assert_eq!(booted, "1");
+        assert_eq!(calls, 3);
+    }
+}
@@ -0,0 +1,53 @@
+mod common;
+
+#[cfg(feature = "blocking")]
+mod test_logging_mock {
+    use crate::common::*;
+    use log::{Level, LevelFilter, Log, Metadata, Record};
+    use radb::client::AdbClient;
+    use std::sync::Mutex;
+
+    /// 收集 radb 输出的全部日志及其级别的测试 logger。
+    struct Collector(Mutex<Vec<(Level, String)>>);
+
+    impl Log for Collector {
+        fn enabled(&self, metadata: &Metadata) -> bool {
+            metadata.target().starts_with("radb")
+        }
+
+        fn log(&self, record: &Record) {
+            if self.enabled(record.metadata()) {
+                self.0
+                    .lock()
+                    .unwrap()
+                    .push((record.level(), record.args().to_string()));
+            }
+        }
+
+        fn flush(&self) {}
+    }
+
+    static LOGGER: Collector = Collector(Mutex::new(Vec::new()));
+
+    #[test]
+    fn test_server_version_emits_no_info_logs() {
+        log::set_logger(&LOGGER).unwrap();
+        log::set_max_level(LevelFilter::Trace);
+
+        let addr = serve(|listener| {
+            let (mut stream, _) = listener.accept().unwrap();
+            assert_eq!(read_request(&mut stream), "host:version");
+            write_okay(&mut stream);
+            write_block(&mut stream, "0029");
+        });
+        let mut adb = AdbClient::new(&addr);
+        assert_eq!(adb.server_version().unwrap(), "41");
+
+        let logs = LOGGER.0.lock().unwrap();
+        assert!(
+            logs.iter().all(|(level, _)| *level > Level::Info),
+            "{:?}",
+            logs
+        );
+    }
+}
@@ -0,0 +1,59 @@
+mod common;
+
+#[cfg(all(feature = "blocking", feature = "metrics"))]
+mod test_metrics_mock {
+    use crate::common::*;
+    use log::{Level, LevelFilter, Log, Metadata, Record};
+    use radb::client::AdbDevice;
+    use radb::metrics;
+    use std::sync::Mutex;
+    use std::time::Duration;
+
+    /// 收集 warn 级别日志的测试 logger。
+    struct WarnCollector(Mutex<Vec<String>>);
+
+    impl Log for WarnCollector {
+        fn enabled(&self, metadata: &Metadata) -> bool {
+            metadata.level() <= Level::Warn
+        }
+
+        fn log(&self, record: &Record) {
+            if self.enabled(record.metadata()) {
+                self.0.lock().unwrap().push(record.args().to_string());
+            }
+        }
+
+        fn flush(&self) {}
+    }
+
+    static LOGGER: WarnCollector = WarnCollector(Mutex::new(Vec::new()));
+
+    #[test]
+    fn test_slow_shell_warns() {
+        log::set_logger(&LOGGER).unwrap();
+        log::set_max_level(LevelFilter::Warn);
+        metrics::set_slow_threshold(Duration::from_millis(50));
+
+        let addr = serve(|listener| {
+            for delay in [0, 200] {
+                let (mut stream, _) = accept_transport(&listener);
+                std::thread::sleep(Duration::from_millis(delay));
+                std::io::Write::write_all(&mut stream, b"ok").unwrap();
+            }
+        });
+        let mut device = AdbDevice::new("emulator-5554", addr);
+        device.shell(&["echo", "ok"]).unwrap();
+        device.shell(&["sleep", "1"]).unwrap();
+
+        let stats = metrics::snapshot().get("shell").cloned().unwrap();
+        assert_eq!(stats.count, 2);
+        assert_eq!(stats.slow, 1);
+        assert!(stats.max >= Duration::from_millis(200));
+        let warnings = LOGGER.0.lock().unwrap();
+        assert!(
+            warnings.iter().any(|w| w.starts_with("shell took")),
+            "{:?}",
+            warnings
+        );
+    }
+}